@@ -0,0 +1,141 @@
+use claude_code_agent_sdk::Message;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Name of the API interaction log in each workspace (`--verbose`)
+pub const API_LOG_FILE: &str = "api.log";
+
+/// Per-request metadata of an implementation session: the model, each
+/// response's token usage and latency, and the session totals. Written line
+/// by line as the session streams, so a session that dies still leaves it.
+pub struct ApiLog {
+    file: File,
+    started: Instant,
+    /// When the request being answered went out: the prompt, or the latest
+    /// tool results
+    request_sent: Instant,
+    turn: u32,
+    /// Claude Code sends one message per content block; they share an id
+    last_message_id: Option<String>,
+}
+
+impl ApiLog {
+    /// Open the log at `path`, adding to what earlier attempts in the same
+    /// workspace wrote
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let now = Instant::now();
+        Ok(Self {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+            started: now,
+            request_sent: now,
+            turn: 0,
+            last_message_id: None,
+        })
+    }
+
+    /// Append a line stamped with the time since the session started. A log
+    /// that can't be written shouldn't fail the session, so errors are dropped.
+    pub fn line(&mut self, text: &str) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let _ = writeln!(self.file, "[+{:.1}s] {}", elapsed, text);
+    }
+
+    /// Note a request going out, which the next response's latency is
+    /// measured from
+    pub fn request(&mut self, what: &str) {
+        self.request_sent = Instant::now();
+        self.line(&format!("request: {}", what));
+    }
+
+    /// Log what `message` says about the API traffic
+    pub fn record(&mut self, message: &Message) {
+        match message {
+            Message::System(system) => self.line(&format!(
+                "system {}: model={} permission_mode={} session={}",
+                system.subtype,
+                system.model.as_deref().unwrap_or("-"),
+                system.permission_mode.as_deref().unwrap_or("-"),
+                system.session_id.as_deref().unwrap_or("-")
+            )),
+            Message::Assistant(assistant) => {
+                let inner = &assistant.message;
+                if inner.id.is_some() && inner.id == self.last_message_id {
+                    return;
+                }
+                self.last_message_id = inner.id.clone();
+                self.turn += 1;
+                let latency = self.request_sent.elapsed().as_secs_f64();
+                let text = format!(
+                    "response {} ({}): model={} stop_reason={} latency={:.1}s {}",
+                    self.turn,
+                    inner.id.as_deref().unwrap_or("-"),
+                    inner.model.as_deref().unwrap_or("-"),
+                    inner.stop_reason.as_deref().unwrap_or("-"),
+                    latency,
+                    format_usage(inner.usage.as_ref())
+                );
+                self.line(&text);
+            }
+            Message::User(_) => self.request("tool results"),
+            Message::Result(result) => self.line(&format!(
+                "result {}: turns={} duration={}ms api_duration={}ms cost={} {}",
+                result.subtype,
+                result.num_turns,
+                result.duration_ms,
+                result.duration_api_ms,
+                result
+                    .total_cost_usd
+                    .map_or("-".to_string(), |c| format!("${:.4}", c)),
+                format_usage(result.usage.as_ref())
+            )),
+            _ => {}
+        }
+    }
+}
+
+/// Token counts from an API `usage` object, e.g. "tokens: in=12 out=340
+/// cache_read=9000 cache_write=1200"
+fn format_usage(usage: Option<&serde_json::Value>) -> String {
+    let Some(usage) = usage else {
+        return "tokens: -".to_string();
+    };
+    let count = |key: &str| usage.get(key).and_then(serde_json::Value::as_u64);
+    let fields = [
+        ("in", "input_tokens"),
+        ("out", "output_tokens"),
+        ("cache_read", "cache_read_input_tokens"),
+        ("cache_write", "cache_creation_input_tokens"),
+    ];
+    let counts: Vec<String> = fields
+        .iter()
+        .filter_map(|(label, key)| Some(format!("{}={}", label, count(key)?)))
+        .collect();
+    if counts.is_empty() {
+        "tokens: -".to_string()
+    } else {
+        format!("tokens: {}", counts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_usage() {
+        let usage = serde_json::json!({
+            "input_tokens": 12,
+            "output_tokens": 340,
+            "cache_read_input_tokens": 9000,
+            "service_tier": "standard",
+        });
+        assert_eq!(
+            format_usage(Some(&usage)),
+            "tokens: in=12 out=340 cache_read=9000"
+        );
+        assert_eq!(format_usage(None), "tokens: -");
+        assert_eq!(format_usage(Some(&serde_json::json!({}))), "tokens: -");
+    }
+}
@@ -0,0 +1,291 @@
+use crate::conductor::InstanceResult;
+use crate::git;
+use crate::spawn;
+use anyhow::Context;
+use crossterm::style::Stylize;
+use serde::Deserialize;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// The part of results.json `actually apply` needs
+#[derive(Deserialize)]
+struct ResultsFile {
+    instances: Vec<InstanceResult>,
+}
+
+/// One file's part of a patch: its `diff --git` header and its hunks
+#[derive(Debug, Clone, PartialEq)]
+struct FilePatch {
+    header: String,
+    hunks: Vec<String>,
+}
+
+impl FilePatch {
+    /// Path from the `diff --git a/<path> b/<path>` line
+    fn path(&self) -> &str {
+        self.header
+            .lines()
+            .next()
+            .and_then(|line| line.rsplit_once(" b/"))
+            .map_or("?", |(_, path)| path)
+    }
+
+    /// The header followed by `hunks`
+    fn with_hunks(&self, hunks: &[String]) -> String {
+        let mut patch = self.header.clone();
+        for hunk in hunks {
+            patch.push_str(hunk);
+        }
+        patch
+    }
+}
+
+/// Split a `git diff` into files and hunks. Changes without hunks (binary
+/// files, renames, mode changes) are all header.
+fn parse_patch(patch: &str) -> Vec<FilePatch> {
+    let mut files: Vec<FilePatch> = Vec::new();
+    for line in patch.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FilePatch {
+                header: line.to_string(),
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            file.hunks.push(line.to_string());
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.push_str(line);
+        } else {
+            file.header.push_str(line);
+        }
+    }
+    files
+}
+
+/// An answer to "Apply this hunk?"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    Yes,
+    No,
+    Edit,
+    /// This and the file's remaining hunks
+    All,
+    /// None of the file's remaining hunks
+    Done,
+    Quit,
+}
+
+const HELP: &str = "y - apply this hunk
+n - do not apply this hunk
+e - edit this hunk in $EDITOR, then apply it
+a - apply this hunk and the rest of the file's hunks
+d - do not apply this hunk or the rest of the file's hunks
+q - quit; do not apply this hunk or any remaining ones
+? - print help";
+
+/// `actually apply`: walk through an instance's patch hunk by hunk and stage
+/// the accepted hunks in the repository in the current directory. Without
+/// `instance`, the run's suggested winner (or the judge's pick) is used.
+pub async fn run(run_dir: &Path, instance: Option<usize>) -> anyhow::Result<()> {
+    let path = run_dir.join("results.json");
+    let json = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let results: ResultsFile = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let result = match instance {
+        Some(id) => results
+            .instances
+            .iter()
+            .find(|r| r.instance_id == id)
+            .with_context(|| format!("No instance C{} in {}", id, path.display()))?,
+        None => results
+            .instances
+            .iter()
+            .find(|r| r.suggested_winner)
+            .or_else(|| results.instances.iter().find(|r| r.judge_rank == Some(1)))
+            .context("The run has no suggested winner; choose one with --instance")?,
+    };
+    let patch_name = result.patch.as_ref().with_context(|| {
+        format!(
+            "{} has no patch (only seeded or --auto-commit runs produce one)",
+            result.label()
+        )
+    })?;
+    let patch = std::fs::read_to_string(run_dir.join(patch_name))
+        .with_context(|| format!("Failed to read {}", patch_name))?;
+
+    let files = parse_patch(&patch);
+    println!(
+        "Applying {}'s changes ({} file{}) from {}",
+        result.label(),
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+        patch_name
+    );
+    let (selected, count) = select_hunks(&files)?;
+    if count == 0 {
+        println!("Nothing selected, nothing applied");
+        return Ok(());
+    }
+    git::apply_to_index(Path::new("."), &selected, "").await?;
+    println!(
+        "Applied and staged {} change{}; review them with `git diff --cached`",
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Ask about every hunk of `files`, returning the patch of the accepted
+/// ones and how many there are
+fn select_hunks(files: &[FilePatch]) -> anyhow::Result<(String, usize)> {
+    let mut selected = String::new();
+    let mut count = 0;
+    'files: for file in files {
+        println!();
+        println!("{}", file.header.trim_end().bold());
+        if file.hunks.is_empty() {
+            match ask("Apply this change [y,n,q,?]? ")? {
+                Choice::Yes | Choice::All => {
+                    selected.push_str(&file.header);
+                    count += 1;
+                }
+                Choice::Quit => break 'files,
+                _ => {}
+            }
+            continue;
+        }
+
+        let mut accepted: Vec<String> = Vec::new();
+        let mut hunks = file.hunks.iter().enumerate();
+        while let Some((i, hunk)) = hunks.next() {
+            print_hunk(hunk);
+            let prompt = format!(
+                "({}/{}) Apply this hunk to {} [y,n,e,a,d,q,?]? ",
+                i + 1,
+                file.hunks.len(),
+                file.path()
+            );
+            match ask(&prompt)? {
+                Choice::Yes => accepted.push(hunk.clone()),
+                Choice::No => {}
+                Choice::Edit => match edit_hunk(hunk)? {
+                    Some(edited) => accepted.push(edited),
+                    None => println!("Hunk left out"),
+                },
+                Choice::All => {
+                    accepted.push(hunk.clone());
+                    accepted.extend(hunks.by_ref().map(|(_, hunk)| hunk.clone()));
+                }
+                Choice::Done => break,
+                Choice::Quit => {
+                    count += accepted.len();
+                    if !accepted.is_empty() {
+                        selected.push_str(&file.with_hunks(&accepted));
+                    }
+                    break 'files;
+                }
+            }
+        }
+        count += accepted.len();
+        if !accepted.is_empty() {
+            selected.push_str(&file.with_hunks(&accepted));
+        }
+    }
+    Ok((selected, count))
+}
+
+fn print_hunk(hunk: &str) {
+    for line in hunk.lines() {
+        if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Prompt until a recognized answer is given; end of input quits
+fn ask(prompt: &str) -> io::Result<Choice> {
+    let stdin = io::stdin();
+    loop {
+        print!("{}", prompt.blue().bold());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            println!();
+            return Ok(Choice::Quit);
+        }
+        match answer.trim() {
+            "y" => return Ok(Choice::Yes),
+            "n" => return Ok(Choice::No),
+            "e" => return Ok(Choice::Edit),
+            "a" => return Ok(Choice::All),
+            "d" => return Ok(Choice::Done),
+            "q" => return Ok(Choice::Quit),
+            _ => println!("{}", HELP),
+        }
+    }
+}
+
+/// Open `hunk` in $EDITOR. Returns `None` if everything was deleted.
+fn edit_hunk(hunk: &str) -> io::Result<Option<String>> {
+    let temp_path = spawn::temp_file(
+        &format!(
+            "# Edit the hunk below. Lines starting with # are ignored.\n\
+             # Remove '-' lines by making them ' ' lines, remove '+' lines by deleting them.\n\
+             # Delete everything to leave the hunk out.\n{}",
+            hunk
+        ),
+        ".diff",
+    )?;
+
+    let status = spawn::edit(&temp_path)?;
+    if !status.success() {
+        return Ok(None);
+    }
+
+    let content = spawn::read_edited(&temp_path)?;
+    let edited: String = content
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    Ok((!edited.trim().is_empty()).then_some(edited))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patch() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                     index 1111111..2222222 100644\n\
+                     --- a/src/lib.rs\n\
+                     +++ b/src/lib.rs\n\
+                     @@ -1,2 +1,2 @@\n fn a() {}\n-fn b() {}\n+fn c() {}\n\
+                     @@ -10 +10 @@\n-x\n+y\n\
+                     diff --git a/logo.png b/logo.png\n\
+                     index 3333333..4444444 100644\n\
+                     GIT binary patch\n";
+        let files = parse_patch(patch);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path(), "src/lib.rs");
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[0].hunks[1], "@@ -10 +10 @@\n-x\n+y\n");
+        assert!(files[1].hunks.is_empty());
+        assert_eq!(files[1].path(), "logo.png");
+
+        let second_only = files[0].with_hunks(&files[0].hunks[1..]);
+        assert!(second_only.starts_with("diff --git a/src/lib.rs"));
+        assert!(second_only.ends_with("+++ b/src/lib.rs\n@@ -10 +10 @@\n-x\n+y\n"));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::cancel::CancellationToken;
+use crate::config::BackendConfig;
+use crate::session::{SessionError, SessionEvent, SessionResult};
+use crate::transcript::Transcript;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Built-in backend: Claude Code through the SDK
+pub const DEFAULT_BACKEND: &str = "claude";
+
+/// Placeholder in a backend command replaced by the implementation prompt
+const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+#[derive(Error, Debug)]
+pub enum BackendError {
+    #[error(
+        "Unknown backend `{0}` in --backends; define it under [backends.{0}] in the config file"
+    )]
+    Unknown(String),
+    #[error("Backend `{0}` has an empty command")]
+    EmptyCommand(String),
+}
+
+/// What an implementation instance runs on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backend {
+    pub name: String,
+    /// External agent command run in the workspace; `None` runs Claude Code
+    pub command: Option<Vec<String>>,
+    /// Extra environment for the session or command
+    pub env: HashMap<String, String>,
+    /// Model to run Claude Code with instead of `--impl-model`
+    pub model: Option<String>,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_BACKEND.to_string(),
+            command: None,
+            env: HashMap::new(),
+            model: None,
+        }
+    }
+}
+
+impl Backend {
+    /// Look up each of `names` (from `--backends`) in the config's
+    /// `[backends]` table; `claude` needs no entry
+    pub fn resolve(
+        names: &[String],
+        configured: &HashMap<String, BackendConfig>,
+    ) -> Result<Vec<Self>, BackendError> {
+        names
+            .iter()
+            .map(|name| match configured.get(name) {
+                Some(config) => {
+                    if config.command.as_ref().is_some_and(|c| c.is_empty()) {
+                        return Err(BackendError::EmptyCommand(name.clone()));
+                    }
+                    Ok(Self {
+                        name: name.clone(),
+                        command: config.command.clone(),
+                        env: config.env.clone(),
+                        model: config.model.clone(),
+                    })
+                }
+                None if name == DEFAULT_BACKEND => Ok(Self::default()),
+                None => Err(BackendError::Unknown(name.clone())),
+            })
+            .collect()
+    }
+
+    /// Backend for instance `id`: the list is assigned in order and repeats
+    /// when there are more instances than backends
+    pub fn for_instance(backends: &[Self], id: usize) -> Self {
+        if backends.is_empty() {
+            Self::default()
+        } else {
+            backends[id % backends.len()].clone()
+        }
+    }
+}
+
+/// Run an external agent `command` in `cwd` with `prompt`, substituted for
+/// `{prompt}` in its arguments or otherwise written to its stdin. Its output
+/// becomes the transcript, held to `max_transcript_bytes`; it succeeds if the
+/// command exits successfully.
+pub async fn run_command(
+    command: &[String],
+    cwd: &Path,
+    prompt: &str,
+    env: &HashMap<String, String>,
+    cancel: &CancellationToken,
+    events: Option<UnboundedSender<SessionEvent>>,
+    max_transcript_bytes: Option<usize>,
+) -> Result<SessionResult, SessionError> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(SessionError::Backend("empty command".to_string()));
+    };
+    tracing::debug!(program = %program, cwd = ?cwd, "Running backend command");
+
+    let prompt_in_args = args.iter().any(|a| a.contains(PROMPT_PLACEHOLDER));
+    let mut child = Command::new(program)
+        .args(args.iter().map(|a| a.replace(PROMPT_PLACEHOLDER, prompt)))
+        .current_dir(cwd)
+        .envs(env)
+        .stdin(if prompt_in_args {
+            Stdio::null()
+        } else {
+            Stdio::piped()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| SessionError::Backend(format!("failed to start {}: {}", program, e)))?;
+
+    // Write from a task so a command that talks before reading can't deadlock
+    if let Some(mut stdin) = child.stdin.take() {
+        let prompt = prompt.to_string();
+        tokio::spawn(async move {
+            let _ = stdin.write_all(prompt.as_bytes()).await;
+        });
+    }
+
+    let mut transcript = Transcript::new(max_transcript_bytes);
+    transcript.push_str(&format!("=== PROMPT ===\n{}\n\n", prompt));
+    transcript.push_str(&format!("=== SESSION ({}) ===\n", program));
+
+    let mut stdout = child.stdout.take().map(|s| BufReader::new(s).lines());
+    let mut stderr = child.stderr.take().map(|s| BufReader::new(s).lines());
+    while stdout.is_some() || stderr.is_some() {
+        let line = tokio::select! {
+            line = next_line(&mut stdout), if stdout.is_some() => line,
+            line = next_line(&mut stderr), if stderr.is_some() => line,
+            _ = cancel.cancelled() => {
+                tracing::debug!("Backend command cancelled, killing it");
+                child.kill().await.ok();
+                transcript.push_str("\n=== CANCELLED ===\n");
+                return Ok(SessionResult {
+                    transcript: transcript.into_string(),
+                    success: false,
+                    cost_usd: None,
+                    num_turns: None,
+                    cancelled: true,
+                    over_budget: false,
+                    error: None,
+                    summary: None,
+                });
+            }
+        };
+        if let Some(line) = line {
+            transcript.push_str(&line);
+            transcript.push('\n');
+            if let Some(events) = &events {
+                let _ = events.send(SessionEvent::Output(line));
+            }
+        }
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| SessionError::Backend(format!("failed to wait for {}: {}", program, e)))?;
+    let error = (!status.success())
+        .then(|| SessionError::Backend(format!("{} exited with {}", program, status)));
+    if let Some(e) = &error {
+        transcript.push_str(&format!("\n=== ERROR ===\n{}\n", e));
+    }
+    Ok(SessionResult {
+        transcript: transcript.into_string(),
+        success: error.is_none(),
+        cost_usd: None,
+        num_turns: None,
+        cancelled: false,
+        over_budget: false,
+        error,
+        summary: None,
+    })
+}
+
+/// Next line from one of the command's output streams, which is set to
+/// `None` once it ends (or fails)
+async fn next_line<R: tokio::io::AsyncBufRead + Unpin>(
+    lines: &mut Option<tokio::io::Lines<R>>,
+) -> Option<String> {
+    let line = match lines {
+        Some(l) => l.next_line().await.ok().flatten(),
+        None => None,
+    };
+    if line.is_none() {
+        *lines = None;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_assign_backends() {
+        let mut configured = HashMap::new();
+        configured.insert(
+            "local".to_string(),
+            BackendConfig {
+                command: None,
+                env: HashMap::from([(
+                    "ANTHROPIC_BASE_URL".to_string(),
+                    "http://localhost:4000".to_string(),
+                )]),
+                model: Some("qwen3-coder".to_string()),
+            },
+        );
+        let names = vec!["claude".to_string(), "local".to_string()];
+        let backends = Backend::resolve(&names, &configured).unwrap();
+        assert_eq!(Backend::for_instance(&backends, 0).name, "claude");
+        assert_eq!(Backend::for_instance(&backends, 1).name, "local");
+        assert_eq!(Backend::for_instance(&backends, 2).name, "claude");
+        assert_eq!(Backend::for_instance(&[], 5), Backend::default());
+
+        let unknown = Backend::resolve(&["codex".to_string()], &configured);
+        assert!(matches!(unknown, Err(BackendError::Unknown(name)) if name == "codex"));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::cancel::CancellationToken;
+use crate::conductor::InstanceResult;
+use crate::verify;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Reads the first number in the benchmark's output unless `--bench-pattern`
+/// says otherwise
+pub const DEFAULT_BENCH_PATTERN: &str = r"(\d+(?:\.\d+)?)";
+
+/// A benchmark to run in every successful workspace (`--bench`)
+#[derive(Debug, Clone)]
+pub struct Bench {
+    pub command: String,
+    /// Where the measurement is in the output: the first capture group of
+    /// the first match, or the whole match without groups
+    pub pattern: Regex,
+    /// Rank bigger numbers first (throughput) instead of smaller (time)
+    pub higher_is_better: bool,
+}
+
+/// One instance's benchmark run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub command: String,
+    /// The number read from the output; `None` if the pattern didn't match
+    pub value: Option<f64>,
+    /// `None` if the command couldn't start or was stopped for taking too long
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub higher_is_better: bool,
+}
+
+impl Bench {
+    /// Run the benchmark in `dir`, writing its output to `log`. Returns
+    /// `None` if `cancel` fires first.
+    pub async fn run(
+        &self,
+        dir: &Path,
+        env: &HashMap<String, String>,
+        log: &Path,
+        cancel: &CancellationToken,
+    ) -> Option<BenchResult> {
+        let output = verify::run_logged(&self.command, dir, env, log, cancel).await?;
+        // Skip the echoed command line, which may contain numbers of its own
+        let printed = output.text.split_once('\n').map_or("", |(_, rest)| rest);
+        Some(BenchResult {
+            command: self.command.clone(),
+            value: parse_value(&self.pattern, printed),
+            exit_code: output.exit_code,
+            duration_ms: output.duration_ms,
+            higher_is_better: self.higher_is_better,
+        })
+    }
+}
+
+/// The measurement `pattern` finds in `output`; thousands separators are
+/// ignored
+pub fn parse_value(pattern: &Regex, output: &str) -> Option<f64> {
+    let captures = pattern.captures(output)?;
+    let matched = captures.get(1).or_else(|| captures.get(0))?;
+    matched.as_str().replace(',', "").trim().parse().ok()
+}
+
+/// Markdown table of the instances' benchmark results, best first, with
+/// each value relative to the best. `None` if no instance was benchmarked.
+pub fn performance_table(results: &[InstanceResult]) -> Option<String> {
+    let mut measured: Vec<(String, f64)> = results
+        .iter()
+        .filter_map(|r| Some((r.label(), r.bench.as_ref()?.value?)))
+        .collect();
+    let failed: Vec<String> = results
+        .iter()
+        .filter(|r| r.bench.as_ref().is_some_and(|b| b.value.is_none()))
+        .map(InstanceResult::label)
+        .collect();
+    if measured.is_empty() && failed.is_empty() {
+        return None;
+    }
+    let higher_is_better = results
+        .iter()
+        .find_map(|r| r.bench.as_ref())
+        .is_some_and(|b| b.higher_is_better);
+    measured.sort_by(|a, b| {
+        let order = a.1.total_cmp(&b.1);
+        if higher_is_better {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+
+    let mut table = String::from("| Rank | Instance | Result | vs. best |\n|---|---|---|---|\n");
+    let best = measured.first().map(|&(_, value)| value);
+    for (rank, (label, value)) in measured.iter().enumerate() {
+        let relative = match best {
+            Some(best) if best != 0.0 => format!("{:.2}x", value / best),
+            _ => "-".to_string(),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            rank + 1,
+            label,
+            value,
+            relative
+        ));
+    }
+    for label in failed {
+        table.push_str(&format!("| - | {} | no result | - |\n", label));
+    }
+    Some(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value() {
+        let default = Regex::new(DEFAULT_BENCH_PATTERN).unwrap();
+        assert_eq!(parse_value(&default, "took 12.5 ms"), Some(12.5));
+        assert_eq!(parse_value(&default, "no numbers"), None);
+
+        let custom = Regex::new(r"throughput: ([\d,.]+) req/s").unwrap();
+        assert_eq!(
+            parse_value(&custom, "warmup 3\nthroughput: 1,250.5 req/s\n"),
+            Some(1250.5)
+        );
+    }
+}
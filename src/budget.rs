@@ -0,0 +1,324 @@
+use crate::cancel::CancellationToken;
+use crate::deadline::parse_duration;
+use crate::output::format_duration_ms;
+use crate::pause::PauseControl;
+use crate::spend::Spend;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the run is held against its budgets
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Interactive runs are asked about a budget once this share of it (in
+/// tenths) is used up, so the question comes before it is exceeded
+const ASK_AT_TENTHS: u64 = 9;
+
+/// What to do once the run is about to exceed a budget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetChoice {
+    /// Raise the limit to this amount (millionths of a dollar, or
+    /// milliseconds into the run) and carry on
+    Extend(u64),
+    /// Let the running instances finish, but start nothing more: no
+    /// instance still waiting to start, no evaluation
+    Stop,
+    /// Stop the running instances now: like Ctrl+C for the cost budget,
+    /// like the deadline passing for the deadline
+    Kill,
+}
+
+/// What a budget is held against
+#[derive(Debug, Clone)]
+enum Measure {
+    /// What the run's sessions have cost so far, in millionths of a dollar
+    Cost(Spend),
+    /// How long the run has been going since it started, in milliseconds
+    Time(Instant),
+}
+
+/// A ceiling on the run's cost (`--max-cost`) or duration (`--deadline`).
+/// Interactive runs are asked what to do once it is nearly used up;
+/// headless runs are stopped once it is.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    measure: Measure,
+    /// The budget as given, which extending adds on unless told otherwise
+    step: u64,
+    /// In the measure's units
+    limit: Arc<AtomicU64>,
+    stopped: Arc<AtomicBool>,
+    /// What killing cancels: the run, or for the deadline its sessions
+    kill: CancellationToken,
+}
+
+impl Budget {
+    /// `--max-cost`: `limit_usd` of what the run's sessions report spending
+    pub fn cost(limit_usd: f64, spend: Spend, cancel: &CancellationToken) -> Self {
+        Self::new(
+            Measure::Cost(spend),
+            (limit_usd * 1e6).round() as u64,
+            cancel,
+        )
+    }
+
+    /// `--deadline`: `duration` from `started`, killing `sessions`
+    pub fn time(started: Instant, duration: Duration, sessions: &CancellationToken) -> Self {
+        Self::new(
+            Measure::Time(started),
+            duration.as_millis() as u64,
+            sessions,
+        )
+    }
+
+    fn new(measure: Measure, limit: u64, kill: &CancellationToken) -> Self {
+        Self {
+            measure,
+            step: limit,
+            limit: Arc::new(AtomicU64::new(limit)),
+            stopped: Arc::new(AtomicBool::new(false)),
+            kill: kill.clone(),
+        }
+    }
+
+    /// What the budget is called in messages
+    pub fn name(&self) -> &'static str {
+        match self.measure {
+            Measure::Cost(_) => "cost budget",
+            Measure::Time(_) => "deadline",
+        }
+    }
+
+    fn spent(&self) -> u64 {
+        match &self.measure {
+            Measure::Cost(spend) => (spend.total_usd() * 1e6).round() as u64,
+            Measure::Time(started) => started.elapsed().as_millis() as u64,
+        }
+    }
+
+    fn limit(&self) -> u64 {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// `amount` in the budget's units for people, e.g. `$2.50` or `45m 00s`
+    pub fn format(&self, amount: u64) -> String {
+        match self.measure {
+            Measure::Cost(_) => format!("${:.2}", amount as f64 / 1e6),
+            Measure::Time(_) => format_duration_ms(amount),
+        }
+    }
+
+    /// The run has used up most of the budget and nobody has said what to
+    /// do yet
+    pub fn reached(&self) -> bool {
+        !self.is_stopped() && self.spent() * 10 >= self.limit() * ASK_AT_TENTHS
+    }
+
+    /// The run has used up the whole budget
+    pub fn exceeded(&self) -> bool {
+        self.spent() >= self.limit()
+    }
+
+    /// Told to stop gracefully: nothing more is to be started
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// What extending raises the budget to when no amount is given: the
+    /// budget as given on top of what is used
+    pub fn next_limit(&self) -> u64 {
+        self.spent().max(self.limit()) + self.step
+    }
+
+    /// How far the run is into its budget, said when asking
+    pub fn describe(&self) -> String {
+        let (spent, limit) = (self.format(self.spent()), self.format(self.limit()));
+        match self.measure {
+            Measure::Cost(_) => format!("The run has spent {} of its {} budget", spent, limit),
+            Measure::Time(_) => format!(
+                "The run has been going for {} of its {} deadline",
+                spent, limit
+            ),
+        }
+    }
+
+    /// What `e` takes an amount in, for `--plain`'s question
+    pub fn amount_hint(&self) -> &'static str {
+        match self.measure {
+            Measure::Cost(_) => "<usd>",
+            Measure::Time(_) => "<duration>",
+        }
+    }
+
+    /// A `--plain` answer: `e` to extend to `next_limit`, `e <amount>` to
+    /// extend to an amount past what is used, `s` to stop or `k` to kill
+    pub fn parse_answer(&self, answer: &str) -> Option<BudgetChoice> {
+        let mut words = answer.split_whitespace();
+        let choice = match (words.next()?, words.next()) {
+            ("e", None) => BudgetChoice::Extend(self.next_limit()),
+            ("e", Some(amount)) => {
+                let amount = match self.measure {
+                    Measure::Cost(_) => {
+                        let usd: f64 = amount.trim_start_matches('$').parse().ok()?;
+                        if usd.is_nan() || usd < 0.0 {
+                            return None;
+                        }
+                        (usd * 1e6).round() as u64
+                    }
+                    Measure::Time(_) => parse_duration(amount).ok()?.as_millis() as u64,
+                };
+                if amount <= self.spent() {
+                    return None;
+                }
+                BudgetChoice::Extend(amount)
+            }
+            ("s", None) => BudgetChoice::Stop,
+            ("k", None) => BudgetChoice::Kill,
+            _ => return None,
+        };
+        match words.next() {
+            None => Some(choice),
+            Some(_) => None,
+        }
+    }
+
+    pub fn apply(&self, choice: BudgetChoice) {
+        let name = self.name();
+        match choice {
+            BudgetChoice::Extend(limit) => {
+                tracing::info!(limit = %self.format(limit), "Extended the {}", name);
+                self.limit.store(limit, Ordering::Relaxed);
+            }
+            BudgetChoice::Stop => {
+                tracing::info!("Stopping at the {} once running instances finish", name);
+                self.stopped.store(true, Ordering::Relaxed);
+            }
+            BudgetChoice::Kill => {
+                tracing::info!("Stopping running instances at the {}", name);
+                self.kill.cancel();
+            }
+        }
+    }
+}
+
+/// Hold the run to `budgets` while its instances implement, for runs
+/// without the dashboard (which asks for itself): headless runs are
+/// stopped once one is used up, `--plain` runs pause and ask on stdin once
+/// one nearly is. Returns once `done` is cancelled, after any question has
+/// been answered.
+pub async fn watch(
+    budgets: Vec<Budget>,
+    interactive: bool,
+    pause: PauseControl,
+    cancel: CancellationToken,
+    done: CancellationToken,
+) {
+    loop {
+        tokio::select! {
+            _ = done.cancelled() => return,
+            _ = cancel.cancelled() => return,
+            _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+        }
+        if !interactive {
+            if let Some(budget) = budgets.iter().find(|b| b.exceeded()) {
+                tracing::warn!(limit = %budget.format(budget.limit()), "Run reached its {}, stopping", budget.name());
+                budget.apply(BudgetChoice::Kill);
+                return;
+            }
+            continue;
+        }
+        let Some(budget) = budgets.iter().find(|b| b.reached()) else {
+            continue;
+        };
+
+        // Nothing new starts while the question is open
+        let was_paused = pause.is_paused();
+        pause.set_paused(true);
+        println!("{}.", budget.describe());
+        let choice = loop {
+            let question = format!(
+                "[e] extend to {} (or e {}), [s] stop once running instances finish, [k] kill them now? ",
+                budget.format(budget.next_limit()),
+                budget.amount_hint()
+            );
+            let answer = tokio::task::spawn_blocking(move || ask(&question))
+                .await
+                .ok()
+                .and_then(|answer| answer.ok());
+            // With nobody left to answer, spend no more than what is running
+            let Some(Some(answer)) = answer else {
+                break BudgetChoice::Stop;
+            };
+            match budget.parse_answer(&answer) {
+                Some(choice) => break choice,
+                None => println!(
+                    "Answer e, e {} past what is used, s or k",
+                    budget.amount_hint()
+                ),
+            }
+        };
+        pause.set_paused(was_paused);
+        budget.apply(choice);
+    }
+}
+
+fn ask(question: &str) -> io::Result<Option<String>> {
+    print!("{}", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let spend = Spend::default();
+        let cancel = CancellationToken::default();
+        let budget = Budget::cost(5.0, spend.clone(), &cancel);
+        spend.add(4.0);
+        assert!(!budget.reached());
+        spend.add(0.6);
+        assert!(budget.reached() && !budget.exceeded());
+        spend.add(0.9);
+        assert!(budget.exceeded());
+
+        assert_eq!(
+            budget.parse_answer("e"),
+            Some(BudgetChoice::Extend(10_500_000))
+        );
+        assert_eq!(
+            budget.parse_answer("e $8"),
+            Some(BudgetChoice::Extend(8_000_000))
+        );
+        assert_eq!(budget.parse_answer("e 3"), None);
+        assert_eq!(budget.parse_answer("k now"), None);
+        budget.apply(BudgetChoice::Extend(8_000_000));
+        assert!(!budget.reached());
+
+        spend.add(3.0);
+        budget.apply(budget.parse_answer("s").unwrap());
+        assert!(budget.is_stopped() && !budget.reached());
+        assert!(!cancel.is_cancelled());
+        budget.apply(BudgetChoice::Kill);
+        assert!(cancel.is_cancelled());
+
+        let sessions = CancellationToken::default();
+        let started = Instant::now() - Duration::from_secs(55);
+        let deadline = Budget::time(started, Duration::from_secs(60), &sessions);
+        assert!(deadline.reached() && !deadline.exceeded());
+        assert_eq!(
+            deadline.parse_answer("e 2h"),
+            Some(BudgetChoice::Extend(7_200_000))
+        );
+        assert_eq!(deadline.parse_answer("e 30s"), None);
+        deadline.apply(BudgetChoice::Kill);
+        assert!(sessions.is_cancelled());
+    }
+}
@@ -0,0 +1,332 @@
+use crate::layout::RunLayout;
+use crate::output::path_in_run_dir;
+use crate::workspace::RECORD_FILES;
+use anyhow::Context;
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Written into the bundled run directory, so `actually import` knows
+/// where the run came from
+const MANIFEST: &str = "bundle.json";
+
+/// What replaces a secret
+const REDACTED: &str = "[REDACTED]";
+
+/// Environment values shorter than this aren't searched for, since they
+/// would match ordinary text
+const MIN_SECRET_LEN: usize = 8;
+
+/// Secrets recognized by their shape: API keys and tokens of common
+/// services, private keys, and `SOME_TOKEN=value` style assignments
+const SECRET_PATTERNS: &[&str] = &[
+    r"sk-ant-[A-Za-z0-9_\-]{20,}",
+    r"sk-(?:proj-)?[A-Za-z0-9_\-]{32,}",
+    r"gh[pousr]_[A-Za-z0-9]{36,}",
+    r"github_pat_[A-Za-z0-9_]{40,}",
+    r"AKIA[0-9A-Z]{16}",
+    r"xox[abposr]-[A-Za-z0-9\-]{10,}",
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+];
+
+/// Names of environment variables whose values are secret
+const SECRET_NAME: &str = r"[A-Z0-9_]*(?:API_KEY|SECRET|TOKEN|PASSWORD)[A-Z0-9_]*";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    /// Where the run directory was when it was exported
+    source_run_dir: PathBuf,
+    strip_workspaces: bool,
+    redacted: bool,
+}
+
+/// Replaces secrets in the text of a bundle
+struct Redactor {
+    patterns: Vec<Regex>,
+    assignment: Regex,
+    /// Values of secret-looking environment variables of this process
+    literals: Vec<String>,
+}
+
+impl Redactor {
+    fn new(env: impl Iterator<Item = (String, String)>) -> Self {
+        let name = Regex::new(&format!("^{}$", SECRET_NAME)).unwrap();
+        Self {
+            patterns: SECRET_PATTERNS
+                .iter()
+                .map(|p| Regex::new(p).unwrap())
+                .collect(),
+            // The value stops at quotes and backslashes so JSON files stay valid
+            assignment: Regex::new(&format!(
+                r#"\b({})(\s*[=:]\s*["']?)([^\s"'\\]{{{},}})"#,
+                SECRET_NAME, MIN_SECRET_LEN
+            ))
+            .unwrap(),
+            literals: env
+                .filter(|(key, value)| name.is_match(key) && value.len() >= MIN_SECRET_LEN)
+                .map(|(_, value)| value)
+                .collect(),
+        }
+    }
+
+    /// `text` with its secrets replaced, and how many there were
+    fn redact(&self, text: &str) -> (String, usize) {
+        let mut count = 0;
+        let mut text = text.to_string();
+        for literal in &self.literals {
+            count += text.matches(literal.as_str()).count();
+            text = text.replace(literal.as_str(), REDACTED);
+        }
+        for pattern in &self.patterns {
+            count += pattern.find_iter(&text).count();
+            text = pattern.replace_all(&text, REDACTED).into_owned();
+        }
+        let text = self
+            .assignment
+            .replace_all(&text, |caps: &regex::Captures| {
+                // Already replaced by a pattern above
+                if &caps[3] != REDACTED {
+                    count += 1;
+                }
+                format!("{}{}{}", &caps[1], &caps[2], REDACTED)
+            })
+            .into_owned();
+        (text, count)
+    }
+}
+
+/// `actually export`: pack the run in `run_dir` into an archive a teammate
+/// can `actually import` and open with `actually show`. The archive is
+/// made by `tar`, compressed by the file extension (`.tar.zst`, `.tar.gz`,
+/// ...). `strip_workspaces` leaves the workspaces' code out (the patches
+/// in the run directory keep the changes); `redact` replaces secrets in
+/// every text file and leaves out the workspaces' git histories, which
+/// can't be scrubbed.
+pub fn export(
+    run_dir: &Path,
+    bundle: &Path,
+    strip_workspaces: bool,
+    redact: bool,
+) -> anyhow::Result<()> {
+    if !run_dir.join("results.json").is_file() {
+        anyhow::bail!("{} is not a finished run directory", run_dir.display());
+    }
+    let run_dir = run_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", run_dir.display()))?;
+    let name = run_dir
+        .file_name()
+        .context("The run directory has no name")?
+        .to_os_string();
+
+    let staging = tempfile::tempdir()?;
+    let target = staging.path().join(&name);
+    let redactor = redact.then(|| Redactor::new(std::env::vars()));
+    let mut redacted = 0;
+    let layout = RunLayout::of(&run_dir);
+    let root = run_dir.clone();
+    let walker = WalkBuilder::new(&run_dir)
+        .hidden(false)
+        .parents(false)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            if redact && entry.file_name() == ".git" {
+                return false;
+            }
+            // The workspaces' record of the session is kept, not the code
+            // (which is in the patches)
+            let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+            if let Some(depth) = layout
+                .depth_in_workspace(relative)
+                .filter(|_| strip_workspaces)
+            {
+                let name = entry.file_name().to_string_lossy();
+                return depth == 1
+                    && entry.file_type().is_some_and(|t| t.is_file())
+                    && RECORD_FILES.contains(&name.as_ref());
+            }
+            true
+        })
+        .build();
+    for entry in walker {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(&run_dir)?;
+        let dest = target.join(relative);
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            std::fs::create_dir_all(&dest)?;
+            continue;
+        }
+        match (&redactor, std::fs::read_to_string(entry.path())) {
+            (Some(redactor), Ok(text)) => {
+                let (text, count) = redactor.redact(&text);
+                redacted += count;
+                std::fs::write(&dest, text)?;
+            }
+            _ => {
+                std::fs::copy(entry.path(), &dest)
+                    .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+            }
+        }
+    }
+    let manifest = Manifest {
+        source_run_dir: run_dir.clone(),
+        strip_workspaces,
+        redacted: redact,
+    };
+    std::fs::write(
+        target.join(MANIFEST),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    let bundle_path = std::env::current_dir()?.join(bundle);
+    let packed = tar(&[
+        "-caf".as_ref(),
+        bundle_path.as_os_str(),
+        "-C".as_ref(),
+        staging.path().as_os_str(),
+        name.as_os_str(),
+    ]);
+    if packed.is_err() {
+        let _ = std::fs::remove_file(&bundle_path);
+    }
+    packed?;
+    println!("Exported {} to {}", run_dir.display(), bundle.display());
+    if redact {
+        println!("Secrets redacted: {}", redacted);
+    }
+    Ok(())
+}
+
+/// `actually import`: unpack a bundle from `actually export` into
+/// `into`, pointing its workspace paths at their new place
+pub fn import(bundle: &Path, into: &Path) -> anyhow::Result<()> {
+    let unpacked = tempfile::tempdir_in(into)
+        .with_context(|| format!("Failed to unpack into {}", into.display()))?;
+    tar(&[
+        "-xf".as_ref(),
+        bundle.as_os_str(),
+        "-C".as_ref(),
+        unpacked.path().as_os_str(),
+    ])?;
+    let entries: Vec<PathBuf> = std::fs::read_dir(unpacked.path())?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    let [run] = entries.as_slice() else {
+        anyhow::bail!("{} is not a bundle from actually export", bundle.display());
+    };
+    let manifest: Manifest = std::fs::read_to_string(run.join(MANIFEST))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .with_context(|| format!("{} is not a bundle from actually export", bundle.display()))?;
+
+    let dest = into.join(run.file_name().context("Empty bundle")?);
+    if dest.exists() {
+        anyhow::bail!("{} already exists", dest.display());
+    }
+    std::fs::rename(run, &dest)?;
+    let dest = dest.canonicalize()?;
+    for file in ["results.json", "checkpoint.json"] {
+        let path = dest.join(file);
+        let Some(mut json) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+        else {
+            continue;
+        };
+        rebase_paths(&mut json, &manifest.source_run_dir, &dest);
+        std::fs::write(&path, serde_json::to_string_pretty(&json)?)?;
+    }
+
+    println!("Imported {}", dest.display());
+    if manifest.strip_workspaces {
+        println!("Its workspaces hold only their session logs; the changes are in the patches");
+    }
+    println!("Open it with: actually show {}", dest.display());
+    Ok(())
+}
+
+/// Point every path under `from` in `json` to the same place under `to`
+fn rebase_paths(json: &mut Value, from: &Path, to: &Path) {
+    match json {
+        Value::String(s) => {
+            if let Some(rest) = path_in_run_dir(Path::new(s.as_str()), from) {
+                *s = to.join(rest).to_string_lossy().into_owned();
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| rebase_paths(value, from, to)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|value| rebase_paths(value, from, to)),
+        _ => {}
+    }
+}
+
+pub fn tar(args: &[&std::ffi::OsStr]) -> anyhow::Result<()> {
+    let output = Command::new("tar")
+        .args(args)
+        .output()
+        .context("Failed to run tar")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let env = [
+            ("MY_API_KEY".to_string(), "hunter2hunter2".to_string()),
+            ("HOME".to_string(), "/home/someone".to_string()),
+        ];
+        let redactor = Redactor::new(env.into_iter());
+        let (text, count) = redactor.redact(
+            "key hunter2hunter2 in /home/someone\n\
+             export GITHUB_TOKEN=\"ghs_abcdefgh\"\n\
+             {\"text\": \"ANTHROPIC_API_KEY=sk-ant-REDACTED\\n\"}\n\
+             input_tokens: 123456789",
+        );
+        assert_eq!(
+            text,
+            "key [REDACTED] in /home/someone\n\
+             export GITHUB_TOKEN=\"[REDACTED]\"\n\
+             {\"text\": \"ANTHROPIC_API_KEY=[REDACTED]\\n\"}\n\
+             input_tokens: 123456789"
+        );
+        assert_eq!(count, 3);
+
+        let mut json = serde_json::json!({ "workspace_path": "/tmp/run/c0", "n": 1 });
+        rebase_paths(&mut json, Path::new("/tmp/run"), Path::new("/home/me/run"));
+        assert_eq!(json["workspace_path"], "/home/me/run/c0");
+
+        let mut json = serde_json::json!({
+            "instances": [
+                { "workspace_path": "./actually-123/c0", "patch": "c0.patch" },
+                { "workspace_path": "runs/actually-123/c1" },
+                { "workspace_path": "/elsewhere/actually-123/c2" },
+            ]
+        });
+        rebase_paths(
+            &mut json,
+            Path::new("/tmp/runs/actually-123"),
+            Path::new("/home/me/actually-123"),
+        );
+        let instances = &json["instances"];
+        assert_eq!(instances[0]["workspace_path"], "/home/me/actually-123/c0");
+        assert_eq!(instances[0]["patch"], "c0.patch");
+        assert_eq!(instances[1]["workspace_path"], "/home/me/actually-123/c1");
+        assert_eq!(instances[2]["workspace_path"], "/elsewhere/actually-123/c2");
+    }
+}
@@ -0,0 +1,156 @@
+use futures::future::select_all;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Cooperative cancellation shared by everything a run starts. Cancelling
+/// doesn't abort tasks: sessions notice, disconnect cleanly and return what
+/// they have, so transcripts and results still get written.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<watch::Sender<bool>>,
+    /// Cancelling the parent cancels this token too, but not the other way round
+    parent: Option<Arc<CancellationToken>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self {
+            cancelled: Arc::new(watch::Sender::new(false)),
+            parent: None,
+        }
+    }
+}
+
+impl CancellationToken {
+    /// A token that is cancelled along with this one, and can also be
+    /// cancelled on its own without stopping anything else
+    pub fn child(&self) -> Self {
+        Self {
+            parent: Some(Arc::new(self.clone())),
+            ..Self::default()
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.lineage().any(|token| *token.cancelled.borrow())
+    }
+
+    /// Resolve once the token (or one of its parents) is cancelled
+    pub async fn cancelled(&self) {
+        let mut cancelled: Vec<_> = self
+            .lineage()
+            .map(|token| token.cancelled.subscribe())
+            .collect();
+        // The senders live in `self`, so the channels can't close while we wait
+        let _ = select_all(
+            cancelled
+                .iter_mut()
+                .map(|cancelled| Box::pin(cancelled.wait_for(|cancelled| *cancelled))),
+        )
+        .await;
+    }
+
+    /// This token, then its parent, its parent's parent and so on
+    fn lineage(&self) -> impl Iterator<Item = &CancellationToken> {
+        std::iter::successors(Some(self), |token| token.parent.as_deref())
+    }
+
+    /// Drive `future` to completion unless the token is cancelled first, in
+    /// which case it is dropped and `None` is returned
+    pub async fn run_until_cancelled<F: Future>(&self, future: F) -> Option<F::Output> {
+        tokio::select! {
+            output = future => Some(output),
+            _ = self.cancelled() => None,
+        }
+    }
+
+    /// Cancel on SIGINT or SIGTERM. A second signal exits immediately, for
+    /// when cleaning up takes too long.
+    pub fn cancel_on_signals(&self, interactive: bool) {
+        let token = self.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            if interactive {
+                println!(
+                    "\nInterrupted: stopping instances and writing results (again to force quit)"
+                );
+            } else {
+                tracing::info!("Received shutdown signal, stopping instances and writing results");
+            }
+            token.cancel();
+            shutdown_signal().await;
+            std::process::exit(130);
+        });
+    }
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+        }
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellation() {
+        // A waiter that is already blocked when the token is cancelled wakes up
+        let token = CancellationToken::default();
+        let waiter = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Cancelling a child leaves its parent running; cancelling the
+        // parent reaches every child, including one already waiting
+        let parent = CancellationToken::default();
+        let child = parent.child();
+        let sibling = parent.child();
+        sibling.cancel();
+        assert!(sibling.is_cancelled() && !parent.is_cancelled() && !child.is_cancelled());
+        let waiter = tokio::spawn({
+            let child = child.clone();
+            async move { child.cancelled().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        parent.cancel();
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(child.is_cancelled());
+        let pending = std::future::pending::<()>();
+        assert_eq!(child.run_until_cancelled(pending).await, None);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::conductor::InstanceResult;
+use crate::output::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Checkpoint file name inside the run directory
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("Failed to access checkpoint {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("Failed to read checkpoint {0}: {1}")]
+    ParseFailed(PathBuf, serde_json::Error),
+    #[error("Failed to serialize checkpoint: {0}")]
+    SerializeFailed(#[from] serde_json::Error),
+}
+
+/// How far a run got. Phases before this one are complete and are not
+/// redone on `--resume`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    /// Collecting strategies (those in `strategies` are done)
+    #[default]
+    Strategies,
+    /// Strategies reviewed; implementing (those in `finished` are done)
+    Implementation,
+    /// Every instance finished; cross-review, judging and tournaments remain
+    Evaluation,
+    Complete,
+}
+
+impl Phase {
+    /// How the phase is named in checkpoint.json and to hooks (`ACTUALLY_PHASE`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Strategies => "strategies",
+            Self::Implementation => "implementation",
+            Self::Evaluation => "evaluation",
+            Self::Complete => "complete",
+        }
+    }
+}
+
+/// Conductor state persisted to `checkpoint.json` as a run progresses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub prompt: String,
+    pub phase: Phase,
+    pub strategies: Vec<SavedStrategy>,
+    /// Instances whose implementation finished (successfully or not)
+    pub finished: Vec<FinishedInstance>,
+    /// Where the workspaces are, when not in the run directory
+    /// (`--workspace-dir`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedStrategy {
+    pub markdown: String,
+    /// Empty in checkpoints from before titles, which derive it again
+    #[serde(default)]
+    pub title: String,
+    pub transcript: String,
+    pub failed: bool,
+    pub error: Option<String>,
+    pub manually_edited: bool,
+    /// Taken from an earlier run with `--seed-strategies`
+    #[serde(default)]
+    pub imported: bool,
+    /// Left out of implementation in review (or not picked with `--candidates`)
+    pub skipped: bool,
+    pub duration_ms: Option<u64>,
+    /// Extra instructions for this instance's implementation, from review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+/// An instance result together with its transcript, which `InstanceResult`
+/// leaves out of its serialized form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinishedInstance {
+    #[serde(flatten)]
+    pub result: InstanceResult,
+    pub transcript: String,
+}
+
+impl FinishedInstance {
+    pub fn new(result: &InstanceResult) -> Self {
+        Self {
+            result: result.clone(),
+            transcript: result.transcript.clone(),
+        }
+    }
+
+    pub fn into_result(self) -> InstanceResult {
+        InstanceResult {
+            transcript: self.transcript,
+            ..self.result
+        }
+    }
+}
+
+impl Checkpoint {
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Load the checkpoint a previous run left in `run_dir`
+    pub fn load(run_dir: &Path) -> Result<Self, CheckpointError> {
+        let path = run_dir.join(CHECKPOINT_FILE);
+        let json =
+            std::fs::read_to_string(&path).map_err(|e| CheckpointError::Io(path.clone(), e))?;
+        serde_json::from_str(&json).map_err(|e| CheckpointError::ParseFailed(path, e))
+    }
+
+    /// Atomically replace the checkpoint in `run_dir`
+    pub fn save(&self, run_dir: &Path) -> Result<(), CheckpointError> {
+        let path = run_dir.join(CHECKPOINT_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(&path, json.as_bytes()).map_err(|e| CheckpointError::Io(path, e))
+    }
+
+    /// The strategies this run implemented, marked imported, for a later run
+    /// to start from (`--seed-strategies`)
+    pub fn seed_strategies(&self) -> Vec<SavedStrategy> {
+        self.strategies
+            .iter()
+            .filter(|s| !s.failed && !s.skipped)
+            .map(|s| SavedStrategy {
+                manually_edited: false,
+                imported: true,
+                duration_ms: None,
+                ..s.clone()
+            })
+            .collect()
+    }
+
+    /// Result of instance `id`, if its implementation already finished
+    pub fn finished_instance(&self, id: usize) -> Option<InstanceResult> {
+        self.finished
+            .iter()
+            .find(|f| f.result.instance_id == id)
+            .map(|f| f.clone().into_result())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_round_trip_keeps_transcripts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut checkpoint = Checkpoint::new("task");
+        checkpoint.phase = Phase::Implementation;
+        checkpoint
+            .finished
+            .push(FinishedInstance::new(&InstanceResult {
+                instance_id: 2,
+                success: true,
+                transcript: "=== SESSION ===".to_string(),
+                ..Default::default()
+            }));
+        checkpoint.save(dir.path()).unwrap();
+
+        let loaded = Checkpoint::load(dir.path()).unwrap();
+        assert_eq!(loaded.prompt, "task");
+        assert_eq!(loaded.phase, Phase::Implementation);
+        let result = loaded.finished_instance(2).unwrap();
+        assert!(result.success);
+        assert_eq!(result.transcript, "=== SESSION ===");
+        assert!(loaded.finished_instance(0).is_none());
+    }
+
+    #[test]
+    fn test_seed_strategies() {
+        let saved = |markdown: &str, failed, skipped| SavedStrategy {
+            markdown: markdown.to_string(),
+            failed,
+            skipped,
+            manually_edited: true,
+            duration_ms: Some(1000),
+            ..Default::default()
+        };
+        let mut checkpoint = Checkpoint::new("task");
+        checkpoint.strategies = vec![
+            saved("**A** cache", false, false),
+            saved("", true, false),
+            saved("**B** rewrite", false, true),
+            saved("**C** index", false, false),
+        ];
+        let seeds = checkpoint.seed_strategies();
+        let markdown: Vec<&str> = seeds.iter().map(|s| s.markdown.as_str()).collect();
+        assert_eq!(markdown, ["**A** cache", "**C** index"]);
+        assert!(seeds
+            .iter()
+            .all(|s| s.imported && !s.manually_edited && s.duration_ms.is_none()));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::bundle;
+use crate::conductor::InstanceResult;
+use crate::layout::RunLayout;
+use crate::quota::{dir_size, format_size};
+use crate::workspace;
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Which workspaces are left whole once a run is over
+/// (`--keep-workspaces`); the others are stripped to their session record
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeepWorkspaces {
+    /// Every workspace
+    #[default]
+    All,
+    /// The suggested winner, the judge's and the tournament's picks, and
+    /// the synthesized solution
+    Winners,
+    /// None of them
+    None,
+}
+
+/// Whether `result` is one of the run's picks: the suggested winner, the
+/// judge's first choice, the tournament winner (the one nobody beat) or a
+/// synthesis
+fn is_winner(result: &InstanceResult, results: &[InstanceResult]) -> bool {
+    let tournament_winner = !result.defeated.is_empty()
+        && !results
+            .iter()
+            .any(|r| r.defeated.contains(&result.instance_id));
+    result.suggested_winner
+        || result.judge_rank == Some(1)
+        || tournament_winner
+        || !result.synthesized_from.is_empty()
+}
+
+/// Strip the workspaces `keep` doesn't keep down to their session logs,
+/// strategy and notes (the changes stay in the run's patches). With
+/// `archive`, each is first packed into `<workspace>.tar.gz` among its
+/// instance's files in `run_dir`.
+/// Returns how many workspaces were stripped and how many bytes that freed.
+pub fn prune_workspaces(
+    results: &[InstanceResult],
+    run_dir: &Path,
+    keep: KeepWorkspaces,
+    archive: bool,
+) -> anyhow::Result<(usize, u64)> {
+    let (mut pruned, mut freed) = (0, 0);
+    let layout = RunLayout::of(run_dir);
+    for result in results {
+        let kept = match keep {
+            KeepWorkspaces::All => true,
+            KeepWorkspaces::Winners => is_winner(result, results),
+            KeepWorkspaces::None => false,
+        };
+        let dir = Path::new(&result.workspace_path);
+        if kept || result.workspace_path.is_empty() || !dir.is_dir() {
+            continue;
+        }
+        let (Some(parent), Some(name)) = (dir.parent(), dir.file_name()) else {
+            continue;
+        };
+        if archive {
+            layout.create_artifact_dir(run_dir, result.instance_id)?;
+            let stem = layout.stem(dir, result.instance_id, result.name.as_deref());
+            let archive = std::env::current_dir()?.join(layout.artifact(
+                run_dir,
+                result.instance_id,
+                &format!("{}.tar.gz", stem),
+            ));
+            bundle::tar(&[
+                "-czf".as_ref(),
+                archive.as_os_str(),
+                "-C".as_ref(),
+                parent.as_os_str(),
+                name,
+            ])
+            .with_context(|| format!("Failed to archive {}", dir.display()))?;
+        }
+        let before = dir_size(dir);
+        workspace::strip(dir).with_context(|| format!("Failed to clean up {}", dir.display()))?;
+        freed += before.saturating_sub(dir_size(dir));
+        pruned += 1;
+    }
+    Ok((pruned, freed))
+}
+
+/// The outcome of `prune_workspaces`, for people
+pub fn describe(pruned: usize, freed: u64, archive: bool) -> String {
+    format!(
+        "{} {} workspace{} ({} freed)",
+        if archive { "Archived" } else { "Cleaned up" },
+        pruned,
+        if pruned == 1 { "" } else { "s" },
+        format_size(freed)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_workspaces() {
+        let run_dir = tempfile::tempdir().unwrap();
+        let results: Vec<InstanceResult> = (0..3)
+            .map(|id| {
+                let dir = run_dir.path().join(format!("c{}", id));
+                std::fs::create_dir_all(dir.join("src")).unwrap();
+                std::fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+                std::fs::write(dir.join("session.log"), "log").unwrap();
+                InstanceResult {
+                    instance_id: id,
+                    workspace_path: dir.to_string_lossy().into_owned(),
+                    judge_rank: Some(id + 1),
+                    defeated: if id == 2 { vec![1] } else { Vec::new() },
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let (pruned, freed) =
+            prune_workspaces(&results, run_dir.path(), KeepWorkspaces::Winners, false).unwrap();
+        // C0 is the judge's pick and C2 won the tournament
+        assert_eq!((pruned, freed), (1, 12));
+        assert!(run_dir.path().join("c0/src/main.rs").is_file());
+        assert!(!run_dir.path().join("c1/src").exists());
+        assert!(run_dir.path().join("c1/session.log").is_file());
+        assert!(run_dir.path().join("c2/src/main.rs").is_file());
+    }
+}
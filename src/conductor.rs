@@ -1,11 +1,50 @@
-use crate::session::{ClaudeSession, SessionResult};
+use crate::api_log::API_LOG_FILE;
+use crate::backend::{self, Backend};
+use crate::bench::{Bench, BenchResult};
+use crate::budget::{self, Budget};
+use crate::cancel::CancellationToken;
+use crate::checkpoint::{Checkpoint, FinishedInstance, Phase, SavedStrategy};
+use crate::config::{BuildCacheConfig, Criterion, HooksConfig, WinnerWeights};
+use crate::critique::{cross_review, Critique};
+use crate::dashboard::{self, DashboardEvent, StatusBar};
+use crate::deadline::Deadline;
+use crate::debate::{run_debate, Side};
+use crate::event_stream::{self, StreamEvent};
+use crate::git::{self, AutoCommit, DiffStat};
+use crate::hooks::{self, Hook, HookError};
+use crate::icons::{Status, StatusIcons};
+use crate::judge::{self, judge, tournament, Match, Verdict};
+use crate::layout::RunLayout;
+use crate::names::{self, InstanceNames};
+use crate::output::{format_duration_ms, write_atomic};
+use crate::pause::PauseControl;
+use crate::protect::Protection;
+use crate::quota::{format_size, SizeAction, SizeLimit, SizeWatch};
+use crate::session::{
+    ClaudeSession, ModelRetry, SessionError, SessionEvent, SessionResult, TranscriptDetail,
+};
+use crate::spawn;
+use crate::spend::Spend;
 use crate::strategy::{
-    build_implementation_prompt, build_strategy_prompt, parse_strategy, Strategy,
+    build_implementation_prompt, build_strategy_prompt, build_synthesis_prompt, parse_strategy,
+    render_claude_md, render_strategy_md, ExclusionStrength, FailedStrategies, StackConstraints,
+    Strategy, StrategyDepth, Variation, SELF_SUMMARY_PROMPT,
+};
+use crate::strategy_source::StrategySource;
+use crate::tail::TRANSCRIPT_LOG_SUFFIX;
+use crate::text_edit::{EditOutcome, TextEditor};
+use crate::transcript::{truncate_middle, LiveLog};
+use crate::verify::{self, Check, CheckKind, VerifyMode};
+use crate::vote::vote;
+use crate::winner::suggest_winner;
+use crate::workspace::{
+    build_cache_env, instance_env, Workspace, ACTUALLY_IGNORE_FILE, NOTES_FILE, STRATEGY_FILE,
 };
-use crate::workspace::Workspace;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
     ExecutableCommand,
 };
 use futures::future::join_all;
@@ -13,28 +52,198 @@ use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
-use std::io::{stdout, Write};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{stdout, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tempfile::NamedTempFile;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InstanceResult {
     pub instance_id: usize,
+    /// Human-friendly name from `--names`; `None` goes by `C<id>`
+    #[serde(default)]
+    pub name: Option<String>,
     pub strategy: String,
     pub workspace_path: String,
     pub success: bool,
     pub error: Option<String>,
+    #[serde(skip)]
     pub transcript: String,
+    /// Implementation session cost in USD, as reported by the SDK
+    pub cost_usd: Option<f64>,
+    /// Wall-clock time spent in the implementation phase
+    pub duration_ms: Option<u64>,
+    /// Number of turns the implementation session took
+    pub num_turns: Option<u32>,
+    /// Changes relative to the workspace baseline (seeded or auto-committed runs)
+    pub diffstat: Option<DiffStat>,
+    /// Path of the instance's changes as a patch within the run directory,
+    /// e.g. `c0.patch` (runs with a baseline)
+    #[serde(default)]
+    pub patch: Option<String>,
+    /// Critiques of this instance written by other instances (`--cross-review`)
+    pub critiques: Vec<Critique>,
+    /// Position in the judge's ranking, 1 being best (`--judge`)
+    pub judge_rank: Option<usize>,
+    /// The judge's weighted rubric score, 0-10 (`--judge`)
+    #[serde(default)]
+    pub judge_score: Option<f64>,
+    /// Instances this one was synthesized from; empty for regular instances
+    pub synthesized_from: Vec<usize>,
+    /// Instances this one beat in tournament matches (`--tournament`)
+    pub defeated: Vec<usize>,
+    /// Model the implementation ran with (`None`: the Claude Code default)
+    pub model: Option<String>,
+    /// Stopped for passing `--max-cost-per-instance`
+    #[serde(default)]
+    pub over_budget: bool,
+    /// Stopped mid-session when the run reached its `--deadline`
+    #[serde(default)]
+    pub past_deadline: bool,
+    /// Size of the workspace when it passed `--max-workspace-size`
+    #[serde(default)]
+    pub oversized_workspace_bytes: Option<u64>,
+    /// Protected paths (`[protect]`) the agent changed anyway
+    #[serde(default)]
+    pub protected_violations: Vec<String>,
+    /// Model the instance started on before moving to `--fallback-model`
+    #[serde(default)]
+    pub fallback_from: Option<String>,
+    /// Backend the instance ran on, when `--backends` was given
+    #[serde(default)]
+    pub backend: Option<String>,
+    /// The agent's NOTES.md: decisions, trade-offs, how to run it
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// The agent's answer to `SELF_SUMMARY_PROMPT`: what it built, what's untested
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// The test command run in the workspace after implementation (`--verify`)
+    #[serde(default)]
+    pub verification: Option<Check>,
+    /// The build command run before the tests (`--build`); `passed` says
+    /// whether the solution compiles at all
+    #[serde(default)]
+    pub build: Option<Check>,
+    /// The lint command run after the tests (`--lint`)
+    #[serde(default)]
+    pub lint: Option<Check>,
+    /// The coverage command run last (`--coverage`), with its line coverage
+    #[serde(default)]
+    pub coverage: Option<Check>,
+    /// The benchmark's measurement, for successful instances (`--bench`)
+    #[serde(default)]
+    pub bench: Option<BenchResult>,
+    /// Weighted 0-1 combination of checks, judging, change size and cost
+    #[serde(default)]
+    pub composite_score: Option<f64>,
+    /// Best composite score of the run: a suggestion, not a verdict
+    #[serde(default)]
+    pub suggested_winner: bool,
 }
 
-#[derive(Debug, Clone)]
+impl InstanceResult {
+    /// How the instance is shown to people: its name, or `C<id>`
+    pub fn label(&self) -> String {
+        names::label(self.instance_id, self.name.as_deref())
+    }
+
+    /// The instance's workspace as an absolute path, for agents and commands
+    /// running elsewhere. Runs from before run directories were made
+    /// absolute recorded it relative to where they were started.
+    pub fn workspace(&self) -> PathBuf {
+        std::path::absolute(&self.workspace_path)
+            .unwrap_or_else(|_| PathBuf::from(&self.workspace_path))
+    }
+}
+
+/// Label of instance `id` among `results`, `C<id>` if it isn't one of them
+pub fn label_of(results: &[InstanceResult], id: usize) -> String {
+    results
+        .iter()
+        .find(|r| r.instance_id == id)
+        .map_or_else(|| names::label(id, None), InstanceResult::label)
+}
+
+#[derive(Debug, Clone, Default)]
 struct StrategyInfo {
     strategy: Strategy,
     transcript: String,
     failed: bool,
     error: Option<String>,
     manually_edited: bool,
+    /// Taken from an earlier run (`--seed-strategies`)
+    imported: bool,
+    /// Moderator's verdict from the latest debate this strategy took part in
+    debate: Option<String>,
+    /// Borda points from the other strategy agents' votes (`--vote`)
+    votes: Option<usize>,
+    /// Picked for implementation in the review TUI (`--candidates`)
+    selected: bool,
+    /// Kept for exclusion lists, but not implemented
+    skipped: bool,
+    /// Wall-clock time the strategy query took
+    duration_ms: Option<u64>,
+    /// Added to this instance's implementation prompt after the strategy
+    instructions: Option<String>,
+}
+
+/// Wall-clock time spent in each phase of a run (results.json `timings`)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Timings {
+    pub strategy_ms: u64,
+    /// Time spent in the review TUI (interactive runs only)
+    pub review_ms: Option<u64>,
+    /// From launching the implementations until the last one finished
+    pub implementation_ms: Option<u64>,
+    /// Cross-review, judging, synthesis and tournament
+    pub evaluation_ms: Option<u64>,
+    pub total_ms: u64,
+    pub instances: Vec<InstanceTiming>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceTiming {
+    pub instance_id: usize,
+    pub strategy_ms: Option<u64>,
+    pub implementation_ms: Option<u64>,
+}
+
+impl Timings {
+    /// One-line phase breakdown, e.g. "strategies 41.2s, review 1m 03s, ..."
+    pub fn summary(&self) -> String {
+        let mut phases = vec![format!(
+            "strategies {}",
+            format_duration_ms(self.strategy_ms)
+        )];
+        for (name, ms) in [
+            ("review", self.review_ms),
+            ("implementation", self.implementation_ms),
+            ("evaluation", self.evaluation_ms),
+        ] {
+            if let Some(ms) = ms {
+                phases.push(format!("{} {}", name, format_duration_ms(ms)));
+            }
+        }
+        format!(
+            "{} (total {})",
+            phases.join(", "),
+            format_duration_ms(self.total_ms)
+        )
+    }
+}
+
+/// What the review TUI's in-place editor is editing
+#[derive(Clone, Copy, PartialEq)]
+enum InlineEdit {
+    Strategy,
+    /// The instance's extra instructions
+    Instructions,
 }
 
 /// Result of a chat session with Claude about a strategy
@@ -44,25 +253,283 @@ enum ChatResult {
     Error(String),
 }
 
+/// Settings for a single run, resolved from CLI flags and the config file
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub num_instances: usize,
+    pub dry_run: bool,
+    pub interactive: bool,
+    /// Review strategies as questions and answers on stdin/stdout, and show
+    /// no dashboard (`--plain`)
+    pub plain: bool,
+    /// Model for strategy generation and other planning queries
+    pub strategy_model: Option<String>,
+    /// Model for implementation (`None`: the Claude Code default)
+    pub impl_model: Option<String>,
+    pub build_cache: BuildCacheConfig,
+    /// Copy this directory (respecting .gitignore) into every workspace
+    pub seed_dir: Option<PathBuf>,
+    /// Paths of `seed_dir` agents mustn't change (`[protect]`)
+    pub protection: Protection,
+    /// CLAUDE.md template written into every workspace
+    pub claude_md_template: Option<String>,
+    /// Load the user's ~/.claude settings and the project's CLAUDE.md/.claude
+    /// settings into every session, as an interactive `claude` would
+    pub inherit_claude_settings: bool,
+    /// Commit agent work in each workspace (at the end, or after every tool batch)
+    pub auto_commit: Option<AutoCommit>,
+    /// How each workspace is built after implementation (`Off` without `--build`)
+    pub build: VerifyMode,
+    /// How each workspace is checked after implementation
+    pub verify: VerifyMode,
+    /// How each workspace is linted after implementation (`Off` without `--lint`)
+    pub lint: VerifyMode,
+    /// How test coverage is measured in each workspace (`Off` without `--coverage`)
+    pub coverage: VerifyMode,
+    /// Benchmark run in every successful workspace (`--bench`)
+    pub bench: Option<Bench>,
+    /// Shell commands run around instances and phases (`[hooks]`)
+    pub hooks: HooksConfig,
+    /// Extra environment for each instance, before its placeholders are
+    /// filled in (`[env]`)
+    pub env: HashMap<String, String>,
+    /// Where instance workspaces are created: the run directory, or
+    /// scratch storage outside it (`--workspace-dir`, `--tmpfs`)
+    pub workspace_dir: PathBuf,
+    /// How the run directory arranges the instances' workspaces and files
+    pub layout: RunLayout,
+    /// After implementation, have instances critique each other's work
+    pub cross_review: bool,
+    /// Rounds per side when debating two strategies in the review TUI
+    pub debate_rounds: usize,
+    /// After implementation, have a judge agent rank the instances
+    pub judge: bool,
+    /// After judging, merge the top instances into a new `c-synth` workspace
+    pub synthesize: bool,
+    /// After implementation, pick a winner through pairwise judged elimination
+    pub tournament: bool,
+    /// Before review, have each strategy agent rank the other strategies
+    pub vote: bool,
+    /// Criteria the judge scores solutions on
+    pub rubric: Vec<Criterion>,
+    /// External judge used instead of the judge agent (`--judge-command`)
+    pub judge_command: Option<String>,
+    /// How the suggested winner's composite score is weighted
+    pub winner_weights: WinnerWeights,
+    /// Axes each new strategy must differ from the previous ones on
+    pub diversify_on: Vec<String>,
+    /// Temperaments handed out to the instances in turn (`--variation`)
+    pub variation: Vec<Variation>,
+    /// Language/framework all strategies and implementations must stay within
+    pub stack: StackConstraints,
+    /// Command that proposes strategies before the strategy agent does
+    /// (`--strategy-source`)
+    pub strategy_source: Option<String>,
+    /// Strategies from an earlier run that phase 1 starts from
+    /// (`--seed-strategies`)
+    pub seed_strategies: Vec<SavedStrategy>,
+    /// Generate this many strategies (more than `num_instances`) and
+    /// implement only the `num_instances` picked in review
+    pub candidates: Option<usize>,
+    /// How much tool detail implementation transcripts record
+    pub transcript_detail: TranscriptDetail,
+    /// Cost ceiling in USD for each implementation session
+    pub max_cost_per_instance: Option<f64>,
+    /// Cost ceiling in USD for the whole run, held to while the instances
+    /// implement (`--max-cost`)
+    pub max_cost: Option<f64>,
+    /// Wall-clock limit for the whole run (`--deadline`)
+    pub deadline: Option<Duration>,
+    /// Disk space each workspace may take during its session
+    /// (`--max-workspace-size`)
+    pub max_workspace_size: Option<SizeLimit>,
+    /// Size cap for implementation transcripts, whose middle is cut out
+    pub max_transcript_bytes: Option<usize>,
+    /// Model to move an instance to when its own keeps failing
+    pub fallback_model: Option<String>,
+    /// How much each strategy agent writes (`--strategy-depth`)
+    pub strategy_depth: StrategyDepth,
+    /// How firmly implementation prompts forbid the other strategies
+    pub exclusion_strength: ExclusionStrength,
+    /// What becomes of strategies that failed to generate (`--failed-strategies`)
+    pub failed_strategies: FailedStrategies,
+    /// Added to every implementation prompt after the strategy
+    /// (`--impl-instructions`)
+    pub impl_instructions: Option<String>,
+    /// Have an agent restate every edited strategy before accepting it,
+    /// instead of accepting edits as written
+    pub confirm_strategies: bool,
+    /// Backends assigned to instances in turn (`--backends`); empty runs
+    /// every instance on Claude Code
+    pub backends: Vec<Backend>,
+    /// How implementation instances are named (`--names`)
+    pub names: InstanceNames,
+    /// Emoji or ASCII tags for statuses in the TUIs and summary (`--status-icons`)
+    pub status_icons: StatusIcons,
+    /// Log each Claude Code instance's API traffic to `c<N>/workspace/api.log` (`--verbose`)
+    pub api_log: bool,
+    /// Print implementation transcripts to stdout, prefixed with their
+    /// instance, as they arrive (`--stream`)
+    pub stream: bool,
+    /// Print the run's progress to stdout as one JSON event per line
+    /// (`--events`)
+    pub events: bool,
+    /// Checkpoint of an earlier, unfinished run to pick up from (`--resume`)
+    pub resume: Option<Checkpoint>,
+    /// Stops the run early: sessions disconnect and whatever finished is
+    /// still returned
+    pub cancel: CancellationToken,
+    /// Running total of what the run's sessions have cost, for the TUIs'
+    /// status bars
+    pub spend: Spend,
+}
+
+/// Session settings shared by every implementation instance
+#[derive(Debug, Clone)]
+struct InstanceSettings {
+    model: Option<String>,
+    env: HashMap<String, String>,
+    seed_dir: Option<PathBuf>,
+    protection: Protection,
+    claude_md_template: Option<String>,
+    inherit_claude_settings: bool,
+    auto_commit: Option<AutoCommit>,
+    stack: StackConstraints,
+    exclusion_strength: ExclusionStrength,
+    transcript_detail: TranscriptDetail,
+    max_cost_usd: Option<f64>,
+    max_workspace_size: Option<SizeLimit>,
+    max_transcript_bytes: Option<usize>,
+    fallback_model: Option<String>,
+    /// This instance's backend from `--backends` (`None`: plain Claude Code)
+    backend: Option<Backend>,
+    /// This instance's name from `--names`
+    name: Option<String>,
+    /// `--impl-instructions`, then this instance's extra instructions from
+    /// review
+    instructions: Vec<String>,
+    api_log: bool,
+    stream: bool,
+    events: bool,
+    build: VerifyMode,
+    verify: VerifyMode,
+    lint: VerifyMode,
+    coverage: VerifyMode,
+    bench: Option<Bench>,
+    hooks: HooksConfig,
+    /// `[env]`, filled in for each instance and added to `env`
+    instance_env: HashMap<String, String>,
+    workspace_dir: PathBuf,
+    layout: RunLayout,
+    /// Where to send live progress for the implementation dashboard
+    dashboard: Option<mpsc::UnboundedSender<DashboardEvent>>,
+    cancel: CancellationToken,
+    /// What sessions stop on: `cancel`, or with `--deadline` also the
+    /// deadline, which leaves checks and hooks to finish
+    session_cancel: CancellationToken,
+    spend: Spend,
+}
+
 pub async fn run(
     prompt: &str,
-    n: usize,
     run_dir: &Path,
-    dry_run: bool,
-    interactive: bool,
-    strategy_model: Option<&str>,
-    impl_model: Option<&str>,
-) -> anyhow::Result<Vec<InstanceResult>> {
-    let mut strategy_infos: Vec<StrategyInfo> = Vec::with_capacity(n);
+    options: &RunOptions,
+) -> anyhow::Result<(Vec<InstanceResult>, Timings)> {
+    let run_started = Instant::now();
+    let mut timings = Timings::default();
+    let n = options.num_instances;
+    let dry_run = options.dry_run;
+    let interactive = options.interactive;
+    let impl_model = options.impl_model.as_deref();
+    let num_strategies = options.candidates.unwrap_or(n);
+    let cancel = &options.cancel;
+    let deadline = options
+        .deadline
+        .map(|duration| Deadline::start(run_started, duration, interactive, cancel));
+    // Past the deadline, sessions and evaluation stop as if cancelled
+    let stop = deadline.as_ref().map_or(cancel, Deadline::token);
+    let pause = PauseControl::default();
+    pause.listen_for_signals();
+
+    // With --resume, phases the checkpoint records as done are skipped
+    let resume_phase = options.resume.as_ref().map(|c| c.phase).unwrap_or_default();
+    let checkpoint = Arc::new(Mutex::new(options.resume.clone().unwrap_or_else(|| {
+        Checkpoint {
+            workspace_dir: (options.workspace_dir != run_dir)
+                .then(|| options.workspace_dir.clone()),
+            ..Checkpoint::new(prompt)
+        }
+    })));
+    if resume_phase == Phase::Complete {
+        let checkpoint = checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+        let results = checkpoint
+            .finished
+            .iter()
+            .cloned()
+            .map(FinishedInstance::into_result)
+            .collect();
+        return Ok((results, timings));
+    }
+    let mut strategy_infos: Vec<StrategyInfo> = match &options.resume {
+        Some(resumed) => resumed
+            .strategies
+            .iter()
+            .map(StrategyInfo::from_saved)
+            .collect(),
+        None => options
+            .seed_strategies
+            .iter()
+            .take(num_strategies)
+            .map(StrategyInfo::from_saved)
+            .collect(),
+    };
+    // Seeded strategies count as collected, so phase 1 only asks for the rest
+    if options.resume.is_none() && !strategy_infos.is_empty() && !dry_run {
+        for (i, info) in strategy_infos.iter().enumerate() {
+            if let Err(e) = write_strategy_file(run_dir, options.layout, i, &info.strategy) {
+                tracing::warn!(instance = i, error = %e, "Failed to write strategy file");
+            }
+        }
+        update_checkpoint(&checkpoint, run_dir, |c| {
+            c.strategies = strategy_infos.iter().map(StrategyInfo::to_saved).collect();
+        });
+    }
+    let first_strategy = if resume_phase == Phase::Strategies {
+        strategy_infos.len()
+    } else {
+        num_strategies
+    };
 
     // Phase 1: Sequential strategy collection
-    if interactive {
-        println!("Phase 1: Collecting strategies from {} instances", n);
-    } else {
-        tracing::info!("Phase 1: Collecting strategies from {} instances", n);
+    let collecting = first_strategy < num_strategies;
+    if collecting {
+        run_phase_hook(options, Hook::PrePhase, Phase::Strategies, run_dir).await?;
+        if interactive {
+            println!(
+                "Phase 1: Collecting strategies from {} instances",
+                num_strategies - first_strategy
+            );
+        } else {
+            tracing::info!(
+                "Phase 1: Collecting strategies from {} instances",
+                num_strategies - first_strategy
+            );
+        }
     }
 
-    for i in 0..n {
+    // Like the hooks, the strategy source isn't run by --dry-run
+    let mut source = options
+        .strategy_source
+        .as_deref()
+        .filter(|_| !dry_run)
+        .map(StrategySource::new);
+    for i in first_strategy..num_strategies {
+        cancel
+            .run_until_cancelled(pause.checkpoint(interactive, &format!("strategy C{}", i)))
+            .await;
+        if cancel.is_cancelled() {
+            break;
+        }
         if interactive {
             println!("  Extracting strategy for C{}...", i);
         } else {
@@ -75,7 +542,14 @@ pub async fn run(
             .map(|s| s.strategy.markdown.clone())
             .collect();
 
-        let strategy_prompt = build_strategy_prompt(prompt, &existing_strategies);
+        let strategy_prompt = build_strategy_prompt(
+            prompt,
+            &existing_strategies,
+            &options.diversify_on,
+            &options.stack,
+            options.strategy_depth,
+            variation_of(options, i),
+        );
 
         if dry_run {
             println!("\n=== DRY RUN: Strategy prompt for C{} ===", i);
@@ -91,23 +565,93 @@ pub async fn run(
                 failed: false,
                 error: None,
                 manually_edited: false,
+                ..Default::default()
             });
             continue;
         }
 
-        let session = ClaudeSession::with_model(strategy_model);
+        let sourced = match &mut source {
+            Some(source) => {
+                source
+                    .next(
+                        prompt,
+                        &existing_strategies,
+                        num_strategies - i,
+                        &options.diversify_on,
+                        &options.stack,
+                    )
+                    .await?
+            }
+            None => None,
+        };
+
+        let session = strategy_session(options);
+        let started = Instant::now();
+        let response = if let Some(strategy) = &sourced {
+            Some(Ok(strategy.clone()))
+        } else if interactive {
+            let completed: Vec<u64> = strategy_infos
+                .iter()
+                .filter_map(|s| s.duration_ms)
+                .collect();
+            generate_strategy_live(
+                &session,
+                &strategy_prompt,
+                i,
+                num_strategies,
+                &completed,
+                cancel,
+            )
+            .await
+        } else {
+            Some(session.query_strategy(&strategy_prompt).await)
+        };
+        // A strategy cut short isn't saved, so --resume asks for it again
+        if cancel.is_cancelled() {
+            break;
+        }
+        let Some(response) = response else {
+            // Skipped while it was generated: kept as a failure the user
+            // already chose not to implement
+            println!("  Skipped C{}", i);
+            let error_msg = "Skipped while it was being generated".to_string();
+            strategy_infos.push(StrategyInfo {
+                strategy: Strategy::failed(&error_msg),
+                failed: true,
+                error: Some(error_msg),
+                skipped: true,
+                duration_ms: Some(started.elapsed().as_millis() as u64),
+                ..Default::default()
+            });
+            update_checkpoint(&checkpoint, run_dir, |c| {
+                c.strategies = strategy_infos.iter().map(StrategyInfo::to_saved).collect();
+            });
+            continue;
+        };
 
-        match session.query_strategy(&strategy_prompt).await {
+        match response {
             Ok(response) => {
-                let strategy = parse_strategy(&response);
+                let strategy = if sourced.is_some() {
+                    Strategy::parse(&response)
+                } else {
+                    parse_strategy(&response)
+                };
                 if interactive {
                     println!("  C{}: {}", i, truncate_for_log(&strategy.markdown, 60));
                 } else {
                     tracing::info!(instance = i, strategy = %strategy.markdown, "Strategy extracted");
                 }
 
+                if options.events {
+                    event_stream::emit(&StreamEvent::StrategyGenerated {
+                        instance: i,
+                        title: &strategy.title,
+                        strategy: &strategy.markdown,
+                    });
+                }
+
                 // Write strategy to file immediately
-                if let Err(e) = write_strategy_file(run_dir, i, &strategy) {
+                if let Err(e) = write_strategy_file(run_dir, options.layout, i, &strategy) {
                     tracing::warn!(instance = i, error = %e, "Failed to write strategy file");
                 }
 
@@ -117,13 +661,19 @@ pub async fn run(
                     failed: false,
                     error: None,
                     manually_edited: false,
+                    duration_ms: Some(started.elapsed().as_millis() as u64),
+                    ..Default::default()
                 });
             }
             Err(e) => {
                 let error_msg = format!("Failed to extract strategy: {}", e);
                 eprintln!("ERROR [C{}]: {}", i, error_msg);
                 if !interactive {
-                    tracing::error!(instance = i, error = %e, "Failed to extract strategy");
+                    tracing::error!(instance = i, kind = e.kind(), error = %e, "Failed to extract strategy");
+                }
+                // Every other strategy (and implementation) would fail the same way
+                if e.is_fatal() {
+                    return Err(e.into());
                 }
 
                 strategy_infos.push(StrategyInfo {
@@ -132,41 +682,107 @@ pub async fn run(
                     failed: true,
                     error: Some(error_msg),
                     manually_edited: false,
+                    duration_ms: Some(started.elapsed().as_millis() as u64),
+                    ..Default::default()
                 });
             }
         }
+
+        update_checkpoint(&checkpoint, run_dir, |c| {
+            c.strategies = strategy_infos.iter().map(StrategyInfo::to_saved).collect();
+        });
+    }
+    timings.strategy_ms = run_started.elapsed().as_millis() as u64;
+    if collecting {
+        warn_on_hook_error(
+            run_phase_hook(options, Hook::PostPhase, Phase::Strategies, run_dir).await,
+        );
+    }
+
+    // Voting, review and selection already happened if the checkpoint got
+    // past strategy collection
+    let reviewing = resume_phase == Phase::Strategies;
+    if !dry_run && reviewing && strategy_infos.iter().any(|s| s.failed) {
+        handle_failed_strategies(prompt, &mut strategy_infos, run_dir, options).await;
+        update_checkpoint(&checkpoint, run_dir, |c| {
+            c.strategies = strategy_infos.iter().map(StrategyInfo::to_saved).collect();
+        });
+    }
+    if options.vote && !dry_run && reviewing {
+        vote_on_strategies(prompt, &mut strategy_infos, options).await;
     }
 
     // Interactive strategy review
-    if interactive && !dry_run {
+    if interactive && !dry_run && reviewing {
         println!();
-        strategy_infos =
-            interactive_strategy_review(prompt, strategy_infos, run_dir, strategy_model).await?;
+        let started = Instant::now();
+        strategy_infos = if options.plain {
+            plain_strategy_review(prompt, strategy_infos, run_dir, options).await?
+        } else {
+            let status = StatusBar {
+                started: run_started,
+                spend: options.spend.clone(),
+                models: options
+                    .strategy_model
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_MODEL_LABEL.to_string()),
+            };
+            interactive_strategy_review(prompt, strategy_infos, run_dir, options, &status).await?
+        };
+        timings.review_ms = Some(started.elapsed().as_millis() as u64);
+    }
+
+    // Strategies that weren't picked still steer the others away from them
+    if options.candidates.is_some() && reviewing {
+        if !interactive || dry_run {
+            pick_top_strategies(&mut strategy_infos, n);
+        }
+        for info in &mut strategy_infos {
+            info.skipped |= !info.selected;
+        }
+    }
+    let to_implement = strategy_infos.iter().filter(|s| !s.skipped).count();
+
+    if cancel.is_cancelled() {
+        let results: Vec<InstanceResult> = strategy_infos
+            .into_iter()
+            .enumerate()
+            .filter(|(_, info)| !info.skipped)
+            .map(|(i, info)| InstanceResult {
+                instance_id: i,
+                strategy: info.strategy.markdown,
+                success: false,
+                error: Some("Cancelled before implementation".to_string()),
+                transcript: info.transcript,
+                ..Default::default()
+            })
+            .collect();
+        timings.total_ms = run_started.elapsed().as_millis() as u64;
+        run_post_run_hook(options, run_dir, &results).await;
+        return Ok((results, timings));
     }
 
     if dry_run {
         println!(
             "\n=== DRY RUN: Implementation phase would launch {} parallel instances ===",
-            n
+            to_implement
         );
         for (i, info) in strategy_infos.iter().enumerate() {
-            let excluded: Vec<String> = strategy_infos
-                .iter()
-                .enumerate()
-                .filter(|(idx, s)| *idx != i && !s.failed)
-                .map(|(_, s)| s.strategy.markdown.clone())
-                .collect();
-
-            let impl_prompt =
-                build_implementation_prompt(prompt, &info.strategy.markdown, &excluded);
+            if info.skipped {
+                continue;
+            }
             println!("\n=== DRY RUN: Implementation prompt for C{} ===", i);
-            println!("{}", impl_prompt);
+            println!(
+                "{}",
+                implementation_prompt(prompt, &strategy_infos, i, options)
+            );
             println!("=== END PROMPT ===");
         }
 
-        return Ok(strategy_infos
+        let results: Vec<InstanceResult> = strategy_infos
             .into_iter()
             .enumerate()
+            .filter(|(_, info)| !info.skipped)
             .map(|(i, info)| InstanceResult {
                 instance_id: i,
                 strategy: info.strategy.markdown,
@@ -174,20 +790,111 @@ pub async fn run(
                 success: true,
                 error: None,
                 transcript: info.transcript,
+                ..Default::default()
             })
-            .collect());
+            .collect();
+        timings.total_ms = run_started.elapsed().as_millis() as u64;
+        return Ok((results, timings));
     }
 
+    update_checkpoint(&checkpoint, run_dir, |c| {
+        c.phase = Phase::Implementation;
+        c.strategies = strategy_infos.iter().map(StrategyInfo::to_saved).collect();
+    });
+
+    cancel
+        .run_until_cancelled(pause.checkpoint(interactive, "implementation"))
+        .await;
+    run_phase_hook(options, Hook::PrePhase, Phase::Implementation, run_dir).await?;
     if interactive {
-        println!("Phase 2: Launching {} parallel implementations", n);
+        println!(
+            "Phase 2: Launching {} parallel implementations",
+            to_implement
+        );
     } else {
-        tracing::info!("Phase 2: Launching {} parallel implementations", n);
+        tracing::info!(
+            "Phase 2: Launching {} parallel implementations",
+            to_implement
+        );
     }
 
     // Phase 2: Parallel execution
+    let (dashboard_tx, dashboard_rx) = mpsc::unbounded_channel();
+    let mut settings = InstanceSettings {
+        model: impl_model.map(|s| s.to_string()),
+        env: if options.build_cache.any_enabled() {
+            build_cache_env(&options.build_cache, run_dir)
+        } else {
+            HashMap::new()
+        },
+        seed_dir: options.seed_dir.clone(),
+        protection: options.protection.clone(),
+        claude_md_template: options.claude_md_template.clone(),
+        inherit_claude_settings: options.inherit_claude_settings,
+        auto_commit: options.auto_commit,
+        stack: options.stack.clone(),
+        exclusion_strength: options.exclusion_strength,
+        transcript_detail: options.transcript_detail,
+        max_cost_usd: options.max_cost_per_instance,
+        max_workspace_size: options.max_workspace_size,
+        max_transcript_bytes: options.max_transcript_bytes,
+        fallback_model: options.fallback_model.clone(),
+        backend: None,
+        name: None,
+        instructions: Vec::new(),
+        api_log: options.api_log,
+        stream: options.stream,
+        events: options.events,
+        build: options.build.clone(),
+        verify: options.verify.clone(),
+        lint: options.lint.clone(),
+        coverage: options.coverage.clone(),
+        bench: options.bench.clone(),
+        hooks: options.hooks.clone(),
+        instance_env: options.env.clone(),
+        workspace_dir: options.workspace_dir.clone(),
+        layout: options.layout,
+        dashboard: (interactive && !options.plain).then_some(dashboard_tx),
+        cancel: cancel.clone(),
+        session_cancel: stop.clone(),
+        spend: options.spend.clone(),
+    };
+    let titles: Vec<&str> = strategy_infos
+        .iter()
+        .map(|info| info.strategy.title.as_str())
+        .collect();
+    let instance_names = options.names.assign(&titles);
+    let implementation_started = Instant::now();
+    // Without the dashboard, --plain says as each instance is done
+    let plain = interactive && options.plain;
+    // The dashboard asks about the budgets itself; otherwise a watcher does.
+    // Headless runs leave the deadline to stop them on its own, evaluation
+    // included.
+    let budgets: Vec<Budget> = options
+        .max_cost
+        .map(|limit| Budget::cost(limit, options.spend.clone(), cancel))
+        .into_iter()
+        .chain(
+            deadline
+                .as_ref()
+                .filter(|_| interactive)
+                .map(|deadline| deadline.budget().clone()),
+        )
+        .collect();
+    let budget_done = CancellationToken::default();
+    let budget_watch = (!budgets.is_empty() && (!interactive || plain)).then(|| {
+        tokio::spawn(budget::watch(
+            budgets.clone(),
+            interactive,
+            pause.clone(),
+            cancel.clone(),
+            budget_done.clone(),
+        ))
+    });
     let handles: Vec<_> = strategy_infos
         .iter()
         .enumerate()
+        .filter(|(_, info)| !info.skipped)
         .map(|(i, info)| {
             let prompt = prompt.to_string();
             let strategy = info.strategy.markdown.clone();
@@ -195,45 +902,179 @@ pub async fn run(
             let failed = info.failed;
             let strategy_error = info.error.clone();
 
-            let excluded: Vec<String> = strategy_infos
-                .iter()
-                .enumerate()
-                .filter(|(idx, s)| *idx != i && !s.failed)
-                .map(|(_, s)| s.strategy.markdown.clone())
-                .collect();
+            let excluded = excluded_strategies(&strategy_infos, i);
             let run_dir = run_dir.to_path_buf();
-            let effective_impl_model = impl_model.or(strategy_model).map(|s| s.to_string());
-
-            tokio::spawn(async move {
-                if failed {
-                    return InstanceResult {
+            let mut settings = settings.clone();
+            if !options.backends.is_empty() {
+                settings.backend = Some(Backend::for_instance(&options.backends, i));
+            }
+            let name = instance_names[i].clone();
+            settings.name = name.clone();
+            settings.instructions = extra_instructions(options, info, i)
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            settings.env.extend(instance_env(
+                &settings.instance_env,
+                i,
+                name.as_deref(),
+                &settings
+                    .layout
+                    .workspace(&settings.workspace_dir, i, name.as_deref()),
+                &run_dir,
+            ));
+            let pause = pause.clone();
+            let budgets = budgets.clone();
+            let checkpoint = Arc::clone(&checkpoint);
+            let already_finished = checkpoint
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .finished_instance(i);
+
+            let handle = tokio::spawn(async move {
+                // Instances that haven't started yet hold off while paused
+                if already_finished.is_none() {
+                    settings
+                        .session_cancel
+                        .run_until_cancelled(pause.wait_until_resumed())
+                        .await;
+                }
+                let dashboard = settings.dashboard.clone();
+                let events = settings.events;
+                let resumed = already_finished.is_some();
+                let mut result = if let Some(result) = already_finished {
+                    result
+                } else if settings.cancel.is_cancelled() {
+                    InstanceResult {
+                        instance_id: i,
+                        strategy,
+                        success: false,
+                        error: Some("Cancelled before starting".to_string()),
+                        transcript: strategy_transcript,
+                        ..Default::default()
+                    }
+                } else if let Some(budget) = budgets.iter().find(|b| b.is_stopped()) {
+                    InstanceResult {
+                        instance_id: i,
+                        strategy,
+                        success: false,
+                        error: Some(format!("Not started: the run reached its {}", budget.name())),
+                        transcript: strategy_transcript,
+                        ..Default::default()
+                    }
+                } else if settings.session_cancel.is_cancelled() {
+                    InstanceResult {
+                        instance_id: i,
+                        strategy,
+                        success: false,
+                        error: Some("Not started: the run reached its deadline".to_string()),
+                        transcript: strategy_transcript,
+                        ..Default::default()
+                    }
+                } else if failed {
+                    InstanceResult {
                         instance_id: i,
                         strategy,
                         workspace_path: String::new(),
                         success: false,
                         error: strategy_error,
                         transcript: strategy_transcript,
-                    };
+                        ..Default::default()
+                    }
+                } else {
+                    // A workspace left by an interrupted run would mix with the new attempt
+                    if let Err(e) = Workspace::discard(
+                        &settings.workspace_dir,
+                        settings.layout,
+                        i,
+                        name.as_deref(),
+                    ) {
+                        tracing::warn!(instance = i, error = %e, "Failed to discard stale workspace");
+                    }
+                    let cancel = settings.cancel.clone();
+                    let result = run_instance(
+                        i,
+                        &prompt,
+                        &strategy,
+                        &strategy_transcript,
+                        &excluded,
+                        &run_dir,
+                        settings,
+                    )
+                    .await;
+                    // A cancelled instance runs again on --resume
+                    if !cancel.is_cancelled() {
+                        update_checkpoint(&checkpoint, &run_dir, |c| {
+                            c.finished.push(FinishedInstance::new(&result));
+                        });
+                    }
+                    result
+                };
+                // Results from a checkpoint keep the name they ran under
+                if !resumed {
+                    result.name = name;
                 }
-                run_instance(
+                if let Some(dashboard) = dashboard {
+                    let _ = dashboard.send(DashboardEvent::Finished {
+                        instance: i,
+                        success: result.success,
+                    });
+                } else if plain {
+                    match (&result.error, result.success) {
+                        (_, true) => println!("{} finished", result.label()),
+                        (Some(error), false) => println!("{} failed: {}", result.label(), error),
+                        (None, false) => println!("{} failed", result.label()),
+                    }
+                }
+                if events {
+                    event_stream::emit(&StreamEvent::instance_finished(&result));
+                }
+                result
+            });
+            (i, handle)
+        })
+        .collect();
+
+    let (ids, handles): (Vec<usize>, Vec<_>) = handles.into_iter().unzip();
+
+    // Only the instances hold dashboard senders now, so the dashboard sees
+    // the channel close if they all go away
+    settings.dashboard = None;
+    if interactive && !options.plain {
+        let panels: Vec<(usize, String, String)> = ids
+            .iter()
+            .map(|&i| {
+                (
                     i,
-                    &prompt,
-                    &strategy,
-                    &strategy_transcript,
-                    &excluded,
-                    &run_dir,
-                    effective_impl_model,
+                    names::label(i, instance_names[i].as_deref()),
+                    strategy_infos[i].strategy.raw.clone(),
                 )
-                .await
             })
-        })
-        .collect();
+            .collect();
+        let status = StatusBar {
+            started: run_started,
+            spend: options.spend.clone(),
+            models: impl_models(options, &ids),
+        };
+        dashboard::run(
+            &panels,
+            dashboard_rx,
+            &pause,
+            cancel,
+            options.status_icons,
+            &status,
+            &budgets,
+        )
+        .await?;
+    } else {
+        drop(dashboard_rx);
+    }
 
-    let results: Vec<InstanceResult> = join_all(handles)
+    let mut results: Vec<InstanceResult> = join_all(handles)
         .await
         .into_iter()
-        .enumerate()
-        .map(|(i, r)| match r {
+        .zip(ids)
+        .map(|(r, i)| match r {
             Ok(result) => result,
             Err(e) => InstanceResult {
                 instance_id: i,
@@ -245,46 +1086,1021 @@ pub async fn run(
                 success: false,
                 error: Some(format!("Task join error: {}", e)),
                 transcript: String::new(),
+                ..Default::default()
             },
         })
         .collect();
+    // A budget question still open is answered before going on
+    budget_done.cancel();
+    if let Some(watch) = budget_watch {
+        watch.await.ok();
+    }
+    timings.implementation_ms = Some(implementation_started.elapsed().as_millis() as u64);
+    warn_on_hook_error(
+        run_phase_hook(options, Hook::PostPhase, Phase::Implementation, run_dir).await,
+    );
+
+    let interrupted = cancel.is_cancelled();
+    if !interrupted {
+        update_checkpoint(&checkpoint, run_dir, |c| c.phase = Phase::Evaluation);
+    }
+
+    // After an interrupt there is nothing worth evaluating, and stopping at
+    // the budget or deadline means spending nothing more; report what finished
+    let stopped = budgets.iter().find(|b| b.is_stopped());
+    let late = deadline.as_ref().filter(|deadline| deadline.is_reached());
+    let wanted = options.cross_review || options.judge || options.synthesize || options.tournament;
+    let evaluates = wanted && stopped.is_none() && late.is_none();
+    if let Some(deadline) = late.filter(|_| !interrupted) {
+        let message = format!(
+            "Reached the {} deadline; {}",
+            deadline.describe(),
+            if wanted {
+                "skipping evaluation"
+            } else {
+                "writing results"
+            }
+        );
+        if interactive {
+            println!("{}", message);
+        } else {
+            tracing::info!("{}", message);
+        }
+    } else if let Some(budget) = stopped.filter(|_| wanted && !interrupted) {
+        let message = format!("Stopped at the {}; skipping evaluation", budget.name());
+        if interactive {
+            println!("{}", message);
+        } else {
+            tracing::info!("{}", message);
+        }
+    }
+    if !interrupted && evaluates {
+        cancel
+            .run_until_cancelled(pause.checkpoint(interactive, "evaluation"))
+            .await;
+        run_phase_hook(options, Hook::PrePhase, Phase::Evaluation, run_dir).await?;
+    }
+    // Evaluation steps cancelled midway leave the results as they were
+    let evaluation_started = Instant::now();
+    if evaluates && !stop.is_cancelled() && options.cross_review {
+        stop.run_until_cancelled(cross_review(
+            prompt,
+            &mut results,
+            settings.model.as_deref(),
+            interactive,
+        ))
+        .await;
+    }
+
+    if evaluates && !stop.is_cancelled() && (options.judge || options.synthesize) {
+        stop.run_until_cancelled(judge_and_synthesize(
+            prompt,
+            &mut results,
+            run_dir,
+            options,
+            &settings,
+        ))
+        .await;
+    }
+
+    if evaluates && !stop.is_cancelled() && options.tournament {
+        stop.run_until_cancelled(run_tournament(
+            prompt,
+            &mut results,
+            run_dir,
+            options,
+            settings.model.as_deref(),
+        ))
+        .await;
+    }
+
+    if !cancel.is_cancelled() {
+        if let Some(winner) = suggest_winner(&mut results, &options.winner_weights) {
+            if interactive {
+                println!(
+                    "Suggested winner: {} (best composite score)",
+                    label_of(&results, winner)
+                );
+            } else {
+                tracing::info!(instance = winner, "Suggested winner");
+            }
+        }
+    }
+
+    if !interrupted && evaluates {
+        timings.evaluation_ms = Some(evaluation_started.elapsed().as_millis() as u64);
+        warn_on_hook_error(
+            run_phase_hook(options, Hook::PostPhase, Phase::Evaluation, run_dir).await,
+        );
+    }
+    if !cancel.is_cancelled() {
+        update_checkpoint(&checkpoint, run_dir, |c| {
+            c.phase = Phase::Complete;
+            c.finished = results.iter().map(FinishedInstance::new).collect();
+        });
+    }
+
+    timings.total_ms = run_started.elapsed().as_millis() as u64;
+    timings.instances = results
+        .iter()
+        .map(|r| InstanceTiming {
+            instance_id: r.instance_id,
+            strategy_ms: strategy_infos
+                .get(r.instance_id)
+                .filter(|_| r.synthesized_from.is_empty())
+                .and_then(|s| s.duration_ms),
+            implementation_ms: r.duration_ms,
+        })
+        .collect();
 
     let succeeded = results.iter().filter(|r| r.success).count();
     let failed_count = results.iter().filter(|r| !r.success).count();
 
-    if interactive {
-        println!("Complete: {} succeeded, {} failed", succeeded, failed_count);
+    if interactive {
+        println!("Complete: {} succeeded, {} failed", succeeded, failed_count);
+        println!("Timing: {}", timings.summary());
+    } else {
+        tracing::info!(succeeded, failed = failed_count, "actually complete");
+        tracing::info!(
+            strategy_ms = timings.strategy_ms,
+            review_ms = timings.review_ms,
+            implementation_ms = timings.implementation_ms,
+            evaluation_ms = timings.evaluation_ms,
+            total_ms = timings.total_ms,
+            "Phase timings"
+        );
+    }
+
+    let icons = options.status_icons;
+    for result in &results {
+        if result.success {
+            if interactive {
+                let diffstat = result
+                    .diffstat
+                    .map(|d| format!(" [{}]", d))
+                    .unwrap_or_default();
+                println!(
+                    "  {} {}: {} ({}){}",
+                    icons.tag(Status::Ok),
+                    result.label(),
+                    truncate_for_log(&result.strategy, 40),
+                    result.workspace_path,
+                    diffstat
+                );
+            } else {
+                tracing::info!(
+                    instance = result.instance_id,
+                    name = result.name.as_deref(),
+                    workspace = %result.workspace_path,
+                    strategy = %result.strategy,
+                    files_changed = result.diffstat.map(|d| d.files_changed),
+                    insertions = result.diffstat.map(|d| d.insertions),
+                    deletions = result.diffstat.map(|d| d.deletions),
+                    "Instance succeeded"
+                );
+            }
+        } else if interactive {
+            println!(
+                "  {} {}: {}",
+                icons.tag(Status::Failed),
+                result.label(),
+                result.error.as_deref().unwrap_or("failed")
+            );
+        } else {
+            tracing::error!(
+                instance = result.instance_id,
+                name = result.name.as_deref(),
+                error = ?result.error,
+                "Instance failed"
+            );
+        }
+        if interactive && !result.protected_violations.is_empty() {
+            println!(
+                "    changed protected paths: {}",
+                result.protected_violations.join(", ")
+            );
+        }
+    }
+
+    run_post_run_hook(options, run_dir, &results).await;
+    Ok((results, timings))
+}
+
+/// Run the `pre_phase` or `post_phase` hook for `phase` in the run directory
+async fn run_phase_hook(
+    options: &RunOptions,
+    hook: Hook,
+    phase: Phase,
+    run_dir: &Path,
+) -> Result<(), HookError> {
+    let env = HashMap::from([
+        (
+            "ACTUALLY_RUN_DIR".to_string(),
+            run_dir.to_string_lossy().to_string(),
+        ),
+        ("ACTUALLY_PHASE".to_string(), phase.name().to_string()),
+    ]);
+    let log = run_dir.join(format!("hook-{}-{}.log", hook.name(), phase.name()));
+    hooks::run(&options.hooks, hook, run_dir, env, &log, &options.cancel).await
+}
+
+/// Run the `post_run` hook, even if the run was cancelled
+async fn run_post_run_hook(options: &RunOptions, run_dir: &Path, results: &[InstanceResult]) {
+    let succeeded = results.iter().filter(|r| r.success).count();
+    let env = HashMap::from([
+        (
+            "ACTUALLY_RUN_DIR".to_string(),
+            run_dir.to_string_lossy().to_string(),
+        ),
+        ("ACTUALLY_SUCCEEDED".to_string(), succeeded.to_string()),
+        (
+            "ACTUALLY_FAILED".to_string(),
+            (results.len() - succeeded).to_string(),
+        ),
+        (
+            "ACTUALLY_CANCELLED".to_string(),
+            options.cancel.is_cancelled().to_string(),
+        ),
+    ]);
+    let log = run_dir.join("hook-post_run.log");
+    let result = hooks::run(
+        &options.hooks,
+        Hook::PostRun,
+        run_dir,
+        env,
+        &log,
+        &CancellationToken::default(),
+    )
+    .await;
+    warn_on_hook_error(result);
+}
+
+/// Post hooks can't undo anything, so their failures are only reported
+fn warn_on_hook_error(result: Result<(), HookError>) {
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "Hook failed");
+    }
+}
+
+/// Rank the successful instances with a judge agent, record the verdict in
+/// `judge.md` and `verdict.json`, and with `--synthesize` merge the top ranked instances into a
+/// new `c-synth` workspace that is appended to `results`
+async fn judge_and_synthesize(
+    prompt: &str,
+    results: &mut Vec<InstanceResult>,
+    run_dir: &Path,
+    options: &RunOptions,
+    settings: &InstanceSettings,
+) {
+    let interactive = options.interactive;
+    if interactive {
+        println!("Judging implementations...");
+    } else {
+        tracing::info!("Judging implementations");
+    }
+
+    let judge_impl = judge::configured(options.judge_command.as_deref(), settings.model.as_deref());
+    let verdict = match judge(
+        judge_impl.as_ref(),
+        prompt,
+        results,
+        run_dir,
+        &options.rubric,
+    )
+    .await
+    {
+        Ok(Some(verdict)) => verdict,
+        Ok(None) => {
+            if interactive {
+                println!("Judging skipped: fewer than two successful instances");
+            } else {
+                tracing::info!("Judging skipped: fewer than two successful instances");
+            }
+            return;
+        }
+        Err(e) => {
+            eprintln!("ERROR [judge]: {}", e);
+            return;
+        }
+    };
+
+    for (rank, id) in verdict.ranking.iter().enumerate() {
+        if let Some(result) = results.iter_mut().find(|r| r.instance_id == *id) {
+            result.judge_rank = Some(rank + 1);
+            result.judge_score = verdict
+                .scores
+                .iter()
+                .find(|s| s.instance_id == *id)
+                .map(|s| s.total);
+        }
+    }
+    let ranking: Vec<String> = verdict
+        .ranking
+        .iter()
+        .map(|id| format!("C{}", id))
+        .collect();
+    if interactive {
+        println!("  Ranking: {}", ranking.join(" > "));
+    } else {
+        tracing::info!(ranking = %ranking.join(" > "), "Judging complete");
+    }
+    let judge_md = format!(
+        "# Judge\n\nRanking: {}\n\n{}\n",
+        ranking.join(" > "),
+        verdict.notes
+    );
+    if let Err(e) = write_atomic(&run_dir.join("judge.md"), judge_md.as_bytes()) {
+        tracing::warn!(error = %e, "Failed to write judge notes");
+    }
+    match serde_json::to_vec_pretty(&verdict.to_file(results, &options.rubric)) {
+        Ok(json) => {
+            if let Err(e) = write_atomic(&run_dir.join(judge::VERDICT_FILE), &json) {
+                tracing::warn!(error = %e, "Failed to write judge verdict");
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "Failed to serialize judge verdict"),
+    }
+
+    if options.synthesize {
+        if interactive {
+            println!("Synthesizing the top implementations into c-synth...");
+        } else {
+            tracing::info!("Synthesizing the top implementations");
+        }
+        let synthesized = synthesize(prompt, results, &verdict, run_dir, settings.clone()).await;
+        if options.events {
+            event_stream::emit(&StreamEvent::instance_finished(&synthesized));
+        }
+        results.push(synthesized);
+    }
+}
+
+/// Run a judged elimination bracket and record it in `tournament.md`
+async fn run_tournament(
+    prompt: &str,
+    results: &mut [InstanceResult],
+    run_dir: &Path,
+    options: &RunOptions,
+    model: Option<&str>,
+) {
+    let interactive = options.interactive;
+    if interactive {
+        println!("Tournament: judging implementations pairwise...");
+    } else {
+        tracing::info!("Tournament starting");
+    }
+
+    let on_match = |m: &Match| {
+        if interactive {
+            println!(
+                "  Round {}: {} vs {} -> {}{}",
+                m.round,
+                label_of(results, m.a),
+                label_of(results, m.b),
+                label_of(results, m.winner),
+                if m.decided { "" } else { " (undecided)" }
+            );
+        } else {
+            tracing::info!(
+                round = m.round,
+                a = m.a,
+                b = m.b,
+                winner = m.winner,
+                decided = m.decided,
+                "Match judged"
+            );
+        }
+    };
+    let judge_impl = judge::configured(options.judge_command.as_deref(), model);
+    let Some(bracket) = tournament(
+        judge_impl.as_ref(),
+        prompt,
+        results,
+        run_dir,
+        &options.rubric,
+        on_match,
+    )
+    .await
+    else {
+        if interactive {
+            println!("Tournament skipped: fewer than two successful instances");
+        } else {
+            tracing::info!("Tournament skipped: fewer than two successful instances");
+        }
+        return;
+    };
+
+    for m in bracket.matches.iter().filter(|m| m.decided) {
+        let loser = if m.winner == m.a { m.b } else { m.a };
+        if let Some(result) = results.iter_mut().find(|r| r.instance_id == m.winner) {
+            result.defeated.push(loser);
+        }
+    }
+
+    if interactive {
+        println!("  Tournament winner: {}", label_of(results, bracket.winner));
+    } else {
+        tracing::info!(winner = bracket.winner, "Tournament complete");
+    }
+    let path = run_dir.join("tournament.md");
+    if let Err(e) = write_atomic(&path, bracket.to_markdown().as_bytes()) {
+        tracing::warn!(error = %e, "Failed to write tournament bracket");
+    }
+}
+
+/// How many of the judge's top instances the synthesis agent merges
+const MAX_SYNTHESIS_SOURCES: usize = 3;
+
+async fn synthesize(
+    prompt: &str,
+    results: &[InstanceResult],
+    verdict: &Verdict,
+    run_dir: &Path,
+    mut settings: InstanceSettings,
+) -> InstanceResult {
+    let id = results.iter().map(|r| r.instance_id + 1).max().unwrap_or(0);
+    let sources: Vec<&InstanceResult> = verdict
+        .ranking
+        .iter()
+        .take(MAX_SYNTHESIS_SOURCES)
+        .filter_map(|src| results.iter().find(|r| r.instance_id == *src))
+        .collect();
+    let source_ids: Vec<usize> = sources.iter().map(|r| r.instance_id).collect();
+    let strategy = format!(
+        "Synthesis of {}",
+        source_ids
+            .iter()
+            .map(|id| format!("C{}", id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    // Flat runs set the synthesis apart from the instances as `c-synth`
+    let created = match settings.layout {
+        RunLayout::Flat => Workspace::create_named(&settings.workspace_dir, "c-synth"),
+        RunLayout::Nested => Workspace::create(&settings.workspace_dir, settings.layout, id, None),
+    };
+    let workspace = match created {
+        Ok(ws) => ws,
+        Err(e) => {
+            return InstanceResult {
+                instance_id: id,
+                strategy,
+                success: false,
+                error: Some(format!("Failed to create workspace: {}", e)),
+                synthesized_from: source_ids,
+                ..Default::default()
+            };
+        }
+    };
+
+    let source_list = sources
+        .iter()
+        .enumerate()
+        .map(|(rank, r)| {
+            format!(
+                "{}. C{} in {}\n   Strategy: {}",
+                rank + 1,
+                r.instance_id,
+                r.workspace().display(),
+                r.strategy
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let full_prompt = build_synthesis_prompt(prompt, &source_list, &verdict.notes);
+    settings.env.extend(instance_env(
+        &settings.instance_env,
+        id,
+        None,
+        workspace.path(),
+        run_dir,
+    ));
+
+    let mut result = implement_in_workspace(
+        id,
+        &workspace,
+        prompt,
+        &strategy,
+        "",
+        &full_prompt,
+        run_dir,
+        settings,
+    )
+    .await;
+    result.synthesized_from = source_ids;
+    result
+}
+
+/// Without the review TUI, select the `n` best-voted working strategies
+/// (or the first `n`, when there was no vote)
+fn pick_top_strategies(strategy_infos: &mut [StrategyInfo], n: usize) {
+    let mut order: Vec<usize> = (0..strategy_infos.len()).collect();
+    order.sort_by_key(|&i| {
+        let info = &strategy_infos[i];
+        (info.failed, std::cmp::Reverse(info.votes.unwrap_or(0)))
+    });
+    for (rank, i) in order.into_iter().enumerate() {
+        strategy_infos[i].selected = rank < n;
+    }
+}
+
+/// Apply `--failed-strategies`: ask again for the strategies that failed,
+/// or skip them, so they don't go on to implementation as certain failures
+async fn handle_failed_strategies(
+    prompt: &str,
+    strategy_infos: &mut [StrategyInfo],
+    run_dir: &Path,
+    options: &RunOptions,
+) {
+    // Those skipped while being generated were given up on already
+    let failed: Vec<usize> = (0..strategy_infos.len())
+        .filter(|&i| strategy_infos[i].failed && !strategy_infos[i].skipped)
+        .collect();
+    for i in failed {
+        if options.failed_strategies == FailedStrategies::Retry && !options.cancel.is_cancelled() {
+            if options.interactive {
+                println!("  Retrying failed strategy C{}...", i);
+            } else {
+                tracing::info!(instance = i, "Retrying failed strategy C{}", i);
+            }
+            let existing_strategies: Vec<String> = strategy_infos
+                .iter()
+                .filter(|s| !s.failed)
+                .map(|s| s.strategy.markdown.clone())
+                .collect();
+            let strategy_prompt = build_strategy_prompt(
+                prompt,
+                &existing_strategies,
+                &options.diversify_on,
+                &options.stack,
+                options.strategy_depth,
+                variation_of(options, i),
+            );
+            let started = Instant::now();
+            match strategy_session(options)
+                .query_strategy(&strategy_prompt)
+                .await
+            {
+                Ok(response) => {
+                    let strategy = parse_strategy(&response);
+                    if options.interactive {
+                        println!("  C{}: {}", i, truncate_for_log(&strategy.markdown, 60));
+                    }
+                    if let Err(e) = write_strategy_file(run_dir, options.layout, i, &strategy) {
+                        tracing::warn!(instance = i, error = %e, "Failed to write strategy file");
+                    }
+                    strategy_infos[i] = StrategyInfo {
+                        strategy,
+                        transcript: response,
+                        duration_ms: Some(started.elapsed().as_millis() as u64),
+                        ..Default::default()
+                    };
+                    continue;
+                }
+                Err(e) => {
+                    if options.interactive {
+                        println!("  C{} failed again, skipping it: {}", i, e);
+                    } else {
+                        tracing::warn!(instance = i, error = %e, "Strategy failed again, skipping it");
+                    }
+                }
+            }
+        }
+        if options.failed_strategies != FailedStrategies::Implement {
+            strategy_infos[i].skipped = true;
+        }
+    }
+}
+
+/// Have every strategy agent rank the other strategies and record each
+/// strategy's aggregate score
+async fn vote_on_strategies(
+    prompt: &str,
+    strategy_infos: &mut [StrategyInfo],
+    options: &RunOptions,
+) {
+    let candidates: Vec<(usize, &str)> = strategy_infos
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.failed)
+        .map(|(i, s)| (i, s.strategy.markdown.as_str()))
+        .collect();
+    if candidates.len() < 3 {
+        // With two strategies each voter has a single choice, so there is nothing to rank
+        if options.interactive {
+            println!("Voting skipped: fewer than three strategies");
+        } else {
+            tracing::info!("Voting skipped: fewer than three strategies");
+        }
+        return;
+    }
+
+    if options.interactive {
+        println!(
+            "Voting: {} agents ranking each other's strategies...",
+            candidates.len()
+        );
+    } else {
+        tracing::info!(voters = candidates.len(), "Strategy vote starting");
+    }
+
+    let session = strategy_session(options);
+    let points = vote(&session, prompt, &candidates).await;
+    let ids: Vec<usize> = candidates.iter().map(|(i, _)| *i).collect();
+    for (i, points) in ids.into_iter().zip(points) {
+        strategy_infos[i].votes = Some(points);
+        if options.interactive {
+            println!("  C{}: {} points", i, points);
+        } else {
+            tracing::info!(instance = i, points, "Strategy vote tallied");
+        }
+    }
+}
+
+/// Build a session for strategy generation (phase 1 and in-TUI additions)
+impl StrategyInfo {
+    fn from_saved(saved: &SavedStrategy) -> Self {
+        let mut strategy = if saved.failed {
+            Strategy::failed(saved.error.as_deref().unwrap_or_default())
+        } else {
+            Strategy::parse(&saved.markdown)
+        };
+        if !saved.title.is_empty() {
+            strategy.title = saved.title.clone();
+        }
+        Self {
+            strategy,
+            transcript: saved.transcript.clone(),
+            failed: saved.failed,
+            error: saved.error.clone(),
+            manually_edited: saved.manually_edited,
+            imported: saved.imported,
+            skipped: saved.skipped,
+            duration_ms: saved.duration_ms,
+            instructions: saved.instructions.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn to_saved(&self) -> SavedStrategy {
+        SavedStrategy {
+            markdown: self.strategy.markdown.clone(),
+            title: self.strategy.title.clone(),
+            transcript: self.transcript.clone(),
+            failed: self.failed,
+            error: self.error.clone(),
+            manually_edited: self.manually_edited,
+            imported: self.imported,
+            skipped: self.skipped,
+            duration_ms: self.duration_ms,
+            instructions: self.instructions.clone(),
+        }
+    }
+}
+
+/// Update the run's checkpoint and write it to `run_dir`. A failed write is
+/// logged rather than failing the run.
+fn update_checkpoint(
+    checkpoint: &Mutex<Checkpoint>,
+    run_dir: &Path,
+    update: impl FnOnce(&mut Checkpoint),
+) {
+    let mut checkpoint = checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+    update(&mut checkpoint);
+    if let Err(e) = checkpoint.save(run_dir) {
+        tracing::warn!(error = %e, "Failed to write checkpoint");
+    }
+}
+
+/// Spinner frames for the strategy collection progress line
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How a strategy generation shown by `with_strategy_progress` ended
+enum Generation<T> {
+    Done(T),
+    /// `r`: throw it away and start over
+    Retry,
+    /// `s`: give up on it
+    Skip,
+}
+
+/// Generate a strategy in front of the user, its text streamed as it
+/// arrives and a spinner shown until then. `r` starts it over; `None` means
+/// it was skipped with `s`.
+async fn generate_strategy_live(
+    session: &ClaudeSession,
+    strategy_prompt: &str,
+    index: usize,
+    total: usize,
+    completed: &[u64],
+    cancel: &CancellationToken,
+) -> Option<Result<String, SessionError>> {
+    loop {
+        let streaming = AtomicBool::new(false);
+        let query = session.query_strategy_streaming(strategy_prompt, |text| {
+            print_streamed_text(text, &streaming)
+        });
+        let generation =
+            with_strategy_progress(query, index, total, completed, &streaming, cancel).await;
+        if streaming.load(Ordering::Relaxed) {
+            println!();
+        }
+        match generation {
+            Generation::Done(response) => return Some(response),
+            Generation::Retry => println!("  Starting C{} over...", index),
+            Generation::Skip => return None,
+        }
+    }
+}
+
+/// Drive `query` while redrawing a spinner line with the elapsed time and a
+/// rough ETA for the remaining strategies, based on how long the completed
+/// ones took. The line is cleared once the query finishes, or as soon as
+/// `streaming` is set because response text started arriving. On a
+/// terminal, `r` and `s` drop the query to retry or skip it; keys are read
+/// in raw mode, so Ctrl+C and Ctrl+Z are passed on as the signals they'd be.
+async fn with_strategy_progress<T>(
+    query: impl std::future::Future<Output = T>,
+    index: usize,
+    total: usize,
+    completed: &[u64],
+    streaming: &AtomicBool,
+    cancel: &CancellationToken,
+) -> Generation<T> {
+    let started = Instant::now();
+    let mut ticker = tokio::time::interval(std::time::Duration::from_millis(100));
+    tokio::pin!(query);
+    let mut frame = 0;
+    let mut out = stdout();
+    let mut keys = std::io::stdin().is_terminal() && enable_raw_mode().is_ok();
+    let finish = |keys: bool| {
+        if keys {
+            let _ = disable_raw_mode();
+        }
+        if !streaming.load(Ordering::Relaxed) {
+            clear_line();
+        }
+    };
+    loop {
+        tokio::select! {
+            result = &mut query => {
+                finish(keys);
+                return Generation::Done(result);
+            }
+            _ = ticker.tick() => {
+                while keys && event::poll(std::time::Duration::ZERO).unwrap_or(false) {
+                    let Ok(Event::Key(key)) = event::read() else {
+                        continue;
+                    };
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+                    match key.code {
+                        KeyCode::Char('c') if control => {
+                            // Back to cooked mode for the interrupt's messages
+                            let _ = disable_raw_mode();
+                            keys = false;
+                            interrupt(cancel);
+                        }
+                        KeyCode::Char('z') if control => suspend(),
+                        KeyCode::Char('r') => {
+                            finish(keys);
+                            return Generation::Retry;
+                        }
+                        KeyCode::Char('s') => {
+                            finish(keys);
+                            return Generation::Skip;
+                        }
+                        _ => {}
+                    }
+                }
+                if streaming.load(Ordering::Relaxed) {
+                    continue;
+                }
+                let elapsed = started.elapsed().as_millis() as u64;
+                let eta = match strategy_eta_ms(completed, total - index, elapsed) {
+                    Some(ms) => format!("~{} left", format_duration_ms(ms)),
+                    None => "estimating time left".to_string(),
+                };
+                print!(
+                    "\r  {} C{} ({}/{}) {} elapsed, {}{}",
+                    SPINNER[frame % SPINNER.len()],
+                    index,
+                    index + 1,
+                    total,
+                    format_duration_ms(elapsed),
+                    eta,
+                    if keys { " (r: retry, s: skip)" } else { "" }
+                );
+                let _ = out.execute(terminal::Clear(terminal::ClearType::UntilNewLine));
+                let _ = out.flush();
+                frame += 1;
+            }
+        }
+    }
+}
+
+/// Interrupt the run as a Ctrl+C read in raw mode would have outside it
+fn interrupt(cancel: &CancellationToken) {
+    #[cfg(unix)]
+    {
+        // As a real SIGINT, so a second Ctrl+C still force quits
+        let _ = cancel;
+        unsafe { libc::raise(libc::SIGINT) };
+    }
+    #[cfg(not(unix))]
+    cancel.cancel();
+}
+
+/// Toggle the pause as a Ctrl+Z read in raw mode would have outside it
+fn suspend() {
+    #[cfg(unix)]
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+}
+
+/// Echo streamed strategy text, indented under the "Extracting" line. The
+/// first chunk replaces the spinner.
+fn print_streamed_text(text: &str, streaming: &AtomicBool) {
+    if !streaming.swap(true, Ordering::Relaxed) {
+        clear_line();
+        print!("    ");
+    }
+    // With a carriage return, as the terminal may be in raw mode
+    print!("{}", text.replace('\n', "\r\n    "));
+    let _ = stdout().flush();
+}
+
+fn clear_line() {
+    let mut out = stdout();
+    let _ = out.execute(terminal::Clear(terminal::ClearType::CurrentLine));
+    print!("\r");
+    let _ = out.flush();
+}
+
+/// Time left for `remaining` strategies (including the one in flight, which
+/// has run for `elapsed_ms`), at the average pace of the completed ones
+fn strategy_eta_ms(completed: &[u64], remaining: usize, elapsed_ms: u64) -> Option<u64> {
+    if completed.is_empty() {
+        return None;
+    }
+    let average = completed.iter().sum::<u64>() / completed.len() as u64;
+    Some((average * remaining as u64).saturating_sub(elapsed_ms))
+}
+
+fn strategy_session(options: &RunOptions) -> ClaudeSession {
+    ClaudeSession::with_model(options.strategy_model.as_deref())
+        .inherit_settings(options.inherit_claude_settings)
+        .with_cancellation(options.cancel.clone())
+        .with_spend(options.spend.clone())
+}
+
+/// The other strategies instance `i` is told to keep away from: all but
+/// the failed ones, including those skipped in review
+fn excluded_ids(strategy_infos: &[StrategyInfo], i: usize) -> Vec<usize> {
+    (0..strategy_infos.len())
+        .filter(|&idx| idx != i && !strategy_infos[idx].failed)
+        .collect()
+}
+
+fn excluded_strategies(strategy_infos: &[StrategyInfo], i: usize) -> Vec<String> {
+    excluded_ids(strategy_infos, i)
+        .into_iter()
+        .map(|idx| strategy_infos[idx].strategy.markdown.clone())
+        .collect()
+}
+
+/// The pre-launch confirmation: each instance about to be implemented with
+/// its final strategy, and the strategies it will be told to keep away
+/// from as rebuilt from the reviewed set. With `selecting` (`--candidates`)
+/// only the selected strategies are implemented.
+fn launch_summary(
+    strategy_infos: &[StrategyInfo],
+    options: &RunOptions,
+    selecting: bool,
+) -> String {
+    let mut lines = Vec::new();
+    for (i, info) in strategy_infos.iter().enumerate() {
+        if info.failed || info.skipped || (selecting && !info.selected) {
+            continue;
+        }
+        let edited = if info.manually_edited {
+            " (edited)"
+        } else if info.imported {
+            " (imported)"
+        } else {
+            ""
+        };
+        lines.push(format!("C{}: {}{}", i, info.strategy.title, edited));
+        if let Some(instructions) = info.instructions.as_deref().and_then(|i| i.lines().next()) {
+            lines.push(format!("    Instructions: {}", instructions));
+        }
+        let avoided: Vec<String> = excluded_ids(strategy_infos, i)
+            .into_iter()
+            .map(|idx| format!("C{} {}", idx, strategy_infos[idx].strategy.title))
+            .collect();
+        lines.push(if options.exclusion_strength == ExclusionStrength::None {
+            "    Avoids: nothing (--exclusion-strength none)".to_string()
+        } else if avoided.is_empty() {
+            "    Avoids: nothing".to_string()
+        } else {
+            format!("    Avoids: {}", avoided.join("; "))
+        });
+        lines.push(String::new());
+    }
+    lines.join("\n")
+}
+
+/// Instance `i`'s temperament: the `--variation` list taken in turn
+fn variation_of(options: &RunOptions, i: usize) -> Variation {
+    if options.variation.is_empty() {
+        Variation::Balanced
     } else {
-        tracing::info!(succeeded, failed = failed_count, "actually complete");
+        options.variation[i % options.variation.len()]
     }
+}
 
-    for result in &results {
-        if result.success {
-            if interactive {
-                println!(
-                    "  C{}: {} ({})",
-                    result.instance_id,
-                    truncate_for_log(&result.strategy, 40),
-                    result.workspace_path
-                );
-            } else {
-                tracing::info!(
-                    instance = result.instance_id,
-                    workspace = %result.workspace_path,
-                    strategy = %result.strategy,
-                    "Instance succeeded"
-                );
-            }
-        } else if !interactive {
-            tracing::error!(
-                instance = result.instance_id,
-                error = ?result.error,
-                "Instance failed"
-            );
-        }
+/// What instance `i` (whose strategy is `info`) is told on top of its
+/// strategy: `--impl-instructions`, its `--variation`, then its own
+/// instructions from review
+fn extra_instructions<'a>(
+    options: &'a RunOptions,
+    info: &'a StrategyInfo,
+    i: usize,
+) -> Vec<&'a str> {
+    options
+        .impl_instructions
+        .iter()
+        .map(String::as_str)
+        .chain(variation_of(options, i).instruction())
+        .chain(info.instructions.iter().map(String::as_str))
+        .collect()
+}
+
+/// The prompt instance `i`'s implementation session will be started with
+fn implementation_prompt(
+    prompt: &str,
+    strategy_infos: &[StrategyInfo],
+    i: usize,
+    options: &RunOptions,
+) -> String {
+    let info = &strategy_infos[i];
+    build_implementation_prompt(
+        prompt,
+        &info.strategy.markdown,
+        &extra_instructions(options, info, i),
+        &excluded_strategies(strategy_infos, i),
+        &options.stack,
+        options.exclusion_strength,
+    )
+}
+
+/// The order the review TUI lists strategies in: the failed ones last
+fn list_order(strategy_infos: &[StrategyInfo]) -> Vec<usize> {
+    let (ok, failed): (Vec<usize>, Vec<usize>) =
+        (0..strategy_infos.len()).partition(|&i| !strategy_infos[i].failed);
+    ok.into_iter().chain(failed).collect()
+}
+
+/// Where the review TUI draws a window over the list and preview: all but
+/// a tenth of the screen on each side
+fn overlay_area(area: Rect) -> Rect {
+    let (margin_x, margin_y) = (area.width / 10, area.height / 10);
+    Rect {
+        x: margin_x,
+        y: margin_y,
+        width: area.width - 2 * margin_x,
+        height: area.height - 2 * margin_y,
     }
+}
 
-    Ok(results)
+/// How the status bars name a model that wasn't chosen
+const DEFAULT_MODEL_LABEL: &str = "default model";
+
+/// The models instances `ids` implement on, for the dashboard's status bar:
+/// `--impl-model`, or each backend they are spread over
+fn impl_models(options: &RunOptions, ids: &[usize]) -> String {
+    if options.backends.is_empty() {
+        return options
+            .impl_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MODEL_LABEL.to_string());
+    }
+    let mut models: Vec<String> = Vec::new();
+    for &id in ids {
+        let backend = Backend::for_instance(&options.backends, id);
+        let model = match backend.model.or_else(|| options.impl_model.clone()) {
+            Some(model) if backend.command.is_none() => format!("{} ({})", backend.name, model),
+            _ => backend.name,
+        };
+        if !models.contains(&model) {
+            models.push(model);
+        }
+    }
+    models.join(", ")
 }
 
 fn truncate_for_log(s: &str, max_len: usize) -> String {
@@ -296,9 +2112,15 @@ fn truncate_for_log(s: &str, max_len: usize) -> String {
 }
 
 /// Write a strategy to a file in the run directory
-fn write_strategy_file(run_dir: &Path, idx: usize, strategy: &Strategy) -> std::io::Result<()> {
-    let path = run_dir.join(format!("C{}-strategy.md", idx));
-    std::fs::write(&path, &strategy.markdown)
+fn write_strategy_file(
+    run_dir: &Path,
+    layout: RunLayout,
+    idx: usize,
+    strategy: &Strategy,
+) -> std::io::Result<()> {
+    layout.create_artifact_dir(run_dir, idx)?;
+    let path = layout.artifact(run_dir, idx, &format!("C{}-strategy.md", idx));
+    write_atomic(&path, strategy.markdown.as_bytes())
 }
 
 /// Wrap a Line to fit within max_width, preserving styles
@@ -386,7 +2208,7 @@ fn wrap_styled_line(line: Line<'static>, max_width: usize) -> Vec<Line<'static>>
 }
 
 /// Wrap all lines in a Text to fit within max_width
-fn wrap_styled_text(text: Text<'static>, max_width: usize) -> Text<'static> {
+pub(crate) fn wrap_styled_text(text: Text<'static>, max_width: usize) -> Text<'static> {
     let wrapped_lines: Vec<Line<'static>> = text
         .lines
         .into_iter()
@@ -396,7 +2218,7 @@ fn wrap_styled_text(text: Text<'static>, max_width: usize) -> Text<'static> {
 }
 
 /// Convert markdown text to ratatui styled Text with syntax highlighting
-fn markdown_to_styled_text(md: &str) -> Text<'static> {
+pub(crate) fn markdown_to_styled_text(md: &str) -> Text<'static> {
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut in_code_block = false;
 
@@ -553,19 +2375,38 @@ async fn interactive_strategy_review(
     prompt: &str,
     mut strategy_infos: Vec<StrategyInfo>,
     run_dir: &Path,
-    strategy_model: Option<&str>,
+    options: &RunOptions,
+    status: &StatusBar,
 ) -> anyhow::Result<Vec<StrategyInfo>> {
+    let icons = options.status_icons;
     // Setup terminal
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
+    // Selection by strategy index (`n`: the accept button), and by row of
+    // the list as drawn, where failed strategies are grouped at the bottom
     let mut list_state = ListState::default();
     list_state.select(Some(0));
+    let mut rows_state = ListState::default();
 
     let mut status_message: Option<String> = None;
     let mut clipboard = arboard::Clipboard::new().ok();
     let mut show_help_popup = false;
+    // First strategy picked with `b`, waiting for its opponent
+    let mut debate_first: Option<usize> = None;
+    // Strategy being edited in place with `i`
+    // Strategy or instructions being edited in place with `i` or `n`
+    let mut inline_edit: Option<(usize, InlineEdit, TextEditor)> = None;
+    // Implementation prompt shown with `p`: instance, prompt, first line shown
+    let mut prompt_view: Option<(usize, String, usize)> = None;
+    // Pre-launch confirmation once accepted: summary, first line shown
+    let mut launch_view: Option<(String, usize)> = None;
+    // Too narrow for the preview beside the list, so `v` swaps between them
+    let mut narrow = false;
+    let mut full_preview = false;
+    // With --candidates, exactly this many strategies must be selected
+    let required_selection = options.candidates.map(|_| options.num_instances);
 
     loop {
         let n = strategy_infos.len();
@@ -573,16 +2414,44 @@ async fn interactive_strategy_review(
 
         // Draw UI
         terminal.draw(|frame| {
-            let area = frame.area();
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(7), Constraint::Length(1)])
+                .split(frame.area());
+            let area = rows[0];
+            let count = |matches: fn(&StrategyInfo) -> bool| {
+                strategy_infos.iter().filter(|info| matches(info)).count()
+            };
+            status.render(
+                frame,
+                rows[1],
+                &[
+                    (count(|s| !s.failed && !s.skipped), "ready", Color::Green),
+                    (count(|s| s.manually_edited), "edited", Color::Yellow),
+                    (count(|s| s.imported), "imported", Color::Blue),
+                    (count(|s| s.skipped), "skipped", Color::DarkGray),
+                    (count(|s| s.failed), "failed", Color::Red),
+                ],
+            );
 
-            // Determine if we have enough width for preview panel (min 80 cols for preview)
-            let show_preview = area.width >= 100;
+            // The preview goes beside the list when there's room, else under
+            // it if the terminal is tall for its width (a cell is about twice
+            // as tall as it is wide), else only when toggled with `v`
+            let side_by_side = area.width >= 100;
+            let stacked = !side_by_side && area.height >= 24 && area.height * 2 >= area.width;
+            let show_preview = side_by_side || stacked;
+            narrow = !show_preview;
 
-            let main_chunks = if show_preview {
+            let main_chunks = if side_by_side {
                 Layout::default()
                     .direction(Direction::Horizontal)
                     .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
                     .split(area)
+            } else if stacked {
+                Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                    .split(area)
             } else {
                 Layout::default()
                     .direction(Direction::Horizontal)
@@ -601,48 +2470,133 @@ async fn interactive_strategy_review(
 
             // Build list items (truncated for list view)
             let list_width = left_chunks[0].width.saturating_sub(15) as usize; // Account for prefix
-            let mut items: Vec<ListItem> = strategy_infos
+            let order = list_order(&strategy_infos);
+            let mut items: Vec<ListItem> = order
                 .iter()
-                .enumerate()
-                .map(|(i, info)| {
-                    // Only show status for failed/edited, not OK
+                .map(|&i| {
+                    let info = &strategy_infos[i];
+                    // Only show status for failed/edited/imported, not OK
                     let status_spans: Vec<Span> = if info.failed {
                         vec![
-                            Span::styled("[FAIL]", Style::default().fg(Color::Red)),
+                            Span::styled(
+                                icons.tag(Status::Failed),
+                                Style::default().fg(Color::Red),
+                            ),
                             Span::raw(" "),
                         ]
                     } else if info.manually_edited {
                         vec![
-                            Span::styled("[EDIT]", Style::default().fg(Color::Yellow)),
+                            Span::styled(
+                                icons.tag(Status::Edited),
+                                Style::default().fg(Color::Yellow),
+                            ),
+                            Span::raw(" "),
+                        ]
+                    } else if info.imported {
+                        vec![
+                            Span::styled(
+                                icons.tag(Status::Imported),
+                                Style::default().fg(Color::Blue),
+                            ),
+                            Span::raw(" "),
+                        ]
+                    } else {
+                        vec![]
+                    };
+                    let skip_spans: Vec<Span> = if info.skipped {
+                        vec![
+                            Span::styled(
+                                icons.tag(Status::Skipped),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            Span::raw(" "),
+                        ]
+                    } else {
+                        vec![]
+                    };
+                    let debate_spans: Vec<Span> = if debate_first == Some(i) {
+                        vec![
+                            Span::styled(
+                                icons.tag(Status::Debating),
+                                Style::default().fg(Color::Magenta),
+                            ),
                             Span::raw(" "),
                         ]
                     } else {
                         vec![]
                     };
 
-                    // Show strategy highlights or truncated raw text
-                    let strategy_display = if !info.strategy.highlights.is_empty() {
+                    // Title on the first line, highlights (or the start of
+                    // the text) dimmed underneath
+                    let detail = if !info.strategy.highlights.is_empty() {
                         info.strategy.highlights.join(" · ")
-                    } else if info.strategy.raw.len() > list_width {
-                        format!("{}…", &info.strategy.raw[..list_width.saturating_sub(1)])
                     } else {
-                        info.strategy.raw.clone()
+                        info.strategy.raw.replace('\n', " ")
+                    };
+                    let detail = if detail.chars().count() > list_width {
+                        let cut: String =
+                            detail.chars().take(list_width.saturating_sub(1)).collect();
+                        format!("{}…", cut)
+                    } else {
+                        detail
                     };
 
-                    let mut spans = vec![Span::styled(
+                    let mut spans = Vec::new();
+                    if required_selection.is_some() {
+                        spans.push(Span::raw(if info.selected { "[x] " } else { "[ ] " }));
+                    }
+                    spans.push(Span::styled(
                         format!("C{} ", i),
                         Style::default().fg(Color::Cyan),
-                    )];
+                    ));
                     spans.extend(status_spans);
-                    spans.push(Span::raw(strategy_display));
-
-                    ListItem::new(Line::from(spans))
+                    spans.extend(skip_spans);
+                    spans.extend(debate_spans);
+                    if let Some(points) = info.votes {
+                        spans.push(Span::styled(
+                            format!("[{} pts] ", points),
+                            Style::default().fg(Color::Green),
+                        ));
+                    }
+                    spans.push(Span::styled(
+                        info.strategy.title.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+
+                    ListItem::new(vec![
+                        Line::from(spans),
+                        Line::from(Span::styled(
+                            format!("    {}", detail),
+                            Style::default().fg(Color::DarkGray),
+                        )),
+                    ])
                 })
                 .collect();
+            let failed_from = order.iter().position(|&i| strategy_infos[i].failed);
+            if let Some(row) = failed_from {
+                items.insert(
+                    row,
+                    ListItem::new(Line::from(Span::styled(
+                        "── Failed ──",
+                        Style::default().fg(Color::Red),
+                    ))),
+                );
+            }
 
             // Add Accept option
+            let accept_label = match required_selection {
+                Some(required) => format!(
+                    ">>> Implement selected ({}/{}) <<<",
+                    strategy_infos
+                        .iter()
+                        .filter(|s| s.selected && !s.skipped)
+                        .count(),
+                    required
+                ),
+                None => ">>> Accept all and begin implementation <<<".to_string(),
+            };
             items.push(ListItem::new(Line::from(vec![Span::styled(
-                ">>> Accept all and begin implementation <<<",
+                accept_label,
                 Style::default()
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD),
@@ -657,11 +2611,28 @@ async fn interactive_strategy_review(
                 )
                 .highlight_symbol("▶ ");
 
-            frame.render_stateful_widget(list, left_chunks[0], &mut list_state);
+            // A full-width preview takes the list's place
+            let preview_area = if show_preview {
+                Some(main_chunks[1])
+            } else {
+                full_preview.then_some(left_chunks[0])
+            };
+            if show_preview || !full_preview {
+                let row = match order.iter().position(|&i| i == selected_idx) {
+                    Some(pos) => pos + usize::from(failed_from.is_some_and(|from| pos >= from)),
+                    None => order.len() + usize::from(failed_from.is_some()),
+                };
+                rows_state.select(Some(row));
+                frame.render_stateful_widget(list, left_chunks[0], &mut rows_state);
+            }
 
             // Help hint
-            let help =
-                Paragraph::new("?: Help & keymaps").style(Style::default().fg(Color::DarkGray));
+            let hint = match (show_preview, full_preview) {
+                (true, _) => "?: Help & keymaps",
+                (false, false) => "?: Help & keymaps  v: preview",
+                (false, true) => "?: Help & keymaps  v: back to list  j/k: next/previous",
+            };
+            let help = Paragraph::new(hint).style(Style::default().fg(Color::DarkGray));
             frame.render_widget(help, left_chunks[1]);
 
             // Status message
@@ -671,7 +2642,7 @@ async fn interactive_strategy_review(
             }
 
             // Preview panel (if showing)
-            if show_preview {
+            if let Some(preview_area) = preview_area {
                 let preview_title = if selected_idx < n {
                     format!(" C{} Preview ", selected_idx)
                 } else {
@@ -682,9 +2653,46 @@ async fn interactive_strategy_review(
                     let info = &strategy_infos[selected_idx];
 
                     // Render strategy with markdown styling
-                    let strategy_text = markdown_to_styled_text(&info.strategy.markdown);
+                    let mut strategy_text = markdown_to_styled_text(&info.strategy.markdown);
+                    if let Some(points) = info.votes {
+                        let mut lines = vec![
+                            Line::from(Span::styled(
+                                format!("Votes: {} points", points),
+                                Style::default()
+                                    .fg(Color::Green)
+                                    .add_modifier(Modifier::BOLD),
+                            )),
+                            Line::from(""),
+                        ];
+                        lines.extend(strategy_text.lines);
+                        strategy_text = Text::from(lines);
+                    }
+                    if let Some(verdict) = &info.debate {
+                        strategy_text.lines.push(Line::from(""));
+                        strategy_text.lines.push(Line::from(Span::styled(
+                            "Debate verdict",
+                            Style::default()
+                                .fg(Color::Magenta)
+                                .add_modifier(Modifier::BOLD),
+                        )));
+                        strategy_text
+                            .lines
+                            .extend(markdown_to_styled_text(verdict).lines);
+                    }
+                    if let Some(instructions) = &info.instructions {
+                        strategy_text.lines.push(Line::from(""));
+                        strategy_text.lines.push(Line::from(Span::styled(
+                            "Extra instructions",
+                            Style::default()
+                                .fg(Color::Cyan)
+                                .add_modifier(Modifier::BOLD),
+                        )));
+                        strategy_text
+                            .lines
+                            .extend(instructions.lines().map(|l| Line::from(l.to_string())));
+                    }
 
-                    // Prepend status line for failed/edited
+                    // Prepend status line for failed/edited/imported
                     if info.failed {
                         let mut lines = vec![
                             Line::from(Span::styled(
@@ -707,6 +2715,18 @@ async fn interactive_strategy_review(
                         ];
                         lines.extend(strategy_text.lines);
                         Text::from(lines)
+                    } else if info.imported {
+                        let mut lines = vec![
+                            Line::from(Span::styled(
+                                "Status: IMPORTED",
+                                Style::default()
+                                    .fg(Color::Blue)
+                                    .add_modifier(Modifier::BOLD),
+                            )),
+                            Line::from(""),
+                        ];
+                        lines.extend(strategy_text.lines);
+                        Text::from(lines)
                     } else {
                         // OK case - just return the styled strategy directly
                         strategy_text
@@ -716,13 +2736,60 @@ async fn interactive_strategy_review(
                 };
 
                 // Wrap text to fit panel width (account for borders)
-                let wrap_width = main_chunks[1].width.saturating_sub(2) as usize;
+                let wrap_width = preview_area.width.saturating_sub(2) as usize;
                 let wrapped_text = wrap_styled_text(preview_text, wrap_width);
 
                 let preview = Paragraph::new(wrapped_text)
                     .block(Block::default().borders(Borders::ALL).title(preview_title));
 
-                frame.render_widget(preview, main_chunks[1]);
+                frame.render_widget(preview, preview_area);
+            }
+
+            // In-place editor overlay, over most of the screen
+            if let Some((idx, target, editor)) = inline_edit.as_mut() {
+                let title = match target {
+                    InlineEdit::Strategy => format!("Edit C{}", idx),
+                    InlineEdit::Instructions => {
+                        format!("Extra instructions for C{} (empty: none)", idx)
+                    }
+                };
+                editor.render(frame, overlay_area(area), &title);
+            }
+
+            // Implementation prompt overlay, scrolled to `scroll`
+            if let Some((idx, text, scroll)) = prompt_view.as_mut() {
+                let view_area = overlay_area(area);
+                let width = view_area.width.saturating_sub(2) as usize;
+                let visible = view_area.height.saturating_sub(2) as usize;
+                let lines = wrap_styled_text(Text::from(text.clone()), width).lines;
+                *scroll = (*scroll).min(lines.len().saturating_sub(visible));
+                let shown: Vec<Line> = lines.into_iter().skip(*scroll).take(visible).collect();
+                let view = Paragraph::new(shown).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" C{} implementation prompt ", idx))
+                        .title_bottom(" j/k, PgUp/PgDn: scroll  Esc: close "),
+                );
+                frame.render_widget(Clear, view_area);
+                frame.render_widget(view, view_area);
+            }
+
+            // Pre-launch confirmation overlay, scrolled to `scroll`
+            if let Some((text, scroll)) = launch_view.as_mut() {
+                let view_area = overlay_area(area);
+                let width = view_area.width.saturating_sub(2) as usize;
+                let visible = view_area.height.saturating_sub(2) as usize;
+                let lines = wrap_styled_text(Text::from(text.clone()), width).lines;
+                *scroll = (*scroll).min(lines.len().saturating_sub(visible));
+                let shown: Vec<Line> = lines.into_iter().skip(*scroll).take(visible).collect();
+                let view = Paragraph::new(shown).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(" Launch these implementations? ")
+                        .title_bottom(" Enter/y: launch  Esc/n: back to review  j/k: scroll "),
+                );
+                frame.render_widget(Clear, view_area);
+                frame.render_widget(view, view_area);
             }
 
             // Help popup overlay
@@ -736,10 +2803,26 @@ async fn interactive_strategy_review(
                         Span::styled("↑/↓ or k/j", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("  Navigate"),
                     ]),
+                    Line::from(vec![
+                        Span::styled("0-9", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("         Jump to C0-C9"),
+                    ]),
                     Line::from(vec![
                         Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("       Edit strategy with $EDITOR"),
                     ]),
+                    Line::from(vec![
+                        Span::styled("a", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Accept and implement"),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("i", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Edit strategy here"),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("n", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Extra instructions for it"),
+                    ]),
                     Line::from(vec![
                         Span::styled("t", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("           Chat about strategy"),
@@ -752,10 +2835,30 @@ async fn interactive_strategy_review(
                         Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("           Delete strategy"),
                     ]),
+                    Line::from(vec![
+                        Span::styled("x", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Skip/unskip strategy"),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("b", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Debate two strategies"),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("Space", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("       Select (with --candidates)"),
+                    ]),
                     Line::from(vec![
                         Span::styled("c", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("           Copy strategy to clipboard"),
                     ]),
+                    Line::from(vec![
+                        Span::styled("p", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Show implementation prompt"),
+                    ]),
+                    Line::from(vec![
+                        Span::styled("v", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("           Toggle preview (narrow screens)"),
+                    ]),
                     Line::from(vec![
                         Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("           Quit"),
@@ -798,6 +2901,91 @@ async fn interactive_strategy_review(
                         return Ok(vec![]);
                     }
 
+                    // The in-place editor takes every key until it's closed
+                    if let Some((idx, target, editor)) = inline_edit.as_mut() {
+                        let (idx, target) = (*idx, *target);
+                        match editor.handle_key(key) {
+                            EditOutcome::Editing => {}
+                            EditOutcome::Cancel => {
+                                inline_edit = None;
+                                status_message = Some("Edit cancelled".to_string());
+                            }
+                            EditOutcome::Save(edited) if target == InlineEdit::Instructions => {
+                                inline_edit = None;
+                                let edited = edited.trim();
+                                strategy_infos[idx].instructions =
+                                    (!edited.is_empty()).then(|| edited.to_string());
+                                status_message = Some(format!(
+                                    "C{} instructions {}",
+                                    idx,
+                                    if edited.is_empty() {
+                                        "removed"
+                                    } else {
+                                        "saved"
+                                    }
+                                ));
+                            }
+                            EditOutcome::Save(edited) => {
+                                inline_edit = None;
+                                let edited = edited.trim();
+                                status_message = Some(
+                                    if edited.is_empty()
+                                        || edited == strategy_infos[idx].strategy.markdown
+                                    {
+                                        "Strategy unchanged".to_string()
+                                    } else {
+                                        apply_strategy_edit(
+                                            prompt,
+                                            &mut strategy_infos,
+                                            idx,
+                                            edited,
+                                            run_dir,
+                                            options,
+                                        )
+                                        .await
+                                    },
+                                );
+                            }
+                        }
+                        continue;
+                    }
+
+                    // So does the prompt view
+                    if let Some((_, _, scroll)) = prompt_view.as_mut() {
+                        let page = (terminal.size()?.height as usize / 2).max(1);
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') => {
+                                prompt_view = None
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => *scroll += 1,
+                            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                            KeyCode::PageDown | KeyCode::Char(' ') => *scroll += page,
+                            KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+                            KeyCode::Char('g') | KeyCode::Home => *scroll = 0,
+                            KeyCode::Char('G') | KeyCode::End => *scroll = usize::MAX,
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    // And the launch confirmation, which starts phase 2 or goes
+                    // back to review
+                    if let Some((_, scroll)) = launch_view.as_mut() {
+                        let page = (terminal.size()?.height as usize / 2).max(1);
+                        match key.code {
+                            KeyCode::Enter | KeyCode::Char('y') => break,
+                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => {
+                                launch_view = None
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => *scroll += 1,
+                            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                            KeyCode::PageDown | KeyCode::Char(' ') => *scroll += page,
+                            KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     // Handle help popup
                     if show_help_popup {
                         show_help_popup = false;
@@ -815,22 +3003,118 @@ async fn interactive_strategy_review(
                             stdout().execute(LeaveAlternateScreen)?;
                             return Ok(vec![]); // Return empty to signal quit
                         }
-                        KeyCode::Up | KeyCode::Char('k') => {
-                            let selected = list_state.selected().unwrap_or(0);
-                            let new_selected = if selected == 0 { n } else { selected - 1 };
-                            list_state.select(Some(new_selected));
+                        KeyCode::Char('p') => {
+                            let selected = list_state.selected().unwrap_or(n);
+                            if selected >= n {
+                                status_message =
+                                    Some("Select a strategy to see its prompt".to_string());
+                            } else if strategy_infos[selected].failed {
+                                status_message =
+                                    Some(format!("C{} failed and won't be implemented", selected));
+                            } else {
+                                let text = implementation_prompt(
+                                    prompt,
+                                    &strategy_infos,
+                                    selected,
+                                    options,
+                                );
+                                prompt_view = Some((selected, text, 0));
+                            }
+                        }
+                        KeyCode::Char('v') => {
+                            if narrow {
+                                full_preview = !full_preview;
+                            } else {
+                                status_message = Some("The preview is already shown".to_string());
+                            }
+                        }
+                        KeyCode::Char('i') => {
+                            let selected = list_state.selected().unwrap_or(n);
+                            if selected < n {
+                                let markdown = &strategy_infos[selected].strategy.markdown;
+                                inline_edit = Some((
+                                    selected,
+                                    InlineEdit::Strategy,
+                                    TextEditor::new(markdown),
+                                ));
+                            } else {
+                                status_message = Some("Select a strategy to edit".to_string());
+                            }
+                        }
+                        KeyCode::Char('n') => {
+                            let selected = list_state.selected().unwrap_or(n);
+                            if selected < n {
+                                let instructions = strategy_infos[selected]
+                                    .instructions
+                                    .as_deref()
+                                    .unwrap_or_default();
+                                inline_edit = Some((
+                                    selected,
+                                    InlineEdit::Instructions,
+                                    TextEditor::new(instructions),
+                                ));
+                            } else {
+                                status_message =
+                                    Some("Select a strategy to give instructions".to_string());
+                            }
+                        }
+                        KeyCode::Up | KeyCode::Char('k') | KeyCode::Down | KeyCode::Char('j') => {
+                            // Through the list as drawn, wrapping around
+                            let rows: Vec<usize> =
+                                list_order(&strategy_infos).into_iter().chain([n]).collect();
+                            let pos = rows
+                                .iter()
+                                .position(|&i| Some(i) == list_state.selected())
+                                .unwrap_or(0);
+                            let step = if matches!(key.code, KeyCode::Up | KeyCode::Char('k')) {
+                                rows.len() - 1
+                            } else {
+                                1
+                            };
+                            list_state.select(Some(rows[(pos + step) % rows.len()]));
                         }
-                        KeyCode::Down | KeyCode::Char('j') => {
-                            let selected = list_state.selected().unwrap_or(0);
-                            let new_selected = if selected >= n { 0 } else { selected + 1 };
-                            list_state.select(Some(new_selected));
+                        KeyCode::Char(digit @ '0'..='9') => {
+                            let idx = digit as usize - '0' as usize;
+                            if idx < n {
+                                list_state.select(Some(idx));
+                            } else {
+                                status_message = Some(format!("There is no C{}", idx));
+                            }
                         }
-                        KeyCode::Enter => {
+                        KeyCode::Enter | KeyCode::Char('a') => {
+                            // `a` is Enter on the accept button
+                            if key.code == KeyCode::Char('a') {
+                                list_state.select(Some(n));
+                            }
                             let selected = list_state.selected().unwrap_or(n);
 
                             if selected == n {
-                                // Accept selected - exit loop
-                                break;
+                                if let Some(required) = required_selection {
+                                    let count = strategy_infos
+                                        .iter()
+                                        .filter(|s| s.selected && !s.skipped)
+                                        .count();
+                                    if count != required {
+                                        status_message = Some(format!(
+                                            "Select exactly {} strategies with Space ({} selected)",
+                                            required, count
+                                        ));
+                                        continue;
+                                    }
+                                } else if strategy_infos.iter().all(|s| s.skipped) {
+                                    status_message = Some("Every strategy is skipped".to_string());
+                                    continue;
+                                }
+                                // Accepted: confirm what launches before leaving the loop
+                                launch_view = Some((
+                                    launch_summary(
+                                        &strategy_infos,
+                                        options,
+                                        required_selection.is_some(),
+                                    ),
+                                    0,
+                                ));
+                                continue;
                             }
 
                             // Edit strategy - need to exit TUI temporarily
@@ -844,37 +3128,23 @@ async fn interactive_strategy_review(
                                 Ok(Some(edited_markdown))
                                     if edited_markdown != original_markdown =>
                                 {
-                                    println!(
-                                        "Strategy modified for C{}, creating new agent...",
-                                        idx
-                                    );
-
-                                    match create_agent_with_edited_strategy(
-                                        prompt,
-                                        &strategy_infos,
-                                        idx,
-                                        &edited_markdown,
-                                        strategy_model,
-                                    )
-                                    .await
-                                    {
-                                        Ok(new_info) => {
-                                            strategy_infos[idx] = new_info;
-                                            // Write updated strategy to file
-                                            if let Err(e) = write_strategy_file(
-                                                run_dir,
-                                                idx,
-                                                &strategy_infos[idx].strategy,
-                                            ) {
-                                                tracing::warn!(instance = idx, error = %e, "Failed to write strategy file");
-                                            }
-                                            status_message =
-                                                Some(format!("C{} strategy updated", idx));
-                                        }
-                                        Err(e) => {
-                                            status_message = Some(format!("Error: {}", e));
-                                        }
+                                    if options.confirm_strategies {
+                                        println!(
+                                            "Strategy modified for C{}, creating new agent...",
+                                            idx
+                                        );
                                     }
+                                    status_message = Some(
+                                        apply_strategy_edit(
+                                            prompt,
+                                            &mut strategy_infos,
+                                            idx,
+                                            &edited_markdown,
+                                            run_dir,
+                                            options,
+                                        )
+                                        .await,
+                                    );
                                 }
                                 Ok(_) => {
                                     status_message = Some("Strategy unchanged".to_string());
@@ -886,6 +3156,7 @@ async fn interactive_strategy_review(
 
                             // Re-enter TUI
                             enable_raw_mode()?;
+                            spawn::discard_pending_input()?;
                             stdout().execute(EnterAlternateScreen)?;
                             terminal.clear()?;
                         }
@@ -894,6 +3165,7 @@ async fn interactive_strategy_review(
                             if selected < n && n > 1 {
                                 // Remove strategy from list (must keep at least 1)
                                 strategy_infos.remove(selected);
+                                debate_first = None;
                                 status_message = Some(format!("Removed C{}", selected));
 
                                 // Adjust selection if needed
@@ -944,12 +3216,34 @@ async fn interactive_strategy_review(
                                 .map(|s| s.strategy.markdown.clone())
                                 .collect();
 
-                            let strategy_prompt =
-                                build_strategy_prompt(prompt, &existing_strategies);
-                            let session = ClaudeSession::with_model(strategy_model);
+                            let strategy_prompt = build_strategy_prompt(
+                                prompt,
+                                &existing_strategies,
+                                &options.diversify_on,
+                                &options.stack,
+                                options.strategy_depth,
+                                variation_of(options, n),
+                            );
+                            let session = strategy_session(options);
+                            let completed: Vec<u64> = strategy_infos
+                                .iter()
+                                .filter_map(|s| s.duration_ms)
+                                .collect();
 
-                            match session.query_strategy(&strategy_prompt).await {
-                                Ok(response) => {
+                            match generate_strategy_live(
+                                &session,
+                                &strategy_prompt,
+                                n,
+                                n + 1,
+                                &completed,
+                                &options.cancel,
+                            )
+                            .await
+                            {
+                                None => {
+                                    status_message = Some(format!("Skipped generating C{}", n));
+                                }
+                                Some(Ok(response)) => {
                                     let strategy = parse_strategy(&response);
                                     println!(
                                         "  C{}: {}",
@@ -958,7 +3252,9 @@ async fn interactive_strategy_review(
                                     );
 
                                     // Write new strategy to file
-                                    if let Err(e) = write_strategy_file(run_dir, n, &strategy) {
+                                    if let Err(e) =
+                                        write_strategy_file(run_dir, options.layout, n, &strategy)
+                                    {
                                         tracing::warn!(instance = n, error = %e, "Failed to write strategy file");
                                     }
 
@@ -968,10 +3264,11 @@ async fn interactive_strategy_review(
                                         failed: false,
                                         error: None,
                                         manually_edited: false,
+                                        ..Default::default()
                                     });
                                     status_message = Some(format!("Added C{}", n));
                                 }
-                                Err(e) => {
+                                Some(Err(e)) => {
                                     let error_msg = format!("Failed to generate strategy: {}", e);
                                     eprintln!("ERROR: {}", error_msg);
                                     strategy_infos.push(StrategyInfo {
@@ -980,6 +3277,7 @@ async fn interactive_strategy_review(
                                         failed: true,
                                         error: Some(error_msg.clone()),
                                         manually_edited: false,
+                                        ..Default::default()
                                     });
                                     status_message = Some(format!("C{} failed: {}", n, error_msg));
                                 }
@@ -987,9 +3285,86 @@ async fn interactive_strategy_review(
 
                             // Re-enter TUI
                             enable_raw_mode()?;
+                            spawn::discard_pending_input()?;
                             stdout().execute(EnterAlternateScreen)?;
                             terminal.clear()?;
                         }
+                        KeyCode::Char('x') => {
+                            let selected = list_state.selected().unwrap_or(n);
+                            if selected < n {
+                                let info = &mut strategy_infos[selected];
+                                info.skipped = !info.skipped;
+                                status_message = Some(format!(
+                                    "C{} {}",
+                                    selected,
+                                    if info.skipped {
+                                        "will not be implemented"
+                                    } else {
+                                        "will be implemented"
+                                    }
+                                ));
+                            } else {
+                                status_message = Some("Select a strategy to skip".to_string());
+                            }
+                        }
+                        KeyCode::Char(' ') if required_selection.is_some() => {
+                            let selected = list_state.selected().unwrap_or(n);
+                            if selected < n && !strategy_infos[selected].failed {
+                                let info = &mut strategy_infos[selected];
+                                info.selected = !info.selected;
+                            } else if selected < n {
+                                status_message =
+                                    Some("Cannot select a failed strategy".to_string());
+                            }
+                        }
+                        KeyCode::Char('b') => {
+                            let selected = list_state.selected().unwrap_or(n);
+                            if selected >= n || strategy_infos[selected].failed {
+                                status_message =
+                                    Some("Select a working strategy to debate".to_string());
+                            } else if debate_first.is_none() {
+                                debate_first = Some(selected);
+                                status_message = Some(format!(
+                                    "C{} will debate; press b on its opponent",
+                                    selected
+                                ));
+                            } else if debate_first == Some(selected) {
+                                debate_first = None;
+                                status_message = Some("Debate cancelled".to_string());
+                            } else if let Some(first) = debate_first.take() {
+                                // Exit TUI temporarily so the debate can stream to the terminal
+                                disable_raw_mode()?;
+                                stdout().execute(LeaveAlternateScreen)?;
+
+                                match debate_strategies(
+                                    prompt,
+                                    &strategy_infos,
+                                    (first, selected),
+                                    run_dir,
+                                    options,
+                                )
+                                .await
+                                {
+                                    Ok(verdict) => {
+                                        strategy_infos[first].debate = Some(verdict.clone());
+                                        strategy_infos[selected].debate = Some(verdict);
+                                        status_message = Some(format!(
+                                            "Debate C{} vs C{} finished",
+                                            first, selected
+                                        ));
+                                    }
+                                    Err(e) => {
+                                        status_message = Some(format!("Debate error: {}", e));
+                                    }
+                                }
+
+                                // Re-enter TUI
+                                enable_raw_mode()?;
+                                spawn::discard_pending_input()?;
+                                stdout().execute(EnterAlternateScreen)?;
+                                terminal.clear()?;
+                            }
+                        }
                         KeyCode::Char('t') => {
                             let selected = list_state.selected().unwrap_or(n);
                             if selected < n {
@@ -1011,6 +3386,7 @@ async fn interactive_strategy_review(
                                     selected,
                                     &excluded,
                                     run_dir,
+                                    options.layout,
                                 ) {
                                     ChatResult::NoChanges => {
                                         status_message =
@@ -1026,10 +3402,15 @@ async fn interactive_strategy_review(
                                             failed: false,
                                             error: None,
                                             manually_edited: true,
+                                            instructions: strategy_infos[selected]
+                                                .instructions
+                                                .take(),
+                                            ..Default::default()
                                         };
                                         // Write revised strategy to file
                                         if let Err(e) = write_strategy_file(
                                             run_dir,
+                                            options.layout,
                                             selected,
                                             &strategy_infos[selected].strategy,
                                         ) {
@@ -1043,49 +3424,402 @@ async fn interactive_strategy_review(
                                     }
                                 }
 
-                                // Re-enter TUI
-                                enable_raw_mode()?;
-                                stdout().execute(EnterAlternateScreen)?;
-                                terminal.clear()?;
-                            } else {
-                                status_message = Some("Select a strategy to discuss".to_string());
-                            }
+                                // Re-enter TUI
+                                enable_raw_mode()?;
+                                spawn::discard_pending_input()?;
+                                stdout().execute(EnterAlternateScreen)?;
+                                terminal.clear()?;
+                            } else {
+                                status_message = Some("Select a strategy to discuss".to_string());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    // Cleanup
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+
+    Ok(strategy_infos)
+}
+
+/// What `plain_strategy_review` accepts at its prompt
+const PLAIN_REVIEW_HELP: &str = "\
+Commands:
+  <n>        Read strategy Cn in full
+  l          List the strategies again
+  e <n>      Edit Cn in $EDITOR
+  n <n> ...  Give Cn's implementation extra instructions (none: remove them)
+  t <n>      Discuss Cn with Claude
+  o          Add a strategy
+  d <n>      Delete Cn
+  x <n>      Skip or unskip Cn
+  b <n> <m>  Debate Cn and Cm
+  s <n>      Select or unselect Cn (with --candidates)
+  y          Implement the strategies
+  q          Quit";
+
+/// Print `question`, then read the answer from stdin; `None` at end of input
+fn ask_plain(question: &str) -> std::io::Result<Option<String>> {
+    print!("{}", question);
+    stdout().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_string()))
+}
+
+/// `--plain` strategy review: the same choices as the review TUI, made by
+/// answering questions on stdin, with nothing but plain lines on stdout
+/// for screen readers and dumb terminals
+async fn plain_strategy_review(
+    prompt: &str,
+    mut strategy_infos: Vec<StrategyInfo>,
+    run_dir: &Path,
+    options: &RunOptions,
+) -> anyhow::Result<Vec<StrategyInfo>> {
+    let icons = StatusIcons::Ascii;
+    // With --candidates, exactly this many strategies must be selected
+    let required_selection = options.candidates.map(|_| options.num_instances);
+    let list = |strategy_infos: &[StrategyInfo]| {
+        println!("Strategies:");
+        let mut failed_shown = false;
+        for i in list_order(strategy_infos) {
+            let info = &strategy_infos[i];
+            if info.failed && !failed_shown {
+                println!("Failed:");
+                failed_shown = true;
+            }
+            let mut tags = Vec::new();
+            if required_selection.is_some() && info.selected {
+                tags.push("[SELECTED]".to_string());
+            }
+            if info.failed {
+                tags.push(icons.tag(Status::Failed).to_string());
+            } else if info.manually_edited {
+                tags.push(icons.tag(Status::Edited).to_string());
+            } else if info.imported {
+                tags.push(icons.tag(Status::Imported).to_string());
+            }
+            if info.skipped {
+                tags.push(icons.tag(Status::Skipped).to_string());
+            }
+            if let Some(points) = info.votes {
+                tags.push(format!("[{} pts]", points));
+            }
+            tags.push(info.strategy.title.clone());
+            println!("C{}. {}", i, tags.join(" "));
+            if !info.strategy.highlights.is_empty() {
+                println!("    {}", info.strategy.highlights.join(", "));
+            }
+        }
+        println!();
+    };
+    list(&strategy_infos);
+    println!("Type a strategy number to read it, y to implement, or ? for commands.");
+
+    loop {
+        let Some(answer) = ask_plain("> ")? else {
+            return Ok(vec![]);
+        };
+        let n = strategy_infos.len();
+        let mut words = answer.split_whitespace();
+        let Some(command) = words.next().map(str::to_lowercase) else {
+            continue;
+        };
+        // Strategies go by their number, with or without its `C`
+        let parse_id = |word: Option<&str>| {
+            word.map(|w| w.trim_start_matches(['C', 'c']))
+                .and_then(|w| w.parse::<usize>().ok())
+                .filter(|&i| i < n)
+        };
+        let target = parse_id(words.next());
+        match (command.as_str(), target) {
+            ("?" | "h" | "help", _) => println!("{}", PLAIN_REVIEW_HELP),
+            ("l", _) => list(&strategy_infos),
+            ("q", _) => return Ok(vec![]),
+            ("y", _) => {
+                if let Some(required) = required_selection {
+                    let count = strategy_infos
+                        .iter()
+                        .filter(|s| s.selected && !s.skipped)
+                        .count();
+                    if count != required {
+                        println!(
+                            "Select exactly {} strategies with s ({} selected)",
+                            required, count
+                        );
+                        continue;
+                    }
+                } else if strategy_infos.iter().all(|s| s.skipped) {
+                    println!("Every strategy is skipped");
+                    continue;
+                }
+                println!("About to implement:");
+                println!(
+                    "{}",
+                    launch_summary(&strategy_infos, options, required_selection.is_some())
+                );
+                // Anything but a no launches, so scripted answers that end here still do
+                match ask_plain("Launch? [Y/n] ")? {
+                    Some(answer) if answer.to_lowercase().starts_with('n') => {
+                        println!("Back to review");
+                    }
+                    _ => return Ok(strategy_infos),
+                }
+            }
+            ("o", _) => {
+                println!("Generating new strategy C{}...", n);
+                let existing_strategies: Vec<String> = strategy_infos
+                    .iter()
+                    .filter(|s| !s.failed)
+                    .map(|s| s.strategy.markdown.clone())
+                    .collect();
+                let strategy_prompt = build_strategy_prompt(
+                    prompt,
+                    &existing_strategies,
+                    &options.diversify_on,
+                    &options.stack,
+                    options.strategy_depth,
+                    variation_of(options, n),
+                );
+                match strategy_session(options)
+                    .query_strategy(&strategy_prompt)
+                    .await
+                {
+                    Ok(response) => {
+                        let strategy = parse_strategy(&response);
+                        if let Err(e) = write_strategy_file(run_dir, options.layout, n, &strategy) {
+                            tracing::warn!(instance = n, error = %e, "Failed to write strategy file");
                         }
-                        _ => {}
+                        println!("Added C{}: {}", n, strategy.title);
+                        strategy_infos.push(StrategyInfo {
+                            strategy,
+                            transcript: response,
+                            ..Default::default()
+                        });
+                    }
+                    Err(e) => println!("Failed to generate strategy: {}", e),
+                }
+            }
+            (_, None) if command.parse::<usize>().is_ok() || command.starts_with('c') => {
+                match parse_id(Some(&command)) {
+                    Some(i) => {
+                        let info = &strategy_infos[i];
+                        println!(
+                            "C{}. {}\n\n{}\n",
+                            i, info.strategy.title, info.strategy.markdown
+                        );
+                        if let Some(error) = &info.error {
+                            println!("Error: {}\n", error);
+                        }
+                        if let Some(debate) = &info.debate {
+                            println!("Debate verdict:\n{}\n", debate);
+                        }
+                        if let Some(instructions) = &info.instructions {
+                            println!("Extra instructions:\n{}\n", instructions);
+                        }
+                    }
+                    None => println!("There is no strategy {}", command),
+                }
+            }
+            ("n", Some(i)) => {
+                let instructions = words.collect::<Vec<_>>().join(" ");
+                strategy_infos[i].instructions =
+                    (!instructions.is_empty()).then(|| instructions.clone());
+                if instructions.is_empty() {
+                    println!("C{} has no extra instructions", i);
+                } else {
+                    println!("C{} instructions saved", i);
+                }
+            }
+            ("e" | "n" | "t" | "d" | "x" | "b" | "s", None) => {
+                println!("Which strategy? e.g. `{} 0`", command)
+            }
+            ("e", Some(i)) => {
+                let original_markdown = strategy_infos[i].strategy.markdown.clone();
+                match edit_strategy_in_editor(&original_markdown) {
+                    Ok(Some(edited)) if edited != original_markdown => {
+                        let outcome = apply_strategy_edit(
+                            prompt,
+                            &mut strategy_infos,
+                            i,
+                            &edited,
+                            run_dir,
+                            options,
+                        )
+                        .await;
+                        println!("{}", outcome);
+                    }
+                    Ok(_) => println!("Strategy unchanged"),
+                    Err(e) => println!("Editor error: {}", e),
+                }
+            }
+            ("t", Some(i)) => {
+                let excluded: Vec<String> = strategy_infos
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, s)| *j != i && !s.failed)
+                    .map(|(_, s)| s.strategy.markdown.clone())
+                    .collect();
+                match chat_with_strategy(
+                    prompt,
+                    &strategy_infos[i],
+                    i,
+                    &excluded,
+                    run_dir,
+                    options.layout,
+                ) {
+                    ChatResult::NoChanges => println!("Chat ended without changes"),
+                    ChatResult::RevisedStrategy(new_markdown) => {
+                        strategy_infos[i] = StrategyInfo {
+                            strategy: Strategy::parse(&new_markdown),
+                            transcript: format!("Revised via chat: {}", new_markdown),
+                            manually_edited: true,
+                            instructions: strategy_infos[i].instructions.take(),
+                            ..Default::default()
+                        };
+                        if let Err(e) = write_strategy_file(
+                            run_dir,
+                            options.layout,
+                            i,
+                            &strategy_infos[i].strategy,
+                        ) {
+                            tracing::warn!(instance = i, error = %e, "Failed to write strategy file");
+                        }
+                        println!("C{} strategy revised", i);
+                    }
+                    ChatResult::Error(msg) => println!("Chat error: {}", msg),
+                }
+            }
+            ("d", Some(_)) if n == 1 => println!("Cannot remove last strategy"),
+            ("d", Some(i)) => {
+                strategy_infos.remove(i);
+                println!("Removed C{}; the strategies after it move up one", i);
+            }
+            ("x", Some(i)) => {
+                let info = &mut strategy_infos[i];
+                info.skipped = !info.skipped;
+                println!(
+                    "C{} {}",
+                    i,
+                    if info.skipped {
+                        "will not be implemented"
+                    } else {
+                        "will be implemented"
+                    }
+                );
+            }
+            ("s", Some(_)) if required_selection.is_none() => {
+                println!("Selecting only applies with --candidates")
+            }
+            ("s", Some(i)) if strategy_infos[i].failed => {
+                println!("Cannot select a failed strategy")
+            }
+            ("s", Some(i)) => {
+                let info = &mut strategy_infos[i];
+                info.selected = !info.selected;
+                println!(
+                    "C{} {}",
+                    i,
+                    if info.selected {
+                        "selected"
+                    } else {
+                        "unselected"
                     }
+                );
+            }
+            ("b", Some(first)) => {
+                let Some(second) = parse_id(words.next()).filter(|&j| j != first) else {
+                    println!("Debate which two? e.g. `b 0 1`");
+                    continue;
+                };
+                if strategy_infos[first].failed || strategy_infos[second].failed {
+                    println!("Only working strategies can debate");
+                    continue;
+                }
+                match debate_strategies(prompt, &strategy_infos, (first, second), run_dir, options)
+                    .await
+                {
+                    Ok(verdict) => {
+                        strategy_infos[first].debate = Some(verdict.clone());
+                        strategy_infos[second].debate = Some(verdict);
+                    }
+                    Err(e) => println!("Debate error: {}", e),
                 }
             }
+            _ => println!("Unknown command `{}`; type ? for commands", answer),
         }
     }
+}
 
-    // Cleanup
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
+/// Run a moderated debate between strategies `a` and `b`, printing each turn
+/// as it arrives. The transcript is saved as `debate-C{a}-vs-C{b}.md` in the
+/// run directory; the moderator's verdict is returned.
+async fn debate_strategies(
+    prompt: &str,
+    strategy_infos: &[StrategyInfo],
+    (a, b): (usize, usize),
+    run_dir: &Path,
+    options: &RunOptions,
+) -> anyhow::Result<String> {
+    let (a_label, b_label) = (format!("C{}", a), format!("C{}", b));
+    let rule = if options.plain { "--" } else { "──" };
+    println!(
+        "Debating {} vs {} ({} rounds each)...\n",
+        a_label, b_label, options.debate_rounds
+    );
 
-    Ok(strategy_infos)
+    let session = strategy_session(options);
+    let result = run_debate(
+        &session,
+        prompt,
+        Side {
+            label: &a_label,
+            strategy: &strategy_infos[a].strategy.markdown,
+        },
+        Side {
+            label: &b_label,
+            strategy: &strategy_infos[b].strategy.markdown,
+        },
+        options.debate_rounds,
+        |speaker, argument| println!("{rule} {} {rule}\n{}\n", speaker, argument),
+    )
+    .await?;
+
+    println!("{rule} Verdict {rule}\n{}\n", result.verdict);
+
+    let path = run_dir.join(format!("debate-{}-vs-{}.md", a_label, b_label));
+    let content = format!(
+        "# Debate: {} vs {}\n\n{}## Verdict\n\n{}\n",
+        a_label, b_label, result.transcript, result.verdict
+    );
+    if let Err(e) = write_atomic(&path, content.as_bytes()) {
+        tracing::warn!(path = %path.display(), error = %e, "Failed to write debate transcript");
+    }
+
+    Ok(result.verdict)
 }
 
 /// Open a strategy in $EDITOR for editing
 fn edit_strategy_in_editor(strategy: &str) -> anyhow::Result<Option<String>> {
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
-
-    let mut temp_file = NamedTempFile::new()?;
-    writeln!(
-        temp_file,
-        "# Edit the strategy below. Lines starting with # are ignored."
+    let temp_path = spawn::temp_file(
+        &format!(
+            "# Edit the strategy below. Lines starting with # are ignored.\n\
+             # Save and exit to apply changes, or exit without saving to cancel.\n\
+             \n{}\n",
+            strategy
+        ),
+        ".md",
     )?;
-    writeln!(
-        temp_file,
-        "# Save and exit to apply changes, or exit without saving to cancel."
-    )?;
-    writeln!(temp_file)?;
-    writeln!(temp_file, "{}", strategy)?;
-    temp_file.flush()?;
-
-    let temp_path = temp_file.path().to_path_buf();
     let before_mtime = std::fs::metadata(&temp_path)?.modified()?;
 
-    let status = Command::new(&editor).arg(&temp_path).status()?;
+    let status = spawn::edit(&temp_path)?;
 
     if !status.success() {
         return Ok(None);
@@ -1096,7 +3830,7 @@ fn edit_strategy_in_editor(strategy: &str) -> anyhow::Result<Option<String>> {
         return Ok(None);
     }
 
-    let content = std::fs::read_to_string(&temp_path)?;
+    let content = spawn::read_edited(&temp_path)?;
 
     let edited: String = content
         .lines()
@@ -1120,9 +3854,14 @@ fn chat_with_strategy(
     strategy_idx: usize,
     excluded_strategies: &[String],
     run_dir: &Path,
+    layout: RunLayout,
 ) -> ChatResult {
     // Use the strategy file in run_dir for revised output
-    let strategy_path = run_dir.join(format!("C{}-strategy.md", strategy_idx));
+    let strategy_path = layout.artifact(
+        run_dir,
+        strategy_idx,
+        &format!("C{}-strategy.md", strategy_idx),
+    );
     let original_content = strategy_info.strategy.markdown.clone();
 
     // Build forbidden approaches section
@@ -1179,7 +3918,13 @@ After writing the revised strategy, tell the user: "Strategy revised. Type `/exi
 
     // Spawn claude CLI as subprocess (interactive TUI mode with system prompt)
     // Pass a simple prompt to trigger Claude's greeting message
-    let status = Command::new("claude")
+    let claude = spawn::program("claude");
+    let system_prompt = if spawn::is_batch_script(&claude) {
+        system_prompt.lines().collect::<Vec<_>>().join(" ")
+    } else {
+        system_prompt
+    };
+    let status = Command::new(&claude)
         .arg("--system-prompt")
         .arg(&system_prompt)
         .arg("Talk strategy")
@@ -1217,14 +3962,55 @@ After writing the revised strategy, tell the user: "Strategy revised. Type `/exi
     ChatResult::NoChanges
 }
 
+/// Put `edited` in place of strategy `idx` (restated by an agent with
+/// `--confirm-strategies`) and save it, returning what happened for the
+/// status line
+async fn apply_strategy_edit(
+    prompt: &str,
+    strategy_infos: &mut [StrategyInfo],
+    idx: usize,
+    edited: &str,
+    run_dir: &Path,
+    options: &RunOptions,
+) -> String {
+    match create_agent_with_edited_strategy(prompt, strategy_infos, idx, edited, options).await {
+        Ok(new_info) => {
+            strategy_infos[idx] = StrategyInfo {
+                // Instructions are given for the instance, not the strategy
+                instructions: strategy_infos[idx].instructions.take(),
+                ..new_info
+            };
+            // Write updated strategy to file
+            if let Err(e) =
+                write_strategy_file(run_dir, options.layout, idx, &strategy_infos[idx].strategy)
+            {
+                tracing::warn!(instance = idx, error = %e, "Failed to write strategy file");
+            }
+            format!("C{} strategy updated", idx)
+        }
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
 /// Create a fresh agent with an edited strategy
 async fn create_agent_with_edited_strategy(
     prompt: &str,
     existing_infos: &[StrategyInfo],
     target_idx: usize,
     edited_strategy: &str,
-    strategy_model: Option<&str>,
+    options: &RunOptions,
 ) -> anyhow::Result<StrategyInfo> {
+    // Restating the strategy costs a query and changes nothing the
+    // implementation sees, so it only happens on request
+    if !options.confirm_strategies {
+        return Ok(StrategyInfo {
+            strategy: Strategy::parse(edited_strategy),
+            transcript: format!("=== EDITED STRATEGY ===\n{}", edited_strategy),
+            manually_edited: true,
+            ..Default::default()
+        });
+    }
+
     let existing_strategies: Vec<String> = existing_infos
         .iter()
         .enumerate()
@@ -1261,7 +4047,7 @@ STRATEGY: <restate the strategy in your own words>"#,
         }
     );
 
-    let session = ClaudeSession::with_model(strategy_model);
+    let session = strategy_session(options);
 
     match session.query_strategy(&strategy_prompt).await {
         Ok(response) => {
@@ -1277,6 +4063,7 @@ STRATEGY: <restate the strategy in your own words>"#,
                 failed: false,
                 error: None,
                 manually_edited: true,
+                ..Default::default()
             })
         }
         Err(e) => {
@@ -1288,6 +4075,7 @@ STRATEGY: <restate the strategy in your own words>"#,
                 failed: true,
                 error: Some(error_msg),
                 manually_edited: false,
+                ..Default::default()
             })
         }
     }
@@ -1300,9 +4088,14 @@ async fn run_instance(
     strategy_transcript: &str,
     excluded_strategies: &[String],
     run_dir: &Path,
-    impl_model: Option<String>,
+    settings: InstanceSettings,
 ) -> InstanceResult {
-    let workspace = match Workspace::create(run_dir, id) {
+    let workspace = match Workspace::create(
+        &settings.workspace_dir,
+        settings.layout,
+        id,
+        settings.name.as_deref(),
+    ) {
         Ok(ws) => ws,
         Err(e) => {
             return InstanceResult {
@@ -1312,45 +4105,690 @@ async fn run_instance(
                 success: false,
                 error: Some(format!("Failed to create workspace: {}", e)),
                 transcript: String::new(),
+                ..Default::default()
             };
         }
     };
 
-    let full_prompt = build_implementation_prompt(prompt, strategy, excluded_strategies);
-    let session = ClaudeSession::with_cwd_and_model(workspace.path(), impl_model.as_deref());
+    let full_prompt = build_implementation_prompt(
+        prompt,
+        strategy,
+        &settings
+            .instructions
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        excluded_strategies,
+        &settings.stack,
+        settings.exclusion_strength,
+    );
+    // Build first: a workspace that doesn't compile fails the rest anyway,
+    // but "builds at all" is worth recording on its own
+    let checks = [
+        (CheckKind::Build, settings.build.clone()),
+        (CheckKind::Test, settings.verify.clone()),
+        (CheckKind::Lint, settings.lint.clone()),
+        (CheckKind::Coverage, settings.coverage.clone()),
+    ];
+    let bench = settings.bench.clone();
+    let file_prefix = names::file_prefix(id, settings.name.as_deref());
+    let layout = settings.layout;
+    let env = settings.env.clone();
+    let cancel = settings.cancel.clone();
+    let hooks = settings.hooks.clone();
+    let hook_env = hooks::instance_env(
+        id,
+        settings.name.as_deref(),
+        workspace.path(),
+        run_dir,
+        &env,
+    );
+    let mut result = implement_in_workspace(
+        id,
+        &workspace,
+        prompt,
+        strategy,
+        strategy_transcript,
+        &full_prompt,
+        run_dir,
+        settings,
+    )
+    .await;
+    result.notes = workspace.read_notes();
+    if result.success && result.notes.is_none() {
+        tracing::warn!(
+            instance = id,
+            "Agent finished without writing {}",
+            NOTES_FILE
+        );
+    }
+
+    // A session stopped at the deadline left its work unfinished
+    for (kind, mode) in checks {
+        if cancel.is_cancelled() || result.past_deadline {
+            break;
+        }
+        let Some((command, inferred)) = mode.command_for(kind, workspace.path()) else {
+            continue;
+        };
+        let log = layout.artifact(
+            run_dir,
+            id,
+            &format!("{}-{}.log", file_prefix, kind.log_name()),
+        );
+        let check = verify::run_check(
+            kind,
+            &command,
+            inferred,
+            workspace.path(),
+            &env,
+            &log,
+            &cancel,
+        )
+        .await;
+        if let Some(check) = &check {
+            tracing::info!(instance = id, ?kind, "Check {}", check.describe());
+        }
+        match kind {
+            CheckKind::Build => result.build = check,
+            CheckKind::Test => result.verification = check,
+            CheckKind::Lint => result.lint = check,
+            CheckKind::Coverage => result.coverage = check,
+        }
+    }
+
+    if let Some(bench) =
+        bench.filter(|_| result.success && !cancel.is_cancelled() && !result.past_deadline)
+    {
+        let log = layout.artifact(run_dir, id, &format!("{}-bench.log", file_prefix));
+        result.bench = bench.run(workspace.path(), &env, &log, &cancel).await;
+        if let Some(value) = result.bench.as_ref().and_then(|b| b.value) {
+            tracing::info!(instance = id, value, "Benchmark measured");
+        }
+    }
+
+    let mut hook_env = hook_env;
+    hook_env.insert("ACTUALLY_SUCCESS".to_string(), result.success.to_string());
+    let log = layout.artifact(
+        run_dir,
+        id,
+        &format!("{}-{}.log", file_prefix, Hook::PostInstance.name()),
+    );
+    warn_on_hook_error(
+        hooks::run(
+            &hooks,
+            Hook::PostInstance,
+            workspace.path(),
+            hook_env,
+            &log,
+            &cancel,
+        )
+        .await,
+    );
+    result
+}
+
+/// Prepare `workspace` (seed, settings, CLAUDE.md, STRATEGY.md, baseline) and run an
+/// implementation session in it with `full_prompt`
+#[allow(clippy::too_many_arguments)]
+async fn implement_in_workspace(
+    id: usize,
+    workspace: &Workspace,
+    prompt: &str,
+    strategy: &str,
+    strategy_transcript: &str,
+    full_prompt: &str,
+    run_dir: &Path,
+    settings: InstanceSettings,
+) -> InstanceResult {
+    let started = Instant::now();
+    if let Err(e) = settings.layout.create_artifact_dir(run_dir, id) {
+        tracing::warn!(instance = id, error = %e, "Failed to create the instance's log directory");
+    }
+    if settings.events {
+        event_stream::emit(&StreamEvent::InstanceStarted {
+            instance: id,
+            name: settings.name.as_deref(),
+            strategy,
+            workspace: &workspace.path().to_string_lossy(),
+        });
+    }
+
+    if let Some(seed_dir) = &settings.seed_dir {
+        match workspace.seed_from(seed_dir, run_dir, &settings.protection) {
+            Ok(count) => {
+                tracing::debug!(instance = id, files = count, "Seeded workspace");
+            }
+            Err(e) => {
+                return InstanceResult {
+                    instance_id: id,
+                    strategy: strategy.to_string(),
+                    workspace_path: workspace.path().to_string_lossy().to_string(),
+                    success: false,
+                    error: Some(format!("Failed to seed workspace: {}", e)),
+                    transcript: String::new(),
+                    ..Default::default()
+                };
+            }
+        }
+    }
+
+    // Propagate the source repo's CLAUDE.md and .claude/ settings before any
+    // template is appended, so the template adds to the project's conventions
+    if settings.inherit_claude_settings {
+        if let Ok(source) = std::env::current_dir() {
+            if let Err(e) = workspace.copy_claude_files(&source) {
+                tracing::warn!(instance = id, error = %e, "Failed to copy Claude settings");
+            }
+        }
+    }
+
+    if let Some(template) = &settings.claude_md_template {
+        let claude_md = render_claude_md(template, prompt, strategy, id);
+        if let Err(e) = workspace.write_claude_md(&claude_md) {
+            tracing::warn!(instance = id, error = %e, "Failed to write CLAUDE.md");
+        }
+    }
+
+    // Part of the baseline, so it doesn't show up as the agent's change
+    match workspace.write_strategy_md(&render_strategy_md(prompt, strategy, id)) {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!(
+                instance = id,
+                "Workspace already has a {}, leaving it",
+                STRATEGY_FILE
+            )
+        }
+        Err(e) => tracing::warn!(instance = id, error = %e, "Failed to write {}", STRATEGY_FILE),
+    }
+
+    // Before the baseline, so what the hook sets up isn't counted as the agent's
+    let hook_env = hooks::instance_env(
+        id,
+        settings.name.as_deref(),
+        workspace.path(),
+        run_dir,
+        &settings.env,
+    );
+    let log = settings.layout.artifact(
+        run_dir,
+        id,
+        &format!(
+            "{}-{}.log",
+            names::file_prefix(id, settings.name.as_deref()),
+            Hook::PreInstance.name()
+        ),
+    );
+    if let Err(e) = hooks::run(
+        &settings.hooks,
+        Hook::PreInstance,
+        workspace.path(),
+        hook_env,
+        &log,
+        &settings.cancel,
+    )
+    .await
+    {
+        return InstanceResult {
+            instance_id: id,
+            strategy: strategy.to_string(),
+            workspace_path: workspace.path().to_string_lossy().to_string(),
+            success: false,
+            error: Some(e.to_string()),
+            transcript: String::new(),
+            ..Default::default()
+        };
+    }
+
+    // A baseline commit lets us measure (and optionally commit) the agent's changes
+    let has_baseline = if settings.seed_dir.is_some() || settings.auto_commit.is_some() {
+        // The API log is actually's, not part of the agent's work
+        let mut exclude = format!("/{}\n", API_LOG_FILE);
+        if let Some(rules) = settings
+            .seed_dir
+            .as_ref()
+            .and_then(|dir| std::fs::read_to_string(dir.join(ACTUALLY_IGNORE_FILE)).ok())
+        {
+            exclude.push_str(&rules);
+        }
+        match git::init_with_baseline(workspace.path(), Some(&exclude)).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(instance = id, error = %e, "Failed to initialize workspace repository");
+                false
+            }
+        }
+    } else {
+        false
+    };
+
+    // Feed the dashboard's file list; seeding and the baseline are done, so
+    // everything reported from here on is the agent's doing
+    let _file_watcher = settings.dashboard.clone().and_then(|dashboard| {
+        workspace
+            .watch(move |change| {
+                let _ = dashboard.send(DashboardEvent::FileChanged {
+                    instance: id,
+                    change,
+                });
+            })
+            .map_err(|e| tracing::warn!(instance = id, error = %e, "Failed to watch workspace"))
+            .ok()
+    });
+
+    // Only what was seeded has anything to protect
+    let lock = settings.seed_dir.as_ref().and_then(|_| {
+        settings
+            .protection
+            .lock(workspace.path())
+            .map_err(|e| tracing::warn!(instance = id, error = %e, "Failed to make protected paths read-only"))
+            .ok()
+    });
+
+    // A session that keeps failing on its model moves to --fallback-model,
+    // carrying on in the same workspace
+    let backend = settings.backend.clone().unwrap_or_default();
+    let mut model = if backend.command.is_some() {
+        None
+    } else {
+        backend.model.clone().or_else(|| settings.model.clone())
+    };
+    let mut fallback_from = None;
+    let mut attempt = 1;
+    let mut failed_attempts = String::new();
+    let mut failed_cost_usd = None;
+    let transcript_log = settings.layout.artifact(
+        run_dir,
+        id,
+        &format!(
+            "{}-{}",
+            names::file_prefix(id, settings.name.as_deref()),
+            TRANSCRIPT_LOG_SUFFIX
+        ),
+    );
+    // A workspace that grows past --max-workspace-size stops the session
+    // through its own token, so the rest of the run carries on
+    let size_watch = settings.max_workspace_size.map(|limit| {
+        SizeWatch::start(
+            workspace.path().to_path_buf(),
+            limit,
+            names::label(id, settings.name.as_deref()),
+            &settings.session_cancel,
+        )
+    });
+    let session_settings = InstanceSettings {
+        cancel: match &size_watch {
+            Some((_, cancel)) => cancel.clone(),
+            None => settings.session_cancel.clone(),
+        },
+        ..settings.clone()
+    };
+    let session_result = loop {
+        let result = run_session(
+            id,
+            workspace,
+            full_prompt,
+            &backend,
+            model.as_deref(),
+            &transcript_log,
+            &session_settings,
+        )
+        .await;
+        let error = match &result {
+            Ok(r) => r.error.clone(),
+            Err(e) => Some(e.clone()),
+        };
+        let (Some(error), Some(fallback)) = (error, &settings.fallback_model) else {
+            break result;
+        };
+        let retry = error.model_retry(attempt, fallback_from.is_none());
+        if retry == ModelRetry::GiveUp {
+            break result;
+        }
+
+        match &result {
+            Ok(r) => {
+                failed_attempts.push_str(&r.transcript);
+                failed_cost_usd = add_costs(failed_cost_usd, r.cost_usd);
+            }
+            Err(e) => failed_attempts.push_str(&format!("=== ERROR ===\n{}\n", e)),
+        }
+        let label = model.as_deref().unwrap_or("the default model").to_string();
+        if retry == ModelRetry::Retry {
+            attempt += 1;
+            tracing::warn!(instance = id, kind = error.kind(), error = %error, "Retrying on {}", label);
+        } else {
+            tracing::warn!(
+                instance = id,
+                kind = error.kind(),
+                error = %error,
+                "Falling back from {} to {}",
+                label,
+                fallback
+            );
+            fallback_from = Some(label);
+            model = Some(fallback.clone());
+        }
+        failed_attempts.push_str(&format!(
+            "\n=== RETRY ON {} ===\n",
+            model.as_deref().unwrap_or("the default model")
+        ));
+    };
+
+    // Writable again for the checks and hooks to come
+    drop(lock);
+    let protected_violations =
+        if has_baseline && settings.seed_dir.is_some() && !settings.protection.is_empty() {
+            match git::changed_paths(workspace.path()).await {
+                Ok(changed) => settings.protection.violations(&changed),
+                Err(e) => {
+                    tracing::warn!(instance = id, error = %e, "Failed to check protected paths");
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+    if !protected_violations.is_empty() {
+        tracing::warn!(instance = id, paths = ?protected_violations, "Protected paths were changed");
+    }
+
+    let oversized = size_watch.and_then(|(watch, _)| watch.exceeded());
+    let size_aborted = oversized.is_some()
+        && settings.max_workspace_size.map(|limit| limit.action) == Some(SizeAction::Abort);
+    // Only the deadline stops sessions without cancelling the run
+    let past_deadline =
+        !size_aborted && settings.session_cancel.is_cancelled() && !settings.cancel.is_cancelled();
+
+    if has_baseline && settings.auto_commit.is_some() {
+        let message = format!(
+            "actually: {} implementation\n\n{}",
+            names::label(id, settings.name.as_deref()),
+            strategy
+        );
+        if let Err(e) = git::commit_all(workspace.path(), &message).await {
+            tracing::warn!(instance = id, error = %e, "Failed to commit implementation");
+        }
+    }
+
+    let diffstat = if has_baseline {
+        match git::diffstat(workspace.path()).await {
+            Ok(stat) => Some(stat),
+            Err(e) => {
+                tracing::warn!(instance = id, error = %e, "Failed to compute diffstat");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let patch = if has_baseline {
+        write_patch(
+            workspace,
+            run_dir,
+            settings.layout,
+            id,
+            settings.name.as_deref(),
+        )
+        .await
+    } else {
+        None
+    };
 
-    match session.run_implementation(&full_prompt).await {
+    match session_result {
         Ok(SessionResult {
             transcript,
             success,
+            cost_usd,
+            num_turns,
+            cancelled,
+            over_budget,
+            error,
+            summary,
         }) => {
-            let full_transcript = format!(
-                "=== STRATEGY SELECTION ===\n{}\n\n{}",
-                strategy_transcript, transcript
+            let full_transcript = truncate_middle(
+                &format!(
+                    "=== STRATEGY SELECTION ===\n{}\n\n{}{}",
+                    strategy_transcript, failed_attempts, transcript
+                ),
+                settings.max_transcript_bytes,
             );
+            let cost_usd = add_costs(failed_cost_usd, cost_usd);
             InstanceResult {
                 instance_id: id,
                 strategy: strategy.to_string(),
                 workspace_path: workspace.path().to_string_lossy().to_string(),
-                success,
-                error: if success {
+                success: success && !size_aborted,
+                error: if size_aborted {
+                    Some(format!(
+                        "Workspace too large: {}, limit {}",
+                        format_size(oversized.unwrap_or_default()),
+                        format_size(
+                            settings
+                                .max_workspace_size
+                                .map_or(0, |limit| limit.max_bytes)
+                        )
+                    ))
+                } else if cancelled && past_deadline {
+                    Some("Stopped at the run's deadline".to_string())
+                } else if cancelled {
+                    Some("Cancelled".to_string())
+                } else if over_budget {
+                    Some(format!(
+                        "Over budget: cost ${:.2}, limit ${:.2}",
+                        cost_usd.unwrap_or_default(),
+                        settings.max_cost_usd.unwrap_or_default()
+                    ))
+                } else if let Some(e) = error {
+                    Some(e.to_string())
+                } else if success {
                     None
                 } else {
                     Some("Session reported failure".to_string())
                 },
                 transcript: full_transcript,
+                cost_usd,
+                duration_ms: Some(started.elapsed().as_millis() as u64),
+                num_turns,
+                diffstat,
+                patch,
+                model,
+                fallback_from,
+                over_budget,
+                past_deadline: cancelled && past_deadline,
+                oversized_workspace_bytes: oversized,
+                protected_violations,
+                backend: settings.backend.map(|b| b.name),
+                summary,
+                ..Default::default()
             }
         }
-        Err(e) => InstanceResult {
-            instance_id: id,
-            strategy: strategy.to_string(),
-            workspace_path: workspace.path().to_string_lossy().to_string(),
-            success: false,
-            error: Some(e.to_string()),
-            transcript: format!(
-                "=== STRATEGY SELECTION ===\n{}\n\n=== ERROR ===\n{}",
-                strategy_transcript, e
-            ),
-        },
+        Err(e) => {
+            let past_deadline = past_deadline && matches!(e, SessionError::Cancelled);
+            InstanceResult {
+                instance_id: id,
+                strategy: strategy.to_string(),
+                workspace_path: workspace.path().to_string_lossy().to_string(),
+                success: false,
+                error: Some(if past_deadline {
+                    "Stopped at the run's deadline".to_string()
+                } else {
+                    e.to_string()
+                }),
+                transcript: format!(
+                    "=== STRATEGY SELECTION ===\n{}\n\n{}=== ERROR ===\n{}",
+                    strategy_transcript, failed_attempts, e
+                ),
+                cost_usd: failed_cost_usd,
+                duration_ms: Some(started.elapsed().as_millis() as u64),
+                diffstat,
+                patch,
+                model,
+                fallback_from,
+                past_deadline,
+                protected_violations,
+                backend: settings.backend.map(|b| b.name),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Write the workspace's changes since its baseline to `<workspace>.patch`
+/// among instance `id`'s files in the run directory, returning its path
+/// there. Empty changes get no file.
+async fn write_patch(
+    workspace: &Workspace,
+    run_dir: &Path,
+    layout: RunLayout,
+    id: usize,
+    name: Option<&str>,
+) -> Option<String> {
+    let patch = match git::patch(workspace.path()).await {
+        Ok(patch) => patch,
+        Err(e) => {
+            tracing::warn!(workspace = ?workspace.path(), error = %e, "Failed to export patch");
+            return None;
+        }
+    };
+    if patch.is_empty() {
+        return None;
+    }
+    let file_name = format!("{}.patch", layout.stem(workspace.path(), id, name));
+    let path = layout.artifact(run_dir, id, &file_name);
+    let relative = path.strip_prefix(run_dir).unwrap_or(&path);
+    match std::fs::write(&path, patch) {
+        Ok(()) => Some(relative.to_string_lossy().into_owned()),
+        Err(e) => {
+            tracing::warn!(patch = %relative.display(), error = %e, "Failed to write patch");
+            None
+        }
+    }
+}
+
+/// Run one implementation session in `workspace` on `backend`, with
+/// `model` if it is Claude Code, streaming its output to `transcript_log`
+async fn run_session(
+    id: usize,
+    workspace: &Workspace,
+    full_prompt: &str,
+    backend: &Backend,
+    model: Option<&str>,
+    transcript_log: &Path,
+    settings: &InstanceSettings,
+) -> Result<SessionResult, SessionError> {
+    let mut env = settings.env.clone();
+    env.extend(backend.env.clone());
+
+    let (events, events_rx) = mpsc::unbounded_channel();
+    let event_handler = tokio::spawn(handle_session_events(
+        id,
+        workspace.path().to_path_buf(),
+        events_rx,
+        transcript_log.to_path_buf(),
+        settings.clone(),
+    ));
+
+    // The event sender is dropped with the session (or command), closing
+    // the channel and ending the handler
+    let result = match &backend.command {
+        Some(command) => {
+            backend::run_command(
+                command,
+                workspace.path(),
+                full_prompt,
+                &env,
+                &settings.cancel,
+                Some(events),
+                settings.max_transcript_bytes,
+            )
+            .await
+        }
+        None => {
+            let mut session = ClaudeSession::with_cwd_and_model(workspace.path(), model)
+                .with_env(env)
+                .inherit_settings(settings.inherit_claude_settings)
+                .with_transcript_detail(settings.transcript_detail)
+                .with_cancellation(settings.cancel.clone())
+                .with_spend(settings.spend.clone())
+                .with_max_cost(settings.max_cost_usd)
+                .with_max_transcript_bytes(settings.max_transcript_bytes)
+                .with_summary_prompt(SELF_SUMMARY_PROMPT);
+            if settings.api_log {
+                session = session.with_api_log(&workspace.path().join(API_LOG_FILE));
+            }
+            session
+                .with_events(events)
+                .run_implementation(full_prompt)
+                .await
+        }
+    };
+    event_handler.await.ok();
+    result
+}
+
+/// Sum two optional costs, known if either is
+fn add_costs(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Append transcript output to `transcript_log` (for `actually tail`), pass
+/// it on to the dashboard, `--stream` and `--events`, and with
+/// `--auto-commit turn` commit the workspace every time a batch of tool
+/// calls completes, so the agent's progress has a step-by-step history
+async fn handle_session_events(
+    id: usize,
+    workspace: PathBuf,
+    mut events: mpsc::UnboundedReceiver<SessionEvent>,
+    transcript_log: PathBuf,
+    settings: InstanceSettings,
+) {
+    let commit_turns = settings.auto_commit == Some(AutoCommit::Turn);
+    let stream_prefix = settings
+        .stream
+        .then(|| format!("[{}]", names::label(id, settings.name.as_deref())));
+    let mut log = LiveLog::open(&transcript_log, settings.max_transcript_bytes)
+        .map_err(|e| tracing::warn!(log = ?transcript_log, error = %e, "Failed to create transcript log"))
+        .ok();
+    let mut batch = 0;
+    while let Some(event) = events.recv().await {
+        match event {
+            SessionEvent::Output(text) => {
+                if let Some(log) = &mut log {
+                    let _ = log.write_line(&text);
+                }
+                if let Some(prefix) = &stream_prefix {
+                    for line in text.lines() {
+                        println!("{} {}", prefix, line);
+                    }
+                }
+                if settings.events {
+                    event_stream::emit(&StreamEvent::Message {
+                        instance: id,
+                        text: &text,
+                    });
+                }
+                if let Some(dashboard) = &settings.dashboard {
+                    let _ = dashboard.send(DashboardEvent::Output { instance: id, text });
+                }
+            }
+            SessionEvent::ToolBatchComplete if commit_turns => {
+                batch += 1;
+                let message = format!("actually: C{} tool batch {}", id, batch);
+                if let Err(e) = git::commit_all(&workspace, &message).await {
+                    tracing::warn!(instance = id, error = %e, "Failed to commit tool batch");
+                }
+            }
+            SessionEvent::ToolBatchComplete => {}
+        }
+    }
+    if let Some(Err(e)) = log.map(LiveLog::finish) {
+        tracing::warn!(log = ?transcript_log, error = %e, "Failed to write transcript log");
     }
 }
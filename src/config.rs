@@ -0,0 +1,436 @@
+use crate::cleanup::KeepWorkspaces;
+use crate::git::AutoCommit;
+use crate::icons::StatusIcons;
+use crate::layout::RunLayout;
+use crate::quota::SizeAction;
+use crate::session::TranscriptDetail;
+use crate::strategy::{ExclusionStrength, FailedStrategies, StrategyDepth, Variation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Model used for strategy generation unless one is configured: planning is
+/// a short task that doesn't need the implementation model
+pub const DEFAULT_STRATEGY_MODEL: &str = "haiku";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    ReadFailed(PathBuf, std::io::Error),
+    #[error("Failed to parse config file {0}: {1}")]
+    ParseFailed(PathBuf, toml::de::Error),
+    #[error("No profile `{0}` in {1} (profiles: {2})")]
+    UnknownProfile(String, PathBuf, String),
+    #[error("--profile {0} needs a config file defining [profiles.{0}]")]
+    NoConfigFile(String),
+}
+
+/// Settings loaded from `actually.toml`
+///
+/// Lookup order: `--config <path>`, then `./actually.toml`, then
+/// `$XDG_CONFIG_HOME/actually/config.toml` (or `~/.config/actually/config.toml`).
+/// Command-line flags take precedence over anything set here. A
+/// `[profiles.<name>]` table holds any of these keys too, and `--profile`
+/// lays them over the rest.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub build_cache: BuildCacheConfig,
+    /// Same as `--num`
+    pub num: Option<usize>,
+    /// Same as `--model`
+    pub model: Option<String>,
+    /// Same as `--impl-model`
+    pub impl_model: Option<String>,
+    /// Model for strategy generation (see `--strategy-model`)
+    pub strategy_model: Option<String>,
+    /// CLAUDE.md template dropped into every workspace (see `--claude-md`)
+    pub claude_md: Option<PathBuf>,
+    /// Same as `--allow-dirty`
+    pub allow_dirty: bool,
+    /// Same as `--layout`
+    pub layout: Option<RunLayout>,
+    /// Same as `--workspace-dir`
+    pub workspace_dir: Option<PathBuf>,
+    /// Same as `--tmpfs`
+    pub tmpfs: bool,
+    /// Same as `--copy-back-workspaces`
+    pub copy_back_workspaces: bool,
+    /// Same as `--keep-workspaces`
+    pub keep_workspaces: Option<KeepWorkspaces>,
+    /// Same as `--archive-workspaces`
+    pub archive_workspaces: bool,
+    /// Same as `--inherit-claude-settings`
+    pub inherit_claude_settings: bool,
+    /// Same as `--auto-commit`
+    pub auto_commit: Option<AutoCommit>,
+    /// Same as `--transcript-detail`
+    pub transcript_detail: Option<TranscriptDetail>,
+    /// Same as `--status-icons`
+    pub status_icons: Option<StatusIcons>,
+    /// Same as `--max-cost-per-instance`
+    pub max_cost_per_instance: Option<f64>,
+    /// Same as `--max-cost`
+    pub max_cost: Option<f64>,
+    /// Same as `--deadline`, e.g. `"45m"`
+    pub deadline: Option<String>,
+    /// Same as `--max-workspace-size`, e.g. `"2G"`
+    pub max_workspace_size: Option<String>,
+    /// Same as `--workspace-size-action`
+    pub workspace_size_action: Option<SizeAction>,
+    /// Same as `--max-transcript-bytes`
+    pub max_transcript_bytes: Option<usize>,
+    /// Same as `--fallback-model`
+    pub fallback_model: Option<String>,
+    /// Same as `--stream` (headless mode only)
+    pub stream: bool,
+    /// Same as `--plain`
+    pub plain: bool,
+    /// Same as `--confirm-strategies`
+    pub confirm_strategies: bool,
+    /// Same as `--strategy-depth`
+    pub strategy_depth: Option<StrategyDepth>,
+    /// Same as `--exclusion-strength`
+    pub exclusion_strength: Option<ExclusionStrength>,
+    /// Same as `--failed-strategies`
+    pub failed_strategies: Option<FailedStrategies>,
+    /// Same as `--impl-instructions`
+    pub impl_instructions: Option<String>,
+    /// Same as `--verify`
+    pub verify: Option<String>,
+    /// Same as `--no-verify`
+    pub no_verify: bool,
+    /// Same as `--build` (with the build command detected)
+    pub build: bool,
+    /// Same as `--build <command>`
+    pub build_command: Option<String>,
+    /// Same as `--lint` (with the linter detected)
+    pub lint: bool,
+    /// Same as `--lint <command>`
+    pub lint_command: Option<String>,
+    /// Same as `--coverage` (with the coverage tool detected)
+    pub coverage: bool,
+    /// Same as `--coverage <command>`
+    pub coverage_command: Option<String>,
+    /// Same as `--bench`
+    pub bench: Option<String>,
+    /// Same as `--bench-pattern`
+    pub bench_pattern: Option<String>,
+    /// Same as `--bench-higher-is-better`
+    pub bench_higher_is_better: bool,
+    /// Same as `--cross-review`
+    pub cross_review: bool,
+    /// Same as `--debate-rounds`
+    pub debate_rounds: Option<u32>,
+    /// Same as `--judge`
+    pub judge: bool,
+    /// Same as `--synthesize`
+    pub synthesize: bool,
+    /// Same as `--tournament`
+    pub tournament: bool,
+    /// Same as `--judge-command`
+    pub judge_command: Option<String>,
+    /// Same as `--vote`
+    pub vote: bool,
+    /// Same as `--diversify-on`
+    pub diversify_on: Vec<String>,
+    /// Same as `--variation`
+    pub variation: Vec<Variation>,
+    /// Same as `--language`
+    pub language: Option<String>,
+    /// Same as `--framework`
+    pub framework: Option<String>,
+    /// Same as `--strategy-source`
+    pub strategy_source: Option<String>,
+    /// Same as `--seed-strategies`
+    pub seed_strategies: Option<PathBuf>,
+    /// Criteria the judge scores solutions on (default: `Criterion::default_rubric`)
+    pub rubric: Vec<Criterion>,
+    /// How the suggested winner is scored (`[winner]`)
+    pub winner: WinnerWeights,
+    /// Same as `--names`
+    pub names: Vec<String>,
+    /// Backends `--backends` can name, e.g. `[backends.local]`
+    pub backends: HashMap<String, BackendConfig>,
+    /// Shell commands run around instances and phases (`[hooks]`)
+    pub hooks: HooksConfig,
+    /// Environment for every implementation instance, with per-instance
+    /// placeholders (`[env]`, see `workspace::instance_env`)
+    pub env: HashMap<String, String>,
+    /// Proxy and CA settings for everything actually starts (`[network]`)
+    pub network: NetworkConfig,
+    /// Paths agents mustn't change in seeded workspaces (`[protect]`)
+    pub protect: ProtectConfig,
+}
+
+/// Paths of the seeded directory that agents mustn't change, as gitignore
+/// patterns (e.g. `ci/`, `/LICENSE`). A change to any of them is reported
+/// as a violation in the results.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProtectConfig {
+    /// Copied into the workspace, but read-only during the session
+    pub read_only: Vec<String>,
+    /// Left out of the workspace altogether
+    pub exclude: Vec<String>,
+}
+
+/// How sessions and every other process actually starts reach the network,
+/// e.g. through a corporate proxy
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Same as `--proxy`
+    pub proxy: Option<String>,
+    /// Same as `--no-proxy`
+    pub no_proxy: Option<String>,
+    /// Same as `--ca-cert`
+    pub ca_cert: Option<PathBuf>,
+}
+
+/// Shell commands run at points of a run, for setup and teardown such as
+/// database fixtures. Each gets `ACTUALLY_*` environment variables saying
+/// where in the run it is.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Run in each workspace before its session; failing fails the instance
+    pub pre_instance: Option<String>,
+    /// Run in each workspace after its session and checks
+    pub post_instance: Option<String>,
+    /// Run in the run directory before each phase; failing stops the run
+    pub pre_phase: Option<String>,
+    /// Run in the run directory after each phase
+    pub post_phase: Option<String>,
+    /// Run in the run directory when the run is over
+    pub post_run: Option<String>,
+}
+
+/// An implementation backend: Claude Code with its own environment and
+/// model (e.g. pointed at a local model server), or another agent's CLI
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackendConfig {
+    /// Command run in the workspace instead of Claude Code; `{prompt}` in an
+    /// argument is replaced by the prompt, which otherwise goes to stdin
+    pub command: Option<Vec<String>>,
+    /// Extra environment variables for the session or command
+    pub env: HashMap<String, String>,
+    /// Model for Claude Code (ignored with `command`)
+    pub model: Option<String>,
+}
+
+/// Weights of the factors combined into each instance's composite score,
+/// from which the results suggest a winner. A weight of 0 ignores a factor.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WinnerWeights {
+    /// The workspace builds (`--build`)
+    pub build: f64,
+    /// The tests pass (`--verify`)
+    pub tests: f64,
+    /// Few lint warnings (`--lint`)
+    pub lint: f64,
+    /// The judge's score or ranking (`--judge`)
+    pub judge: f64,
+    /// A small change (lines inserted plus deleted)
+    pub diff_size: f64,
+    /// A cheap implementation session
+    pub cost: f64,
+}
+
+impl Default for WinnerWeights {
+    fn default() -> Self {
+        Self {
+            build: 2.0,
+            tests: 3.0,
+            lint: 0.5,
+            judge: 3.0,
+            diff_size: 0.5,
+            cost: 1.0,
+        }
+    }
+}
+
+/// Build caches shared across instance workspaces, per language
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildCacheConfig {
+    /// Share one `CARGO_TARGET_DIR` between all workspaces
+    pub rust: bool,
+    /// Share the npm cache and pnpm store between all workspaces
+    pub node: bool,
+    /// Where the shared caches live (default: `{run_dir}/.build-cache`)
+    pub dir: Option<PathBuf>,
+}
+
+/// One judging criterion, e.g. `[[rubric]] name = "correctness" weight = 3`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Criterion {
+    pub name: String,
+    /// Relative importance; scores are combined as a weighted average
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// What the judge should look for, included in the judge prompt
+    pub description: Option<String>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl Criterion {
+    fn new(name: &str, weight: f64, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            weight,
+            description: Some(description.to_string()),
+        }
+    }
+
+    /// Rubric used when the config file doesn't define one
+    pub fn default_rubric() -> Vec<Self> {
+        vec![
+            Self::new(
+                "correctness",
+                2.0,
+                "Does it fully and correctly solve the task, including edge cases?",
+            ),
+            Self::new(
+                "simplicity",
+                1.0,
+                "Is the change small, readable, and easy to maintain?",
+            ),
+            Self::new(
+                "test_coverage",
+                1.0,
+                "Are the changes covered by meaningful tests?",
+            ),
+            Self::new("performance", 1.0, "Is it efficient for realistic inputs?"),
+        ]
+    }
+}
+
+impl BuildCacheConfig {
+    pub fn any_enabled(&self) -> bool {
+        self.rust || self.node
+    }
+}
+
+impl Config {
+    /// Load the config from an explicit path, or from the first default
+    /// location that exists, with `profile`'s settings laid over it.
+    /// Returns the default config if none is found.
+    pub fn load(explicit: Option<&Path>, profile: Option<&str>) -> Result<Self, ConfigError> {
+        let path = match explicit {
+            Some(p) => p.to_path_buf(),
+            None => match default_paths().into_iter().find(|p| p.is_file()) {
+                Some(p) => p,
+                None => match profile {
+                    Some(name) => return Err(ConfigError::NoConfigFile(name.to_string())),
+                    None => return Ok(Self::default()),
+                },
+            },
+        };
+
+        let content =
+            fs::read_to_string(&path).map_err(|e| ConfigError::ReadFailed(path.clone(), e))?;
+        let mut table: toml::Table =
+            toml::from_str(&content).map_err(|e| ConfigError::ParseFailed(path.clone(), e))?;
+        let profiles = match table.remove("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles,
+            _ => toml::Table::new(),
+        };
+        if let Some(name) = profile {
+            match profiles.get(name) {
+                Some(toml::Value::Table(overrides)) => overlay(&mut table, overrides),
+                _ => {
+                    let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                    let names = if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    };
+                    return Err(ConfigError::UnknownProfile(name.to_string(), path, names));
+                }
+            }
+        }
+        table
+            .try_into()
+            .map_err(|e| ConfigError::ParseFailed(path, e))
+    }
+}
+
+/// Lay `overrides` over `table`: tables such as `[env]` are merged key by
+/// key, and everything else is replaced
+fn overlay(table: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => overlay(base, value),
+            _ => {
+                table.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn default_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("actually.toml")];
+    if let Ok(xdg) = std::env::var("XDG_CONFIG_HOME") {
+        paths.push(PathBuf::from(xdg).join("actually").join("config.toml"));
+    } else if let Ok(home) = std::env::var("HOME") {
+        paths.push(
+            PathBuf::from(home)
+                .join(".config")
+                .join("actually")
+                .join("config.toml"),
+        );
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actually.toml");
+        fs::write(
+            &path,
+            r#"
+num = 3
+verify = "make test"
+env = { RUST_LOG = "info", CI = "1" }
+
+[profiles.cheap]
+num = 2
+model = "haiku"
+env = { RUST_LOG = "warn" }
+
+[profiles.typo]
+verfy = "make"
+"#,
+        )
+        .unwrap();
+
+        let base = Config::load(Some(&path), None).unwrap();
+        assert_eq!((base.num, base.model), (Some(3), None));
+        let cheap = Config::load(Some(&path), Some("cheap")).unwrap();
+        assert_eq!(cheap.num, Some(2));
+        assert_eq!(cheap.model.as_deref(), Some("haiku"));
+        assert_eq!(cheap.verify.as_deref(), Some("make test"));
+        assert_eq!(cheap.env["RUST_LOG"], "warn");
+        assert_eq!(cheap.env["CI"], "1");
+
+        assert!(matches!(
+            Config::load(Some(&path), Some("thorough")),
+            Err(ConfigError::UnknownProfile(..))
+        ));
+        assert!(Config::load(Some(&path), Some("typo")).is_err());
+    }
+}
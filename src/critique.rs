@@ -0,0 +1,128 @@
+use crate::conductor::InstanceResult;
+use crate::git;
+use crate::session::ClaudeSession;
+use crate::strategy::build_cross_review_prompt;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Diffs larger than this are cut off before being put in a review prompt
+pub(crate) const MAX_REVIEW_DIFF_CHARS: usize = 40_000;
+
+/// One instance's critique of another instance's implementation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Critique {
+    /// Instance that wrote the critique
+    pub reviewer: usize,
+    pub text: String,
+}
+
+/// Peer review: every successful instance critiques the next successful
+/// instance's work (C0 reviews C1, C1 reviews C2, ..., the last reviews C0).
+/// Critiques are attached to the reviewed instance.
+pub async fn cross_review(
+    task: &str,
+    results: &mut [InstanceResult],
+    model: Option<&str>,
+    interactive: bool,
+) {
+    let reviewable: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.success && !r.workspace_path.is_empty())
+        .map(|(i, _)| i)
+        .collect();
+
+    if reviewable.len() < 2 {
+        if interactive {
+            println!("Cross-review skipped: fewer than two successful instances");
+        } else {
+            tracing::info!("Cross-review skipped: fewer than two successful instances");
+        }
+        return;
+    }
+
+    let pairs: Vec<(usize, usize)> = reviewable
+        .iter()
+        .enumerate()
+        .map(|(j, &reviewer)| (reviewer, reviewable[(j + 1) % reviewable.len()]))
+        .collect();
+
+    if interactive {
+        println!(
+            "Cross-review: {} instances critiquing each other",
+            pairs.len()
+        );
+    } else {
+        tracing::info!(reviews = pairs.len(), "Cross-review starting");
+    }
+
+    let reviews = pairs.iter().map(|&(reviewer, reviewed)| {
+        let target = &results[reviewed];
+        async move {
+            let text = review_instance(task, target, model).await;
+            (reviewer, reviewed, text)
+        }
+    });
+
+    for (reviewer, reviewed, text) in join_all(reviews).await {
+        let reviewer_id = results[reviewer].instance_id;
+        let reviewed_id = results[reviewed].instance_id;
+        let (reviewer_label, reviewed_label) =
+            (results[reviewer].label(), results[reviewed].label());
+        match text {
+            Ok(text) => {
+                if interactive {
+                    println!("  {} reviewed {}", reviewer_label, reviewed_label);
+                } else {
+                    tracing::info!(
+                        reviewer = reviewer_id,
+                        reviewed = reviewed_id,
+                        "Critique written"
+                    );
+                }
+                results[reviewed].critiques.push(Critique {
+                    reviewer: reviewer_id,
+                    text,
+                });
+            }
+            Err(e) => {
+                eprintln!(
+                    "ERROR [{} reviewing {}]: {}",
+                    reviewer_label, reviewed_label, e
+                );
+            }
+        }
+    }
+}
+
+async fn review_instance(
+    task: &str,
+    target: &InstanceResult,
+    model: Option<&str>,
+) -> anyhow::Result<String> {
+    let workspace = Path::new(&target.workspace_path);
+    let changes = changes_for_prompt(workspace, MAX_REVIEW_DIFF_CHARS).await;
+
+    let prompt = build_cross_review_prompt(task, &target.strategy, &changes);
+    let session = ClaudeSession::with_cwd_and_model(workspace, model);
+    let response = session.query_read_only(&prompt).await?;
+    Ok(response.trim().to_string())
+}
+
+/// An instance's diff against its baseline, fenced for a prompt and cut off
+/// after `max_chars`. Falls back to pointing the agent at the files when the
+/// workspace has no baseline.
+pub(crate) async fn changes_for_prompt(workspace: &Path, max_chars: usize) -> String {
+    match git::diff(workspace).await {
+        Ok(diff) if diff.chars().count() > max_chars => {
+            let cut: String = diff.chars().take(max_chars).collect();
+            format!(
+                "```diff\n{}\n```\n(diff truncated; inspect the files for the rest)",
+                cut
+            )
+        }
+        Ok(diff) => format!("```diff\n{}\n```", diff),
+        Err(_) => "Inspect the files in the solution's directory.".to_string(),
+    }
+}
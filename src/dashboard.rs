@@ -0,0 +1,643 @@
+use crate::budget::{Budget, BudgetChoice};
+use crate::cancel::CancellationToken;
+use crate::icons::{Status, StatusIcons};
+use crate::output::format_duration_ms;
+use crate::pause::PauseControl;
+use crate::spend::Spend;
+use crate::workspace::{ChangeKind, FileChange};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use std::collections::VecDeque;
+use std::io::stdout;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{error::TryRecvError, UnboundedReceiver};
+
+/// Recently changed files kept per instance for display
+const MAX_FILES: usize = 50;
+
+/// Transcript lines kept per instance for the log view; older ones are
+/// dropped, so a long session doesn't hold its whole transcript in memory
+const MAX_LINES: usize = 5000;
+
+/// Progress reported by implementation instances while they run
+#[derive(Debug, Clone)]
+pub enum DashboardEvent {
+    /// Text appended to an instance's transcript
+    Output {
+        instance: usize,
+        text: String,
+    },
+    /// A file in an instance's workspace was created, modified or removed
+    FileChanged {
+        instance: usize,
+        change: FileChange,
+    },
+    Finished {
+        instance: usize,
+        success: bool,
+    },
+}
+
+/// Run-wide totals along the bottom of the review TUI and the dashboard:
+/// time since the run started, what it has cost so far and its models
+pub struct StatusBar {
+    pub started: Instant,
+    pub spend: Spend,
+    /// Models (or backends) the phase runs on, e.g. `opus` or `claude, codex`
+    pub models: String,
+}
+
+impl StatusBar {
+    /// Draw the bar into `area`, with how many things are in each state
+    /// (e.g. `(2, "running", Color::Yellow)`) after the elapsed time; states
+    /// nothing is in are left out
+    pub fn render(&self, frame: &mut Frame, area: Rect, counts: &[(usize, &str, Color)]) {
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut spans = vec![Span::raw(format!(
+            " {}",
+            format_duration_ms(self.started.elapsed().as_millis() as u64)
+        ))];
+        for (count, state, color) in counts.iter().filter(|(count, _, _)| *count > 0) {
+            spans.push(Span::styled(" · ", dim));
+            spans.push(Span::styled(
+                format!("{} {}", count, state),
+                Style::default().fg(*color),
+            ));
+        }
+        spans.push(Span::styled(" · ", dim));
+        spans.push(Span::raw(format!("${:.2} so far", self.spend.total_usd())));
+        spans.push(Span::styled(" · ", dim));
+        spans.push(Span::raw(self.models.clone()));
+        let bar = Paragraph::new(Line::from(spans))
+            .style(Style::default().bg(Color::Black).fg(Color::White));
+        frame.render_widget(bar, area);
+    }
+}
+
+struct InstancePanel {
+    id: usize,
+    label: String,
+    strategy: String,
+    /// Elapsed time and outcome once the instance is done
+    finished: Option<(Duration, bool)>,
+    /// The latest `MAX_LINES` transcript lines, which the log view scrolls
+    /// through
+    lines: VecDeque<String>,
+    /// Most recently changed files, newest last, one entry per path
+    files: VecDeque<FileChange>,
+}
+
+impl InstancePanel {
+    /// Add `text`'s lines, returning how many old ones made way for them
+    fn push_output(&mut self, text: &str) -> usize {
+        let mut dropped = 0;
+        for line in text.lines() {
+            if self.lines.len() == MAX_LINES {
+                self.lines.pop_front();
+                dropped += 1;
+            }
+            self.lines.push_back(line.to_string());
+        }
+        dropped
+    }
+
+    /// Move `change` to the end of the feed. A file created and then written
+    /// during the run still shows as created.
+    fn push_file_change(&mut self, mut change: FileChange) {
+        if let Some(pos) = self.files.iter().position(|f| f.path == change.path) {
+            if let Some(previous) = self.files.remove(pos) {
+                if previous.kind == ChangeKind::Created && change.kind == ChangeKind::Modified {
+                    change.kind = ChangeKind::Created;
+                }
+            }
+        }
+        if self.files.len() == MAX_FILES {
+            self.files.pop_front();
+        }
+        self.files.push_back(change);
+    }
+}
+
+enum View {
+    Overview,
+    /// One instance's full transcript. `scroll` is the first visible line;
+    /// while `follow` is set the view sticks to the newest output.
+    Log {
+        scroll: usize,
+        follow: bool,
+    },
+}
+
+impl View {
+    /// `count` lines were dropped from the front of the shown transcript:
+    /// a view scrolled back moves up with the lines it shows
+    fn lines_dropped(&mut self, count: usize) {
+        if let View::Log {
+            scroll,
+            follow: false,
+        } = self
+        {
+            *scroll = scroll.saturating_sub(count);
+        }
+    }
+}
+
+/// Live view of the implementation phase: one panel per instance showing
+/// its status and the latest transcript output, and `Enter` for a
+/// scrollable log of the selected instance. Ctrl+C cancels `cancel`; the
+/// dashboard stays up while the instances wind down and returns once every
+/// instance has finished. `status` runs along the bottom. Once the run has
+/// nearly used up one of its `budgets`, nothing new starts until the user
+/// says whether to extend it, stop once the running instances finish, or
+/// kill them.
+pub async fn run(
+    instances: &[(usize, String, String)],
+    mut events: UnboundedReceiver<DashboardEvent>,
+    pause: &PauseControl,
+    cancel: &CancellationToken,
+    icons: StatusIcons,
+    status: &StatusBar,
+    budgets: &[Budget],
+) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let mut panels: Vec<InstancePanel> = instances
+        .iter()
+        .map(|(id, label, strategy)| InstancePanel {
+            id: *id,
+            label: label.clone(),
+            strategy: strategy.lines().next().unwrap_or_default().to_string(),
+            finished: None,
+            lines: VecDeque::new(),
+            files: VecDeque::new(),
+        })
+        .collect();
+
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let mut selected = 0;
+    let mut view = View::Overview;
+    // The budget asked about, and whether the run was paused already when
+    // the question opened
+    let mut budget_question: Option<(&Budget, bool)> = None;
+
+    loop {
+        let mut closed = false;
+        loop {
+            match events.try_recv() {
+                Ok(DashboardEvent::Output { instance, text }) => {
+                    if let Some(i) = panels.iter().position(|p| p.id == instance) {
+                        let dropped = panels[i].push_output(&text);
+                        if i == selected {
+                            view.lines_dropped(dropped);
+                        }
+                    }
+                }
+                Ok(DashboardEvent::FileChanged { instance, change }) => {
+                    if let Some(panel) = panels.iter_mut().find(|p| p.id == instance) {
+                        panel.push_file_change(change);
+                    }
+                }
+                Ok(DashboardEvent::Finished { instance, success }) => {
+                    if let Some(panel) = panels.iter_mut().find(|p| p.id == instance) {
+                        panel.finished = Some((started.elapsed(), success));
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+        // Ctrl+C answers the question too
+        if let Some((_, was_paused)) = budget_question.filter(|_| cancel.is_cancelled()) {
+            pause.set_paused(was_paused);
+            budget_question = None;
+        }
+        if budget_question.is_none() && !cancel.is_cancelled() {
+            if let Some(budget) = budgets.iter().find(|b| b.reached()) {
+                budget_question = Some((budget, pause.is_paused()));
+                pause.set_paused(true);
+            }
+        }
+        // An open budget question is still answered once everything is done
+        if budget_question.is_none() && (closed || panels.iter().all(|p| p.finished.is_some())) {
+            break;
+        }
+
+        let elapsed = started.elapsed();
+        let mut log_height = 0;
+        terminal.draw(|frame| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(4), Constraint::Length(1)])
+                .split(frame.area());
+            match &mut view {
+                View::Overview => draw_overview(
+                    frame,
+                    chunks[0],
+                    &panels,
+                    selected,
+                    elapsed,
+                    pause.is_paused(),
+                    cancel.is_cancelled(),
+                    icons,
+                ),
+                View::Log { scroll, follow } => {
+                    log_height = draw_log(
+                        frame,
+                        chunks[0],
+                        &panels[selected],
+                        elapsed,
+                        scroll,
+                        follow,
+                        icons,
+                    );
+                }
+            }
+            let count = |state: Option<bool>| {
+                panels
+                    .iter()
+                    .filter(|p| p.finished.map(|(_, success)| success) == state)
+                    .count()
+            };
+            status.render(
+                frame,
+                chunks[1],
+                &[
+                    (count(None), "running", Color::Yellow),
+                    (count(Some(true)), "done", Color::Green),
+                    (count(Some(false)), "failed", Color::Red),
+                ],
+            );
+            if let Some((budget, _)) = budget_question {
+                draw_budget_question(frame, chunks[0], budget);
+            }
+        })?;
+
+        while event::poll(Duration::ZERO)? {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                cancel.cancel();
+                continue;
+            }
+            if let Some((budget, was_paused)) = budget_question {
+                let choice = match key.code {
+                    KeyCode::Char('e') => BudgetChoice::Extend(budget.next_limit()),
+                    KeyCode::Char('s') => BudgetChoice::Stop,
+                    KeyCode::Char('k') => BudgetChoice::Kill,
+                    _ => continue,
+                };
+                budget.apply(choice);
+                pause.set_paused(was_paused);
+                budget_question = None;
+                continue;
+            }
+            if key.code == KeyCode::Char('p') {
+                pause.toggle();
+                continue;
+            }
+            match &mut view {
+                View::Overview => match key.code {
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        selected = (selected + 1) % panels.len();
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        selected = (selected + panels.len() - 1) % panels.len();
+                    }
+                    KeyCode::Enter => {
+                        view = View::Log {
+                            scroll: 0,
+                            follow: true,
+                        };
+                    }
+                    _ => {}
+                },
+                View::Log { scroll, follow } => {
+                    let page = log_height.max(1);
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') => view = View::Overview,
+                        KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            *scroll = scroll.saturating_sub(1);
+                            *follow = false;
+                        }
+                        KeyCode::PageDown | KeyCode::Char(' ') => {
+                            *scroll = scroll.saturating_add(page)
+                        }
+                        KeyCode::PageUp => {
+                            *scroll = scroll.saturating_sub(page);
+                            *follow = false;
+                        }
+                        KeyCode::Char('g') | KeyCode::Home => {
+                            *scroll = 0;
+                            *follow = false;
+                        }
+                        KeyCode::Char('G') | KeyCode::End => *follow = true,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}
+
+/// The question asked over the dashboard once the run has nearly used up
+/// `budget`
+fn draw_budget_question(frame: &mut Frame, area: Rect, budget: &Budget) {
+    let key = |key: &'static str, text: String| {
+        Line::from(vec![
+            Span::styled(key, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(text),
+        ])
+    };
+    let text = vec![
+        Line::from(format!("{}.", budget.describe())),
+        Line::from(Span::styled(
+            "Nothing new starts until you choose.",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+        key(
+            "e",
+            format!(
+                "  Extend the {} to {}",
+                budget.name(),
+                budget.format(budget.next_limit())
+            ),
+        ),
+        key("s", "  Stop once the running instances finish".to_string()),
+        key("k", "  Kill the running instances now".to_string()),
+    ];
+
+    let popup_width = 56;
+    let popup_height = text.len() as u16 + 2;
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(popup_width) / 2,
+        y: area.y + area.height.saturating_sub(popup_height) / 2,
+        width: popup_width.min(area.width),
+        height: popup_height.min(area.height),
+    };
+
+    frame.render_widget(Clear, popup_area);
+    let popup = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(format!(" Nearly at the {} ", budget.name())),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+/// Panel title: instance id, colored status, and the strategy's first line
+fn panel_title(panel: &InstancePanel, elapsed: Duration, icons: StatusIcons) -> Line<'_> {
+    let (status, color) = match panel.finished {
+        None => (
+            format!(
+                "{}running {}",
+                icons.prefix(Status::Running),
+                format_duration_ms(elapsed.as_millis() as u64)
+            ),
+            Color::Yellow,
+        ),
+        Some((took, true)) => (
+            format!(
+                "{}done in {}",
+                icons.prefix(Status::Ok),
+                format_duration_ms(took.as_millis() as u64)
+            ),
+            Color::Green,
+        ),
+        Some((took, false)) => (
+            format!(
+                "{}failed after {}",
+                icons.prefix(Status::Failed),
+                format_duration_ms(took.as_millis() as u64)
+            ),
+            Color::Red,
+        ),
+    };
+    Line::from(vec![
+        Span::raw(format!(" {} ", panel.label)),
+        Span::styled(status, Style::default().fg(color)),
+        Span::raw(format!(" {} ", panel.strategy)),
+    ])
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_overview(
+    frame: &mut Frame,
+    area: Rect,
+    panels: &[InstancePanel],
+    selected: usize,
+    elapsed: Duration,
+    paused: bool,
+    stopping: bool,
+    icons: StatusIcons,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    let count = panels.len().max(1) as u32;
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            panels
+                .iter()
+                .map(|_| Constraint::Ratio(1, count))
+                .collect::<Vec<_>>(),
+        )
+        .split(chunks[0]);
+
+    for (i, (panel, area)) in panels.iter().zip(areas.iter()).enumerate() {
+        let border = if i == selected {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .split(*area);
+
+        // Show the tails that fit inside the borders
+        let visible = area.height.saturating_sub(2) as usize;
+        let skip = panel.lines.len().saturating_sub(visible);
+        let text: Vec<Line> = panel
+            .lines
+            .iter()
+            .skip(skip)
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        let body = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border)
+                .title(panel_title(panel, elapsed, icons)),
+        );
+        frame.render_widget(body, columns[0]);
+
+        let skip = panel.files.len().saturating_sub(visible);
+        let files: Vec<Line> = panel
+            .files
+            .iter()
+            .skip(skip)
+            .map(|f| {
+                let (marker, color) = match f.kind {
+                    ChangeKind::Created => ("+", Color::Green),
+                    ChangeKind::Modified => ("~", Color::Yellow),
+                    ChangeKind::Removed => ("-", Color::Red),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                    Span::raw(f.path.as_str()),
+                ])
+            })
+            .collect();
+        let feed = Paragraph::new(files).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border)
+                .title(" Files "),
+        );
+        frame.render_widget(feed, columns[1]);
+    }
+
+    let help = if stopping {
+        Paragraph::new("STOPPING: waiting for instances to disconnect and save their work")
+            .style(Style::default().fg(Color::Red))
+    } else if paused {
+        Paragraph::new("PAUSED: running instances finish, nothing new starts  p: resume")
+            .style(Style::default().fg(Color::Yellow))
+    } else {
+        Paragraph::new("j/k: select  Enter: view log  p: pause  Ctrl+C: stop all instances")
+            .style(Style::default().fg(Color::DarkGray))
+    };
+    frame.render_widget(help, chunks[1]);
+}
+
+/// Full-screen transcript of one instance. Clamps `scroll` to the output,
+/// pins it to the bottom while `follow` is set (and sets `follow` again once
+/// the user scrolls back down). Returns the number of visible lines.
+fn draw_log(
+    frame: &mut Frame,
+    area: Rect,
+    panel: &InstancePanel,
+    elapsed: Duration,
+    scroll: &mut usize,
+    follow: &mut bool,
+    icons: StatusIcons,
+) -> usize {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+
+    let visible = chunks[0].height.saturating_sub(2) as usize;
+    let bottom = panel.lines.len().saturating_sub(visible);
+    if *follow || *scroll >= bottom {
+        *scroll = bottom;
+        *follow = true;
+    }
+
+    let text: Vec<Line> = panel
+        .lines
+        .iter()
+        .skip(*scroll)
+        .take(visible)
+        .map(|l| Line::from(l.as_str()))
+        .collect();
+    let log = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(panel_title(panel, elapsed, icons)),
+    );
+    frame.render_widget(log, chunks[0]);
+
+    let position = if *follow {
+        "following".to_string()
+    } else {
+        format!("line {}/{}", *scroll + 1, panel.lines.len())
+    };
+    let help = Paragraph::new(format!(
+        "j/k, PgUp/PgDn: scroll  g/G: top/follow  Esc: back  ({})",
+        position
+    ))
+    .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help, chunks[1]);
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrolled_back_log_keeps_its_place() {
+        let mut panel = InstancePanel {
+            id: 0,
+            label: "C0".to_string(),
+            strategy: String::new(),
+            finished: None,
+            lines: VecDeque::new(),
+            files: VecDeque::new(),
+        };
+        let output: String = (0..MAX_LINES).map(|i| format!("line {}\n", i)).collect();
+        assert_eq!(panel.push_output(&output), 0);
+
+        let mut view = View::Log {
+            scroll: 100,
+            follow: false,
+        };
+        let mut following = View::Log {
+            scroll: MAX_LINES,
+            follow: true,
+        };
+        let dropped = panel.push_output("new 0\nnew 1\nnew 2\n");
+        assert_eq!(dropped, 3);
+        assert_eq!(panel.lines.len(), MAX_LINES);
+        view.lines_dropped(dropped);
+        following.lines_dropped(dropped);
+        let View::Log { scroll, .. } = view else {
+            unreachable!()
+        };
+        assert_eq!(panel.lines[scroll], "line 100");
+        assert!(matches!(
+            following,
+            View::Log {
+                scroll: MAX_LINES,
+                ..
+            }
+        ));
+
+        // Scrolled to the very top, the view stays there
+        let mut view = View::Log {
+            scroll: 1,
+            follow: false,
+        };
+        view.lines_dropped(panel.push_output("new 3\nnew 4\n"));
+        assert!(matches!(view, View::Log { scroll: 0, .. }));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::budget::Budget;
+use crate::cancel::CancellationToken;
+use crate::output::format_duration_ms;
+use std::time::{Duration, Instant};
+
+/// Parse a duration such as `45m`, `90s`, `2h` or `1h30m`. Each number
+/// needs its unit (`h`, `m` or `s`), so `45` can't be misread.
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let mut rest = duration.trim();
+    if rest.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    let mut secs = 0.0;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration `{}`", duration))?;
+        let tail = tail.trim_start();
+        let unit_len = tail
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        secs += number
+            * match unit.to_ascii_lowercase().as_str() {
+                "h" => 3600.0,
+                "m" => 60.0,
+                "s" => 1.0,
+                _ => {
+                    return Err(format!(
+                        "invalid duration unit in `{}` (use h, m or s, e.g. 45m)",
+                        duration
+                    ))
+                }
+            };
+        rest = tail.trim_start();
+    }
+    if secs < 1.0 {
+        return Err(format!("duration `{}` must be at least a second", duration));
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// The run's wall-clock limit (`--deadline`), counted from its start. Once
+/// it passes, nothing more starts and running sessions are stopped the way
+/// Ctrl+C stops them, but what finished is still checked and reported.
+/// Interactive runs are asked about it through its `budget` instead, before
+/// it passes.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    duration: Duration,
+    budget: Budget,
+    stop: CancellationToken,
+}
+
+impl Deadline {
+    /// Start counting `duration` from `started`; headless runs stop once it
+    /// has passed
+    pub fn start(
+        started: Instant,
+        duration: Duration,
+        interactive: bool,
+        cancel: &CancellationToken,
+    ) -> Self {
+        let stop = cancel.child();
+        let deadline = Self {
+            duration,
+            budget: Budget::time(started, duration, &stop),
+            stop,
+        };
+        tokio::spawn({
+            let at = started + duration;
+            let cancel = cancel.clone();
+            let stop = deadline.stop.clone();
+            async move {
+                let passed = async {
+                    if interactive {
+                        std::future::pending::<()>().await;
+                    }
+                    tokio::time::sleep_until(at.into()).await;
+                };
+                tokio::select! {
+                    _ = cancel.cancelled() => {}
+                    _ = passed => {
+                        tracing::warn!(
+                            deadline = %format_duration_ms(duration.as_millis() as u64),
+                            "Run reached its deadline, stopping instances"
+                        );
+                        stop.cancel();
+                    }
+                }
+            }
+        });
+        deadline
+    }
+
+    /// The instances were stopped at the deadline (or with the run)
+    pub fn is_reached(&self) -> bool {
+        self.stop.is_cancelled()
+    }
+
+    /// What interactive runs are asked about
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Cancelled along with the run and once the deadline passes: what
+    /// sessions and evaluation stop on in place of the run's own token
+    pub fn token(&self) -> &CancellationToken {
+        &self.stop
+    }
+
+    /// The deadline as given, e.g. `45m 00s`
+    pub fn describe(&self) -> String {
+        format_duration_ms(self.duration.as_millis() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline() {
+        assert_eq!(parse_duration("45m"), Ok(Duration::from_secs(45 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("1.5h"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("2m 30s"), Ok(Duration::from_secs(150)));
+        assert!(parse_duration("45").is_err());
+        assert!(parse_duration("3 days").is_err());
+        assert!(parse_duration("0s").is_err());
+
+        let cancel = CancellationToken::default();
+        let deadline = Deadline::start(Instant::now(), Duration::from_millis(50), false, &cancel);
+        assert!(!deadline.is_reached());
+        deadline.token().cancelled().await;
+        assert!(deadline.is_reached() && !cancel.is_cancelled());
+
+        // Interactive runs are asked instead, so only Ctrl+C stops them here
+        let asked = Deadline::start(Instant::now(), Duration::from_millis(50), true, &cancel);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!asked.is_reached() && asked.budget().exceeded());
+        cancel.cancel();
+        asked.token().cancelled().await;
+    }
+}
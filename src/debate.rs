@@ -0,0 +1,64 @@
+use crate::session::{ClaudeSession, SessionError};
+use crate::strategy::{build_debate_turn_prompt, build_debate_verdict_prompt};
+
+/// Rounds per debate when neither `--debate-rounds` nor the config sets it
+pub const DEFAULT_ROUNDS: usize = 2;
+
+/// Outcome of a moderated debate between two strategies
+#[derive(Debug, Clone)]
+pub struct Debate {
+    /// Every turn, in order, as Markdown
+    pub transcript: String,
+    /// The moderator's summary and recommendation
+    pub verdict: String,
+}
+
+/// One side of a debate: a label such as "C1" and the strategy it argues for
+pub struct Side<'a> {
+    pub label: &'a str,
+    pub strategy: &'a str,
+}
+
+/// Have two agents argue for their strategies over `rounds` rounds, then ask
+/// a moderator to summarize. `on_turn` is called with each speaker's label
+/// and argument as soon as the turn completes.
+pub async fn run_debate(
+    session: &ClaudeSession,
+    task: &str,
+    a: Side<'_>,
+    b: Side<'_>,
+    rounds: usize,
+    mut on_turn: impl FnMut(&str, &str),
+) -> Result<Debate, SessionError> {
+    let mut history = String::new();
+
+    for round in 1..=rounds {
+        for (speaker, opponent) in [(&a, &b), (&b, &a)] {
+            let prompt = build_debate_turn_prompt(
+                task,
+                speaker.label,
+                speaker.strategy,
+                opponent.strategy,
+                &history,
+                round,
+                rounds,
+            );
+            let argument = session.query_read_only(&prompt).await?;
+            let argument = argument.trim();
+            on_turn(speaker.label, argument);
+            history.push_str(&format!(
+                "### {} (round {})\n\n{}\n\n",
+                speaker.label, round, argument
+            ));
+        }
+    }
+
+    let verdict_prompt =
+        build_debate_verdict_prompt(task, (a.label, a.strategy), (b.label, b.strategy), &history);
+    let verdict = session.query_read_only(&verdict_prompt).await?;
+
+    Ok(Debate {
+        transcript: history,
+        verdict: verdict.trim().to_string(),
+    })
+}
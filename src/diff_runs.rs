@@ -0,0 +1,312 @@
+use crate::checkpoint::Checkpoint;
+use crate::conductor::InstanceResult;
+use crate::history::strategy_archetype;
+use crate::output::format_duration_ms;
+use crate::strategy::Strategy;
+use crate::verify::Check;
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Word overlap at which two strategies count as the same approach
+const SIMILAR_THRESHOLD: f64 = 0.35;
+
+/// Words shorter than this don't count towards strategy overlap
+const MIN_WORD_LEN: usize = 4;
+
+/// The part of results.json `actually diff-runs` needs
+#[derive(Deserialize)]
+struct ResultsFile {
+    instances: Vec<InstanceResult>,
+}
+
+/// One side of the comparison
+struct RunSummary {
+    prompt: Option<String>,
+    instances: Vec<InstanceResult>,
+    /// Parsed strategy of each instance, in the same order
+    strategies: Vec<Strategy>,
+}
+
+impl RunSummary {
+    fn load(run_dir: &Path) -> anyhow::Result<Self> {
+        let path = run_dir.join("results.json");
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let results: ResultsFile = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let strategies = results
+            .instances
+            .iter()
+            .map(|r| Strategy::parse(&r.strategy))
+            .collect();
+        Ok(Self {
+            prompt: Checkpoint::load(run_dir).ok().map(|c| c.prompt),
+            instances: results.instances,
+            strategies,
+        })
+    }
+
+    fn succeeded(&self) -> usize {
+        self.instances.iter().filter(|r| r.success).count()
+    }
+
+    fn total_cost(&self) -> Option<f64> {
+        let costs: Vec<f64> = self.instances.iter().filter_map(|r| r.cost_usd).collect();
+        (!costs.is_empty()).then(|| costs.iter().sum())
+    }
+
+    fn mean_duration_ms(&self) -> Option<u64> {
+        let durations: Vec<u64> = self
+            .instances
+            .iter()
+            .filter_map(|r| r.duration_ms)
+            .collect();
+        (!durations.is_empty()).then(|| durations.iter().sum::<u64>() / durations.len() as u64)
+    }
+
+    fn mean_turns(&self) -> Option<f64> {
+        let turns: Vec<u32> = self.instances.iter().filter_map(|r| r.num_turns).collect();
+        (!turns.is_empty()).then(|| turns.iter().sum::<u32>() as f64 / turns.len() as f64)
+    }
+
+    /// "passed/run" for the check `check` picks out, `-` if it never ran
+    fn check_rate(&self, check: impl Fn(&InstanceResult) -> &Option<Check>) -> String {
+        let checks: Vec<&Check> = self
+            .instances
+            .iter()
+            .filter_map(|r| check(r).as_ref())
+            .collect();
+        if checks.is_empty() {
+            return "-".to_string();
+        }
+        let passed = checks.iter().filter(|c| c.passed).count();
+        format!("{}/{}", passed, checks.len())
+    }
+
+    fn models(&self) -> String {
+        let models: BTreeSet<&str> = self
+            .instances
+            .iter()
+            .map(|r| r.model.as_deref().unwrap_or("default"))
+            .collect();
+        models.into_iter().collect::<Vec<_>>().join(", ")
+    }
+
+    /// Strategy archetypes and how many instances took each
+    fn archetypes(&self) -> String {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for result in &self.instances {
+            *counts
+                .entry(strategy_archetype(&result.strategy))
+                .or_default() += 1;
+        }
+        counts
+            .iter()
+            .map(|(name, n)| format!("{} {}", name, n))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Content words of a strategy, for telling how alike two are
+fn words(strategy: &Strategy) -> BTreeSet<String> {
+    strategy
+        .raw
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() >= MIN_WORD_LEN)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Share of their combined words two strategies have in common (Jaccard)
+fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Pair up strategies of the two runs that take the same approach, most
+/// alike first, each strategy in at most one pair: `(a index, b index,
+/// similarity)`
+fn match_strategies(a: &[Strategy], b: &[Strategy]) -> Vec<(usize, usize, f64)> {
+    let a_words: Vec<_> = a.iter().map(words).collect();
+    let b_words: Vec<_> = b.iter().map(words).collect();
+    let mut candidates: Vec<(usize, usize, f64)> = Vec::new();
+    for (i, aw) in a_words.iter().enumerate() {
+        for (j, bw) in b_words.iter().enumerate() {
+            let score = similarity(aw, bw);
+            if score >= SIMILAR_THRESHOLD {
+                candidates.push((i, j, score));
+            }
+        }
+    }
+    candidates.sort_by(|x, y| y.2.total_cmp(&x.2));
+
+    let mut pairs = Vec::new();
+    let (mut used_a, mut used_b) = (BTreeSet::new(), BTreeSet::new());
+    for (i, j, score) in candidates {
+        if !used_a.contains(&i) && !used_b.contains(&j) {
+            used_a.insert(i);
+            used_b.insert(j);
+            pairs.push((i, j, score));
+        }
+    }
+    pairs.sort_by_key(|&(i, _, _)| i);
+    pairs
+}
+
+/// `actually diff-runs`: compare two runs side by side (success rates,
+/// costs, verification outcomes and which strategies they share), e.g. to
+/// see what a different model or prompt template changed
+pub fn run(a_dir: &Path, b_dir: &Path) -> anyhow::Result<()> {
+    let a = RunSummary::load(a_dir)?;
+    let b = RunSummary::load(b_dir)?;
+
+    println!("A: {}", a_dir.display());
+    println!("B: {}", b_dir.display());
+    match (&a.prompt, &b.prompt) {
+        (Some(pa), Some(pb)) if pa != pb => {
+            println!();
+            println!("The runs had different prompts:");
+            println!("  A: {}", pa);
+            println!("  B: {}", pb);
+        }
+        _ => {}
+    }
+
+    let rate = |run: &RunSummary| {
+        let n = run.instances.len();
+        format!(
+            "{}/{} ({:.0}%)",
+            run.succeeded(),
+            n,
+            if n == 0 {
+                0.0
+            } else {
+                run.succeeded() as f64 * 100.0 / n as f64
+            }
+        )
+    };
+    let cost = |run: &RunSummary| {
+        run.total_cost()
+            .map_or_else(|| "-".to_string(), |c| format!("${:.2}", c))
+    };
+    let cost_per_success = |run: &RunSummary| match (run.total_cost(), run.succeeded()) {
+        (Some(c), n) if n > 0 => format!("${:.2}", c / n as f64),
+        _ => "-".to_string(),
+    };
+    let duration = |run: &RunSummary| {
+        run.mean_duration_ms()
+            .map_or_else(|| "-".to_string(), format_duration_ms)
+    };
+    let turns = |run: &RunSummary| {
+        run.mean_turns()
+            .map_or_else(|| "-".to_string(), |t| format!("{:.1}", t))
+    };
+    let rows: [(&str, String, String); 10] = [
+        ("Succeeded", rate(&a), rate(&b)),
+        ("Total cost", cost(&a), cost(&b)),
+        (
+            "Cost per success",
+            cost_per_success(&a),
+            cost_per_success(&b),
+        ),
+        ("Mean duration", duration(&a), duration(&b)),
+        ("Mean turns", turns(&a), turns(&b)),
+        (
+            "Build passed",
+            a.check_rate(|r| &r.build),
+            b.check_rate(|r| &r.build),
+        ),
+        (
+            "Tests passed",
+            a.check_rate(|r| &r.verification),
+            b.check_rate(|r| &r.verification),
+        ),
+        (
+            "Lint passed",
+            a.check_rate(|r| &r.lint),
+            b.check_rate(|r| &r.lint),
+        ),
+        (
+            "Coverage passed",
+            a.check_rate(|r| &r.coverage),
+            b.check_rate(|r| &r.coverage),
+        ),
+        ("Models", a.models(), b.models()),
+    ];
+    let width = rows.iter().map(|(_, va, _)| va.len()).max().unwrap_or(0);
+    println!();
+    println!("  {:<18} {:<width$}  B", "", "A", width = width);
+    for (name, va, vb) in rows {
+        println!("  {:<18} {:<width$}  {}", name, va, vb, width = width);
+    }
+
+    println!();
+    println!("Strategies");
+    println!("  Archetypes A: {}", a.archetypes());
+    println!("  Archetypes B: {}", b.archetypes());
+    let pairs = match_strategies(&a.strategies, &b.strategies);
+    let describe = |run: &RunSummary, i: usize| {
+        let result = &run.instances[i];
+        format!(
+            "{} \"{}\" ({})",
+            result.label(),
+            run.strategies[i].title,
+            if result.success { "ok" } else { "failed" }
+        )
+    };
+    if !pairs.is_empty() {
+        println!("  In both:");
+        for &(i, j, score) in &pairs {
+            println!(
+                "    A {}  ~  B {}  ({:.0}% alike)",
+                describe(&a, i),
+                describe(&b, j),
+                score * 100.0
+            );
+        }
+    }
+    for (side, run, paired) in [
+        ("A", &a, pairs.iter().map(|p| p.0).collect::<BTreeSet<_>>()),
+        ("B", &b, pairs.iter().map(|p| p.1).collect::<BTreeSet<_>>()),
+    ] {
+        let only: Vec<usize> = (0..run.instances.len())
+            .filter(|i| !paired.contains(i))
+            .collect();
+        if !only.is_empty() {
+            println!("  Only in {}:", side);
+            for i in only {
+                println!("    {}", describe(run, i));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_strategies() {
+        let a = [
+            Strategy::parse("Cache parsed templates in a global hashmap keyed by path"),
+            Strategy::parse("Rewrite the parser from scratch using a recursive descent design"),
+        ];
+        let b = [
+            Strategy::parse("Write a recursive descent parser from scratch, replacing the old one"),
+            Strategy::parse("Cache parsed templates in a hashmap keyed by their path"),
+            Strategy::parse("Vendor a parsing library"),
+        ];
+        let pairs: Vec<(usize, usize)> = match_strategies(&a, &b)
+            .into_iter()
+            .map(|(i, j, _)| (i, j))
+            .collect();
+        assert_eq!(pairs, vec![(0, 1), (1, 0)]);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::conductor::InstanceResult;
+use serde::Serialize;
+
+/// One line of the `--events` stream, for tools driving their own UI on top
+/// of a headless run
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StreamEvent<'a> {
+    /// The run is starting, writing its output to `run_dir`
+    RunStarted {
+        run_dir: &'a str,
+        prompt: &'a str,
+        num_instances: usize,
+    },
+    /// Phase 1 produced the strategy for `instance`
+    StrategyGenerated {
+        instance: usize,
+        title: &'a str,
+        strategy: &'a str,
+    },
+    /// An implementation session is starting in `workspace`
+    InstanceStarted {
+        instance: usize,
+        name: Option<&'a str>,
+        strategy: &'a str,
+        workspace: &'a str,
+    },
+    /// Transcript output from an implementation session
+    Message { instance: usize, text: &'a str },
+    /// An instance is done, checks included
+    InstanceFinished {
+        instance: usize,
+        name: Option<&'a str>,
+        success: bool,
+        error: Option<&'a str>,
+        cost_usd: Option<f64>,
+        duration_ms: Option<u64>,
+        num_turns: Option<u32>,
+    },
+    /// The run is over and its results are written to `run_dir`
+    RunComplete {
+        run_dir: &'a str,
+        succeeded: usize,
+        failed: usize,
+        cost_usd: Option<f64>,
+        cancelled: bool,
+        suggested_winner: Option<usize>,
+    },
+}
+
+impl<'a> StreamEvent<'a> {
+    pub fn instance_finished(result: &'a InstanceResult) -> Self {
+        Self::InstanceFinished {
+            instance: result.instance_id,
+            name: result.name.as_deref(),
+            success: result.success,
+            error: result.error.as_deref(),
+            cost_usd: result.cost_usd,
+            duration_ms: result.duration_ms,
+            num_turns: result.num_turns,
+        }
+    }
+}
+
+/// Print `event` as one line of JSON on stdout
+pub fn emit(event: &StreamEvent) {
+    match serde_json::to_string(event) {
+        Ok(line) => println!("{}", line),
+        Err(e) => tracing::warn!(error = %e, "Failed to serialize event"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json() {
+        let event = StreamEvent::Message {
+            instance: 1,
+            text: "Reading src/main.rs",
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"message","instance":1,"text":"Reading src/main.rs"}"#
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+use tokio::process::Command;
+
+/// When to commit agent work in each workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoCommit {
+    /// Once, after the implementation session finishes
+    End,
+    /// After every batch of tool calls, plus once at the end
+    Turn,
+}
+
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error("Failed to run git: {0}")]
+    SpawnFailed(#[from] std::io::Error),
+    #[error("git {0} failed: {1}")]
+    CommandFailed(String, String),
+    #[error("{0} has no repository of its own")]
+    NoRepository(String),
+}
+
+/// Refuse to operate on `dir` unless it is the root of its own repository.
+/// Workspaces usually live inside the user's project, and git would
+/// otherwise happily discover (and stage files in) that parent repository.
+fn ensure_own_repo(dir: &Path) -> Result<(), GitError> {
+    if dir.join(".git").exists() {
+        Ok(())
+    } else {
+        Err(GitError::NoRepository(dir.display().to_string()))
+    }
+}
+
+/// Identity used for commits actually makes on the agents' behalf, so
+/// committing works even where the user has no git identity configured
+const COMMITTER: [&str; 4] = [
+    "-c",
+    "user.name=actually",
+    "-c",
+    "user.email=actually@localhost",
+];
+
+/// Run a git subcommand in `dir`, returning stdout
+async fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .args(COMMITTER)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(
+            args.first().copied().unwrap_or_default().to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Tag marking the workspace's starting contents, before any agent work
+pub const BASELINE_TAG: &str = "actually-baseline";
+
+/// Size of an instance's changes relative to its baseline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl fmt::Display for DiffStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} file{}, +{} -{}",
+            self.files_changed,
+            if self.files_changed == 1 { "" } else { "s" },
+            self.insertions,
+            self.deletions
+        )
+    }
+}
+
+/// Initialize a repository in `dir` and record its current contents as the
+/// baseline commit that agent work is compared against. `exclude` holds
+/// extra gitignore-style rules (from .actuallyignore) that keep matching
+/// files out of commits and diffs.
+pub async fn init_with_baseline(dir: &Path, exclude: Option<&str>) -> Result<(), GitError> {
+    git(dir, &["init", "--quiet"]).await?;
+    if let Some(rules) = exclude {
+        let info_dir = dir.join(".git").join("info");
+        tokio::fs::create_dir_all(&info_dir).await?;
+        tokio::fs::write(info_dir.join("exclude"), rules).await?;
+    }
+    git(dir, &["add", "--all"]).await?;
+    git(
+        dir,
+        &[
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "--no-verify",
+            "-m",
+            "actually: baseline",
+        ],
+    )
+    .await?;
+    git(dir, &["tag", BASELINE_TAG]).await?;
+    Ok(())
+}
+
+/// Measure everything that changed since the baseline, committed or not
+pub async fn diffstat(dir: &Path) -> Result<DiffStat, GitError> {
+    ensure_own_repo(dir)?;
+    git(dir, &["add", "--all"]).await?;
+    let numstat = git(dir, &["diff", "--cached", "--numstat", BASELINE_TAG]).await?;
+    Ok(parse_numstat(&numstat))
+}
+
+/// Paths that changed since the baseline, committed or not, relative to `dir`
+pub async fn changed_paths(dir: &Path) -> Result<Vec<String>, GitError> {
+    ensure_own_repo(dir)?;
+    git(dir, &["add", "--all"]).await?;
+    let names = git(
+        dir,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "--no-renames",
+            BASELINE_TAG,
+        ],
+    )
+    .await?;
+    Ok(names.lines().map(str::to_string).collect())
+}
+
+/// Full unified diff of everything that changed since the baseline
+pub async fn diff(dir: &Path) -> Result<String, GitError> {
+    ensure_own_repo(dir)?;
+    git(dir, &["add", "--all"]).await?;
+    git(dir, &["diff", "--cached", BASELINE_TAG]).await
+}
+
+/// Changes since the baseline, leaving the index alone so a workspace an
+/// agent is still working in can be looked at. New files are listed by
+/// name after the diff.
+pub async fn working_diff(dir: &Path) -> Result<String, GitError> {
+    ensure_own_repo(dir)?;
+    let mut diff = git(dir, &["diff", BASELINE_TAG]).await?;
+    let untracked = git(dir, &["ls-files", "--others", "--exclude-standard"]).await?;
+    for path in untracked.lines() {
+        diff.push_str(&format!("New file: {}\n", path));
+    }
+    Ok(diff)
+}
+
+/// Everything that changed since the baseline as a patch `git apply` can
+/// take, binary files included
+pub async fn patch(dir: &Path) -> Result<String, GitError> {
+    ensure_own_repo(dir)?;
+    git(dir, &["add", "--all"]).await?;
+    git(dir, &["diff", "--cached", "--binary", BASELINE_TAG]).await
+}
+
+/// Sum `git diff --numstat` output. Binary files ("-\t-\tpath") count as
+/// changed files without line counts.
+fn parse_numstat(numstat: &str) -> DiffStat {
+    let mut stat = DiffStat::default();
+    for line in numstat.lines() {
+        let mut fields = line.split('\t');
+        let (Some(added), Some(removed), Some(_path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        stat.files_changed += 1;
+        stat.insertions += added.parse::<usize>().unwrap_or(0);
+        stat.deletions += removed.parse::<usize>().unwrap_or(0);
+    }
+    stat
+}
+
+/// Stage everything and commit it. Returns false if there was nothing to commit.
+pub async fn commit_all(dir: &Path, message: &str) -> Result<bool, GitError> {
+    ensure_own_repo(dir)?;
+    git(dir, &["add", "--all"]).await?;
+    if git(dir, &["status", "--porcelain"])
+        .await?
+        .trim()
+        .is_empty()
+    {
+        return Ok(false);
+    }
+    git(dir, &["commit", "--quiet", "--no-verify", "-m", message]).await?;
+    Ok(true)
+}
+
+/// What `git status --short` lists under `dir` in the repository holding it,
+/// untracked files included, or `None` when `dir` isn't in a repository
+pub async fn uncommitted_changes(dir: &Path) -> Result<Option<Vec<String>>, GitError> {
+    match git(dir, &["rev-parse", "--is-inside-work-tree"]).await {
+        Ok(_) => {}
+        Err(GitError::CommandFailed(..)) => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let status = git(
+        dir,
+        &["status", "--short", "--untracked-files=all", "--", "."],
+    )
+    .await?;
+    Ok(Some(
+        status
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.trim_end().to_string())
+            .collect(),
+    ))
+}
+
+/// Commit everything that changed in `workspace` since its baseline onto a
+/// new `branch` in the repository containing `source_dir`, starting from
+/// that repository's HEAD. The commit is made in a temporary worktree, so
+/// the source's checkout and index are left alone. Returns false if the
+/// workspace has no changes.
+pub async fn branch_from_workspace(
+    source_dir: &Path,
+    workspace: &Path,
+    branch: &str,
+    message: &str,
+) -> Result<bool, GitError> {
+    let patch = patch(workspace).await?;
+    if patch.trim().is_empty() {
+        return Ok(false);
+    }
+    // Workspaces are seeded from `source_dir`, which may be a subdirectory
+    let prefix = git(source_dir, &["rev-parse", "--show-prefix"]).await?;
+    let prefix = prefix.trim();
+
+    let worktree = tempfile::tempdir()?;
+    let worktree_arg = worktree.path().to_string_lossy().to_string();
+    git(
+        source_dir,
+        &[
+            "worktree",
+            "add",
+            "--quiet",
+            "-b",
+            branch,
+            &worktree_arg,
+            "HEAD",
+        ],
+    )
+    .await?;
+    let committed = commit_patch(worktree.path(), prefix, &patch, message).await;
+    let removed = git(
+        source_dir,
+        &["worktree", "remove", "--force", &worktree_arg],
+    )
+    .await;
+    if committed.is_err() {
+        // Don't leave a branch behind that has none of the changes
+        let _ = git(source_dir, &["branch", "--quiet", "-D", branch]).await;
+    }
+    committed?;
+    removed?;
+    Ok(true)
+}
+
+/// Apply `patch` under `prefix` in `dir` and commit the result
+async fn commit_patch(
+    dir: &Path,
+    prefix: &str,
+    patch: &str,
+    message: &str,
+) -> Result<(), GitError> {
+    apply_to_index(dir, patch, prefix).await?;
+    git(dir, &["commit", "--quiet", "--no-verify", "-m", message]).await?;
+    Ok(())
+}
+
+/// Apply `patch` to the working tree and index of the repository at `dir`,
+/// with its paths under `prefix` (empty for as-is). Hunk line counts are
+/// recounted, so hand-edited hunks apply.
+pub async fn apply_to_index(dir: &Path, patch: &str, prefix: &str) -> Result<(), GitError> {
+    let patch_file = tempfile::NamedTempFile::new()?;
+    tokio::fs::write(patch_file.path(), patch).await?;
+    let patch_arg = patch_file.path().to_string_lossy().to_string();
+    let directory = format!("--directory={}", prefix);
+    let mut args = vec!["apply", "--index", "--recount"];
+    if !prefix.is_empty() {
+        args.push(&directory);
+    }
+    args.push(&patch_arg);
+    git(dir, &args).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numstat() {
+        let numstat = "10\t2\tsrc/main.rs\n0\t5\tREADME.md\n-\t-\tlogo.png\n";
+        assert_eq!(
+            parse_numstat(numstat),
+            DiffStat {
+                files_changed: 3,
+                insertions: 10,
+                deletions: 7,
+            }
+        );
+        assert_eq!(parse_numstat(""), DiffStat::default());
+    }
+
+    #[tokio::test]
+    async fn test_uncommitted_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(uncommitted_changes(dir.path()).await.unwrap(), None);
+
+        init_with_baseline(dir.path(), None).await.unwrap();
+        assert_eq!(
+            uncommitted_changes(dir.path()).await.unwrap(),
+            Some(Vec::new())
+        );
+        std::fs::write(dir.path().join("notes.md"), "draft").unwrap();
+        assert_eq!(
+            uncommitted_changes(dir.path()).await.unwrap(),
+            Some(vec!["?? notes.md".to_string()])
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::checkpoint::Checkpoint;
+use crate::conductor::InstanceResult;
+use crate::layout::RunLayout;
+use crate::names;
+use crate::output::PROMPT_FILE;
+use crate::tail::{self, TRANSCRIPT_LOG_SUFFIX};
+use crate::workspace::NOTES_FILE;
+use anyhow::Context;
+use crossterm::style::{Color, Stylize};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Files of the run as a whole that are searched too
+const RUN_FILES: &[&str] = &[PROMPT_FILE, "strategies.md", "judge.md", "tournament.md"];
+
+/// The part of results.json `actually grep` needs
+#[derive(Deserialize)]
+struct ResultsFile {
+    instances: Vec<InstanceResult>,
+}
+
+/// A file to search and where it comes from
+struct Source {
+    path: PathBuf,
+    /// The instance's label, or `run` for the run's own files
+    label: String,
+    id: Option<usize>,
+    /// What the file is: `strategy`, `transcript`, `notes` or its name
+    kind: String,
+}
+
+/// What one matching line (with its context) looks like
+#[derive(Debug, PartialEq)]
+pub struct Line<'a> {
+    /// 1-based line number
+    pub number: usize,
+    pub text: &'a str,
+    /// Whether the line matches, rather than being context
+    pub matched: bool,
+}
+
+/// The lines of `text` matching `regex`, each with up to `context` lines
+/// either side; matches whose context overlaps share a block
+pub fn search<'a>(text: &'a str, regex: &Regex, context: usize) -> Vec<Vec<Line<'a>>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut blocks: Vec<Vec<Line>> = Vec::new();
+    let mut shown_until = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if !regex.is_match(line) {
+            continue;
+        }
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(lines.len());
+        if blocks.is_empty() || start > shown_until {
+            blocks.push(Vec::new());
+        }
+        let block = blocks.last_mut().expect("a block was just pushed");
+        for (n, text) in lines
+            .iter()
+            .enumerate()
+            .take(end)
+            .skip(start.max(shown_until))
+        {
+            block.push(Line {
+                number: n + 1,
+                text,
+                matched: regex.is_match(text),
+            });
+        }
+        shown_until = shown_until.max(end);
+    }
+    blocks
+}
+
+/// The instances of the run in `run_dir`: its results, or for a run still
+/// going, the ones its checkpoint has finished
+fn instances(run_dir: &Path) -> Vec<InstanceResult> {
+    let results = std::fs::read_to_string(run_dir.join("results.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<ResultsFile>(&json).ok());
+    match results {
+        Some(results) => results.instances,
+        None => Checkpoint::load(run_dir)
+            .map(|c| c.finished.into_iter().map(|f| f.result).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// Everything in `run_dir` worth searching: each instance's strategy,
+/// transcript and notes, then the run's summaries
+fn sources(run_dir: &Path, instance: Option<usize>) -> anyhow::Result<Vec<Source>> {
+    let instances = instances(run_dir);
+    let label = |id: usize| crate::conductor::label_of(&instances, id);
+    let layout = RunLayout::of(run_dir);
+    let mut per_instance: BTreeMap<usize, Vec<Source>> = BTreeMap::new();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for dir in layout.artifact_dirs(run_dir) {
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        let mut entries: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        entries.sort();
+        files.extend(entries);
+    }
+    for path in files {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (id, kind) = if let Some((id, _)) = tail::parse_log_name(&file_name) {
+            (id, "transcript")
+        } else if let Some(id) = file_name
+            .strip_prefix('C')
+            .and_then(|rest| rest.strip_suffix("-strategy.md"))
+            .and_then(|id| id.parse().ok())
+        {
+            (id, "strategy")
+        } else {
+            continue;
+        };
+        per_instance.entry(id).or_default().push(Source {
+            path,
+            label: label(id),
+            id: Some(id),
+            kind: kind.to_string(),
+        });
+    }
+    for result in &instances {
+        let workspace = Path::new(&result.workspace_path);
+        if result.workspace_path.is_empty() {
+            continue;
+        }
+        let sources = per_instance.entry(result.instance_id).or_default();
+        // Runs from before transcripts were streamed only have session.log
+        let prefix = names::file_prefix(result.instance_id, result.name.as_deref());
+        let transcript = format!("{}-{}", prefix, TRANSCRIPT_LOG_SUFFIX);
+        if !layout
+            .artifact(run_dir, result.instance_id, &transcript)
+            .is_file()
+        {
+            sources.push(Source {
+                path: workspace.join("session.log"),
+                label: result.label(),
+                id: Some(result.instance_id),
+                kind: "transcript".to_string(),
+            });
+        }
+        sources.push(Source {
+            path: workspace.join(NOTES_FILE),
+            label: result.label(),
+            id: Some(result.instance_id),
+            kind: "notes".to_string(),
+        });
+    }
+
+    let mut sources: Vec<Source> = per_instance
+        .into_iter()
+        .filter(|(id, _)| instance.is_none_or(|wanted| wanted == *id))
+        .flat_map(|(_, sources)| sources)
+        .collect();
+    if instance.is_none() {
+        sources.extend(RUN_FILES.iter().map(|file| Source {
+            path: run_dir.join(file),
+            label: "run".to_string(),
+            id: None,
+            kind: file.to_string(),
+        }));
+    }
+    Ok(sources)
+}
+
+/// `line` with each match of `regex` highlighted
+fn highlight(line: &str, regex: &Regex) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for m in regex.find_iter(line) {
+        out.push_str(&line[last..m.start()]);
+        out.push_str(&m.as_str().with(Color::Red).bold().to_string());
+        last = m.end();
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+/// `actually grep`: print the lines matching `pattern` (a regex) in the
+/// strategies, transcripts, notes and summaries of each run in `run_dirs`,
+/// prefixed with the run, instance and file they are in, and with
+/// `context` lines around them. Only `instance`'s files are searched if
+/// given.
+pub fn run(
+    run_dirs: &[PathBuf],
+    pattern: &str,
+    ignore_case: bool,
+    context: usize,
+    instance: Option<usize>,
+) -> anyhow::Result<()> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .with_context(|| format!("Invalid pattern `{}`", pattern))?;
+    let mut matches = 0;
+    let mut first_block = true;
+    for run_dir in run_dirs {
+        if !run_dir.is_dir() {
+            anyhow::bail!("{} is not a run directory", run_dir.display());
+        }
+        for source in sources(run_dir, instance)? {
+            // Not every run has every file
+            let Ok(bytes) = std::fs::read(&source.path) else {
+                continue;
+            };
+            let text = String::from_utf8_lossy(&bytes);
+            let prefix = if run_dirs.len() > 1 {
+                format!("{}/{} {}", run_dir.display(), source.label, source.kind)
+            } else {
+                format!("{} {}", source.label, source.kind)
+            };
+            let color = source.id.map_or(Color::Grey, tail::color);
+            for block in search(&text, &regex, context) {
+                if context > 0 && !first_block {
+                    println!("--");
+                }
+                first_block = false;
+                for line in block {
+                    let separator = if line.matched { ':' } else { '-' };
+                    let location = format!("{}{}{}", prefix, separator, line.number);
+                    if line.matched {
+                        matches += 1;
+                        println!(
+                            "{}{} {}",
+                            location.with(color).bold(),
+                            separator,
+                            highlight(line.text, &regex)
+                        );
+                    } else {
+                        println!("{}{} {}", location.with(color), separator, line.text);
+                    }
+                }
+            }
+        }
+    }
+    if matches == 0 {
+        println!("No matches");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let text = "use std::io;\nasync fn run() {\n    tokio::select! {\n    }\n}\n\nfn other() {}\nfn select() {}\n";
+        let regex = Regex::new("select").unwrap();
+
+        let blocks = search(text, &regex, 0);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0][0].number, 3);
+        assert_eq!(blocks[1][0].text, "fn select() {}");
+
+        // Context that touches or overlaps merges the blocks
+        let blocks = search(text, &regex, 2);
+        assert_eq!(blocks.len(), 1);
+        let numbers: Vec<usize> = blocks[0].iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(blocks[0][2].matched && !blocks[0][3].matched);
+
+        assert!(search(text, &Regex::new("tokio::spawn").unwrap(), 1).is_empty());
+    }
+}
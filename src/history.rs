@@ -0,0 +1,197 @@
+use crate::conductor::InstanceResult;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("No history location: neither XDG_DATA_HOME nor HOME is set")]
+    NoLocation,
+    #[error("Failed to access history file {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("Failed to serialize run record: {0}")]
+    SerializeFailed(#[from] serde_json::Error),
+}
+
+/// One finished run, as appended to the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Unix timestamp (seconds) when the run finished
+    pub timestamp: u64,
+    pub prompt: String,
+    pub run_dir: String,
+    pub instances: Vec<InstanceRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceRecord {
+    pub instance_id: usize,
+    /// Implementation model, or "default" when the Claude Code default was used
+    pub model: String,
+    /// Coarse strategy category (see `strategy_archetype`)
+    pub archetype: String,
+    pub success: bool,
+    pub cost_usd: Option<f64>,
+    pub judge_rank: Option<usize>,
+    /// Instances this one beat in tournament matches
+    #[serde(default)]
+    pub defeated: Vec<usize>,
+}
+
+impl RunRecord {
+    pub fn new(prompt: &str, run_dir: &Path, results: &[InstanceResult]) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            timestamp,
+            prompt: prompt.to_string(),
+            run_dir: run_dir.display().to_string(),
+            instances: results
+                .iter()
+                .map(|r| InstanceRecord {
+                    instance_id: r.instance_id,
+                    model: r.model.clone().unwrap_or_else(|| "default".to_string()),
+                    archetype: strategy_archetype(&r.strategy).to_string(),
+                    success: r.success,
+                    cost_usd: r.cost_usd,
+                    judge_rank: r.judge_rank,
+                    defeated: r.defeated.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Head-to-head outcomes in this run as (winner, loser) instance pairs:
+    /// every judged instance beats each one ranked below it, and every
+    /// tournament match counts once. Failed instances lose to every success.
+    pub fn outcomes(&self) -> Vec<(&InstanceRecord, &InstanceRecord)> {
+        let mut outcomes = Vec::new();
+        for a in &self.instances {
+            for b in &self.instances {
+                let a_wins = match (a.judge_rank, b.judge_rank) {
+                    (Some(ra), Some(rb)) => ra < rb,
+                    _ => a.success && !b.success,
+                };
+                if a_wins || a.defeated.contains(&b.instance_id) {
+                    outcomes.push((a, b));
+                }
+            }
+        }
+        outcomes
+    }
+}
+
+/// Where run history is kept: `$XDG_DATA_HOME/actually/history.jsonl`
+/// (or `~/.local/share/actually/history.jsonl`)
+pub fn history_path() -> Result<PathBuf, HistoryError> {
+    let data_dir = if let Ok(xdg) = std::env::var("XDG_DATA_HOME") {
+        PathBuf::from(xdg)
+    } else if let Ok(home) = std::env::var("HOME") {
+        PathBuf::from(home).join(".local").join("share")
+    } else {
+        return Err(HistoryError::NoLocation);
+    };
+    Ok(data_dir.join("actually").join("history.jsonl"))
+}
+
+/// Append a run to the history file, one JSON object per line
+pub fn record(run: &RunRecord) -> Result<(), HistoryError> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| HistoryError::Io(parent.to_path_buf(), e))?;
+    }
+    let mut line = serde_json::to_vec(run)?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| f.write_all(&line))
+        .map_err(|e| HistoryError::Io(path, e))
+}
+
+/// Every recorded run, oldest first. Lines that fail to parse (e.g. from a
+/// newer version of actually) are skipped.
+pub fn load() -> Result<Vec<RunRecord>, HistoryError> {
+    let path = history_path()?;
+    let file = match fs::File::open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(HistoryError::Io(path, e)),
+    };
+    let mut runs = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| HistoryError::Io(path.clone(), e))?;
+        match serde_json::from_str(&line) {
+            Ok(run) => runs.push(run),
+            Err(e) => tracing::debug!(error = %e, "Skipping unreadable history line"),
+        }
+    }
+    Ok(runs)
+}
+
+/// Coarse category of a strategy, inferred from keywords so similar
+/// approaches group together across runs
+pub fn strategy_archetype(strategy: &str) -> &'static str {
+    const ARCHETYPES: &[(&str, &[&str])] = &[
+        (
+            "test-first",
+            &["test-driven", "tdd", "tests first", "test first"],
+        ),
+        ("rewrite", &["rewrite", "from scratch", "replace"]),
+        ("library", &["crate", "library", "dependency", "package"]),
+        ("refactor", &["refactor", "extract", "abstraction", "trait"]),
+        (
+            "performance",
+            &["cache", "parallel", "concurren", "optimi", "fast"],
+        ),
+        ("minimal", &["minimal", "simple", "smallest", "single"]),
+    ];
+    let lower = strategy.to_lowercase();
+    ARCHETYPES
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|k| lower.contains(k)))
+        .map(|(name, _)| *name)
+        .unwrap_or("other")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: usize, success: bool, judge_rank: Option<usize>) -> InstanceRecord {
+        InstanceRecord {
+            instance_id: id,
+            model: "default".to_string(),
+            archetype: "other".to_string(),
+            success,
+            cost_usd: None,
+            judge_rank,
+            defeated: vec![],
+        }
+    }
+
+    #[test]
+    fn test_outcomes_follow_judge_ranking() {
+        let run = RunRecord {
+            timestamp: 0,
+            prompt: String::new(),
+            run_dir: String::new(),
+            instances: vec![
+                instance(0, true, Some(2)),
+                instance(1, true, Some(1)),
+                instance(2, false, None),
+            ],
+        };
+        let pairs: Vec<(usize, usize)> = run
+            .outcomes()
+            .iter()
+            .map(|(w, l)| (w.instance_id, l.instance_id))
+            .collect();
+        assert_eq!(pairs, vec![(0, 2), (1, 0), (1, 2)]);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::cancel::CancellationToken;
+use crate::config::HooksConfig;
+use crate::names;
+use crate::verify;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// A point in a run where a `[hooks]` command can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// In a prepared workspace, before its baseline and session; failing
+    /// fails the instance
+    PreInstance,
+    /// In the workspace, after its session and checks
+    PostInstance,
+    /// In the run directory, before strategies, implementation or
+    /// evaluation; failing stops the run
+    PrePhase,
+    /// In the run directory, after a phase
+    PostPhase,
+    /// In the run directory, once the run is over, cancelled or not
+    PostRun,
+}
+
+impl Hook {
+    /// The hook's key in `[hooks]`, also given to its command as `ACTUALLY_HOOK`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PreInstance => "pre_instance",
+            Self::PostInstance => "post_instance",
+            Self::PrePhase => "pre_phase",
+            Self::PostPhase => "post_phase",
+            Self::PostRun => "post_run",
+        }
+    }
+
+    fn command(self, hooks: &HooksConfig) -> Option<&str> {
+        match self {
+            Self::PreInstance => hooks.pre_instance.as_deref(),
+            Self::PostInstance => hooks.post_instance.as_deref(),
+            Self::PrePhase => hooks.pre_phase.as_deref(),
+            Self::PostPhase => hooks.post_phase.as_deref(),
+            Self::PostRun => hooks.post_run.as_deref(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("{hook} hook failed (see {log})")]
+    Failed { hook: &'static str, log: PathBuf },
+}
+
+/// Run `hook`'s command, if `hooks` has one, with `sh -c` in `dir`, writing
+/// its output to `log`. The command gets `env` and `ACTUALLY_HOOK` on top
+/// of actually's own environment. A command that can't start or exits
+/// non-zero is an error; one cut short by `cancel` is not.
+pub async fn run(
+    hooks: &HooksConfig,
+    hook: Hook,
+    dir: &Path,
+    mut env: HashMap<String, String>,
+    log: &Path,
+    cancel: &CancellationToken,
+) -> Result<(), HookError> {
+    let Some(command) = hook.command(hooks) else {
+        return Ok(());
+    };
+    env.insert("ACTUALLY_HOOK".to_string(), hook.name().to_string());
+    tracing::info!(hook = hook.name(), command, "Running hook");
+    match verify::run_logged(command, dir, &env, log, cancel).await {
+        Some(output) if output.exit_code != Some(0) => Err(HookError::Failed {
+            hook: hook.name(),
+            log: log.to_path_buf(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Environment of the `pre_instance` and `post_instance` hooks: the
+/// session's own plus where the instance is. The paths are absolute, since
+/// the hooks run in the workspace.
+pub fn instance_env(
+    id: usize,
+    name: Option<&str>,
+    workspace: &Path,
+    run_dir: &Path,
+    env: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let absolute = |path: &Path| {
+        std::path::absolute(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .into_owned()
+    };
+    let mut env = env.clone();
+    env.extend([
+        ("ACTUALLY_RUN_DIR".to_string(), absolute(run_dir)),
+        ("ACTUALLY_INSTANCE".to_string(), id.to_string()),
+        ("ACTUALLY_INSTANCE_NAME".to_string(), names::label(id, name)),
+        ("ACTUALLY_WORKSPACE".to_string(), absolute(workspace)),
+    ]);
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("hook.log");
+        let hooks = HooksConfig {
+            pre_instance: Some(
+                "test \"$ACTUALLY_INSTANCE\" = 3 && echo $ACTUALLY_HOOK".to_string(),
+            ),
+            post_instance: Some("exit 1".to_string()),
+            ..Default::default()
+        };
+        let env = HashMap::from([("ACTUALLY_INSTANCE".to_string(), "3".to_string())]);
+        let cancel = CancellationToken::default();
+
+        run(
+            &hooks,
+            Hook::PreInstance,
+            dir.path(),
+            env.clone(),
+            &log,
+            &cancel,
+        )
+        .await
+        .unwrap();
+        assert!(std::fs::read_to_string(&log)
+            .unwrap()
+            .contains("pre_instance"));
+        assert!(run(
+            &hooks,
+            Hook::PostInstance,
+            dir.path(),
+            env.clone(),
+            &log,
+            &cancel
+        )
+        .await
+        .is_err());
+        // Not configured
+        run(&hooks, Hook::PostRun, dir.path(), env, &log, &cancel)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_instance_env() {
+        // A run directory given relative to where actually was started
+        let run_dir = tempfile::tempdir_in(".").unwrap();
+        let run_dir = Path::new(".").join(run_dir.path().file_name().unwrap());
+        let workspace = run_dir.join("c0");
+        std::fs::create_dir(&workspace).unwrap();
+        let hooks = HooksConfig {
+            pre_instance: Some(
+                "cd \"$ACTUALLY_RUN_DIR\" && touch \"$ACTUALLY_WORKSPACE/marker\"".to_string(),
+            ),
+            ..Default::default()
+        };
+        let env = instance_env(0, None, &workspace, &run_dir, &HashMap::new());
+        run(
+            &hooks,
+            Hook::PreInstance,
+            &workspace,
+            env,
+            &run_dir.join("hook.log"),
+            &CancellationToken::default(),
+        )
+        .await
+        .unwrap();
+        assert!(workspace.join("marker").is_file());
+    }
+}
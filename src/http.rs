@@ -0,0 +1,248 @@
+use anyhow::Context;
+use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 1 << 20;
+
+/// Most header lines read before the rest are ignored
+const MAX_HEADERS: usize = 100;
+
+/// Longest request or header line accepted
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// How long a client gets to send its whole request, so one that stalls
+/// doesn't hold its connection forever
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A client connection to `actually serve` or `actually web`; one request
+/// each, closed after the response
+pub type Connection = BufReader<TcpStream>;
+
+/// The parts of an HTTP/1.1 request the servers look at
+pub struct Request {
+    pub method: String,
+    /// Path without its query string
+    pub path: String,
+    pub host: Option<String>,
+    pub origin: Option<String>,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Whether the request addresses the server directly at `local`, where
+    /// it listens: its Host, and the Origin browsers send with cross-site
+    /// requests, must name localhost, 127.0.0.1, [::1] or the address it
+    /// listens on (any IP address, listening on all of them), with its
+    /// port. Keeps the web pages the user visits (DNS rebinding included)
+    /// from reaching the server, which takes a name rather than an address.
+    pub fn is_local(&self, local: SocketAddr) -> bool {
+        let Some(host) = &self.host else {
+            return false;
+        };
+        let origin_ok = match &self.origin {
+            Some(origin) => origin
+                .strip_prefix("http://")
+                .is_some_and(|origin| is_local_host(origin, local)),
+            None => true,
+        };
+        is_local_host(host, local) && origin_ok
+    }
+
+    /// Whether the body is declared as JSON. Browsers can't send that
+    /// cross-site without asking first, unlike `text/plain`.
+    pub fn is_json(&self) -> bool {
+        self.content_type.as_deref().is_some_and(|content_type| {
+            content_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .eq_ignore_ascii_case("application/json")
+        })
+    }
+}
+
+/// Whether `host` (`name[:port]`) names the server listening on `local`
+fn is_local_host(host: &str, local: SocketAddr) -> bool {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => (name, port.parse().ok()),
+        _ => (host, Some(80)),
+    };
+    let address: Option<IpAddr> = name
+        .strip_prefix('[')
+        .and_then(|name| name.strip_suffix(']'))
+        .unwrap_or(name)
+        .parse()
+        .ok();
+    let named = name.eq_ignore_ascii_case("localhost")
+        || address
+            .is_some_and(|ip| ip.is_loopback() || ip == local.ip() || local.ip().is_unspecified());
+    named && port == Some(local.port())
+}
+
+/// The address `local` a server listens on is reachable from other
+/// machines, and nothing but the Host check stands between them and it
+pub fn is_exposed(local: SocketAddr) -> bool {
+    !local.ip().is_loopback()
+}
+
+/// Read the request on `stream`, giving up after `READ_TIMEOUT`. A request
+/// with an oversized line or body is answered here and gives `None`.
+pub async fn read_request(stream: &mut Connection) -> anyhow::Result<Option<Request>> {
+    tokio::time::timeout(READ_TIMEOUT, read_request_now(stream))
+        .await
+        .context("Timed out reading the request")?
+}
+
+async fn read_request_now(stream: &mut Connection) -> anyhow::Result<Option<Request>> {
+    let Some(request_line) = read_line(stream).await? else {
+        return respond_line_too_long(stream).await;
+    };
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let (mut host, mut origin, mut content_type) = (None, None, None);
+    for _ in 0..MAX_HEADERS {
+        let Some(header) = read_line(stream).await? else {
+            return respond_line_too_long(stream).await;
+        };
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap_or(0),
+                "host" => host = Some(value.to_string()),
+                "origin" => origin = Some(value.to_string()),
+                "content-type" => content_type = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        let error = json!({ "error": "body too large" });
+        respond_json(stream, "413 Payload Too Large", &error).await?;
+        return Ok(None);
+    }
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await?;
+    Ok(Some(Request {
+        method,
+        path,
+        host,
+        origin,
+        content_type,
+        body,
+    }))
+}
+
+/// One line of the request, line break included (empty at the end of the
+/// stream), or `None` if it runs past `MAX_LINE_BYTES`
+async fn read_line(stream: &mut Connection) -> anyhow::Result<Option<String>> {
+    let mut line = Vec::new();
+    (&mut *stream)
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .await?;
+    if line.len() > MAX_LINE_BYTES {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+async fn respond_line_too_long(stream: &mut Connection) -> anyhow::Result<Option<Request>> {
+    let error = json!({ "error": "request line or header too long" });
+    respond_json(stream, "431 Request Header Fields Too Large", &error).await?;
+    Ok(None)
+}
+
+pub async fn respond(
+    stream: &mut Connection,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> anyhow::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+pub async fn respond_json(
+    stream: &mut Connection,
+    status: &str,
+    body: &Value,
+) -> anyhow::Result<()> {
+    respond(
+        stream,
+        status,
+        "application/json",
+        body.to_string().as_bytes(),
+    )
+    .await
+}
+
+/// Answer a request that doesn't address the server directly
+pub async fn respond_forbidden(stream: &mut Connection) -> anyhow::Result<()> {
+    let error = "requests must come from localhost or the address listened on";
+    respond_error(stream, "403 Forbidden", error).await
+}
+
+/// Answer with a JSON `{"error": ...}` body
+pub async fn respond_error(
+    stream: &mut Connection,
+    status: &str,
+    error: &str,
+) -> anyhow::Result<()> {
+    respond_json(stream, status, &json!({ "error": error })).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_read_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let send = |request: Vec<u8>| async move {
+            let mut client = TcpStream::connect(address).await.unwrap();
+            client.write_all(&request).await.unwrap();
+            client
+        };
+
+        let _client =
+            send(b"GET /api/state?x=1 HTTP/1.1\r\nHost: localhost\r\n\r\n".to_vec()).await;
+        let mut stream = BufReader::new(listener.accept().await.unwrap().0);
+        let request = read_request(&mut stream).await.unwrap().unwrap();
+        assert_eq!(request.path, "/api/state");
+        assert_eq!(request.host.as_deref(), Some("localhost"));
+
+        let mut oversized = b"GET / HTTP/1.1\r\nX-Padding: ".to_vec();
+        oversized.extend(vec![b'a'; MAX_LINE_BYTES * 4]);
+        oversized.extend(b"\r\n\r\n");
+        let mut client = send(oversized).await;
+        let mut stream = BufReader::new(listener.accept().await.unwrap().0);
+        assert!(read_request(&mut stream).await.unwrap().is_none());
+        let mut response = [0; 12];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response, b"HTTP/1.1 431");
+    }
+}
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+/// How statuses are marked in the TUIs and the end-of-run summary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusIcons {
+    /// Bracketed tags such as `[OK]` and `[FAIL]`
+    #[default]
+    Ascii,
+    /// Emoji such as ✅ and ❌
+    Unicode,
+}
+
+/// Something a status marker can say about a strategy or an instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Failed,
+    Edited,
+    Imported,
+    Running,
+    Skipped,
+    Debating,
+    Suggested,
+    NoBuild,
+}
+
+impl StatusIcons {
+    /// Marker for `status` in list rows and summary lines
+    pub fn tag(self, status: Status) -> &'static str {
+        match (self, status) {
+            (Self::Ascii, Status::Ok) => "[OK]",
+            (Self::Ascii, Status::Failed) => "[FAIL]",
+            (Self::Ascii, Status::Edited) => "[EDIT]",
+            (Self::Ascii, Status::Imported) => "[IMPORT]",
+            (Self::Ascii, Status::Running) => "[RUN]",
+            (Self::Ascii, Status::Skipped) => "[SKIP]",
+            (Self::Ascii, Status::Debating) => "[DEBATE]",
+            (Self::Ascii, Status::Suggested) => "[SUGGESTED]",
+            (Self::Ascii, Status::NoBuild) => "[NO BUILD]",
+            (Self::Unicode, Status::Ok) => "✅",
+            (Self::Unicode, Status::Failed) => "❌",
+            (Self::Unicode, Status::Edited) => "✏️",
+            (Self::Unicode, Status::Imported) => "📥",
+            (Self::Unicode, Status::Running) => "⏳",
+            (Self::Unicode, Status::Skipped) => "⏭️",
+            (Self::Unicode, Status::Debating) => "💬",
+            (Self::Unicode, Status::Suggested) => "🏆",
+            (Self::Unicode, Status::NoBuild) => "🧱",
+        }
+    }
+
+    /// Emoji and a space to put in front of a status that is already
+    /// spelled out (the dashboard's "done in 3m"); nothing in ASCII mode
+    pub fn prefix(self, status: Status) -> String {
+        match self {
+            Self::Ascii => String::new(),
+            Self::Unicode => format!("{} ", self.tag(status)),
+        }
+    }
+}
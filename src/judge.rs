@@ -0,0 +1,759 @@
+use crate::conductor::InstanceResult;
+use crate::config::Criterion;
+use crate::critique::{changes_for_prompt, MAX_REVIEW_DIFF_CHARS};
+use crate::session::ClaudeSession;
+use crate::strategy::build_judge_prompt;
+use futures::future::{join_all, BoxFuture};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Longest a `--judge-command` may take over one verdict
+const JUDGE_COMMAND_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// A judge agent's ranking of the successful instances
+#[derive(Debug, Clone, Serialize)]
+pub struct Verdict {
+    /// Instance ids, best first. Every judged instance appears exactly once.
+    pub ranking: Vec<usize>,
+    /// The judge's comparison notes, in Markdown
+    pub notes: String,
+    /// Per-criterion rubric scores, for instances the judge scored
+    pub scores: Vec<Score>,
+    /// Whether the judge ranked or scored the candidates itself, rather than
+    /// leaving `ranking` in instance order
+    #[serde(skip)]
+    pub decided: bool,
+}
+
+/// One instance's rubric scores
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Score {
+    pub instance_id: usize,
+    /// Criterion name to score (0-10)
+    pub criteria: BTreeMap<String, f64>,
+    /// Weighted average over the rubric's criteria
+    pub total: f64,
+}
+
+/// Where a run's verdict goes for tools (`actually stats`, CI gates) to read
+pub const VERDICT_FILE: &str = "verdict.json";
+
+/// What verdict.json holds: the ranking, each ranked instance's scores and
+/// the judge's rationale, with the rubric they were scored against
+#[derive(Debug, Serialize)]
+pub struct VerdictFile {
+    /// Instance ids, best first
+    pub ranking: Vec<usize>,
+    pub rubric: Vec<Criterion>,
+    /// The ranked instances, best first
+    pub instances: Vec<RankedInstance>,
+    /// The judge's comparison notes, in Markdown
+    pub rationale: String,
+}
+
+/// One instance's place in verdict.json
+#[derive(Debug, Serialize)]
+pub struct RankedInstance {
+    pub instance_id: usize,
+    pub label: String,
+    /// 1 for the best
+    pub rank: usize,
+    /// Criterion name to score (0-10); empty when the judge only ranked
+    pub scores: BTreeMap<String, f64>,
+    /// Weighted average over the rubric's criteria, when scored
+    pub total: Option<f64>,
+}
+
+impl Verdict {
+    /// The verdict as verdict.json records it, labelled after `results`
+    pub fn to_file(&self, results: &[InstanceResult], rubric: &[Criterion]) -> VerdictFile {
+        let instances = self
+            .ranking
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| {
+                let score = self.scores.iter().find(|s| s.instance_id == id);
+                RankedInstance {
+                    instance_id: id,
+                    label: crate::conductor::label_of(results, id),
+                    rank: i + 1,
+                    scores: score.map(|s| s.criteria.clone()).unwrap_or_default(),
+                    total: score.map(|s| s.total),
+                }
+            })
+            .collect();
+        VerdictFile {
+            ranking: self.ranking.clone(),
+            rubric: rubric.to_vec(),
+            instances,
+            rationale: self.notes.clone(),
+        }
+    }
+}
+
+/// What a judge is asked to rank: the task, and each candidate's strategy,
+/// workspace, transcript and check results
+pub struct JudgeInput<'a> {
+    pub task: &'a str,
+    pub candidates: &'a [&'a InstanceResult],
+    pub rubric: &'a [Criterion],
+    pub run_dir: &'a Path,
+}
+
+/// Something that ranks implementations, for `--judge`, `--synthesize` and
+/// each `--tournament` match
+pub trait Judge: Sync {
+    /// Rank `input.candidates`, each exactly once, best first
+    fn judge<'a>(&'a self, input: &'a JudgeInput<'a>) -> BoxFuture<'a, anyhow::Result<Verdict>>;
+}
+
+/// The built-in judge: a fresh read-only agent in the run directory
+pub struct AgentJudge {
+    pub model: Option<String>,
+}
+
+impl Judge for AgentJudge {
+    fn judge<'a>(&'a self, input: &'a JudgeInput<'a>) -> BoxFuture<'a, anyhow::Result<Verdict>> {
+        Box::pin(self.judge_candidates(input))
+    }
+}
+
+/// A judge outside actually (`--judge-command`): a shell command run in the
+/// run directory that reads a `JudgeInput` as JSON on stdin and writes a
+/// `CommandVerdict` as JSON on stdout
+pub struct CommandJudge {
+    pub command: String,
+}
+
+impl Judge for CommandJudge {
+    fn judge<'a>(&'a self, input: &'a JudgeInput<'a>) -> BoxFuture<'a, anyhow::Result<Verdict>> {
+        Box::pin(self.run(input))
+    }
+}
+
+/// The judge `--judge-command` asks for, or the built-in agent on `model`
+pub fn configured(command: Option<&str>, model: Option<&str>) -> Box<dyn Judge> {
+    match command {
+        Some(command) => Box::new(CommandJudge {
+            command: command.to_string(),
+        }),
+        None => Box::new(AgentJudge {
+            model: model.map(str::to_string),
+        }),
+    }
+}
+
+/// What a `--judge-command` answers with. Candidates are `"C<id>"` or
+/// plain ids; a ranking left out is taken from the scores.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CommandVerdict {
+    ranking: Vec<Value>,
+    notes: String,
+    /// `{"C0": {"correctness": 7, ...}, ...}`, scored on the rubric
+    scores: HashMap<String, HashMap<String, f64>>,
+}
+
+impl CommandJudge {
+    async fn run(&self, input: &JudgeInput<'_>) -> anyhow::Result<Verdict> {
+        let mut candidates = Vec::with_capacity(input.candidates.len());
+        for candidate in input.candidates {
+            // The command runs in the run directory, not where we were started
+            let workspace = candidate.workspace();
+            let diff = changes_for_prompt(&workspace, MAX_REVIEW_DIFF_CHARS).await;
+            candidates.push(json!({
+                "id": candidate.instance_id,
+                "label": candidate.label(),
+                "strategy": candidate.strategy,
+                "workspace": workspace,
+                "transcript": candidate.transcript,
+                "diff": diff,
+                "build": candidate.build,
+                "tests": candidate.verification,
+                "lint": candidate.lint,
+                "coverage": candidate.coverage,
+                "bench": candidate.bench,
+            }));
+        }
+        let request = json!({
+            "task": input.task,
+            "rubric": input.rubric,
+            "candidates": candidates,
+        });
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .current_dir(input.run_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start judge command: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A judge that doesn't read all of its input still gets a say
+            let _ = stdin.write_all(request.to_string().as_bytes()).await;
+        }
+        let output = tokio::time::timeout(JUDGE_COMMAND_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow::anyhow!("Judge command timed out"))??;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Judge command failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let answer: CommandVerdict = serde_json::from_slice(&output.stdout)
+            .map_err(|e| anyhow::anyhow!("Judge command's verdict is not valid JSON: {}", e))?;
+        let ids: Vec<usize> = input.candidates.iter().map(|c| c.instance_id).collect();
+        Ok(answer.into_verdict(&ids, input.rubric))
+    }
+}
+
+impl CommandVerdict {
+    /// The verdict, made complete the way `parse_verdict` makes the agent's
+    fn into_verdict(self, candidates: &[usize], rubric: &[Criterion]) -> Verdict {
+        let ranking: Vec<String> = self
+            .ranking
+            .iter()
+            .map(|id| match id {
+                Value::String(id) => id.clone(),
+                id => id.to_string(),
+            })
+            .collect();
+        let mut verdict = parse_verdict(
+            &format!("{}\nRANKING: {}", self.notes, ranking.join(", ")),
+            candidates,
+        );
+        if !self.scores.is_empty() {
+            verdict.scores = score(self.scores, candidates, rubric);
+            verdict.decided |= !verdict.scores.is_empty();
+            if ranking.is_empty() {
+                // Unlike the agent, a command may score only some candidates;
+                // the rest go last
+                let total = |id: &usize| {
+                    verdict
+                        .scores
+                        .iter()
+                        .find(|s| s.instance_id == *id)
+                        .map(|s| s.total)
+                };
+                let mut ranking = verdict.ranking.clone();
+                ranking.sort_by(|a, b| {
+                    let (a, b) = (total(a).unwrap_or(-1.0), total(b).unwrap_or(-1.0));
+                    b.total_cmp(&a)
+                });
+                verdict.ranking = ranking;
+            }
+        }
+        verdict
+    }
+}
+
+/// Have `judge` compare every successful instance and rank them.
+/// Returns `None` if there are fewer than two successful instances to compare.
+pub async fn judge(
+    judge: &dyn Judge,
+    task: &str,
+    results: &[InstanceResult],
+    run_dir: &Path,
+    rubric: &[Criterion],
+) -> anyhow::Result<Option<Verdict>> {
+    let candidates: Vec<&InstanceResult> = results
+        .iter()
+        .filter(|r| r.success && !r.workspace_path.is_empty())
+        .collect();
+    if candidates.len() < 2 {
+        return Ok(None);
+    }
+    let input = JudgeInput {
+        task,
+        candidates: &candidates,
+        rubric,
+        run_dir,
+    };
+    judge.judge(&input).await.map(Some)
+}
+
+impl AgentJudge {
+    /// Rank exactly the given candidates
+    async fn judge_candidates(&self, input: &JudgeInput<'_>) -> anyhow::Result<Verdict> {
+        let JudgeInput {
+            task,
+            candidates,
+            rubric,
+            run_dir,
+        } = *input;
+        // Share one prompt-sized diff budget between all candidates
+        let budget = MAX_REVIEW_DIFF_CHARS / candidates.len();
+        let mut sections = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            let workspace = candidate.workspace();
+            let changes = changes_for_prompt(&workspace, budget).await;
+            sections.push(format!(
+                "## C{}\n\nDirectory: {}\n\nStrategy:\n{}\n\n{}Changes:\n{}",
+                candidate.instance_id,
+                workspace.display(),
+                candidate.strategy,
+                scorecard(candidate),
+                changes
+            ));
+        }
+
+        let prompt = build_judge_prompt(task, &sections.join("\n\n"), rubric);
+        let session = ClaudeSession::with_cwd_and_model(run_dir, self.model.as_deref());
+        let response = session.query_read_only(&prompt).await?;
+
+        let ids: Vec<usize> = candidates.iter().map(|c| c.instance_id).collect();
+        let (text, scores_json) = split_json_block(&response);
+        let mut verdict = parse_verdict(&text, &ids);
+        if let Some(json) = scores_json {
+            verdict.scores = parse_scores(json, &ids, rubric);
+            rank_by_scores(&mut verdict);
+        }
+        Ok(verdict)
+    }
+}
+
+/// Automated check results for a candidate, as a prompt section (empty if
+/// no checks ran)
+fn scorecard(candidate: &InstanceResult) -> String {
+    let mut lines: Vec<String> = [
+        ("Build", &candidate.build),
+        ("Tests", &candidate.verification),
+        ("Lint", &candidate.lint),
+        ("Coverage", &candidate.coverage),
+    ]
+    .into_iter()
+    .filter_map(|(name, check)| {
+        check
+            .as_ref()
+            .map(|c| format!("- {}: {}", name, c.describe()))
+    })
+    .collect();
+    if let Some(bench) = &candidate.bench {
+        lines.push(format!(
+            "- Benchmark (`{}`, {} is better): {}",
+            bench.command,
+            if bench.higher_is_better {
+                "higher"
+            } else {
+                "lower"
+            },
+            bench
+                .value
+                .map_or_else(|| "no result".to_string(), |v| v.to_string())
+        ));
+    }
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("Scorecard:\n{}\n\n", lines.join("\n"))
+    }
+}
+
+/// Separate the last ```json fenced block from the rest of a response
+fn split_json_block(response: &str) -> (String, Option<&str>) {
+    let Some(start) = response.rfind("```json") else {
+        return (response.to_string(), None);
+    };
+    let body_start = start + "```json".len();
+    let Some(len) = response[body_start..].find("```") else {
+        return (response.to_string(), None);
+    };
+    let body_end = body_start + len;
+    let text = format!("{}{}", &response[..start], &response[body_end + 3..]);
+    (text, Some(response[body_start..body_end].trim()))
+}
+
+/// Parse `{"C0": {"correctness": 7, ...}, ...}`, keeping only known
+/// candidates and rubric criteria. Criteria the judge skipped count as 0.
+fn parse_scores(json: &str, candidates: &[usize], rubric: &[Criterion]) -> Vec<Score> {
+    let Ok(raw) = serde_json::from_str::<HashMap<String, HashMap<String, f64>>>(json) else {
+        tracing::warn!("Judge scores were not valid JSON");
+        return Vec::new();
+    };
+    score(raw, candidates, rubric)
+}
+
+/// Weighted rubric scores from the judge's per-criterion ones
+fn score(
+    raw: HashMap<String, HashMap<String, f64>>,
+    candidates: &[usize],
+    rubric: &[Criterion],
+) -> Vec<Score> {
+    let total_weight: f64 = rubric.iter().map(|c| c.weight).sum();
+
+    let mut scores: Vec<Score> = raw
+        .into_iter()
+        .filter_map(|(key, per_criterion)| {
+            let id: usize = key.trim_start_matches(['C', 'c']).parse().ok()?;
+            if !candidates.contains(&id) {
+                return None;
+            }
+            let criteria: BTreeMap<String, f64> = rubric
+                .iter()
+                .map(|c| {
+                    let score = per_criterion.get(&c.name).copied().unwrap_or(0.0);
+                    (c.name.clone(), score.clamp(0.0, 10.0))
+                })
+                .collect();
+            let weighted: f64 = rubric.iter().map(|c| c.weight * criteria[&c.name]).sum();
+            let total = if total_weight > 0.0 {
+                weighted / total_weight
+            } else {
+                0.0
+            };
+            Some(Score {
+                instance_id: id,
+                criteria,
+                total,
+            })
+        })
+        .collect();
+    scores.sort_by_key(|s| s.instance_id);
+    scores
+}
+
+/// Order the ranking by weighted score when every ranked instance was
+/// scored; ties keep the judge's own order
+fn rank_by_scores(verdict: &mut Verdict) {
+    let total = |id: &usize| {
+        verdict
+            .scores
+            .iter()
+            .find(|s| s.instance_id == *id)
+            .map(|s| s.total)
+    };
+    if verdict.ranking.iter().any(|id| total(id).is_none()) {
+        return;
+    }
+    let mut ranking = verdict.ranking.clone();
+    ranking.sort_by(|a, b| total(b).unwrap_or(0.0).total_cmp(&total(a).unwrap_or(0.0)));
+    verdict.ranking = ranking;
+    verdict.decided = true;
+}
+
+/// One pairwise comparison in a tournament
+#[derive(Debug, Clone)]
+pub struct Match {
+    /// 1-based bracket round
+    pub round: usize,
+    pub a: usize,
+    pub b: usize,
+    pub winner: usize,
+    /// False when the judge failed or gave no ranking, even on a retry, and
+    /// `a` advanced by default
+    pub decided: bool,
+    /// The judge's notes for this match
+    pub reasoning: String,
+}
+
+/// Outcome of a single-elimination tournament between instances
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub matches: Vec<Match>,
+    /// Instances that advanced without a match (odd-sized rounds), per round
+    pub byes: Vec<(usize, usize)>,
+    pub winner: usize,
+}
+
+/// Single-elimination bracket: successful instances are paired in order
+/// and `judge` picks the winner of each pair until one remains. Matches in a
+/// round run in parallel. A match whose judge fails or gives no ranking is
+/// retried once; if it is still undecided the first instance advances so the
+/// bracket can still finish. Returns `None` with fewer than two contenders.
+pub async fn tournament(
+    judge: &dyn Judge,
+    task: &str,
+    results: &[InstanceResult],
+    run_dir: &Path,
+    rubric: &[Criterion],
+    mut on_match: impl FnMut(&Match),
+) -> Option<Tournament> {
+    let mut contenders: Vec<&InstanceResult> = results
+        .iter()
+        .filter(|r| r.success && !r.workspace_path.is_empty())
+        .collect();
+    if contenders.len() < 2 {
+        return None;
+    }
+
+    let mut matches = Vec::new();
+    let mut byes = Vec::new();
+    let mut round = 1;
+    while contenders.len() > 1 {
+        let pairs: Vec<[&InstanceResult; 2]> = contenders
+            .chunks_exact(2)
+            .map(|pair| [pair[0], pair[1]])
+            .collect();
+        let bye = (contenders.len() % 2 == 1).then(|| contenders[contenders.len() - 1]);
+
+        let inputs: Vec<JudgeInput> = pairs
+            .iter()
+            .map(|pair| JudgeInput {
+                task,
+                candidates: pair,
+                rubric,
+                run_dir,
+            })
+            .collect();
+        let mut verdicts = join_all(inputs.iter().map(|input| judge.judge(input))).await;
+        let undecided =
+            |verdict: &anyhow::Result<Verdict>| !matches!(verdict, Ok(verdict) if verdict.decided);
+        let retries: Vec<usize> = (0..verdicts.len())
+            .filter(|&i| undecided(&verdicts[i]))
+            .collect();
+        let retried = join_all(retries.iter().map(|&i| judge.judge(&inputs[i]))).await;
+        for (i, verdict) in retries.into_iter().zip(retried) {
+            verdicts[i] = verdict;
+        }
+
+        let mut next = Vec::with_capacity(pairs.len() + 1);
+        for (pair, verdict) in pairs.iter().zip(verdicts) {
+            let (a, b) = (pair[0].instance_id, pair[1].instance_id);
+            let (winner, decided, reasoning) = match verdict {
+                Ok(verdict) if verdict.decided => (verdict.ranking[0], true, verdict.notes),
+                Ok(verdict) => {
+                    tracing::warn!("Judge gave no ranking for C{} vs C{}", a, b);
+                    (
+                        a,
+                        false,
+                        format!(
+                            "Judge gave no ranking; C{} advances by default\n\n{}",
+                            a, verdict.notes
+                        )
+                        .trim()
+                        .to_string(),
+                    )
+                }
+                Err(e) => {
+                    tracing::warn!("Judge failed for C{} vs C{}: {}", a, b, e);
+                    (
+                        a,
+                        false,
+                        format!("Judge failed ({}); C{} advances by default", e, a),
+                    )
+                }
+            };
+            let m = Match {
+                round,
+                a,
+                b,
+                winner,
+                decided,
+                reasoning,
+            };
+            on_match(&m);
+            matches.push(m);
+            next.push(if winner == a { pair[0] } else { pair[1] });
+        }
+        if let Some(bye) = bye {
+            byes.push((round, bye.instance_id));
+            next.push(bye);
+        }
+
+        contenders = next;
+        round += 1;
+    }
+
+    Some(Tournament {
+        matches,
+        byes,
+        winner: contenders[0].instance_id,
+    })
+}
+
+impl Tournament {
+    /// Markdown record of the bracket, round by round, with each match's reasoning
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Tournament\n\nWinner: C{}\n", self.winner);
+        let rounds = self.matches.iter().map(|m| m.round).max().unwrap_or(0);
+        for round in 1..=rounds {
+            out.push_str(&format!("\n## Round {}\n", round));
+            for m in self.matches.iter().filter(|m| m.round == round) {
+                let outcome = if m.decided {
+                    format!("C{} wins", m.winner)
+                } else {
+                    format!("undecided, C{} advances", m.winner)
+                };
+                out.push_str(&format!(
+                    "\n### C{} vs C{}: {}\n\n{}\n",
+                    m.a, m.b, outcome, m.reasoning
+                ));
+            }
+            for (_, id) in self.byes.iter().filter(|(r, _)| *r == round) {
+                out.push_str(&format!("\nC{} advances with a bye\n", id));
+            }
+        }
+        out
+    }
+}
+
+/// Split the judge's response into notes and the trailing `RANKING:` line.
+/// Unknown or repeated ids are dropped, and candidates the judge forgot to
+/// rank are appended in instance order so the ranking is always complete.
+pub(crate) fn parse_verdict(response: &str, candidates: &[usize]) -> Verdict {
+    let mut ranking = Vec::with_capacity(candidates.len());
+    let mut notes = Vec::new();
+
+    for line in response.lines() {
+        match line.trim().strip_prefix("RANKING:") {
+            Some(list) => {
+                ranking.clear();
+                for id in list
+                    .split(|c: char| c == ',' || c == '>' || c.is_whitespace())
+                    .filter_map(|tok| tok.trim().trim_start_matches(['C', 'c']).parse().ok())
+                {
+                    if candidates.contains(&id) && !ranking.contains(&id) {
+                        ranking.push(id);
+                    }
+                }
+            }
+            None => notes.push(line),
+        }
+    }
+
+    let decided = !ranking.is_empty();
+    for id in candidates {
+        if !ranking.contains(id) {
+            ranking.push(*id);
+        }
+    }
+
+    Verdict {
+        ranking,
+        notes: notes.join("\n").trim().to_string(),
+        scores: Vec::new(),
+        decided,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_verdict() {
+        let response = "C2 is the most complete.\n\nRANKING: C2, C7, C2, C0\n";
+        let verdict = parse_verdict(response, &[0, 1, 2]);
+        assert_eq!(verdict.ranking, vec![2, 0, 1]);
+        assert_eq!(verdict.notes, "C2 is the most complete.");
+        assert!(verdict.decided);
+
+        let verdict = parse_verdict("No ranking given", &[0, 1]);
+        assert_eq!(verdict.ranking, vec![0, 1]);
+        assert!(!verdict.decided);
+    }
+
+    #[test]
+    fn test_scores_override_ranking() {
+        let rubric = vec![
+            Criterion {
+                name: "correctness".to_string(),
+                weight: 3.0,
+                description: None,
+            },
+            Criterion {
+                name: "simplicity".to_string(),
+                weight: 1.0,
+                description: None,
+            },
+        ];
+        let response = "Notes.\n\n```json\n{\"C0\": {\"correctness\": 10, \"simplicity\": 2}, \"C1\": {\"correctness\": 6, \"simplicity\": 10}}\n```\n\nRANKING: C1, C0\n";
+        let (text, json) = split_json_block(response);
+        assert!(!text.contains("```"));
+        let mut verdict = parse_verdict(&text, &[0, 1]);
+        verdict.scores = parse_scores(json.unwrap(), &[0, 1], &rubric);
+        rank_by_scores(&mut verdict);
+
+        assert_eq!(verdict.ranking, vec![0, 1]);
+        assert_eq!(verdict.scores[0].total, 8.0);
+        assert_eq!(verdict.scores[1].total, 7.0);
+        assert_eq!(verdict.notes, "Notes.");
+
+        let file = serde_json::to_value(verdict.to_file(&[], &rubric)).unwrap();
+        assert_eq!(file["ranking"], json!([0, 1]));
+        assert_eq!(file["rubric"][0]["weight"], json!(3.0));
+        assert_eq!(file["instances"][1]["label"], json!("C1"));
+        assert_eq!(file["instances"][1]["rank"], json!(2));
+        assert_eq!(file["instances"][1]["scores"]["simplicity"], json!(10.0));
+        assert_eq!(file["instances"][0]["total"], json!(8.0));
+        assert_eq!(file["rationale"], json!("Notes."));
+    }
+
+    #[tokio::test]
+    async fn test_command_judge() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut results: Vec<InstanceResult> = (0..3)
+            .map(|id| InstanceResult {
+                instance_id: id,
+                workspace_path: dir.path().to_string_lossy().into_owned(),
+                ..Default::default()
+            })
+            .collect();
+        results[0].workspace_path = "./actually-123/c0".to_string();
+        let candidates: Vec<&InstanceResult> = results.iter().collect();
+        let rubric = Criterion::default_rubric();
+        let input = JudgeInput {
+            task: "Fix the parser",
+            candidates: &candidates,
+            rubric: &rubric,
+            run_dir: dir.path(),
+        };
+        let judge = CommandJudge {
+            command: r#"cat > request.json && echo '{"notes": "C1 is best", "scores": {"C1": {"correctness": 9}, "2": {"correctness": 4}}}'"#.to_string(),
+        };
+
+        let verdict = judge.judge(&input).await.unwrap();
+        assert_eq!(verdict.ranking, vec![1, 2, 0]);
+        assert_eq!(verdict.notes, "C1 is best");
+        let request = std::fs::read_to_string(dir.path().join("request.json")).unwrap();
+        assert!(request.contains("Fix the parser"));
+        let request: Value = serde_json::from_str(&request).unwrap();
+        let workspace = request["candidates"][0]["workspace"].as_str().unwrap();
+        assert!(Path::new(workspace).is_absolute());
+
+        let judge = CommandJudge {
+            command: "echo not json".to_string(),
+        };
+        assert!(judge.judge(&input).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tournament_without_ranking() {
+        let dir = tempfile::tempdir().unwrap();
+        let results: Vec<InstanceResult> = (0..2)
+            .map(|id| InstanceResult {
+                instance_id: id,
+                success: true,
+                workspace_path: dir.path().to_string_lossy().into_owned(),
+                ..Default::default()
+            })
+            .collect();
+        let judge = CommandJudge {
+            command: r#"cat > /dev/null && echo >> calls && echo '{"notes": "Both look fine"}'"#
+                .to_string(),
+        };
+
+        let bracket = tournament(&judge, "Fix the parser", &results, dir.path(), &[], |_| {})
+            .await
+            .unwrap();
+        assert_eq!(bracket.matches.len(), 1);
+        assert!(!bracket.matches[0].decided);
+        assert_eq!(bracket.winner, 0);
+        assert!(bracket
+            .to_markdown()
+            .contains("C0 vs C1: undecided, C0 advances"));
+        let calls = std::fs::read_to_string(dir.path().join("calls")).unwrap();
+        assert_eq!(calls.lines().count(), 2);
+    }
+}
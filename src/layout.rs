@@ -0,0 +1,195 @@
+use crate::names;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Written into every run directory, so whatever reads a run later knows
+/// how it was laid out
+pub const METADATA_FILE: &str = "metadata.json";
+
+/// Directory of a nested run holding one directory per instance
+const INSTANCES_DIR: &str = "instances";
+/// An instance's code, in its own directory
+pub const WORKSPACE_DIR: &str = "workspace";
+/// An instance's strategy, transcripts, logs and patch in a nested run
+const LOGS_DIR: &str = "logs";
+
+/// How a run directory arranges its instances' workspaces and files
+/// (`--layout`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunLayout {
+    /// Workspaces as `c0/workspace/`, `c1/workspace/`, ... with every
+    /// instance's files (`C0-strategy.md`, `C0-transcript.log`, `c0.patch`)
+    /// beside them
+    #[default]
+    Flat,
+    /// One `instances/<id>/` directory per instance, holding its code in
+    /// `workspace/` and its files in `logs/`
+    Nested,
+}
+
+/// What metadata.json holds
+#[derive(Serialize, Deserialize)]
+struct Metadata {
+    layout: RunLayout,
+}
+
+impl RunLayout {
+    /// How the run in `run_dir` is laid out; runs from before metadata.json
+    /// are flat
+    pub fn of(run_dir: &Path) -> Self {
+        std::fs::read_to_string(run_dir.join(METADATA_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Metadata>(&json).ok())
+            .map_or(RunLayout::Flat, |metadata| metadata.layout)
+    }
+
+    /// Write metadata.json into `run_dir`
+    pub fn record(self, run_dir: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(&Metadata { layout: self })?;
+        crate::output::write_atomic(&run_dir.join(METADATA_FILE), &json)
+    }
+
+    /// Instance `id`'s workspace under `workspace_dir`. Nested workspaces
+    /// go by id alone, since names are only given once strategies are.
+    pub fn workspace(self, workspace_dir: &Path, id: usize, name: Option<&str>) -> PathBuf {
+        match self {
+            RunLayout::Flat => workspace_dir
+                .join(names::dir_name(id, name))
+                .join(WORKSPACE_DIR),
+            RunLayout::Nested => workspace_dir
+                .join(INSTANCES_DIR)
+                .join(id.to_string())
+                .join(WORKSPACE_DIR),
+        }
+    }
+
+    /// Where instance `id`'s `file_name` (e.g. `C0-strategy.md`) goes in
+    /// `run_dir`
+    pub fn artifact(self, run_dir: &Path, id: usize, file_name: &str) -> PathBuf {
+        match self {
+            RunLayout::Flat => run_dir.join(file_name),
+            RunLayout::Nested => run_dir
+                .join(INSTANCES_DIR)
+                .join(id.to_string())
+                .join(LOGS_DIR)
+                .join(file_name),
+        }
+    }
+
+    /// Create the directory instance `id`'s artifacts go in, if it isn't
+    /// the run directory itself
+    pub fn create_artifact_dir(self, run_dir: &Path, id: usize) -> std::io::Result<()> {
+        match self {
+            RunLayout::Flat => Ok(()),
+            RunLayout::Nested => std::fs::create_dir_all(self.artifact(run_dir, id, "")),
+        }
+    }
+
+    /// What a workspace's patch and archive are named after: the directory
+    /// holding the workspace when flat (`c0`, `c-synth`), else its instance
+    /// (`c0`)
+    pub fn stem(self, workspace: &Path, id: usize, name: Option<&str>) -> String {
+        match (self, workspace.parent().and_then(Path::file_name)) {
+            (RunLayout::Flat, Some(dir)) => dir.to_string_lossy().into_owned(),
+            _ => names::dir_name(id, name),
+        }
+    }
+
+    /// The directories of `run_dir` holding instance files, in order
+    pub fn artifact_dirs(self, run_dir: &Path) -> Vec<PathBuf> {
+        match self {
+            RunLayout::Flat => vec![run_dir.to_path_buf()],
+            RunLayout::Nested => {
+                let mut dirs: Vec<(usize, PathBuf)> =
+                    std::fs::read_dir(run_dir.join(INSTANCES_DIR))
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .filter_map(|entry| {
+                            let id = entry.file_name().to_string_lossy().parse().ok()?;
+                            Some((id, entry.path().join(LOGS_DIR)))
+                        })
+                        .filter(|(_, dir)| dir.is_dir())
+                        .collect();
+                dirs.sort();
+                dirs.into_iter().map(|(_, dir)| dir).collect()
+            }
+        }
+    }
+
+    /// How deep `relative`, a path in a run directory, is in an instance's
+    /// workspace: 1 for the files right in it, `None` outside of workspaces
+    pub fn depth_in_workspace(self, relative: &Path) -> Option<usize> {
+        let components: Vec<_> = relative.components().map(|c| c.as_os_str()).collect();
+        let depth = match self {
+            RunLayout::Flat => match components.as_slice() {
+                [_, workspace, rest @ ..] if *workspace == WORKSPACE_DIR => rest.len(),
+                _ => return None,
+            },
+            RunLayout::Nested => match components.as_slice() {
+                [instances, _, workspace, rest @ ..]
+                    if *instances == INSTANCES_DIR && *workspace == WORKSPACE_DIR =>
+                {
+                    rest.len()
+                }
+                _ => return None,
+            },
+        };
+        (depth > 0).then_some(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let run_dir = tempfile::tempdir().unwrap();
+        let run_dir = run_dir.path();
+        assert_eq!(RunLayout::of(run_dir), RunLayout::Flat);
+        RunLayout::Nested.record(run_dir).unwrap();
+        assert_eq!(RunLayout::of(run_dir), RunLayout::Nested);
+
+        let workspace = RunLayout::Nested.workspace(run_dir, 2, Some("bravo"));
+        assert_eq!(workspace, run_dir.join("instances/2/workspace"));
+        assert_eq!(
+            RunLayout::Nested.stem(&workspace, 2, Some("bravo")),
+            "c2-bravo"
+        );
+        let workspace = RunLayout::Flat.workspace(run_dir, 2, Some("bravo"));
+        assert_eq!(workspace, run_dir.join("c2-bravo/workspace"));
+        assert_eq!(
+            RunLayout::Flat.stem(&workspace, 2, Some("bravo")),
+            "c2-bravo"
+        );
+        assert_eq!(
+            RunLayout::Flat.stem(&run_dir.join("c-synth/workspace"), 3, None),
+            "c-synth"
+        );
+
+        RunLayout::Nested.create_artifact_dir(run_dir, 2).unwrap();
+        let log = RunLayout::Nested.artifact(run_dir, 2, "C2-bravo-transcript.log");
+        std::fs::write(&log, "").unwrap();
+        assert_eq!(
+            RunLayout::Nested.artifact_dirs(run_dir),
+            vec![run_dir.join("instances/2/logs")]
+        );
+        assert_eq!(
+            RunLayout::Flat.artifact(run_dir, 2, "c2.patch"),
+            run_dir.join("c2.patch")
+        );
+
+        let depth = |layout: RunLayout, path: &str| layout.depth_in_workspace(Path::new(path));
+        assert_eq!(depth(RunLayout::Flat, "c0/workspace/src/main.rs"), Some(2));
+        assert_eq!(depth(RunLayout::Flat, "c0/workspace"), None);
+        assert_eq!(depth(RunLayout::Flat, "C0-strategy.md"), None);
+        assert_eq!(
+            depth(RunLayout::Nested, "instances/0/workspace/NOTES.md"),
+            Some(1)
+        );
+        assert_eq!(depth(RunLayout::Nested, "instances/0/logs/c0.patch"), None);
+        assert_eq!(depth(RunLayout::Nested, "instances/0/workspace"), None);
+    }
+}
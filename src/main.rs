@@ -1,21 +1,88 @@
+mod api_log;
+mod apply;
+mod backend;
+mod bench;
+mod budget;
+mod bundle;
+mod cancel;
+mod checkpoint;
+mod cleanup;
 mod conductor;
+mod config;
+mod critique;
+mod dashboard;
+mod deadline;
+mod debate;
+mod diff_runs;
+mod event_stream;
+mod git;
+mod grep;
+mod history;
+mod hooks;
+mod http;
+mod icons;
+mod judge;
+mod layout;
+mod man;
+mod names;
+mod network;
 mod output;
+mod pause;
+mod protect;
+mod quota;
+mod results;
+mod serve;
 mod session;
+mod spawn;
+mod spend;
+mod stats;
 mod strategy;
+mod strategy_source;
+mod tail;
+mod text_edit;
+mod transcript;
+mod verify;
+mod vote;
+mod web;
+mod winner;
 mod workspace;
 
-use clap::Parser;
+use backend::Backend;
+use bench::Bench;
+use cancel::CancellationToken;
+use checkpoint::Checkpoint;
+use clap::{CommandFactory, Parser, Subcommand};
+use cleanup::KeepWorkspaces;
+use conductor::RunOptions;
+use config::{Config, Criterion, HooksConfig, NetworkConfig};
+use event_stream::StreamEvent;
+use git::AutoCommit;
+use icons::StatusIcons;
+use layout::RunLayout;
+use names::InstanceNames;
 use output::RunOutput;
+use protect::Protection;
+use quota::{SizeAction, SizeLimit};
+use session::TranscriptDetail;
+use spend::Spend;
 use std::io::{self, Read};
-use std::path::Path;
-use tokio::signal;
+use std::path::{Path, PathBuf};
+use strategy::{ExclusionStrength, FailedStrategies, StackConstraints, StrategyDepth, Variation};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use verify::VerifyMode;
+
+/// Instances to run when neither `--num` nor the config says
+const DEFAULT_NUM_INSTANCES: usize = 3;
 
 #[derive(Parser, Debug)]
 #[command(name = "actually")]
 #[command(about = "Orchestrate multiple Claude Code instances with different strategies")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Natural language description of the coding task or problem to solve.
     /// This prompt is sent to multiple AI agents, each using a different strategy.
     /// If omitted, the prompt is read from stdin.
@@ -23,20 +90,199 @@ struct Args {
 
     /// Number of parallel agent instances to spawn, each developing an independent
     /// solution strategy. Higher values provide more diverse approaches but increase
-    /// API costs and execution time.
-    #[arg(short = 'n', long = "num", default_value = "3")]
-    num_instances: usize,
+    /// API costs and execution time.  Defaults to `num` in the config file, else 3.
+    #[arg(short = 'n', long = "num")]
+    num_instances: Option<usize>,
+
+    /// Generate this many strategies (more than --num) and pick --num of them to
+    /// implement in the review TUI.  In headless mode the best-voted (see --vote), or
+    /// otherwise the first, strategies are implemented.
+    #[arg(long, value_name = "M")]
+    candidates: Option<usize>,
+
+    /// Comma-separated axes each new strategy must differ from the previous ones on,
+    /// e.g. `architecture,libraries,algorithm`.  Replaces the generic "utterly different"
+    /// instruction in the strategy prompt.
+    #[arg(long, value_delimiter = ',', value_name = "AXES")]
+    diversify_on: Vec<String>,
+
+    /// Comma-separated temperaments handed out to the instances in turn, from
+    /// `conservative`, `balanced` and `adventurous`, e.g. `conservative,adventurous`.
+    /// Each instance is asked to lean that way in its strategy and implementation.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "STYLES")]
+    variation: Vec<Variation>,
+
+    /// Language every strategy and implementation must use (e.g. `rust`).  Instances then
+    /// vary only their approach within that language.
+    #[arg(long)]
+    language: Option<String>,
+
+    /// Framework every strategy and implementation must use (e.g. `axum`)
+    #[arg(long)]
+    framework: Option<String>,
+
+    /// Shell command that proposes strategies, e.g. `./gen.sh`.  It reads the task and
+    /// the strategies so far as JSON on stdin and writes `{"strategies": [...]}` on
+    /// stdout; once it returns none, the strategy agent proposes the rest.
+    #[arg(long, value_name = "COMMAND")]
+    strategy_source: Option<String>,
+
+    /// Start from the strategies an earlier run implemented, given its run directory
+    /// (e.g. `actually-1700000000`), instead of generating them again.  They are
+    /// marked as imported in review; instances beyond them get new strategies.
+    #[arg(long, value_name = "RUN_DIR", conflicts_with = "resume")]
+    seed_strategies: Option<PathBuf>,
 
     /// Directory where session artifacts are written, including strategy files,
     /// implementation logs, and per-agent workspace directories.
     #[arg(short, long, default_value = ".")]
     out_dir: String,
 
+    /// How the run directory is laid out: `flat` (the default) puts the workspaces in
+    /// c0/workspace/, c1/workspace/, ... beside each instance's strategy, logs and
+    /// patch; `nested` gives each instance an instances/<id>/ directory with its code
+    /// in workspace/ and the rest in logs/.  The layout is recorded in the run's
+    /// metadata.json, and resumed runs keep theirs.
+    #[arg(long, value_enum, value_name = "LAYOUT")]
+    layout: Option<RunLayout>,
+
+    /// Create the instance workspaces under this directory instead of the run
+    /// directory, e.g. on fast scratch storage, while logs and results stay in
+    /// --out-dir.  Each run gets its own actually-<timestamp>/ directory there.
+    #[arg(long, value_name = "DIR", conflicts_with = "tmpfs")]
+    workspace_dir: Option<PathBuf>,
+
+    /// Create the instance workspaces on tmpfs (/dev/shm/actually), so build churn
+    /// stays in memory.  They are gone after a reboot unless --copy-back-workspaces.
+    #[arg(long)]
+    tmpfs: bool,
+
+    /// With --workspace-dir or --tmpfs, move the workspaces into the run directory
+    /// once the run is over, leaving out what their .gitignore ignores.
+    #[arg(long)]
+    copy_back_workspaces: bool,
+
+    /// Which workspaces to keep whole once results are written: `all` (the default),
+    /// `winners` (the suggested winner, the judge's and tournament's picks and any
+    /// synthesis) or `none`.  The rest are stripped to their session log, strategy
+    /// and notes; their changes stay in the run's patches.
+    #[arg(long, value_enum, value_name = "POLICY")]
+    keep_workspaces: Option<KeepWorkspaces>,
+
+    /// Pack each workspace --keep-workspaces removes into `<workspace>.tar.gz` in the
+    /// run directory first
+    #[arg(long)]
+    archive_workspaces: bool,
+
     /// Print detailed execution traces including API requests, token usage,
-    /// and intermediate agent reasoning steps.
+    /// and intermediate agent reasoning steps.  Each implementation instance also
+    /// logs its API traffic (model, token usage and latency per response) to
+    /// `c<N>/workspace/api.log`.
     #[arg(short, long)]
     verbose: bool,
 
+    /// How much of each tool call implementation transcripts and the live dashboard
+    /// show: `brief` (tool names only, the default) or `full` (truncated tool inputs
+    /// and results, plus the files each tool touched).
+    #[arg(long, value_enum, value_name = "LEVEL")]
+    transcript_detail: Option<TranscriptDetail>,
+
+    /// How statuses are marked in the TUIs and the end-of-run summary: `ascii` tags
+    /// such as `[OK]` (the default) or `unicode` emoji such as ✅, for terminals and
+    /// fonts that render them
+    #[arg(long, value_enum, value_name = "STYLE")]
+    status_icons: Option<StatusIcons>,
+
+    /// Stop an implementation session once it has cost more than this many US dollars,
+    /// so one runaway agent can't eat the whole budget.  The instance is reported as
+    /// over budget; its work so far stays in the workspace.
+    #[arg(long, value_name = "USD")]
+    max_cost_per_instance: Option<f64>,
+
+    /// Hold the whole run to this many US dollars while its instances implement.  Once
+    /// nine tenths of it are spent, interactive runs pause and ask whether to extend the
+    /// budget, stop once the running instances finish, or kill them; headless runs are
+    /// cancelled once all of it is.
+    #[arg(long, value_name = "USD")]
+    max_cost: Option<f64>,
+
+    /// Stop the whole run after this long, e.g. `45m` or `1h30m`, for a predictable
+    /// duration in scheduled and CI runs.  Nothing more starts, running sessions are
+    /// stopped, and the finished workspaces are still checked and reported.  Interactive
+    /// runs are asked first, as for --max-cost.
+    #[arg(long, value_name = "DURATION", value_parser = deadline::parse_duration)]
+    deadline: Option<std::time::Duration>,
+
+    /// Check each workspace's disk usage during its session and stop the session once
+    /// it passes this size, e.g. `2G` or `500M`, so an agent can't fill the disk.
+    #[arg(long, value_name = "SIZE", value_parser = quota::parse_size)]
+    max_workspace_size: Option<u64>,
+
+    /// What to do with a workspace over --max-workspace-size: `abort` its session
+    /// (the default) or only `warn`
+    #[arg(long, value_enum, value_name = "ACTION")]
+    workspace_size_action: Option<SizeAction>,
+
+    /// Keep each implementation transcript under this many bytes, in memory and in
+    /// `session.log`, by cutting out its middle: the start of the session and its
+    /// latest output are kept, with a note of how much was left out.
+    #[arg(long, value_name = "BYTES")]
+    max_transcript_bytes: Option<usize>,
+
+    /// Model to move an implementation instance to when its own model keeps failing
+    /// (not found, rate limited or overloaded), e.g. `sonnet` for a run on `opus`.  The
+    /// instance carries on in its workspace, and the switch is noted in the results.
+    #[arg(long, value_name = "MODEL")]
+    fallback_model: Option<String>,
+
+    /// Comma-separated backends for the implementation instances, assigned in order
+    /// and repeated if there are more instances, e.g. `claude,claude,local`.  `claude`
+    /// is built in; others are defined under `[backends.<name>]` in the config file.
+    /// Strategies are always collected with Claude Code.
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    backends: Vec<String>,
+
+    /// How to name the implementation instances in the dashboard, results, logs,
+    /// workspace directories and reports: `titles` (after their strategies' titles),
+    /// a comma-separated list such as `alpha,bravo,charlie`, or `ids` (C0, C1, ...;
+    /// the default)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    names: Vec<String>,
+
+    /// When a strategy is edited in the review TUI, have an agent restate it before
+    /// accepting it (one extra query per edit).  By default edits are accepted as written.
+    #[arg(long)]
+    confirm_strategies: bool,
+
+    /// How much each strategy agent writes: `brief` (a one-sentence approach),
+    /// `standard` (a few sentences and up to 5 steps, the default) or `detailed` (a
+    /// structured plan with ordered steps and the risks to watch).
+    #[arg(long, value_enum, value_name = "DEPTH")]
+    strategy_depth: Option<StrategyDepth>,
+
+    /// How firmly each implementation prompt forbids the other instances' strategies:
+    /// `none` (no exclusions), `soft` (avoid them where possible), `normal` (forbidden,
+    /// the default) or `strict` (forbidden, including borrowing their key qualities).
+    #[arg(long, value_enum, value_name = "LEVEL")]
+    exclusion_strength: Option<ExclusionStrength>,
+
+    /// Leave the other strategies out of implementation prompts, so instances are
+    /// independent samples of their own strategies.  Same as `--exclusion-strength none`.
+    #[arg(long, conflicts_with = "exclusion_strength")]
+    no_exclusions: bool,
+
+    /// What to do with strategies that failed to generate, before review: `implement`
+    /// them anyway as instances that fail at once (the default), `skip` them, or
+    /// `retry` each once and skip the ones that fail again.
+    #[arg(long, value_enum, value_name = "ACTION")]
+    failed_strategies: Option<FailedStrategies>,
+
+    /// Instructions added to every implementation prompt after the strategy, for
+    /// run-wide constraints such as "do not add new dependencies".  Strategy
+    /// collection doesn't see them.
+    #[arg(long, value_name = "TEXT")]
+    impl_instructions: Option<String>,
+
     /// Generate and display the strategy prompts without invoking agents.
     /// Useful for inspecting what would be sent before committing to API calls.
     #[arg(long)]
@@ -47,6 +293,25 @@ struct Args {
     #[arg(long)]
     headless: bool,
 
+    /// Plain output for screen readers and dumb terminals: no full-screen TUIs, box
+    /// drawing or color.  Strategy review becomes numbered questions answered on
+    /// stdin, and implementation progress is printed line by line.
+    #[arg(long, conflicts_with = "headless")]
+    plain: bool,
+
+    /// In headless mode, print every implementation instance's transcript to stdout
+    /// as it arrives, each line prefixed with its instance (`[C0]`, `[C1]`), so CI
+    /// logs show what the agents are doing
+    #[arg(long, requires = "headless")]
+    stream: bool,
+
+    /// In headless mode, print the run's progress to stdout as newline-delimited JSON
+    /// events (`run_started`, `strategy_generated`, `instance_started`, `message`,
+    /// `instance_finished`, `run_complete`) for other tools to build on.  Tracing
+    /// output moves to stderr.
+    #[arg(long, requires = "headless", conflicts_with_all = ["stream", "dry_run"])]
+    events: bool,
+
     /// Optionally specify which model to use within the Claude Code instances.  If not specified,
     /// the model currently set within Claude Code as the default will be used.
     #[arg(short = 'm', long)]
@@ -57,15 +322,426 @@ struct Args {
     /// is not given, the model currently set within Claude Code as the default will be used.
     #[arg(long = "impl-model")]
     impl_model: Option<String>,
+
+    /// Model for strategy generation and the other short planning queries (voting,
+    /// debates).  Falls back to `--model`, then to `strategy_model` in the config file,
+    /// then to `haiku`: planning doesn't need the implementation model.
+    #[arg(long = "strategy-model")]
+    strategy_model: Option<String>,
+
+    /// Path to a config file.  Defaults to ./actually.toml, then
+    /// ~/.config/actually/config.toml.  Command-line flags override config values.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Lay the settings of the config file's `[profiles.<NAME>]` table over the rest
+    /// of it, e.g. `--profile cheap`.  Command-line flags still win.
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Send the agents' and every other process's HTTP(S) traffic through this
+    /// proxy, e.g. `http://proxy.corp:3128`.  Sets HTTPS_PROXY and HTTP_PROXY.
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Comma-separated hosts to reach without the proxy (NO_PROXY)
+    #[arg(long, value_name = "HOSTS")]
+    no_proxy: Option<String>,
+
+    /// CA certificate bundle (PEM) to trust, e.g. a corporate proxy's, for the agent
+    /// sessions and every other process actually starts
+    #[arg(long, value_name = "PATH")]
+    ca_cert: Option<PathBuf>,
+
+    /// Share build caches (cargo target dir, npm cache, pnpm store) across instance
+    /// workspaces.  Enables every language; use `[build_cache]` in the config file to
+    /// choose languages individually.
+    #[arg(long)]
+    share_build_cache: bool,
+
+    /// Copy the current directory into each instance workspace before implementation,
+    /// skipping paths ignored by .gitignore (build artifacts, node_modules, ...).
+    #[arg(long)]
+    seed_cwd: bool,
+
+    /// With --seed-cwd, seed from a repository with uncommitted changes.  Without it the
+    /// run refuses to start, since those changes would be in every instance's baseline
+    /// but not in the branches and patches that are taken back to the repository.
+    #[arg(long)]
+    allow_dirty: bool,
+
+    /// CLAUDE.md (or template) to drop into every instance workspace.  `{task}`,
+    /// `{strategy}` and `{instance}` placeholders are filled in per instance.
+    #[arg(long = "claude-md")]
+    claude_md: Option<PathBuf>,
+
+    /// Give agents your normal Claude Code environment: load ~/.claude settings and the
+    /// project's .claude settings, and copy the project's CLAUDE.md and .claude settings
+    /// into each instance workspace.  By default agents run without filesystem settings.
+    #[arg(long)]
+    inherit_claude_settings: bool,
+
+    /// Initialize a git repository in each instance workspace and commit the agent's
+    /// work: `end` commits once after implementation (the default when the flag is given
+    /// without a value), `turn` also commits after every batch of tool calls.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "end")]
+    auto_commit: Option<AutoCommit>,
+
+    /// Shell command that checks each workspace after implementation, e.g. `make test`.
+    /// Without it, the check is picked from the workspace's project files: `cargo test`
+    /// for Cargo.toml, `npm test` for package.json, `pytest` for pyproject.toml.
+    #[arg(long, value_name = "COMMAND")]
+    verify: Option<String>,
+
+    /// Don't run any check in the workspaces after implementation
+    #[arg(long, conflicts_with = "verify")]
+    no_verify: bool,
+
+    /// Build each workspace before its tests, recording whether it compiles at all.
+    /// Without a command, the build is picked from the project files: `cargo build` for
+    /// Cargo.toml, `npm run build` for package.json, `compileall` for pyproject.toml.
+    #[arg(long, value_name = "COMMAND", num_args = 0..=1, require_equals = true)]
+    build: Option<Option<String>>,
+
+    /// Lint each workspace after its tests and count the warnings, which the judge sees.
+    /// Without a command, the linter is picked from the project files: clippy for
+    /// Cargo.toml, eslint for package.json, ruff for pyproject.toml.
+    #[arg(long, value_name = "COMMAND", num_args = 0..=1, require_equals = true)]
+    lint: Option<Option<String>>,
+
+    /// Measure test coverage in each workspace and compare the line coverage in the
+    /// report and the judge's scorecard.  Without a command it is picked from the
+    /// project files: cargo-llvm-cov, jest --coverage, or pytest-cov.
+    #[arg(long, value_name = "COMMAND", num_args = 0..=1, require_equals = true)]
+    coverage: Option<Option<String>>,
+
+    /// Benchmark command run in each successful workspace after its checks, e.g.
+    /// `./bench.sh`.  The number it prints is compared across instances in a
+    /// performance table in strategies.md.
+    #[arg(long, value_name = "COMMAND")]
+    bench: Option<String>,
+
+    /// Regex locating the benchmark's number in its output: the first capture group, or
+    /// the whole match.  Defaults to the first number printed.
+    #[arg(long, value_name = "REGEX", requires = "bench")]
+    bench_pattern: Option<String>,
+
+    /// Rank bigger benchmark numbers as better (throughput) instead of smaller (time)
+    #[arg(long, requires = "bench")]
+    bench_higher_is_better: bool,
+
+    /// After implementation, have each instance critique another instance's work (bugs,
+    /// risks, strengths).  Critiques are attached to the reviewed instance in the report.
+    #[arg(long)]
+    cross_review: bool,
+
+    /// After implementation, have a judge agent compare the successful instances and rank
+    /// them.  The ranking and the judge's notes are saved to judge.md, and the ranking
+    /// with each instance's rubric scores to verdict.json.
+    #[arg(long)]
+    judge: bool,
+
+    /// After judging (implies --judge), have a fresh agent merge the top 2-3
+    /// implementations into a combined solution in a new c-synth/workspace/ directory.
+    #[arg(long)]
+    synthesize: bool,
+
+    /// After implementation, compare instances pairwise with a judge agent in bracket
+    /// rounds until one winner remains.  The bracket and each match's reasoning are
+    /// saved to tournament.md.
+    #[arg(long)]
+    tournament: bool,
+
+    /// Judge with this shell command instead of a judge agent, for --judge,
+    /// --synthesize and --tournament.  It runs in the run directory, reads the task,
+    /// rubric and candidates as JSON on stdin and writes its ranking as JSON on stdout.
+    #[arg(long, value_name = "COMMAND")]
+    judge_command: Option<String>,
+
+    /// After collecting strategies, have each strategy agent rank the other strategies.
+    /// The aggregate score is shown in the review TUI to help prune weaker strategies.
+    #[arg(long)]
+    vote: bool,
+
+    /// Number of rounds each side gets when debating two strategies in the review
+    /// TUI (key `b`) [default: 2]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    debate_rounds: Option<u32>,
+
+    /// Pick up an interrupted run from its run directory (e.g. `actually-1700000000`).
+    /// Collected strategies, the review, and finished instances are kept from the
+    /// checkpoint; only the unfinished work is redone.  The prompt comes from the
+    /// checkpoint, other options from this invocation.
+    #[arg(long, value_name = "RUN_DIR", conflicts_with = "prompt")]
+    resume: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show Elo ratings for models and strategy archetypes, computed from the judged
+    /// outcomes of past runs
+    Stats,
+    /// Walk through an instance's changes hunk by hunk and stage the accepted
+    /// ones in the repository in the current directory, like `git add -p`
+    Apply {
+        /// Run output directory holding results.json and the instance patches
+        #[arg(value_name = "RUN_DIR")]
+        run_dir: PathBuf,
+        /// Instance to apply (default: the suggested winner, or the judge's pick)
+        #[arg(long, value_name = "N")]
+        instance: Option<usize>,
+    },
+    /// Serve an HTTP API for starting runs, polling their status, following their
+    /// events (server-sent events) and fetching their results
+    Serve {
+        /// Address to listen on.  Anyone who can reach it can start agents, so keep it
+        /// on loopback unless the network is trusted.
+        #[arg(long, value_name = "ADDR", default_value = serve::DEFAULT_LISTEN)]
+        listen: String,
+    },
+    /// Serve a read-only browser dashboard of a run: instance status, transcripts,
+    /// diffs and costs, kept live while the run goes on
+    Web {
+        /// Run output directory
+        #[arg(value_name = "RUN_DIR")]
+        run_dir: PathBuf,
+        /// Address to listen on, e.g. `0.0.0.0:7879` to reach it from other machines
+        #[arg(long, value_name = "ADDR", default_value = web::DEFAULT_LISTEN)]
+        listen: String,
+    },
+    /// Browse a finished run in the results TUI, read-only: strategies, transcripts,
+    /// diffs and verdicts
+    Show {
+        /// Run output directory holding results.json
+        #[arg(value_name = "RUN_DIR")]
+        run_dir: PathBuf,
+        /// How statuses are marked
+        #[arg(long, value_enum, value_name = "STYLE")]
+        status_icons: Option<StatusIcons>,
+    },
+    /// Pack a finished run into an archive (e.g. bundle.tar.zst) to share; `actually
+    /// import` unpacks it for `actually show`
+    Export {
+        /// Run output directory
+        #[arg(value_name = "RUN_DIR")]
+        run_dir: PathBuf,
+        /// Archive to write; `tar` compresses it by its extension (.tar.zst, .tar.gz)
+        #[arg(value_name = "BUNDLE")]
+        bundle: PathBuf,
+        /// Leave the workspaces' code out, keeping their session logs and notes; the
+        /// changes stay in the run's patches
+        #[arg(long)]
+        strip_workspaces: bool,
+        /// Replace API keys, tokens, private keys and secret environment values in the
+        /// bundled files, and leave out the workspaces' git histories
+        #[arg(long)]
+        redact: bool,
+    },
+    /// Unpack a bundle from `actually export` into a run directory
+    Import {
+        #[arg(value_name = "BUNDLE")]
+        bundle: PathBuf,
+        /// Directory to unpack it in [default: the current directory]
+        #[arg(long, value_name = "DIR")]
+        into: Option<PathBuf>,
+    },
+    /// Compare two runs side by side: success rates, costs, verification outcomes and
+    /// the strategies they have in common
+    DiffRuns {
+        /// First run output directory
+        #[arg(value_name = "RUN_DIR_A")]
+        a: PathBuf,
+        /// Second run output directory
+        #[arg(value_name = "RUN_DIR_B")]
+        b: PathBuf,
+    },
+    /// Follow the transcripts of a running (or finished) run from another terminal,
+    /// each line prefixed with its instance
+    Tail {
+        /// Run output directory
+        #[arg(value_name = "RUN_DIR")]
+        run_dir: PathBuf,
+        /// Follow only this instance
+        #[arg(value_name = "INSTANCE")]
+        instance: Option<usize>,
+    },
+    /// Search the strategies, transcripts, notes and summaries of one or more runs,
+    /// e.g. for which instance used `tokio::select`
+    Grep {
+        /// Run output directories to search
+        #[arg(value_name = "RUN_DIR", required = true)]
+        run_dirs: Vec<PathBuf>,
+        /// Regular expression to look for
+        #[arg(value_name = "PATTERN")]
+        pattern: String,
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Lines of context to show around each match
+        #[arg(short = 'C', long, value_name = "N", default_value = "0")]
+        context: usize,
+        /// Search only this instance's files
+        #[arg(long, value_name = "N")]
+        instance: Option<usize>,
+    },
+    /// Print the man page, or write one page per command into a directory for
+    /// packaging
+    Man {
+        /// Directory to write actually.1 and actually-<command>.1 into
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
+    match &args.command {
+        Some(Command::Stats) => return stats::print_stats(),
+        Some(Command::Man { out_dir }) => return man::run(Args::command(), out_dir.as_deref()),
+        Some(Command::DiffRuns { a, b }) => return diff_runs::run(a, b),
+        Some(Command::Export {
+            run_dir,
+            bundle,
+            strip_workspaces,
+            redact,
+        }) => return bundle::export(run_dir, bundle, *strip_workspaces, *redact),
+        Some(Command::Import { bundle, into }) => {
+            return bundle::import(bundle, into.as_deref().unwrap_or(Path::new(".")))
+        }
+        Some(Command::Apply { run_dir, instance }) => return apply::run(run_dir, *instance).await,
+        Some(Command::Tail { run_dir, instance }) => return tail::run(run_dir, *instance).await,
+        Some(Command::Grep {
+            run_dirs,
+            pattern,
+            ignore_case,
+            context,
+            instance,
+        }) => return grep::run(run_dirs, pattern, *ignore_case, *context, *instance),
+        Some(Command::Serve { listen }) => return serve::run(listen).await,
+        Some(Command::Web { run_dir, listen }) => return web::run(run_dir, listen).await,
+        Some(Command::Show {
+            run_dir,
+            status_icons,
+        }) => {
+            let config = Config::load(args.config.as_deref(), args.profile.as_deref())?;
+            let icons = status_icons.or(config.status_icons).unwrap_or_default();
+            return results::show(run_dir, icons).await;
+        }
+        None => {}
+    }
+    let config = Config::load(args.config.as_deref(), args.profile.as_deref())?;
+    let num_instances = args
+        .num_instances
+        .or(config.num)
+        .unwrap_or(DEFAULT_NUM_INSTANCES);
+    // Before anything is spawned, so every process inherits it
+    network::apply(&NetworkConfig {
+        proxy: args.proxy.clone().or_else(|| config.network.proxy.clone()),
+        no_proxy: args
+            .no_proxy
+            .clone()
+            .or_else(|| config.network.no_proxy.clone()),
+        ca_cert: args
+            .ca_cert
+            .clone()
+            .or_else(|| config.network.ca_cert.clone()),
+    })?;
 
-    let prompt = match args.prompt {
-        Some(p) => p,
-        None => {
+    if let Some(m) = args.candidates {
+        if m <= num_instances {
+            anyhow::bail!(
+                "--candidates ({}) must be greater than --num ({})",
+                m,
+                num_instances
+            );
+        }
+    }
+
+    let max_cost_per_instance = args.max_cost_per_instance.or(config.max_cost_per_instance);
+    if let Some(max) = max_cost_per_instance {
+        if max.is_nan() || max <= 0.0 {
+            anyhow::bail!(
+                "--max-cost-per-instance must be a positive amount, got {}",
+                max
+            );
+        }
+    }
+
+    let max_cost = args.max_cost.or(config.max_cost);
+    if let Some(max) = max_cost {
+        if max.is_nan() || max <= 0.0 {
+            anyhow::bail!("--max-cost must be a positive amount, got {}", max);
+        }
+    }
+
+    let deadline = match (args.deadline, &config.deadline) {
+        (Some(duration), _) => Some(duration),
+        (None, Some(duration)) => Some(
+            deadline::parse_duration(duration)
+                .map_err(|e| anyhow::anyhow!("deadline in the config: {}", e))?,
+        ),
+        (None, None) => None,
+    };
+
+    let max_workspace_size = match (args.max_workspace_size, &config.max_workspace_size) {
+        (Some(bytes), _) => Some(bytes),
+        (None, Some(size)) => Some(
+            quota::parse_size(size)
+                .map_err(|e| anyhow::anyhow!("max_workspace_size in the config: {}", e))?,
+        ),
+        (None, None) => None,
+    }
+    .map(|max_bytes| SizeLimit {
+        max_bytes,
+        action: args
+            .workspace_size_action
+            .or(config.workspace_size_action)
+            .unwrap_or_default(),
+    });
+
+    let max_transcript_bytes = args.max_transcript_bytes.or(config.max_transcript_bytes);
+    if max_transcript_bytes == Some(0) {
+        anyhow::bail!("--max-transcript-bytes must be at least 1");
+    }
+
+    if config.debate_rounds == Some(0) {
+        anyhow::bail!("debate_rounds in the config must be at least 1");
+    }
+
+    let backends = Backend::resolve(&args.backends, &config.backends)?;
+
+    let bench = match args.bench.clone().or_else(|| config.bench.clone()) {
+        Some(command) => {
+            let pattern = args
+                .bench_pattern
+                .clone()
+                .or_else(|| config.bench_pattern.clone())
+                .unwrap_or_else(|| bench::DEFAULT_BENCH_PATTERN.to_string());
+            Some(Bench {
+                command,
+                pattern: regex::Regex::new(&pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid --bench-pattern: {}", e))?,
+                higher_is_better: args.bench_higher_is_better || config.bench_higher_is_better,
+            })
+        }
+        None => None,
+    };
+
+    let resume = match &args.resume {
+        Some(dir) => Some(Checkpoint::load(dir)?),
+        None => None,
+    };
+
+    let prompt = match (args.prompt, &resume) {
+        (_, Some(checkpoint)) => checkpoint.prompt.clone(),
+        (Some(p), None) => p,
+        (None, None) if !args.headless && (args.plain || config.plain) => {
+            anyhow::bail!(
+                "--plain reads its review answers from stdin; give the prompt as an argument"
+            );
+        }
+        (None, None) => {
             eprintln!("Reading prompt from stdin...");
             let mut buf = String::new();
             io::stdin().read_to_string(&mut buf)?;
@@ -82,6 +758,7 @@ async fn main() -> anyhow::Result<()> {
     // In interactive mode (default), suppress all tracing output
     // All user-facing output uses println
     let interactive = !args.headless;
+    let plain = interactive && (args.plain || config.plain);
     let filter = if interactive {
         "off"
     } else if args.verbose {
@@ -94,49 +771,376 @@ async fn main() -> anyhow::Result<()> {
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| filter.into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(
+            tracing_subscriber::fmt::layer().with_writer(move || -> Box<dyn io::Write> {
+                // Keep stdout to the event stream
+                if args.events {
+                    Box::new(io::stderr())
+                } else {
+                    Box::new(io::stdout())
+                }
+            }),
+        )
         .init();
 
     if interactive {
         println!(
             "actually starting: {} instances, prompt: \"{}\"",
-            args.num_instances,
+            num_instances,
             truncate(&prompt, 50)
         );
     } else {
-        tracing::info!(
-            num_instances = args.num_instances,
-            dry_run = args.dry_run,
-            "actually starting"
-        );
+        tracing::info!(num_instances, dry_run = args.dry_run, "actually starting");
     }
 
-    // Create run output directory structure
-    let run_output = RunOutput::create(Path::new(&args.out_dir), interactive)?;
+    // Seeded diffs and winner branches are taken against the repository's HEAD
+    if args.seed_cwd && !(args.allow_dirty || config.allow_dirty) {
+        let cwd = std::env::current_dir()?;
+        if let Some(changes) = git::uncommitted_changes(&cwd).await? {
+            if !changes.is_empty() {
+                let shown: Vec<&str> = changes.iter().take(5).map(String::as_str).collect();
+                let more = match changes.len() - shown.len() {
+                    0 => String::new(),
+                    n => format!("\n  ... and {} more", n),
+                };
+                anyhow::bail!(
+                    "{} has uncommitted changes, which --seed-cwd would copy into every \
+                     workspace's baseline:\n  {}{}\nCommit or stash them, or pass \
+                     --allow-dirty to seed from them anyway",
+                    cwd.display(),
+                    shown.join("\n  "),
+                    more
+                );
+            }
+        }
+    }
 
-    // Run with signal handling
-    let results = tokio::select! {
-        result = conductor::run(
-            &prompt,
-            args.num_instances,
-            run_output.path(),
-            args.dry_run,
-            interactive,
-            args.model.as_deref(),
-            args.impl_model.as_deref(),
-        ) => result?,
-        _ = signal::ctrl_c() => {
+    // A resumed run already has its strategies
+    let seed_strategies = match args
+        .seed_strategies
+        .as_ref()
+        .or(config.seed_strategies.as_ref())
+    {
+        Some(dir) if args.resume.is_none() => {
+            let seeded = Checkpoint::load(dir)?;
+            let strategies = seeded.seed_strategies();
+            if strategies.is_empty() {
+                anyhow::bail!("{} has no strategies to seed from", dir.display());
+            }
+            if seeded.prompt.trim() != prompt.trim() {
+                if interactive {
+                    println!("Note: {} was run with a different prompt", dir.display());
+                } else {
+                    tracing::warn!(run_dir = %dir.display(), "Seed run was run with a different prompt");
+                }
+            }
             if interactive {
-                println!("\nInterrupted");
+                println!(
+                    "Seeding {} strategies from {}",
+                    strategies.len(),
+                    dir.display()
+                );
             } else {
-                tracing::info!("Received SIGINT, shutting down");
+                tracing::info!(count = strategies.len(), run_dir = %dir.display(), "Seeding strategies");
             }
-            return Ok(());
+            strategies
         }
+        _ => Vec::new(),
     };
 
+    // Create run output directory structure
+    let mut run_output = match &args.resume {
+        Some(dir) => RunOutput::open(dir)?,
+        None => RunOutput::create(
+            Path::new(&args.out_dir),
+            interactive,
+            args.layout.or(config.layout).unwrap_or_default(),
+        )?,
+    };
+    let workspace_base = match args.workspace_dir.clone().or(config.workspace_dir.clone()) {
+        Some(dir) => Some(dir),
+        None if args.tmpfs || config.tmpfs => {
+            if !Path::new("/dev/shm").is_dir() {
+                anyhow::bail!("--tmpfs needs a tmpfs at /dev/shm; use --workspace-dir");
+            }
+            Some(PathBuf::from(output::TMPFS_DIR))
+        }
+        None => None,
+    };
+    // A resumed run's workspaces stay where they were
+    if !args.dry_run {
+        match resume.as_ref().and_then(|c| c.workspace_dir.clone()) {
+            Some(dir) => run_output.set_workspace_dir(dir)?,
+            None if args.resume.is_some() => {}
+            None => {
+                if let Some(base) = workspace_base {
+                    run_output.create_workspace_dir(&base)?;
+                }
+            }
+        }
+    }
+    if args.events {
+        event_stream::emit(&StreamEvent::RunStarted {
+            run_dir: &run_output.path().to_string_lossy(),
+            prompt: &prompt,
+            num_instances,
+        });
+    }
+    let names = InstanceNames::from_args(if args.names.is_empty() {
+        &config.names
+    } else {
+        &args.names
+    });
+    // Named instances' directories are created once their names are known
+    if names == InstanceNames::Ids {
+        run_output.create_instance_dirs(num_instances)?;
+    }
+
+    let mut build_cache = config.build_cache.clone();
+    if args.share_build_cache {
+        build_cache.rust = true;
+        build_cache.node = true;
+    }
+
+    let claude_md_template = match args.claude_md.as_ref().or(config.claude_md.as_ref()) {
+        Some(path) => Some(std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read CLAUDE.md template {}: {}",
+                path.display(),
+                e
+            )
+        })?),
+        None => None,
+    };
+
+    let options = RunOptions {
+        num_instances,
+        dry_run: args.dry_run,
+        interactive,
+        plain,
+        strategy_model: Some(
+            args.strategy_model
+                .clone()
+                .or_else(|| args.model.clone())
+                .or_else(|| config.strategy_model.clone())
+                .or_else(|| config.model.clone())
+                .unwrap_or_else(|| config::DEFAULT_STRATEGY_MODEL.to_string()),
+        ),
+        impl_model: args
+            .impl_model
+            .clone()
+            .or_else(|| args.model.clone())
+            .or_else(|| config.impl_model.clone())
+            .or_else(|| config.model.clone()),
+        build_cache,
+        seed_dir: if args.seed_cwd {
+            Some(std::env::current_dir()?)
+        } else {
+            None
+        },
+        protection: Protection::new(&config.protect)?,
+        claude_md_template,
+        inherit_claude_settings: args.inherit_claude_settings || config.inherit_claude_settings,
+        auto_commit: args.auto_commit.or(config.auto_commit),
+        resume,
+        transcript_detail: args
+            .transcript_detail
+            .or(config.transcript_detail)
+            .unwrap_or_default(),
+        max_cost_per_instance,
+        max_cost,
+        deadline,
+        max_workspace_size,
+        max_transcript_bytes,
+        backends,
+        names,
+        // Emoji don't read well aloud or show on dumb terminals
+        status_icons: if plain {
+            StatusIcons::Ascii
+        } else {
+            args.status_icons
+                .or(config.status_icons)
+                .unwrap_or_default()
+        },
+        api_log: args.verbose,
+        stream: args.headless && !args.events && (args.stream || config.stream),
+        events: args.events,
+        confirm_strategies: args.confirm_strategies || config.confirm_strategies,
+        strategy_depth: args
+            .strategy_depth
+            .or(config.strategy_depth)
+            .unwrap_or_default(),
+        exclusion_strength: if args.no_exclusions {
+            ExclusionStrength::None
+        } else {
+            args.exclusion_strength
+                .or(config.exclusion_strength)
+                .unwrap_or_default()
+        },
+        failed_strategies: args
+            .failed_strategies
+            .or(config.failed_strategies)
+            .unwrap_or_default(),
+        impl_instructions: args
+            .impl_instructions
+            .clone()
+            .or_else(|| config.impl_instructions.clone()),
+        fallback_model: args
+            .fallback_model
+            .clone()
+            .or_else(|| config.fallback_model.clone()),
+        verify: if args.no_verify || config.no_verify {
+            VerifyMode::Off
+        } else {
+            match args.verify.clone().or_else(|| config.verify.clone()) {
+                Some(command) => VerifyMode::Command(command),
+                None => VerifyMode::Detect,
+            }
+        },
+        build: optional_check(&args.build, config.build, &config.build_command),
+        lint: optional_check(&args.lint, config.lint, &config.lint_command),
+        coverage: optional_check(&args.coverage, config.coverage, &config.coverage_command),
+        bench,
+        hooks: if args.dry_run {
+            HooksConfig::default()
+        } else {
+            config.hooks.clone()
+        },
+        cross_review: args.cross_review || config.cross_review,
+        debate_rounds: args
+            .debate_rounds
+            .or(config.debate_rounds)
+            .map_or(debate::DEFAULT_ROUNDS, |rounds| rounds as usize),
+        judge: args.judge || config.judge,
+        synthesize: args.synthesize || config.synthesize,
+        tournament: args.tournament || config.tournament,
+        judge_command: args
+            .judge_command
+            .clone()
+            .or_else(|| config.judge_command.clone()),
+        vote: args.vote || config.vote,
+        candidates: args.candidates,
+        stack: StackConstraints {
+            language: args.language.clone().or_else(|| config.language.clone()),
+            framework: args.framework.clone().or_else(|| config.framework.clone()),
+        },
+        diversify_on: if args.diversify_on.is_empty() {
+            config.diversify_on.clone()
+        } else {
+            args.diversify_on.clone()
+        },
+        variation: if args.variation.is_empty() {
+            config.variation.clone()
+        } else {
+            args.variation.clone()
+        },
+        strategy_source: args
+            .strategy_source
+            .clone()
+            .or_else(|| config.strategy_source.clone()),
+        seed_strategies,
+        env: config.env.clone(),
+        workspace_dir: run_output.workspace_dir().to_path_buf(),
+        layout: run_output.layout(),
+        rubric: if config.rubric.is_empty() {
+            Criterion::default_rubric()
+        } else {
+            config.rubric.clone()
+        },
+        winner_weights: config.winner.clone(),
+        cancel: CancellationToken::default(),
+        spend: Spend::default(),
+    };
+
+    // A resumed run recorded these when it started
+    if args.resume.is_none() {
+        run_output.write_prompts(
+            &prompt,
+            &strategy::strategy_template(&prompt, &options.stack, options.strategy_depth),
+            &strategy::implementation_template(&prompt, &options.stack),
+        )?;
+    }
+
+    // SIGINT/SIGTERM stop the run cooperatively, so whatever finished is
+    // still written out below
+    options.cancel.cancel_on_signals(interactive);
+    let (mut results, timings) = conductor::run(&prompt, run_output.path(), &options).await?;
+    let cancelled = options.cancel.is_cancelled();
+    if args.copy_back_workspaces || config.copy_back_workspaces {
+        run_output.copy_back_workspaces(&mut results)?;
+    }
+
     // Write output files
-    run_output.write_results(&results)?;
+    run_output.write_results(&results, &timings)?;
+
+    if args.events {
+        let costs: Vec<f64> = results.iter().filter_map(|r| r.cost_usd).collect();
+        event_stream::emit(&StreamEvent::RunComplete {
+            run_dir: &run_output.path().to_string_lossy(),
+            succeeded: results.iter().filter(|r| r.success).count(),
+            failed: results.iter().filter(|r| !r.success).count(),
+            cost_usd: (!costs.is_empty()).then(|| costs.iter().sum()),
+            cancelled,
+            suggested_winner: results
+                .iter()
+                .find(|r| r.suggested_winner)
+                .map(|r| r.instance_id),
+        });
+    }
+
+    if cancelled && !args.dry_run {
+        if interactive {
+            println!(
+                "Run cancelled; continue it with --resume {}",
+                run_output.path().display()
+            );
+        } else {
+            tracing::info!(
+                run_dir = %run_output.path().display(),
+                "Run cancelled; continue it with --resume"
+            );
+        }
+    } else if !args.dry_run {
+        let record = history::RunRecord::new(&prompt, run_output.path(), &results);
+        if let Err(e) = history::record(&record) {
+            tracing::warn!(error = %e, "Failed to record run history");
+        }
+    }
+
+    if plain && !args.dry_run && !cancelled {
+        println!(
+            "Results are in {}",
+            run_output.path().join("strategies.md").display()
+        );
+    } else if interactive && !args.dry_run && !cancelled {
+        results::review(
+            &results,
+            run_output.path(),
+            options.seed_dir.as_deref(),
+            options.status_icons,
+        )
+        .await?;
+    }
+
+    let keep_workspaces = args
+        .keep_workspaces
+        .or(config.keep_workspaces)
+        .unwrap_or_default();
+    if keep_workspaces != KeepWorkspaces::All && !args.dry_run && !cancelled {
+        let archive = args.archive_workspaces || config.archive_workspaces;
+        let (pruned, freed) =
+            cleanup::prune_workspaces(&results, run_output.path(), keep_workspaces, archive)?;
+        if interactive {
+            println!("{}", cleanup::describe(pruned, freed, archive));
+        } else {
+            tracing::info!(
+                pruned,
+                freed_bytes = freed,
+                archived = archive,
+                "Cleaned up workspaces"
+            );
+        }
+    }
 
     if interactive {
         println!("Output: {}", run_output.path().display());
@@ -150,6 +1154,23 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Mode of an opt-in check such as `--lint[=<command>]`: the flag's
+/// command, else the config's `<check>_command`, else detection when the
+/// bare flag or `<check> = true` asks for the check
+fn optional_check(
+    flag: &Option<Option<String>>,
+    enabled: bool,
+    command: &Option<String>,
+) -> VerifyMode {
+    match (flag, command) {
+        (Some(Some(command)), _) => VerifyMode::Command(command.clone()),
+        (_, Some(command)) => VerifyMode::Command(command.clone()),
+        (Some(None), None) => VerifyMode::Detect,
+        (None, None) if enabled => VerifyMode::Detect,
+        (None, None) => VerifyMode::Off,
+    }
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() > max_len {
         format!("{}...", &s[..max_len.saturating_sub(3)])
@@ -0,0 +1,105 @@
+use anyhow::Context;
+use clap_mangen::Man;
+use std::io::Write;
+use std::path::Path;
+
+/// What the flags' own help can't say: where the config comes from and how
+/// it combines with the command line
+const CONFIGURATION: &str = r#".SH CONFIGURATION
+\fBactually\fR reads an optional TOML config file, the first of:
+.IP \(bu 2
+the file given with \fB\-\-config\fR
+.IP \(bu 2
+\fIactually.toml\fR in the current directory
+.IP \(bu 2
+\fI$XDG_CONFIG_HOME/actually/config.toml\fR, or \fI~/.config/actually/config.toml\fR
+.PP
+Most options have a config key of the same name with dashes as underscores,
+e.g. \fBmax_workspace_size = "2G"\fR for \fB\-\-max\-workspace\-size 2G\fR.
+A flag given on the command line always wins over the config file, which wins
+over the built\-in default.  \fB[env]\fR, \fB[hooks]\fR, \fB[network]\fR,
+\fB[protect]\fR, \fB[winner]\fR and \fB[[rubric]]\fR tables configure what has no
+single flag.
+A \fB[profiles.<name>]\fR table holds any of these settings, laid over the
+rest of the file with \fB\-\-profile\fR \fIname\fR.
+"#;
+
+const FILES: &str = r#".SH FILES
+.TP
+\fIactually\-<timestamp>/\fR
+A run's output directory under \fB\-\-out\-dir\fR: strategies, workspaces
+(\fIc<N>/workspace/\fR, or \fIinstances/<N>/workspace/\fR with \fB\-\-layout nested\fR), transcripts,
+patches, \fIresults.json\fR, \fImetadata.json\fR and \fIcheckpoint.json\fR for
+\fB\-\-resume\fR.
+.TP
+\fI$XDG_DATA_HOME/actually/history.jsonl\fR
+Run history (or \fI~/.local/share/actually/history.jsonl\fR), used by
+\fBactually stats\fR.
+"#;
+
+/// `actually man`: the man page for `cmd` on stdout or, with `out_dir`,
+/// `actually.1` and one `actually-<command>.1` per subcommand there
+pub fn run(cmd: clap::Command, out_dir: Option<&Path>) -> anyhow::Result<()> {
+    let mut cmd = cmd.disable_help_subcommand(true);
+    cmd.build();
+    let Some(out_dir) = out_dir else {
+        return render(&cmd, true, &mut std::io::stdout().lock());
+    };
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let pages = std::iter::once((&cmd, true))
+        .chain(cmd.get_subcommands().map(|sub| (sub, false)))
+        .filter(|(page, _)| !page.is_hide_set());
+    for (page, top) in pages {
+        let path = out_dir.join(Man::new(page.clone()).get_filename());
+        let mut file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        render(page, top, &mut file)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// Render `cmd`'s page; the top-level page also documents the config file,
+/// ahead of its VERSION section
+fn render(cmd: &clap::Command, top: bool, w: &mut dyn Write) -> anyhow::Result<()> {
+    let mut page = Vec::new();
+    Man::new(cmd.clone())
+        .manual("actually manual")
+        .render(&mut page)?;
+    if top {
+        let page = String::from_utf8(page)?;
+        let (head, tail) = page.split_at(page.find(".SH VERSION").unwrap_or(page.len()));
+        write!(w, "{}{}{}{}", head, CONFIGURATION, FILES, tail)?;
+    } else {
+        w.write_all(&page)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_render() {
+        let mut cmd = crate::Args::command().disable_help_subcommand(true);
+        cmd.build();
+        let mut page = Vec::new();
+        render(&cmd, true, &mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.starts_with(".ie"));
+        assert!(page.contains(".SH OPTIONS"));
+        assert!(page.contains(r"\-\-keep\-workspaces"));
+        assert!(page.contains("actually\\-stats(1)"));
+        assert!(page.contains(".SH CONFIGURATION"));
+
+        let show = cmd.find_subcommand("show").unwrap();
+        let mut page = Vec::new();
+        render(show, false, &mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.contains("actually\\-show"));
+        assert!(!page.contains(".SH CONFIGURATION"));
+    }
+}
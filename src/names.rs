@@ -0,0 +1,133 @@
+/// Words of a strategy title kept in a title-based name
+const MAX_NAME_WORDS: usize = 3;
+
+/// How implementation instances are named (`--names`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum InstanceNames {
+    /// C0, C1, ...
+    #[default]
+    Ids,
+    /// The first words of each strategy's title, e.g. `event-sourced-rewrite`
+    Titles,
+    /// Names from a list in instance order; instances past its end keep their ids
+    List(Vec<String>),
+}
+
+impl InstanceNames {
+    /// From `--names`: `ids`, `titles`, or the names themselves
+    pub fn from_args(args: &[String]) -> Self {
+        match args {
+            [single] if single.trim() == "ids" => Self::Ids,
+            [single] if single.trim() == "titles" => Self::Titles,
+            list => {
+                let names: Vec<String> = list
+                    .iter()
+                    .map(|name| slugify(name))
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                if names.is_empty() {
+                    Self::Ids
+                } else {
+                    Self::List(names)
+                }
+            }
+        }
+    }
+
+    /// Name for each instance, given the strategy titles in instance order.
+    /// A name already taken gets the instance id appended.
+    pub fn assign(&self, titles: &[&str]) -> Vec<Option<String>> {
+        let mut taken: Vec<String> = Vec::new();
+        (0..titles.len())
+            .map(|id| {
+                let name = match self {
+                    Self::Ids => None,
+                    Self::Titles => {
+                        let words: Vec<&str> =
+                            titles[id].split_whitespace().take(MAX_NAME_WORDS).collect();
+                        Some(slugify(&words.join(" ")))
+                    }
+                    Self::List(names) => names.get(id).cloned(),
+                }
+                .filter(|name| !name.is_empty())?;
+                let name = if taken.contains(&name) {
+                    format!("{}-{}", name, id)
+                } else {
+                    name
+                };
+                taken.push(name.clone());
+                Some(name)
+            })
+            .collect()
+    }
+}
+
+/// Lowercase letters, digits and single dashes
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// How an instance is shown in the TUIs, console and reports: its name, or
+/// `C<id>` without one
+pub fn label(id: usize, name: Option<&str>) -> String {
+    match name {
+        Some(name) => name.to_string(),
+        None => format!("C{}", id),
+    }
+}
+
+/// Prefix of an instance's files in the run directory, e.g. `C0` or
+/// `C0-alpha` (`C0-alpha-verify.log`); the id keeps it unique and sorted
+pub fn file_prefix(id: usize, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("C{}-{}", id, name),
+        None => format!("C{}", id),
+    }
+}
+
+/// Name of an instance's workspace directory, e.g. `c0` or `c0-alpha`
+pub fn dir_name(id: usize, name: Option<&str>) -> String {
+    file_prefix(id, name).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_assign_names() {
+        let titles = ["Event-sourced rewrite with snapshots", "Tiny patch", ""];
+        assert_eq!(InstanceNames::Ids.assign(&titles), vec![None, None, None]);
+        assert_eq!(
+            InstanceNames::from_args(&args(&["titles"])).assign(&titles),
+            vec![
+                Some("event-sourced-rewrite-with".to_string()),
+                Some("tiny-patch".to_string()),
+                None
+            ]
+        );
+        assert_eq!(
+            InstanceNames::from_args(&args(&["Alpha", " bravo", "alpha"])).assign(&titles),
+            vec![
+                Some("alpha".to_string()),
+                Some("bravo".to_string()),
+                Some("alpha-2".to_string())
+            ]
+        );
+        assert_eq!(InstanceNames::from_args(&args(&[" "])), InstanceNames::Ids);
+        assert_eq!(dir_name(2, Some("bravo")), "c2-bravo");
+        assert_eq!(label(3, None), "C3");
+    }
+}
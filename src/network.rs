@@ -0,0 +1,76 @@
+use crate::config::NetworkConfig;
+use anyhow::Context;
+
+/// Proxy variables, in both spellings since tools disagree on which they read
+const PROXY_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"];
+const NO_PROXY_VARS: &[&str] = &["NO_PROXY", "no_proxy"];
+
+/// Variables naming a CA bundle: Claude Code (Node) adds it to its roots;
+/// OpenSSL, Python, curl, git and cargo use it instead of theirs
+const CA_VARS: &[&str] = &[
+    "NODE_EXTRA_CA_CERTS",
+    "SSL_CERT_FILE",
+    "REQUESTS_CA_BUNDLE",
+    "CURL_CA_BUNDLE",
+    "GIT_SSL_CAINFO",
+    "CARGO_HTTP_CAINFO",
+];
+
+/// The environment variables that carry `network` to other processes
+fn vars(network: &NetworkConfig) -> anyhow::Result<Vec<(&'static str, String)>> {
+    let mut vars = Vec::new();
+    if let Some(proxy) = &network.proxy {
+        vars.extend(PROXY_VARS.iter().map(|var| (*var, proxy.clone())));
+    }
+    if let Some(no_proxy) = &network.no_proxy {
+        vars.extend(NO_PROXY_VARS.iter().map(|var| (*var, no_proxy.clone())));
+    }
+    if let Some(ca_cert) = &network.ca_cert {
+        let ca_cert = ca_cert
+            .canonicalize()
+            .with_context(|| format!("Can't read CA certificate {}", ca_cert.display()))?;
+        let ca_cert = ca_cert.to_string_lossy().into_owned();
+        vars.extend(CA_VARS.iter().map(|var| (*var, ca_cert.clone())));
+    }
+    Ok(vars)
+}
+
+/// Put `network` in actually's own environment, so agent sessions, checks,
+/// hooks, git and every other process it starts inherit it. Call before
+/// anything is spawned.
+pub fn apply(network: &NetworkConfig) -> anyhow::Result<()> {
+    for (var, value) in vars(network)? {
+        std::env::set_var(var, value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vars() {
+        let ca = tempfile::NamedTempFile::new().unwrap();
+        let network = NetworkConfig {
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: None,
+            ca_cert: Some(ca.path().to_path_buf()),
+        };
+        let env = vars(&network).unwrap();
+        assert!(env.contains(&("HTTPS_PROXY", "http://proxy.corp:3128".to_string())));
+        assert!(env.contains(&("http_proxy", "http://proxy.corp:3128".to_string())));
+        assert!(!env.iter().any(|(var, _)| *var == "NO_PROXY"));
+        let ca_path = ca.path().canonicalize().unwrap();
+        assert!(env.contains(&(
+            "NODE_EXTRA_CA_CERTS",
+            ca_path.to_string_lossy().into_owned()
+        )));
+
+        let missing = NetworkConfig {
+            ca_cert: Some("/nonexistent/ca.pem".into()),
+            ..Default::default()
+        };
+        assert!(vars(&missing).is_err());
+    }
+}
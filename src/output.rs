@@ -1,41 +1,194 @@
-use crate::conductor::InstanceResult;
+use crate::bench::performance_table;
+use crate::checkpoint::Checkpoint;
+use crate::conductor::{label_of, InstanceResult, Timings};
+use crate::layout::RunLayout;
+use crate::workspace::{self, WorkspaceError};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum OutputError {
     #[error("Failed to create output directory: {0}")]
     CreateDirFailed(#[from] std::io::Error),
+    #[error("Failed to serialize results: {0}")]
+    SerializeFailed(#[from] serde_json::Error),
+    #[error("Run directory {0} does not exist")]
+    MissingRunDir(PathBuf),
+    #[error("{0}")]
+    CopyBackFailed(#[from] WorkspaceError),
+    #[error(
+        "Run directory {0} is in use by another actually (pid {1}); delete {LOCK_FILE} in it if that process is gone"
+    )]
+    Locked(PathBuf, u32),
 }
 
+/// Held in a run directory for as long as a process is running in it, with
+/// that process's id
+pub const LOCK_FILE: &str = ".actually.lock";
+
+/// A run's task, as given
+pub const PROMPT_FILE: &str = "prompt.md";
+/// The strategy prompt of a run, placeholders left for what differs per
+/// instance
+const STRATEGY_PROMPT_FILE: &str = "strategy-prompt.md";
+/// The implementation prompt of a run, likewise
+const IMPLEMENTATION_PROMPT_FILE: &str = "implementation-prompt.md";
+
+/// Where `--tmpfs` puts workspaces
+pub const TMPFS_DIR: &str = "/dev/shm/actually";
+
 /// Manages the output directory for an actually run
-/// Structure:
+/// Structure (`RunLayout::Flat`):
 ///   {base_dir}/actually-{timestamp}/
+///     metadata.json  - The run's layout
 ///     C0-strategy.md - Strategy for instance 0
 ///     C1-strategy.md - Strategy for instance 1
-///     c0/            - Workspace and log for instance 0
-///     c1/            - Workspace and log for instance 1
+///     strategies.md  - Summary of every instance's strategy, status, and cost
+///     results.json   - Machine-readable counterpart of strategies.md
+///     c0.patch       - Instance 0's changes (runs with a baseline)
+///     c0/workspace/  - Workspace and log for instance 0
+///     c1/workspace/  - Workspace and log for instance 1
 ///     ...
+/// `RunLayout::Nested` gives each instance an `instances/{id}/` directory
+/// instead, with its workspace in `workspace/` and its strategy, logs and
+/// patch in `logs/`. With `--workspace-dir`, the workspaces are in
+/// `{workspace dir}/actually-{timestamp}/` instead. While the run goes, the
+/// run directory holds a `.actually.lock`.
 pub struct RunOutput {
     run_dir: PathBuf,
+    workspace_dir: PathBuf,
+    layout: RunLayout,
+    _lock: RunLock,
+}
+
+/// Create `base/name`, or with runs started in the same second (say, by
+/// `actually serve` or a script) having taken it, `base/name-2` and so on,
+/// so that concurrent runs never share a directory
+fn create_unique_dir(base: &Path, name: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(base)?;
+    let mut dir = base.join(name);
+    let mut suffix = 2;
+    loop {
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                dir = base.join(format!("{}-{}", name, suffix));
+                suffix += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A run directory's `LOCK_FILE`, so two processes can't run in (or
+/// `--resume`) the same directory at once; removed again when dropped
+struct RunLock {
+    path: PathBuf,
+}
+
+impl RunLock {
+    fn acquire(run_dir: &Path) -> Result<Self, OutputError> {
+        let path = run_dir.join(LOCK_FILE);
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id())?;
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|pid| pid.trim().parse().ok());
+                    match holder {
+                        Some(pid) if process_alive(pid) => {
+                            return Err(OutputError::Locked(run_dir.to_path_buf(), pid));
+                        }
+                        // Left behind by a process that was killed
+                        _ => match fs::remove_file(&path) {
+                            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                                return Err(e.into())
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Whether process `pid` is still running. Without a way to tell, it is
+/// assumed to be.
+fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // Signal 0 only checks the process exists; EPERM means it does but
+        // isn't ours
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        let signalled = unsafe { libc::kill(pid, 0) } == 0;
+        signalled || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
 }
 
 impl RunOutput {
-    /// Create a new run output directory
-    pub fn create(base_dir: &Path, _interactive: bool) -> Result<Self, OutputError> {
+    /// Create a new run output directory, laid out as `layout`
+    pub fn create(
+        base_dir: &Path,
+        _interactive: bool,
+        layout: RunLayout,
+    ) -> Result<Self, OutputError> {
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        let dir_name = format!("actually-{}", timestamp);
-        let run_dir = base_dir.join(dir_name);
-
-        fs::create_dir_all(&run_dir)?;
+        // Absolute, since sessions, hooks and judges run with their own cwd
+        let run_dir =
+            create_unique_dir(base_dir, &format!("actually-{}", timestamp))?.canonicalize()?;
+        let lock = RunLock::acquire(&run_dir)?;
+        layout.record(&run_dir)?;
+        Ok(Self {
+            workspace_dir: run_dir.clone(),
+            layout,
+            _lock: lock,
+            run_dir,
+        })
+    }
 
-        Ok(Self { run_dir })
+    /// Use the existing run directory of an earlier run (`--resume`), laid
+    /// out the way it was created
+    pub fn open(run_dir: &Path) -> Result<Self, OutputError> {
+        if !run_dir.is_dir() {
+            return Err(OutputError::MissingRunDir(run_dir.to_path_buf()));
+        }
+        let run_dir = &run_dir.canonicalize()?;
+        Ok(Self {
+            run_dir: run_dir.to_path_buf(),
+            workspace_dir: run_dir.to_path_buf(),
+            layout: RunLayout::of(run_dir),
+            _lock: RunLock::acquire(run_dir)?,
+        })
     }
 
     /// Get the run directory path
@@ -43,28 +196,110 @@ impl RunOutput {
         &self.run_dir
     }
 
+    /// How the run directory is laid out
+    pub fn layout(&self) -> RunLayout {
+        self.layout
+    }
+
+    /// Where the instance workspaces go: the run directory, unless
+    /// `set_workspace_dir` moved them
+    pub fn workspace_dir(&self) -> &Path {
+        &self.workspace_dir
+    }
+
+    /// Put the workspaces in a new directory under `base`, named after the
+    /// run directory, e.g. on faster scratch storage (`--workspace-dir`)
+    pub fn create_workspace_dir(&mut self, base: &Path) -> Result<(), OutputError> {
+        let name = self
+            .run_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let dir = create_unique_dir(base, &name)?;
+        self.workspace_dir = dir.canonicalize()?;
+        Ok(())
+    }
+
+    /// Put the workspaces in `dir`, where an earlier run left them
+    /// (`--resume`)
+    pub fn set_workspace_dir(&mut self, dir: PathBuf) -> Result<(), OutputError> {
+        fs::create_dir_all(&dir)?;
+        self.workspace_dir = dir.canonicalize()?;
+        Ok(())
+    }
+
+    /// Move the workspaces of `results` from the workspace directory into
+    /// the run directory (`--copy-back-workspaces`), and point the results
+    /// and the checkpoint at their new places
+    pub fn copy_back_workspaces(
+        &mut self,
+        results: &mut [InstanceResult],
+    ) -> Result<(), OutputError> {
+        if self.workspace_dir == self.run_dir {
+            return Ok(());
+        }
+        for result in results.iter_mut() {
+            let src = PathBuf::from(&result.workspace_path);
+            let Ok(relative) = src.strip_prefix(&self.workspace_dir) else {
+                continue;
+            };
+            let dest = self.run_dir.join(relative);
+            workspace::copy_back(&src, &dest)?;
+            result.workspace_path = dest.to_string_lossy().into_owned();
+        }
+
+        if let Ok(mut checkpoint) = Checkpoint::load(&self.run_dir) {
+            for finished in &mut checkpoint.finished {
+                if let Some(result) = results
+                    .iter()
+                    .find(|r| r.instance_id == finished.result.instance_id)
+                {
+                    finished.result.workspace_path = result.workspace_path.clone();
+                }
+            }
+            checkpoint.workspace_dir = None;
+            if let Err(e) = checkpoint.save(&self.run_dir) {
+                tracing::warn!(error = %e, "Failed to update the checkpoint's workspaces");
+            }
+        }
+        // Whatever is left, such as directories of instances that never ran
+        let _ = fs::remove_dir_all(&self.workspace_dir);
+        self.workspace_dir = self.run_dir.clone();
+        Ok(())
+    }
+
+    /// Create the per-instance directories up front so that a partial run
+    /// still has one directory per instance
+    pub fn create_instance_dirs(&self, count: usize) -> Result<(), OutputError> {
+        for instance_id in 0..count {
+            fs::create_dir_all(self.instance_dir(instance_id, None))?;
+        }
+        Ok(())
+    }
+
     /// Get the workspace path for a specific instance
-    pub fn instance_dir(&self, instance_id: usize) -> PathBuf {
-        self.run_dir.join(format!("c{}", instance_id))
+    pub fn instance_dir(&self, instance_id: usize, name: Option<&str>) -> PathBuf {
+        self.layout
+            .workspace(&self.workspace_dir, instance_id, name)
     }
 
-    /// Write a single agent's session log (inside the instance directory)
+    /// Write a single agent's session log into `dir` (its workspace)
     pub fn write_agent_log(
         &self,
-        instance_id: usize,
+        dir: &Path,
+        label: &str,
         strategy: &str,
         transcript: &str,
         success: bool,
         error: Option<&str>,
     ) -> Result<(), OutputError> {
-        let instance_dir = self.instance_dir(instance_id);
         // Ensure instance dir exists (should already from workspace creation)
-        fs::create_dir_all(&instance_dir)?;
+        fs::create_dir_all(dir)?;
 
-        let log_path = instance_dir.join("session.log");
-        let mut file = fs::File::create(&log_path)?;
+        let log_path = dir.join("session.log");
+        let mut file = Vec::new();
 
-        writeln!(file, "ACTUALLY AGENT C{}", instance_id)?;
+        writeln!(file, "ACTUALLY AGENT {}", label)?;
         writeln!(file, "========================")?;
         writeln!(file)?;
         writeln!(
@@ -83,15 +318,27 @@ impl RunOutput {
         writeln!(file, "-------------------")?;
         writeln!(file, "{}", transcript)?;
 
+        write_atomic(&log_path, &file)?;
         Ok(())
     }
 
     /// Write all outputs from a completed run
-    pub fn write_results(&self, results: &[InstanceResult]) -> Result<(), OutputError> {
+    pub fn write_results(
+        &self,
+        results: &[InstanceResult],
+        timings: &Timings,
+    ) -> Result<(), OutputError> {
         // Write individual agent logs
         for result in results {
+            // Instances that never got a workspace still get a c{id}/workspace/ log
+            let dir = if result.workspace_path.is_empty() {
+                self.instance_dir(result.instance_id, result.name.as_deref())
+            } else {
+                PathBuf::from(&result.workspace_path)
+            };
             self.write_agent_log(
-                result.instance_id,
+                &dir,
+                &result.label(),
                 &result.strategy,
                 &result.transcript,
                 result.success,
@@ -99,6 +346,283 @@ impl RunOutput {
             )?;
         }
 
+        self.write_strategies(results)?;
+        self.write_results_json(results, timings)?;
+
         Ok(())
     }
+
+    /// Write the task (prompt.md) and the strategy and implementation
+    /// prompts as this run resolves them, so the run still shows what it was
+    /// asked however long after
+    pub fn write_prompts(
+        &self,
+        prompt: &str,
+        strategy_template: &str,
+        implementation_template: &str,
+    ) -> Result<(), OutputError> {
+        let files = [
+            (PROMPT_FILE, prompt),
+            (STRATEGY_PROMPT_FILE, strategy_template),
+            (IMPLEMENTATION_PROMPT_FILE, implementation_template),
+        ];
+        for (file, contents) in files {
+            write_atomic(&self.run_dir.join(file), contents.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the human-readable run summary (strategies.md)
+    pub fn write_strategies(&self, results: &[InstanceResult]) -> Result<(), OutputError> {
+        let mut file = Vec::new();
+
+        writeln!(file, "# Strategies")?;
+        writeln!(file)?;
+        writeln!(
+            file,
+            "| Instance | Status | Rank | Cost | Duration | Turns | Changes | Coverage | Workspace |"
+        )?;
+        writeln!(file, "|---|---|---|---|---|---|---|---|---|")?;
+        for result in results {
+            writeln!(
+                file,
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+                result.label(),
+                if result.success { "OK" } else { "FAILED" },
+                result
+                    .judge_rank
+                    .map(|r| format!("#{}", r))
+                    .unwrap_or_else(|| "-".to_string()),
+                result
+                    .cost_usd
+                    .map(|c| format!("${:.4}", c))
+                    .unwrap_or_else(|| "-".to_string()),
+                result
+                    .duration_ms
+                    .map(format_duration_ms)
+                    .unwrap_or_else(|| "-".to_string()),
+                result
+                    .num_turns
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                result
+                    .diffstat
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                result
+                    .coverage
+                    .as_ref()
+                    .and_then(|c| c.coverage_pct)
+                    .map(|pct| format!("{:.1}%", pct))
+                    .unwrap_or_else(|| "-".to_string()),
+                if result.workspace_path.is_empty() {
+                    "-"
+                } else {
+                    &result.workspace_path
+                },
+            )?;
+        }
+
+        if let Some(winner) = results.iter().find(|r| r.suggested_winner) {
+            writeln!(file)?;
+            writeln!(
+                file,
+                "Suggested winner: **{}** (composite score {:.2}). This is an automatic \
+                 suggestion from the checks, judging, change size and cost; review it before \
+                 adopting.",
+                winner.label(),
+                winner.composite_score.unwrap_or_default()
+            )?;
+        }
+
+        if let Some(table) = performance_table(results) {
+            writeln!(file)?;
+            writeln!(file, "## Performance")?;
+            writeln!(file)?;
+            write!(file, "{}", table)?;
+        }
+
+        for result in results {
+            writeln!(file)?;
+            match &result.name {
+                // Judge and tournament reports go by id
+                Some(name) => writeln!(file, "## {} (C{})", name, result.instance_id)?,
+                None => writeln!(file, "## C{}", result.instance_id)?,
+            }
+            writeln!(file)?;
+            writeln!(file, "{}", result.strategy)?;
+            if let Some(backend) = &result.backend {
+                writeln!(file)?;
+                writeln!(file, "Backend: {}", backend)?;
+            }
+            if let Some(err) = &result.error {
+                writeln!(file)?;
+                writeln!(file, "Error: {}", err)?;
+            }
+            if !result.protected_violations.is_empty() {
+                writeln!(file)?;
+                writeln!(
+                    file,
+                    "Changed protected paths: {}",
+                    result.protected_violations.join(", ")
+                )?;
+            }
+            if let Some(from) = &result.fallback_from {
+                writeln!(file)?;
+                writeln!(
+                    file,
+                    "Fell back from {} to {}",
+                    from,
+                    result.model.as_deref().unwrap_or("default")
+                )?;
+            }
+            if let Some(patch) = &result.patch {
+                writeln!(file)?;
+                writeln!(file, "Patch: `{}`", patch)?;
+            }
+            if let Some(score) = result.composite_score {
+                writeln!(file)?;
+                writeln!(file, "Composite score: {:.2}", score)?;
+            }
+            if let Some(check) = &result.build {
+                writeln!(file)?;
+                writeln!(file, "Build: {}", check.describe())?;
+            }
+            if let Some(check) = &result.verification {
+                writeln!(file)?;
+                writeln!(file, "Tests: {}", check.describe())?;
+            }
+            if let Some(check) = &result.lint {
+                writeln!(file)?;
+                writeln!(file, "Lint: {}", check.describe())?;
+            }
+            if let Some(check) = &result.coverage {
+                writeln!(file)?;
+                writeln!(file, "Coverage: {}", check.describe())?;
+            }
+            if let Some(summary) = &result.summary {
+                writeln!(file)?;
+                writeln!(file, "### Agent's summary")?;
+                writeln!(file)?;
+                writeln!(file, "{}", summary)?;
+            }
+            match &result.notes {
+                Some(notes) => {
+                    writeln!(file)?;
+                    writeln!(file, "### Notes")?;
+                    writeln!(file)?;
+                    writeln!(file, "{}", notes.trim_end())?;
+                }
+                None if result.success => {
+                    writeln!(file)?;
+                    writeln!(file, "No NOTES.md was written.")?;
+                }
+                None => {}
+            }
+            for critique in &result.critiques {
+                writeln!(file)?;
+                writeln!(
+                    file,
+                    "### Critique by {}",
+                    label_of(results, critique.reviewer)
+                )?;
+                writeln!(file)?;
+                writeln!(file, "{}", critique.text)?;
+            }
+        }
+
+        write_atomic(&self.run_dir.join("strategies.md"), &file)?;
+        Ok(())
+    }
+
+    /// Write the machine-readable run summary (results.json)
+    pub fn write_results_json(
+        &self,
+        results: &[InstanceResult],
+        timings: &Timings,
+    ) -> Result<(), OutputError> {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "instances": results,
+            "timings": timings,
+        }))?;
+        write_atomic(&self.run_dir.join("results.json"), json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Write a file atomically: the contents go to a temp file in the same
+/// directory, which is then renamed over the destination. A crash mid-write
+/// leaves either the old file or no file, never a truncated one.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut temp_file = NamedTempFile::new_in(dir)?;
+    temp_file.write_all(contents)?;
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Where `path` is in the run directory `run_dir`, if it is. Runs from
+/// before run directories were made absolute recorded their paths relative
+/// to where they were started (`./actually-123/c0`), which is found by the
+/// run directory's name.
+pub fn path_in_run_dir(path: &Path, run_dir: &Path) -> Option<PathBuf> {
+    if let Ok(rest) = path.strip_prefix(run_dir) {
+        return Some(rest.to_path_buf());
+    }
+    if path.is_absolute() {
+        return None;
+    }
+    let name = run_dir.file_name()?;
+    let mut components = path.components();
+    components.find(|c| c.as_os_str() == name)?;
+    Some(components.as_path().to_path_buf())
+}
+
+/// The workspace `run_dir`'s results record as `recorded`, looked up in the
+/// run directory if it was recorded relative to where the run was started
+pub fn recorded_workspace(recorded: &str, run_dir: &Path) -> PathBuf {
+    let path = Path::new(recorded);
+    match path_in_run_dir(path, run_dir) {
+        Some(rest) if path.is_relative() => run_dir.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Format a millisecond duration as e.g. "1m 05s" or "42.3s"
+pub(crate) fn format_duration_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_lock() {
+        let base = tempfile::tempdir().unwrap();
+        let first = RunOutput::create(base.path(), false, RunLayout::Flat).unwrap();
+        let second = RunOutput::create(base.path(), false, RunLayout::Flat).unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().is_absolute());
+
+        // A run still going can't be resumed alongside it
+        assert!(matches!(
+            RunOutput::open(first.path()),
+            Err(OutputError::Locked(..))
+        ));
+        let run_dir = first.path().to_path_buf();
+        drop(first);
+        assert!(!run_dir.join(LOCK_FILE).exists());
+        drop(RunOutput::open(&run_dir).unwrap());
+
+        // One left by a process that is gone is taken over
+        fs::write(run_dir.join(LOCK_FILE), u32::MAX.to_string()).unwrap();
+        assert!(RunOutput::open(&run_dir).is_ok());
+    }
 }
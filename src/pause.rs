@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Shared switch that holds back new work while the run is paused. Work
+/// already in flight (a strategy query, a running implementation session)
+/// finishes; the next strategy, queued instance or phase waits.
+#[derive(Debug, Clone)]
+pub struct PauseControl {
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl Default for PauseControl {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(watch::Sender::new(false)),
+        }
+    }
+}
+
+impl PauseControl {
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.send_replace(paused);
+    }
+
+    /// Flip between paused and running, returning the new state
+    pub fn toggle(&self) -> bool {
+        let paused = !self.is_paused();
+        self.set_paused(paused);
+        paused
+    }
+
+    /// Return immediately when running, otherwise once the run is resumed
+    pub async fn wait_until_resumed(&self) {
+        let mut paused = self.paused.subscribe();
+        // The sender lives in `self`, so the channel can't close while we wait
+        let _ = paused.wait_for(|paused| !*paused).await;
+    }
+
+    /// Before starting `next`, wait out a pause, telling the user how to
+    /// resume
+    pub async fn checkpoint(&self, interactive: bool, next: &str) {
+        if !self.is_paused() {
+            return;
+        }
+        if interactive {
+            println!("Paused before {}; press Ctrl+Z again to resume", next);
+        } else {
+            tracing::info!(
+                pid = std::process::id(),
+                "Paused before {}; send SIGTSTP again or SIGCONT to resume",
+                next
+            );
+        }
+        self.wait_until_resumed().await;
+        if interactive {
+            println!("Resumed");
+        } else {
+            tracing::info!("Resumed");
+        }
+    }
+
+    /// Toggle the pause on SIGTSTP (Ctrl+Z) instead of suspending the
+    /// process, and resume on SIGCONT, for as long as the runtime lives
+    #[cfg(unix)]
+    pub fn listen_for_signals(&self) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let (Ok(mut stop), Ok(mut cont)) = (
+            signal(SignalKind::from_raw(libc::SIGTSTP)),
+            signal(SignalKind::from_raw(libc::SIGCONT)),
+        ) else {
+            tracing::warn!("Failed to install SIGTSTP/SIGCONT handlers");
+            return;
+        };
+        let control = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(()) = stop.recv() => {
+                        control.toggle();
+                    }
+                    Some(()) = cont.recv() => control.set_paused(false),
+                    else => break,
+                }
+            }
+        });
+    }
+
+    #[cfg(not(unix))]
+    pub fn listen_for_signals(&self) {}
+}
@@ -0,0 +1,169 @@
+use crate::config::ProtectConfig;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use std::fs::{self, Permissions};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ProtectError {
+    #[error("Invalid [protect] pattern: {0}")]
+    Pattern(#[from] ignore::Error),
+}
+
+/// Paths of seeded workspaces agents mustn't change (`[protect]`), matched
+/// like .gitignore rules against paths relative to the workspace root
+#[derive(Debug, Clone)]
+pub struct Protection {
+    read_only: Gitignore,
+    exclude: Gitignore,
+}
+
+impl Default for Protection {
+    fn default() -> Self {
+        Self {
+            read_only: Gitignore::empty(),
+            exclude: Gitignore::empty(),
+        }
+    }
+}
+
+/// Paths made read-only for a session, with the permissions to give back
+/// once it is dropped, so checks, cleanup and the user can write to them
+/// again however the session ends
+pub struct Lock(Vec<(PathBuf, Permissions)>);
+
+impl Protection {
+    pub fn new(config: &ProtectConfig) -> Result<Self, ProtectError> {
+        let matcher = |patterns: &[String]| -> Result<Gitignore, ProtectError> {
+            let mut builder = GitignoreBuilder::new("");
+            for pattern in patterns {
+                builder.add_line(None, pattern)?;
+            }
+            Ok(builder.build()?)
+        };
+        Ok(Self {
+            read_only: matcher(&config.read_only)?,
+            exclude: matcher(&config.exclude)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read_only.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether seeding leaves `relative` out of the workspace
+    pub fn excludes(&self, relative: &Path, is_dir: bool) -> bool {
+        matches(&self.exclude, relative, is_dir)
+    }
+
+    /// Whether a change to `relative` breaks the protection
+    pub fn protects(&self, relative: &Path, is_dir: bool) -> bool {
+        matches(&self.read_only, relative, is_dir) || self.excludes(relative, is_dir)
+    }
+
+    /// Make the read-only paths in `workspace` (and everything in the
+    /// directories among them) read-only until the lock is released. An
+    /// agent can still undo that, which `violations` catches afterwards.
+    /// Should one fail, those locked already are given back.
+    pub fn lock(&self, workspace: &Path) -> std::io::Result<Lock> {
+        let mut lock = Lock(Vec::new());
+        if self.read_only.is_empty() {
+            return Ok(lock);
+        }
+        let walker = WalkBuilder::new(workspace)
+            .standard_filters(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+        for entry in walker.flatten() {
+            let Ok(relative) = entry.path().strip_prefix(workspace) else {
+                continue;
+            };
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            if relative.as_os_str().is_empty()
+                || entry.path_is_symlink()
+                || !matches(&self.read_only, relative, is_dir)
+            {
+                continue;
+            }
+            let permissions = entry
+                .metadata()
+                .map_err(std::io::Error::other)?
+                .permissions();
+            let mut read_only = permissions.clone();
+            read_only.set_readonly(true);
+            fs::set_permissions(entry.path(), read_only)?;
+            lock.0.push((entry.path().to_path_buf(), permissions));
+        }
+        Ok(lock)
+    }
+
+    /// The paths among `changed` (relative to the workspace, as git lists
+    /// them) that are protected
+    pub fn violations(&self, changed: &[String]) -> Vec<String> {
+        changed
+            .iter()
+            .filter(|path| self.protects(Path::new(path), false))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        for (path, permissions) in self.0.drain(..).rev() {
+            if let Err(e) = fs::set_permissions(&path, permissions) {
+                tracing::warn!(path = ?path, error = %e, "Failed to restore permissions");
+            }
+        }
+    }
+}
+
+fn matches(matcher: &Gitignore, relative: &Path, is_dir: bool) -> bool {
+    !matcher.is_empty()
+        && matcher
+            .matched_path_or_any_parents(relative, is_dir)
+            .is_ignore()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protection() {
+        let protection = Protection::new(&ProtectConfig {
+            read_only: vec!["ci/".to_string(), "/LICENSE".to_string()],
+            exclude: vec!["secrets/".to_string()],
+        })
+        .unwrap();
+        assert!(protection.excludes(Path::new("secrets"), true));
+        assert!(!protection.excludes(Path::new("ci"), true));
+        assert_eq!(
+            protection.violations(&[
+                "ci/deploy.yml".to_string(),
+                "src/main.rs".to_string(),
+                "LICENSE".to_string(),
+                "docs/LICENSE".to_string(),
+                "secrets/key.pem".to_string(),
+            ]),
+            ["ci/deploy.yml", "LICENSE", "secrets/key.pem"]
+        );
+
+        let workspace = tempfile::tempdir().unwrap();
+        fs::create_dir_all(workspace.path().join("ci")).unwrap();
+        fs::write(workspace.path().join("ci/deploy.yml"), "steps: []").unwrap();
+        fs::write(workspace.path().join("main.rs"), "fn main() {}").unwrap();
+        let lock = protection.lock(workspace.path()).unwrap();
+        let read_only = |path: &str| {
+            fs::metadata(workspace.path().join(path))
+                .unwrap()
+                .permissions()
+                .readonly()
+        };
+        assert!(read_only("ci/deploy.yml") && read_only("ci"));
+        assert!(!read_only("main.rs"));
+        drop(lock);
+        assert!(!read_only("ci/deploy.yml") && !read_only("ci"));
+    }
+}
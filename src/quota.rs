@@ -0,0 +1,174 @@
+use crate::cancel::CancellationToken;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often a workspace's size is measured during its session
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// What happens to an instance whose workspace passes
+/// `--max-workspace-size` (`--workspace-size-action`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeAction {
+    /// Stop its session; its work so far stays in the workspace
+    #[default]
+    Abort,
+    /// Log a warning and let it carry on
+    Warn,
+}
+
+/// The most a workspace may take up on disk during implementation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimit {
+    pub max_bytes: u64,
+    pub action: SizeAction,
+}
+
+/// Parse a size such as `500M`, `2G`, `1.5GB` or a plain number of bytes.
+/// Units are binary: `1K` is 1024 bytes.
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size `{}`", size))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "invalid size unit in `{}` (use K, M, G or T)",
+                size
+            ))
+        }
+    };
+    let bytes = number * multiplier as f64;
+    if bytes < 1.0 {
+        return Err(format!("size `{}` must be at least one byte", size));
+    }
+    Ok(bytes as u64)
+}
+
+/// `bytes` for people, e.g. `1.5 GB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["bytes", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Total size of the files under `dir`, not following symlinks
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Measures a workspace periodically while its session runs. Stops
+/// measuring when dropped.
+pub struct SizeWatch {
+    /// The size it was at when it first passed the limit
+    exceeded: Arc<Mutex<Option<u64>>>,
+    task: JoinHandle<()>,
+}
+
+impl SizeWatch {
+    /// Watch `workspace` against `limit`. The returned token is cancelled
+    /// along with `cancel`, and with `SizeAction::Abort` also once the
+    /// workspace is over the limit; give it to the session in place of
+    /// `cancel`.
+    pub fn start(
+        workspace: PathBuf,
+        limit: SizeLimit,
+        label: String,
+        cancel: &CancellationToken,
+    ) -> (Self, CancellationToken) {
+        let session_cancel = cancel.child();
+        let exceeded = Arc::new(Mutex::new(None));
+        let task = tokio::spawn({
+            let cancel = cancel.clone();
+            let session_cancel = session_cancel.clone();
+            let exceeded = Arc::clone(&exceeded);
+            async move {
+                loop {
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+                    }
+                    let dir = workspace.clone();
+                    let Ok(size) = tokio::task::spawn_blocking(move || dir_size(&dir)).await else {
+                        continue;
+                    };
+                    let mut recorded = exceeded.lock().unwrap_or_else(|e| e.into_inner());
+                    if size <= limit.max_bytes || recorded.is_some() {
+                        continue;
+                    }
+                    *recorded = Some(size);
+                    tracing::warn!(
+                        instance = %label,
+                        size = %format_size(size),
+                        limit = %format_size(limit.max_bytes),
+                        "Workspace is over its size limit"
+                    );
+                    if limit.action == SizeAction::Abort {
+                        session_cancel.cancel();
+                    }
+                }
+            }
+        });
+        (Self { exceeded, task }, session_cancel)
+    }
+
+    /// The workspace's size when it passed the limit, if it did
+    pub fn exceeded(&self) -> Option<u64> {
+        *self.exceeded.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for SizeWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert_eq!(parse_size("2GB"), Ok(2 << 30));
+        assert_eq!(parse_size("1.5g"), Ok(3 << 29));
+        assert!(parse_size("2 apples").is_err());
+        assert!(parse_size("0").is_err());
+        assert_eq!(format_size(3 << 29), "1.5 GB");
+        assert_eq!(format_size(12), "12 bytes");
+    }
+}
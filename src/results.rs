@@ -0,0 +1,799 @@
+use crate::conductor::{markdown_to_styled_text, wrap_styled_text, InstanceResult};
+use crate::git;
+use crate::icons::{Status, StatusIcons};
+use crate::layout::RunLayout;
+use crate::names;
+use crate::output;
+use crate::tail::TRANSCRIPT_LOG_SUFFIX;
+use crate::transcript::truncate_middle;
+use anyhow::Context;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ignore::WalkBuilder;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+use serde::Deserialize;
+use std::io::stdout;
+use std::path::Path;
+
+/// Longest transcript shown whole; longer ones lose their middle
+const MAX_TRANSCRIPT_VIEW_BYTES: usize = 512 * 1024;
+
+/// The part of results.json `actually show` needs
+#[derive(Deserialize)]
+struct ResultsFile {
+    instances: Vec<InstanceResult>,
+}
+
+/// What the results TUI is currently showing
+enum View {
+    /// Instance list with a details panel
+    List,
+    /// Two instances' changes side by side, scrolled together
+    Compare {
+        left: usize,
+        right: usize,
+        left_text: Text<'static>,
+        right_text: Text<'static>,
+        scroll: u16,
+    },
+    /// One scrollable text: an instance's changes or transcript, or the
+    /// run's verdicts
+    Text {
+        title: String,
+        text: Text<'static>,
+        scroll: u16,
+    },
+}
+
+/// `actually show`: open the results TUI on the finished run in `run_dir`,
+/// read-only, to look through its strategies, transcripts, diffs and
+/// verdicts after the fact
+pub async fn show(run_dir: &Path, icons: StatusIcons) -> anyhow::Result<()> {
+    let instances = load(run_dir)?;
+    if instances.is_empty() {
+        println!("No instances in {}", run_dir.display());
+        return Ok(());
+    }
+    review(&instances, run_dir, None, icons).await
+}
+
+/// The instances in `run_dir`'s results.json, with workspaces recorded
+/// relative to where the run was started found in the run directory, so
+/// they can be shown from anywhere
+fn load(run_dir: &Path) -> anyhow::Result<Vec<InstanceResult>> {
+    let path = run_dir.join("results.json");
+    let json = std::fs::read_to_string(&path).with_context(|| {
+        format!(
+            "Failed to read {} (a run still going has none yet)",
+            path.display()
+        )
+    })?;
+    let results: ResultsFile = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let run_dir = run_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", run_dir.display()))?;
+    let mut instances = results.instances;
+    for result in instances
+        .iter_mut()
+        .filter(|r| !r.workspace_path.is_empty())
+    {
+        result.workspace_path = output::recorded_workspace(&result.workspace_path, &run_dir)
+            .to_string_lossy()
+            .into_owned();
+    }
+    Ok(instances)
+}
+
+/// Browse the results of the implementation phase: per-instance status,
+/// cost and changes, with a side-by-side comparison of two marked instances,
+/// each instance's transcript and the run's verdicts. For seeded runs,
+/// `source_dir` is the directory the workspaces were seeded from, which an
+/// instance's changes can be committed back to.
+pub async fn review(
+    results: &[InstanceResult],
+    run_dir: &Path,
+    source_dir: Option<&Path>,
+    icons: StatusIcons,
+) -> anyhow::Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let mut list_state = ListState::default();
+    list_state.select(Some(0));
+    let mut marked: Vec<usize> = Vec::new();
+    let mut view = View::List;
+    let mut status_message: Option<String> = None;
+    let mut show_help_popup = false;
+    // Furthest the compare view scrolls: the longer side's last line at the
+    // bottom of its pane, as of the last draw
+    let mut compare_max_scroll = 0;
+
+    loop {
+        let selected_idx = list_state.selected().unwrap_or(0);
+
+        terminal.draw(|frame| {
+            let area = frame.area();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(5),    // Main view
+                    Constraint::Length(1), // Help hint
+                    Constraint::Length(1), // Status
+                ])
+                .split(area);
+
+            match &view {
+                View::List => {
+                    let main_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(chunks[0]);
+
+                    let items: Vec<ListItem> = results
+                        .iter()
+                        .enumerate()
+                        .map(|(i, result)| {
+                            ListItem::new(result_line(result, marked.contains(&i), icons))
+                        })
+                        .collect();
+                    let list = List::new(items)
+                        .block(Block::default().borders(Borders::ALL).title(" Results "))
+                        .highlight_style(
+                            Style::default()
+                                .bg(Color::DarkGray)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                        .highlight_symbol("▶ ");
+                    frame.render_stateful_widget(list, main_chunks[0], &mut list_state);
+
+                    let result = &results[selected_idx];
+                    let wrap_width = main_chunks[1].width.saturating_sub(2) as usize;
+                    let details = wrap_styled_text(result_details(result), wrap_width);
+                    let preview = Paragraph::new(details).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!(" {} ", result.label())),
+                    );
+                    frame.render_widget(preview, main_chunks[1]);
+                }
+                View::Compare {
+                    left,
+                    right,
+                    left_text,
+                    right_text,
+                    scroll,
+                } => {
+                    let panes = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(chunks[0]);
+                    let lines = left_text.lines.len().max(right_text.lines.len());
+                    let height = panes[0].height.saturating_sub(2) as usize;
+                    compare_max_scroll = lines.saturating_sub(height).min(u16::MAX as usize) as u16;
+                    let scroll = (*scroll).min(compare_max_scroll);
+                    for (pane, idx, text) in
+                        [(panes[0], left, left_text), (panes[1], right, right_text)]
+                    {
+                        let paragraph = Paragraph::new(text.clone()).scroll((scroll, 0)).block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .title(format!(" {} ", results[*idx].label())),
+                        );
+                        frame.render_widget(paragraph, pane);
+                    }
+                }
+                View::Text {
+                    title,
+                    text,
+                    scroll,
+                } => {
+                    let paragraph = Paragraph::new(text.clone())
+                        .wrap(Wrap { trim: false })
+                        .scroll((*scroll, 0))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .title(format!(" {} ", title)),
+                        );
+                    frame.render_widget(paragraph, chunks[0]);
+                }
+            }
+
+            let help =
+                Paragraph::new("?: Help & keymaps").style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(help, chunks[1]);
+
+            if let Some(ref msg) = status_message {
+                let status = Paragraph::new(msg.as_str()).style(Style::default().fg(Color::Yellow));
+                frame.render_widget(status, chunks[2]);
+            }
+
+            if show_help_popup {
+                render_help_popup(frame, area);
+            }
+        })?;
+
+        if !event::poll(std::time::Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        status_message = None;
+
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            break;
+        }
+        if show_help_popup {
+            show_help_popup = false;
+            continue;
+        }
+        if key.code == KeyCode::Char('?') {
+            show_help_popup = true;
+            continue;
+        }
+
+        if let View::Compare { scroll, .. } | View::Text { scroll, .. } = &mut view {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => view = View::List,
+                KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::PageDown | KeyCode::Char(' ') => *scroll = scroll.saturating_add(20),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(20),
+                KeyCode::Char('g') | KeyCode::Home => *scroll = 0,
+                _ => {}
+            }
+            if let View::Compare { scroll, .. } = &mut view {
+                *scroll = (*scroll).min(compare_max_scroll);
+            }
+            continue;
+        }
+
+        let n = results.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Up | KeyCode::Char('k') => {
+                list_state.select(Some(if selected_idx == 0 {
+                    n - 1
+                } else {
+                    selected_idx - 1
+                }));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                list_state.select(Some((selected_idx + 1) % n));
+            }
+            KeyCode::Char('m') | KeyCode::Char(' ') => {
+                if let Some(pos) = marked.iter().position(|&i| i == selected_idx) {
+                    marked.remove(pos);
+                } else {
+                    if marked.len() == 2 {
+                        marked.remove(0);
+                    }
+                    marked.push(selected_idx);
+                }
+                status_message = Some(match marked.len() {
+                    2 => "Press Enter to compare the marked instances".to_string(),
+                    _ => "Mark one more instance to compare".to_string(),
+                });
+            }
+            KeyCode::Char('b') => {
+                let Some(source_dir) = source_dir else {
+                    status_message = Some(
+                        "Only seeded runs (--seed-cwd) can be merged back, at the end of the run"
+                            .to_string(),
+                    );
+                    continue;
+                };
+                let result = &results[selected_idx];
+                let branch = branch_name(run_dir, result);
+                let message = format!(
+                    "actually: {} implementation\n\n{}",
+                    result.label(),
+                    result.strategy
+                );
+                let workspace = Path::new(&result.workspace_path);
+                status_message = Some(
+                    match git::branch_from_workspace(source_dir, workspace, &branch, &message).await
+                    {
+                        Ok(true) => {
+                            format!("Committed {} to branch {}", result.label(), branch)
+                        }
+                        Ok(false) => format!("{} has no changes to commit", result.label()),
+                        Err(e) => format!("Failed to create branch {}: {}", branch, e),
+                    },
+                );
+            }
+            KeyCode::Enter => {
+                if marked.len() != 2 {
+                    status_message = Some("Mark two instances (m) to compare them".to_string());
+                    continue;
+                }
+                let (left, right) = (marked[0], marked[1]);
+                view = View::Compare {
+                    left,
+                    right,
+                    left_text: changes_text(run_dir, &results[left]).await,
+                    right_text: changes_text(run_dir, &results[right]).await,
+                    scroll: 0,
+                };
+            }
+            KeyCode::Char('d') => {
+                let result = &results[selected_idx];
+                view = View::Text {
+                    title: format!("{} changes", result.label()),
+                    text: changes_text(run_dir, result).await,
+                    scroll: 0,
+                };
+            }
+            KeyCode::Char('t') => {
+                let result = &results[selected_idx];
+                match transcript_text(run_dir, result) {
+                    Some(text) => {
+                        view = View::Text {
+                            title: format!("{} transcript", result.label()),
+                            text,
+                            scroll: 0,
+                        }
+                    }
+                    None => status_message = Some(format!("{} has no transcript", result.label())),
+                }
+            }
+            KeyCode::Char('v') => match verdicts_text(run_dir) {
+                Some(text) => {
+                    view = View::Text {
+                        title: "Verdicts".to_string(),
+                        text,
+                        scroll: 0,
+                    }
+                }
+                None => {
+                    status_message =
+                        Some("No verdicts: the run had no --judge or --tournament".to_string())
+                }
+            },
+            _ => {}
+        }
+    }
+
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}
+
+/// One row of the results list
+fn result_line(result: &InstanceResult, marked: bool, icons: StatusIcons) -> Line<'static> {
+    let mut spans = vec![
+        Span::raw(if marked { "[x] " } else { "[ ] " }),
+        Span::styled(
+            format!("{} ", result.label()),
+            Style::default().fg(Color::Cyan),
+        ),
+    ];
+    spans.push(if result.success {
+        Span::styled(icons.tag(Status::Ok), Style::default().fg(Color::Green))
+    } else {
+        Span::styled(icons.tag(Status::Failed), Style::default().fg(Color::Red))
+    });
+    if result.suggested_winner {
+        spans.push(Span::styled(
+            format!(" {}", icons.tag(Status::Suggested)),
+            Style::default().fg(Color::Green),
+        ));
+    }
+    if result.build.as_ref().is_some_and(|b| !b.passed) {
+        spans.push(Span::styled(
+            format!(" {}", icons.tag(Status::NoBuild)),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    if let Some(rank) = result.judge_rank {
+        spans.push(Span::styled(
+            format!(" #{}", rank),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if let Some(cost) = result.cost_usd {
+        spans.push(Span::raw(format!(" ${:.2}", cost)));
+    }
+    if let Some(diffstat) = result.diffstat {
+        spans.push(Span::styled(
+            format!(" {}", diffstat),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Details panel for the selected instance
+fn result_details(result: &InstanceResult) -> Text<'static> {
+    let label =
+        |s: &str| Span::styled(s.to_string(), Style::default().add_modifier(Modifier::BOLD));
+    let mut lines = vec![Line::from(vec![
+        label("Status: "),
+        if result.success {
+            Span::styled("SUCCESS", Style::default().fg(Color::Green))
+        } else {
+            Span::styled("FAILED", Style::default().fg(Color::Red))
+        },
+    ])];
+    if let Some(err) = &result.error {
+        lines.push(Line::from(vec![label("Error: "), Span::raw(err.clone())]));
+    }
+    if let Some(backend) = &result.backend {
+        lines.push(Line::from(vec![
+            label("Backend: "),
+            Span::raw(backend.clone()),
+        ]));
+    }
+    if !result.protected_violations.is_empty() {
+        lines.push(Line::from(vec![
+            label("Protected paths changed: "),
+            Span::styled(
+                result.protected_violations.join(", "),
+                Style::default().fg(Color::Red),
+            ),
+        ]));
+    }
+    if let Some(from) = &result.fallback_from {
+        lines.push(Line::from(vec![
+            label("Model: "),
+            Span::raw(format!(
+                "{} (fell back from {})",
+                result.model.as_deref().unwrap_or("default"),
+                from
+            )),
+        ]));
+    }
+    if let Some(rank) = result.judge_rank {
+        lines.push(Line::from(vec![
+            label("Judge rank: "),
+            Span::raw(format!("#{}", rank)),
+        ]));
+    }
+    if let Some(score) = result.composite_score {
+        lines.push(Line::from(vec![
+            label("Composite score: "),
+            Span::raw(format!("{:.2}", score)),
+            if result.suggested_winner {
+                Span::styled(
+                    " (suggested winner: automatic, check before adopting)",
+                    Style::default().fg(Color::Green),
+                )
+            } else {
+                Span::raw("")
+            },
+        ]));
+    }
+    if let Some(cost) = result.cost_usd {
+        lines.push(Line::from(vec![
+            label("Cost: "),
+            Span::raw(format!("${:.4}", cost)),
+        ]));
+    }
+    if let Some(ms) = result.duration_ms {
+        lines.push(Line::from(vec![
+            label("Duration: "),
+            Span::raw(format!("{}s", ms / 1000)),
+        ]));
+    }
+    if let Some(turns) = result.num_turns {
+        lines.push(Line::from(vec![
+            label("Turns: "),
+            Span::raw(turns.to_string()),
+        ]));
+    }
+    if let Some(diffstat) = result.diffstat {
+        lines.push(Line::from(vec![
+            label("Changes: "),
+            Span::raw(diffstat.to_string()),
+        ]));
+    }
+    if !result.workspace_path.is_empty() {
+        lines.push(Line::from(vec![
+            label("Workspace: "),
+            Span::raw(result.workspace_path.clone()),
+        ]));
+    }
+    for (name, check) in [
+        ("Build: ", &result.build),
+        ("Tests: ", &result.verification),
+        ("Lint: ", &result.lint),
+        ("Coverage: ", &result.coverage),
+    ] {
+        if let Some(check) = check {
+            lines.push(Line::from(vec![
+                label(name),
+                Span::styled(
+                    check.describe(),
+                    Style::default().fg(if check.passed {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    }),
+                ),
+            ]));
+        }
+    }
+    if let Some(bench) = &result.bench {
+        lines.push(Line::from(vec![
+            label("Benchmark: "),
+            Span::raw(
+                bench
+                    .value
+                    .map_or_else(|| "no result".to_string(), |v| v.to_string()),
+            ),
+        ]));
+    }
+    if result.success && result.notes.is_none() {
+        lines.push(Line::from(vec![
+            label("Notes: "),
+            Span::styled("no NOTES.md", Style::default().fg(Color::Yellow)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.extend(markdown_to_styled_text(&result.strategy).lines);
+    if let Some(summary) = &result.summary {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Agent's summary",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(markdown_to_styled_text(summary).lines);
+    }
+    if let Some(notes) = &result.notes {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Notes (NOTES.md)",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(markdown_to_styled_text(notes).lines);
+    }
+    for critique in &result.critiques {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("Critique by C{}", critique.reviewer),
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(markdown_to_styled_text(&critique.text).lines);
+    }
+    Text::from(lines)
+}
+
+/// What an instance produced: its exported patch, or its diff against the
+/// workspace baseline when there is one, otherwise a listing of the files
+/// in its workspace
+async fn changes_text(run_dir: &Path, result: &InstanceResult) -> Text<'static> {
+    if let Some(patch) = &result.patch {
+        if let Ok(diff) = std::fs::read_to_string(run_dir.join(patch)) {
+            return Text::from(diff.lines().map(diff_line).collect::<Vec<_>>());
+        }
+    }
+    if result.workspace_path.is_empty() {
+        return Text::from("No workspace");
+    }
+    let workspace = Path::new(&result.workspace_path);
+    if !workspace.is_dir() {
+        return Text::from(format!("Workspace {} is missing", workspace.display()));
+    }
+
+    if let Ok(diff) = git::diff(workspace).await {
+        if diff.trim().is_empty() {
+            return Text::from("No changes");
+        }
+        return Text::from(diff.lines().map(diff_line).collect::<Vec<_>>());
+    }
+
+    let mut lines = vec![Line::from(Span::styled(
+        "No baseline to diff against; workspace files:",
+        Style::default().fg(Color::DarkGray),
+    ))];
+    let walker = WalkBuilder::new(workspace)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            let relative = entry.path().strip_prefix(workspace).unwrap_or(entry.path());
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            lines.push(Line::from(format!(
+                "{}  ({} bytes)",
+                relative.display(),
+                size
+            )));
+        }
+    }
+    Text::from(lines)
+}
+
+/// The instance's session transcript: the one streamed into the run
+/// directory, or its session.log for runs from before transcripts were
+/// streamed. Very long ones lose their middle.
+fn transcript_text(run_dir: &Path, result: &InstanceResult) -> Option<Text<'static>> {
+    let prefix = names::file_prefix(result.instance_id, result.name.as_deref());
+    let streamed = RunLayout::of(run_dir).artifact(
+        run_dir,
+        result.instance_id,
+        &format!("{}-{}", prefix, TRANSCRIPT_LOG_SUFFIX),
+    );
+    let bytes = std::fs::read(streamed)
+        .or_else(|_| std::fs::read(Path::new(&result.workspace_path).join("session.log")))
+        .ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    let text = truncate_middle(&text, Some(MAX_TRANSCRIPT_VIEW_BYTES));
+    Some(Text::from(
+        text.lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// The judge's ranking (judge.md) and the tournament bracket
+/// (tournament.md), whichever the run has
+fn verdicts_text(run_dir: &Path) -> Option<Text<'static>> {
+    let mut lines = Vec::new();
+    for file in ["judge.md", "tournament.md"] {
+        let Ok(markdown) = std::fs::read_to_string(run_dir.join(file)) else {
+            continue;
+        };
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.extend(markdown_to_styled_text(&markdown).lines);
+    }
+    (!lines.is_empty()).then(|| Text::from(lines))
+}
+
+/// Color a unified diff line
+fn diff_line(line: &str) -> Line<'static> {
+    let style = if line.starts_with("+++") || line.starts_with("---") {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else if line.starts_with('+') {
+        Style::default().fg(Color::Green)
+    } else if line.starts_with('-') {
+        Style::default().fg(Color::Red)
+    } else if line.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else if line.starts_with("diff --git") {
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    Line::from(Span::styled(line.to_string(), style))
+}
+
+/// Branch an instance's changes are committed to in the source repository,
+/// named after the run so instances of different runs don't collide:
+/// `actually/actually-1700000000-c2`
+fn branch_name(run_dir: &Path, result: &InstanceResult) -> String {
+    let run = std::path::absolute(run_dir)
+        .ok()
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "run".to_string());
+    format!(
+        "actually/{}-{}",
+        run,
+        names::dir_name(result.instance_id, result.name.as_deref())
+    )
+}
+
+fn render_help_popup(frame: &mut Frame, area: Rect) {
+    let help_text = vec![
+        Line::from(vec![
+            Span::styled("↑/↓ or k/j", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("  Navigate / scroll"),
+        ]),
+        Line::from(vec![
+            Span::styled("m or Space", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("  Mark for comparison"),
+        ]),
+        Line::from(vec![
+            Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("       Compare marked pair"),
+        ]),
+        Line::from(vec![
+            Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("           Show changes"),
+        ]),
+        Line::from(vec![
+            Span::styled("t", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("           Show transcript"),
+        ]),
+        Line::from(vec![
+            Span::styled("v", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("           Show judge/tournament verdicts"),
+        ]),
+        Line::from(vec![
+            Span::styled("b", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("           Commit to a branch in source"),
+        ]),
+        Line::from(vec![
+            Span::styled("PgUp/PgDn", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("   Scroll comparison or text"),
+        ]),
+        Line::from(vec![
+            Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("         Back to list"),
+        ]),
+        Line::from(vec![
+            Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("           Quit"),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press any key to close",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let popup_width = 42;
+    let popup_height = help_text.len() as u16 + 2;
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 2,
+        width: popup_width.min(area.width),
+        height: popup_height.min(area.height),
+    };
+
+    frame.render_widget(Clear, popup_area);
+    let popup =
+        Paragraph::new(help_text).block(Block::default().borders(Borders::ALL).title(" Keymaps "));
+    frame.render_widget(popup, popup_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load() {
+        let parent = tempfile::tempdir().unwrap();
+        let run_dir = parent.path().join("actually-123");
+        std::fs::create_dir_all(run_dir.join("c0")).unwrap();
+        std::fs::write(run_dir.join("c0/main.rs"), "fn main() {}").unwrap();
+        let instances: Vec<InstanceResult> = (0..2)
+            .map(|id| InstanceResult {
+                instance_id: id,
+                workspace_path: format!("./actually-123/c{}", id),
+                ..Default::default()
+            })
+            .collect();
+        let json = serde_json::json!({ "instances": instances });
+        std::fs::write(run_dir.join("results.json"), json.to_string()).unwrap();
+
+        // Shown from somewhere other than where the run was started
+        let instances = load(&run_dir).unwrap();
+        let run_dir = run_dir.canonicalize().unwrap();
+        let workspace = run_dir.join("c0");
+        assert_eq!(instances[0].workspace_path, workspace.to_string_lossy());
+        let changes = changes_text(&run_dir, &instances[0]).await;
+        assert!(changes.to_string().contains("main.rs"));
+        let changes = changes_text(&run_dir, &instances[1]).await;
+        assert!(changes.to_string().contains("is missing"));
+    }
+}
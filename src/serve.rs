@@ -0,0 +1,463 @@
+use crate::http::{self, Connection};
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+
+/// Where `actually serve` listens by default: loopback only, since whoever
+/// can reach it can start agents that do anything in their workspaces
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7878";
+
+/// Events kept for an SSE client that falls behind before it misses some
+const EVENT_BACKLOG: usize = 1024;
+
+/// Options `POST /runs` may pass on that take a value. Whatever runs a
+/// command, or reads or writes a path of the client's choosing, is left out.
+const VALUE_OPTIONS: &[&str] = &[
+    "-n",
+    "--num",
+    "--candidates",
+    "--diversify-on",
+    "--variation",
+    "--language",
+    "--framework",
+    "--layout",
+    "--keep-workspaces",
+    "--transcript-detail",
+    "--max-cost-per-instance",
+    "--max-cost",
+    "--deadline",
+    "--max-workspace-size",
+    "--workspace-size-action",
+    "--max-transcript-bytes",
+    "--fallback-model",
+    "--backends",
+    "--names",
+    "--strategy-depth",
+    "--exclusion-strength",
+    "--failed-strategies",
+    "--impl-instructions",
+    "-m",
+    "--model",
+    "--impl-model",
+    "--strategy-model",
+    "--profile",
+    "--debate-rounds",
+];
+
+/// Switches `POST /runs` may pass on. `--build`, `--lint` and `--coverage`
+/// only without a command, so they run the detected one.
+const SWITCHES: &[&str] = &[
+    "-v",
+    "--verbose",
+    "--confirm-strategies",
+    "--no-exclusions",
+    "--dry-run",
+    "--share-build-cache",
+    "--seed-cwd",
+    "--allow-dirty",
+    "--no-verify",
+    "--build",
+    "--lint",
+    "--coverage",
+    "--cross-review",
+    "--judge",
+    "--synthesize",
+    "--tournament",
+    "--vote",
+];
+
+/// Body of `POST /runs`
+#[derive(Deserialize)]
+struct StartRequest {
+    prompt: String,
+    /// Further command-line options for the run, e.g. `["-n", "3", "--judge"]`
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// Check that `args` only holds options from `VALUE_OPTIONS` (followed by
+/// their value, or as `--option=value`) and `SWITCHES`
+fn check_args(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, _)) if name.starts_with("--") => (name, true),
+            _ => (arg.as_str(), false),
+        };
+        if VALUE_OPTIONS.contains(&name) {
+            if !inline_value && args.next().is_none() {
+                return Err(format!("{} needs a value", name));
+            }
+        } else if !SWITCHES.contains(&name) || inline_value {
+            return Err(format!("`{}` isn't allowed through actually serve", arg));
+        }
+    }
+    Ok(())
+}
+
+/// A run started through the server: a headless `actually --events`
+/// process whose event stream is kept and passed on
+struct ServedRun {
+    prompt: String,
+    run_dir: Option<String>,
+    /// Exit code once the process has ended (-1 if a signal ended it)
+    exit_code: Option<i32>,
+    /// Latest known state of each instance, from its events
+    instances: BTreeMap<usize, Value>,
+    /// The `run_complete` event, once the run has sent it
+    summary: Option<Value>,
+    /// Every event so far, for SSE clients that connect late
+    events: Vec<String>,
+    /// Live events for SSE clients; dropped when the process ends
+    live: Option<broadcast::Sender<String>>,
+}
+
+impl ServedRun {
+    /// Note what `line` of the event stream says about the run
+    fn record(&mut self, line: &str) {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        let instance = event["instance"].as_u64().map(|id| id as usize);
+        match (event["event"].as_str(), instance) {
+            (Some("run_started"), _) => {
+                self.run_dir = event["run_dir"].as_str().map(str::to_string);
+            }
+            (Some("strategy_generated"), Some(id)) => {
+                self.instances.insert(
+                    id,
+                    json!({ "instance": id, "title": event["title"], "state": "planned" }),
+                );
+            }
+            (Some("instance_started"), Some(id)) => {
+                let status = self.instances.entry(id).or_insert_with(|| json!({}));
+                status["instance"] = json!(id);
+                status["name"] = event["name"].clone();
+                status["workspace"] = event["workspace"].clone();
+                status["state"] = json!("running");
+            }
+            (Some("instance_finished"), Some(id)) => {
+                let status = self.instances.entry(id).or_insert_with(|| json!({}));
+                for key in [
+                    "instance",
+                    "name",
+                    "success",
+                    "error",
+                    "cost_usd",
+                    "duration_ms",
+                ] {
+                    status[key] = event[key].clone();
+                }
+                let succeeded = event["success"].as_bool().unwrap_or(false);
+                status["state"] = json!(if succeeded { "succeeded" } else { "failed" });
+            }
+            (Some("run_complete"), _) => self.summary = Some(event),
+            _ => {}
+        }
+        self.events.push(line.to_string());
+        if let Some(live) = &self.live {
+            let _ = live.send(line.to_string());
+        }
+    }
+
+    fn status(&self, id: usize) -> Value {
+        let state = match self.exit_code {
+            None => "running",
+            Some(0) => "finished",
+            Some(_) => "failed",
+        };
+        json!({
+            "id": id,
+            "prompt": self.prompt,
+            "state": state,
+            "exit_code": self.exit_code,
+            "run_dir": self.run_dir,
+            "instances": self.instances.values().collect::<Vec<_>>(),
+            "summary": self.summary,
+        })
+    }
+}
+
+type Runs = Arc<Mutex<Vec<ServedRun>>>;
+
+/// What a request asks for
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    ListRuns,
+    StartRun,
+    Status(usize),
+    Events(usize),
+    Results(usize),
+}
+
+impl Route {
+    fn parse(method: &str, path: &str) -> Option<Self> {
+        let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, parts.as_slice()) {
+            ("GET", ["runs"]) => Some(Self::ListRuns),
+            ("POST", ["runs"]) => Some(Self::StartRun),
+            ("GET", ["runs", id]) => id.parse().ok().map(Self::Status),
+            ("GET", ["runs", id, "events"]) => id.parse().ok().map(Self::Events),
+            ("GET", ["runs", id, "results"]) => id.parse().ok().map(Self::Results),
+            _ => None,
+        }
+    }
+}
+
+/// `actually serve`: an HTTP API for starting runs, polling their status,
+/// following their events (server-sent events) and fetching their results,
+/// for editor plugins and web frontends
+pub async fn run(listen: &str) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", listen))?;
+    println!(
+        "actually serve listening on http://{}",
+        listener.local_addr()?
+    );
+    let local = listener.local_addr()?;
+    if http::is_exposed(local) {
+        eprintln!(
+            "Warning: {} can be reached from other machines, and the API has no \
+             authentication: anyone who can reach it can start runs",
+            local
+        );
+    }
+    let runs: Runs = Arc::default();
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let runs = Arc::clone(&runs);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, local, runs).await {
+                eprintln!("Request failed: {:#}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, local: SocketAddr, runs: Runs) -> anyhow::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let Some(request) = http::read_request(&mut stream).await? else {
+        return Ok(());
+    };
+    if !request.is_local(local) {
+        return http::respond_forbidden(&mut stream).await;
+    }
+    let Some(route) = Route::parse(&request.method, &request.path) else {
+        return http::respond_error(&mut stream, "404 Not Found", "no such endpoint").await;
+    };
+    let id = match route {
+        Route::Status(id) | Route::Events(id) | Route::Results(id) => Some(id),
+        Route::ListRuns | Route::StartRun => None,
+    };
+    if id.is_some_and(|id| id >= runs.lock().unwrap_or_else(|e| e.into_inner()).len()) {
+        return http::respond_error(&mut stream, "404 Not Found", "no such run").await;
+    }
+
+    match route {
+        Route::ListRuns => {
+            let list: Vec<Value> = runs
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .enumerate()
+                .map(|(id, run)| run.status(id))
+                .collect();
+            http::respond_json(&mut stream, "200 OK", &Value::Array(list)).await
+        }
+        Route::StartRun if !request.is_json() => {
+            let error = "the body must be application/json";
+            http::respond_error(&mut stream, "415 Unsupported Media Type", error).await
+        }
+        Route::StartRun => match serde_json::from_slice::<StartRequest>(&request.body)
+            .map_err(|e| format!("invalid request: {}", e))
+            .and_then(|request| check_args(&request.args).map(|()| request))
+        {
+            Ok(request) => match start_run(request, &runs) {
+                Ok(id) => {
+                    http::respond_json(&mut stream, "201 Created", &json!({ "id": id })).await
+                }
+                Err(e) => {
+                    let error = format!("{:#}", e);
+                    http::respond_error(&mut stream, "500 Internal Server Error", &error).await
+                }
+            },
+            Err(error) => http::respond_error(&mut stream, "400 Bad Request", &error).await,
+        },
+        Route::Status(id) => {
+            let status = runs.lock().unwrap_or_else(|e| e.into_inner())[id].status(id);
+            http::respond_json(&mut stream, "200 OK", &status).await
+        }
+        Route::Events(id) => stream_events(&mut stream, &runs, id).await,
+        Route::Results(id) => {
+            let run_dir = runs.lock().unwrap_or_else(|e| e.into_inner())[id]
+                .run_dir
+                .clone();
+            let results = run_dir
+                .and_then(|dir| std::fs::read_to_string(Path::new(&dir).join("results.json")).ok())
+                .and_then(|json| serde_json::from_str::<Value>(&json).ok());
+            match results {
+                Some(results) => http::respond_json(&mut stream, "200 OK", &results).await,
+                None => {
+                    http::respond_error(&mut stream, "404 Not Found", "the run has no results yet")
+                        .await
+                }
+            }
+        }
+    }
+}
+
+/// Start `actually --headless --events` for `request` and keep its events
+fn start_run(request: StartRequest, runs: &Runs) -> anyhow::Result<usize> {
+    let exe = std::env::current_exe().context("Failed to find the actually executable")?;
+    let mut child = Command::new(exe)
+        .args(["--headless", "--events"])
+        .args(&request.args)
+        .arg("--")
+        .arg(&request.prompt)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("Failed to start the run")?;
+    let stdout = child.stdout.take().context("The run has no stdout")?;
+
+    let (live, _) = broadcast::channel(EVENT_BACKLOG);
+    let id = {
+        let mut runs = runs.lock().unwrap_or_else(|e| e.into_inner());
+        runs.push(ServedRun {
+            prompt: request.prompt,
+            run_dir: None,
+            exit_code: None,
+            instances: BTreeMap::new(),
+            summary: None,
+            events: Vec::new(),
+            live: Some(live),
+        });
+        runs.len() - 1
+    };
+
+    let runs = Arc::clone(runs);
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            runs.lock().unwrap_or_else(|e| e.into_inner())[id].record(&line);
+        }
+        let exit_code = match child.wait().await {
+            Ok(status) => status.code().unwrap_or(-1),
+            Err(_) => -1,
+        };
+        let mut runs = runs.lock().unwrap_or_else(|e| e.into_inner());
+        runs[id].exit_code = Some(exit_code);
+        // Ends every SSE stream following the run
+        runs[id].live = None;
+    });
+    Ok(id)
+}
+
+/// Send run `id`'s events so far, then each new one as it comes, as
+/// server-sent events until the run ends
+async fn stream_events(stream: &mut Connection, runs: &Runs, id: usize) -> anyhow::Result<()> {
+    // Subscribed before the backlog is copied so no event falls in between
+    let (backlog, live) = {
+        let runs = runs.lock().unwrap_or_else(|e| e.into_inner());
+        let run = &runs[id];
+        (
+            run.events.clone(),
+            run.live.as_ref().map(broadcast::Sender::subscribe),
+        )
+    };
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+              Connection: close\r\n\r\n",
+        )
+        .await?;
+    for line in backlog {
+        stream
+            .write_all(format!("data: {}\n\n", line).as_bytes())
+            .await?;
+    }
+    if let Some(mut live) = live {
+        loop {
+            match live.recv().await {
+                Ok(line) => {
+                    stream
+                        .write_all(format!("data: {}\n\n", line).as_bytes())
+                        .await?
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    let note = format!(": {} events dropped for falling behind\n\n", missed);
+                    stream.write_all(note.as_bytes()).await?;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+    stream.write_all(b"event: end\ndata: {}\n\n").await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_parse() {
+        assert_eq!(Route::parse("GET", "/runs"), Some(Route::ListRuns));
+        assert_eq!(Route::parse("POST", "/runs/"), Some(Route::StartRun));
+        assert_eq!(Route::parse("GET", "/runs/3"), Some(Route::Status(3)));
+        assert_eq!(
+            Route::parse("GET", "/runs/0/events"),
+            Some(Route::Events(0))
+        );
+        assert_eq!(
+            Route::parse("GET", "/runs/0/results"),
+            Some(Route::Results(0))
+        );
+        assert_eq!(Route::parse("DELETE", "/runs/0"), None);
+        assert_eq!(Route::parse("GET", "/runs/x"), None);
+    }
+
+    #[test]
+    fn test_check_args() {
+        let args =
+            |args: &[&str]| check_args(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+        assert!(args(&["-n", "3", "--judge", "--max-cost=5", "--build"]).is_ok());
+        assert!(args(&["--verify", "make test"]).is_err());
+        assert!(args(&["--build=rm -rf ~"]).is_err());
+        assert!(args(&["--judge", "stats"]).is_err());
+        assert!(args(&["--", "another prompt"]).is_err());
+        assert!(args(&["-n"]).is_err());
+    }
+
+    #[test]
+    fn test_is_local() {
+        let request = |host: Option<&str>, origin: Option<&str>| http::Request {
+            method: "POST".to_string(),
+            path: "/runs".to_string(),
+            host: host.map(str::to_string),
+            origin: origin.map(str::to_string),
+            content_type: Some("application/json; charset=utf-8".to_string()),
+            body: Vec::new(),
+        };
+        let local: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        assert!(request(Some("127.0.0.1:7878"), None).is_local(local));
+        assert!(request(Some("[::1]:7878"), Some("http://localhost:7878")).is_local(local));
+        assert!(request(Some("localhost:7878"), None).is_json());
+        assert!(!request(Some("evil.example:7878"), None).is_local(local));
+        assert!(!request(Some("localhost:7878"), Some("https://evil.example")).is_local(local));
+        assert!(!request(Some("localhost:8080"), None).is_local(local));
+        assert!(!request(None, None).is_local(local));
+    }
+}
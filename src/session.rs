@@ -1,17 +1,177 @@
-use claude_code_agent_sdk::{query, ClaudeAgentOptions, ClaudeClient, Message, PermissionMode};
+use crate::api_log::ApiLog;
+use crate::cancel::CancellationToken;
+use crate::spend::Spend;
+use crate::transcript::Transcript;
+use claude_code_agent_sdk::{
+    query, query_stream, AssistantMessageError, ClaudeAgentOptions, ClaudeClient, ClaudeError,
+    ContentBlock, Message, PermissionMode, ResultMessage, SettingSource, ToolResultContent,
+};
 use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
 
-#[derive(Error, Debug)]
+/// Attempts for a read-only query that keeps failing with a transient error
+const QUERY_ATTEMPTS: u32 = 3;
+
+/// Wait before retrying a read-only query; doubled for every further retry
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Sessions that fail on their model before moving to `--fallback-model`
+const MODEL_ATTEMPTS: u32 = 2;
+
+/// What an implementation session does after failing on its model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelRetry {
+    /// Keep the failure
+    GiveUp,
+    /// Try once more on the same model
+    Retry,
+    /// Carry on with `--fallback-model`
+    FallBack,
+}
+
+/// Failures talking to Claude Code, grouped by what the user can do about them
+#[derive(Error, Debug, Clone)]
 pub enum SessionError {
+    #[error("Claude Code CLI not found ({0}); install it with `npm install -g @anthropic-ai/claude-code` and make sure `claude` is on your PATH")]
+    CliNotFound(String),
+    #[error(
+        "Not authenticated with Claude ({0}); run `claude` to log in, or set ANTHROPIC_API_KEY"
+    )]
+    Authentication(String),
+    #[error("Rate limited by the Claude API ({0}); wait a while or run fewer instances with -n")]
+    RateLimited(String),
+    #[error("Network error reaching the Claude API ({0}); check your connection")]
+    Network(String),
+    #[error("Model not found ({0}); check the names given to --model, --impl-model and --strategy-model")]
+    ModelNotFound(String),
     #[error("Claude Code SDK error: {0}")]
     SdkError(String),
+    #[error("Backend error: {0}")]
+    Backend(String),
+    #[error("Cancelled")]
+    Cancelled,
 }
 
-impl From<claude_code_agent_sdk::ClaudeError> for SessionError {
-    fn from(e: claude_code_agent_sdk::ClaudeError) -> Self {
-        SessionError::SdkError(e.to_string())
+impl From<ClaudeError> for SessionError {
+    fn from(e: ClaudeError) -> Self {
+        match e {
+            ClaudeError::CliNotFound(e) => SessionError::CliNotFound(e.message),
+            e => SessionError::classify(e.to_string()),
+        }
+    }
+}
+
+impl SessionError {
+    /// Sort an error message from Claude Code or the API by its wording
+    fn classify(message: String) -> Self {
+        let lower = message.to_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+        if mentions(&[
+            "401",
+            "authentication",
+            "api key",
+            "not logged in",
+            "/login",
+        ]) {
+            SessionError::Authentication(message)
+        } else if mentions(&["429", "rate limit", "rate_limit", "overloaded"]) {
+            SessionError::RateLimited(message)
+        } else if lower.contains("model")
+            && mentions(&[
+                "not found",
+                "not_found",
+                "404",
+                "does not exist",
+                "invalid model",
+            ])
+        {
+            SessionError::ModelNotFound(message)
+        } else if mentions(&[
+            "network",
+            "connection refused",
+            "connection reset",
+            "econnrefused",
+            "econnreset",
+            "enotfound",
+            "getaddrinfo",
+            "fetch failed",
+            "timed out",
+        ]) {
+            SessionError::Network(message)
+        } else {
+            SessionError::SdkError(message)
+        }
+    }
+
+    /// An API error reported in an assistant message, with `message` being
+    /// the text Claude Code put in its place
+    fn from_api_error(kind: AssistantMessageError, message: String) -> Self {
+        match kind {
+            AssistantMessageError::AuthenticationFailed => SessionError::Authentication(message),
+            AssistantMessageError::RateLimit => SessionError::RateLimited(message),
+            _ => SessionError::classify(message),
+        }
+    }
+
+    /// Short name for log fields
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SessionError::CliNotFound(_) => "cli_not_found",
+            SessionError::Authentication(_) => "authentication",
+            SessionError::RateLimited(_) => "rate_limited",
+            SessionError::Network(_) => "network",
+            SessionError::ModelNotFound(_) => "model_not_found",
+            SessionError::SdkError(_) => "sdk",
+            SessionError::Backend(_) => "backend",
+            SessionError::Cancelled => "cancelled",
+        }
+    }
+
+    /// Likely to go away if the same request is sent again a little later
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            SessionError::RateLimited(_) | SessionError::Network(_)
+        )
+    }
+
+    /// Caused by the model in use (unknown, rate limited or overloaded), so
+    /// another model may get further
+    pub fn is_model_related(&self) -> bool {
+        matches!(
+            self,
+            SessionError::ModelNotFound(_) | SessionError::RateLimited(_)
+        )
+    }
+
+    /// Bound to fail every other session with the same setup too, so there
+    /// is no point starting more of them
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            SessionError::CliNotFound(_)
+                | SessionError::Authentication(_)
+                | SessionError::ModelNotFound(_)
+        )
+    }
+
+    /// Whether a session that failed with this error on its `attempt`-th
+    /// try (from 1) tries again, given whether it can still fall back to
+    /// `--fallback-model`. Only model-related errors are retried, and only
+    /// when falling back is possible; the model is given `MODEL_ATTEMPTS`.
+    pub fn model_retry(&self, attempt: u32, can_fall_back: bool) -> ModelRetry {
+        if !can_fall_back || !self.is_model_related() {
+            ModelRetry::GiveUp
+        } else if attempt < MODEL_ATTEMPTS {
+            ModelRetry::Retry
+        } else {
+            ModelRetry::FallBack
+        }
     }
 }
 
@@ -22,11 +182,57 @@ pub struct SessionResult {
     pub transcript: String,
     /// Whether the session completed successfully
     pub success: bool,
+    /// Total cost reported by the SDK result message
+    pub cost_usd: Option<f64>,
+    /// Number of conversation turns reported by the SDK result message
+    pub num_turns: Option<u32>,
+    /// The session was stopped early through its cancellation token
+    pub cancelled: bool,
+    /// The session was stopped for spending more than its cost ceiling
+    pub over_budget: bool,
+    /// API error Claude Code gave up on, ending the session early
+    pub error: Option<SessionError>,
+    /// The agent's own summary of what it built, asked for once it finished
+    pub summary: Option<String>,
+}
+
+/// Progress notifications sent while an implementation session streams
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    /// Results for the agent's latest batch of tool calls came back
+    ToolBatchComplete,
+    /// Text just appended to the transcript
+    Output(String),
+}
+
+/// How much of each tool call goes into transcripts and the live dashboard
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptDetail {
+    /// Tool names only
+    #[default]
+    Brief,
+    /// Also tool inputs and results (truncated) and the files tools touched
+    Full,
 }
 
+/// Tool inputs and results longer than this are cut off in `Full` transcripts
+const MAX_TOOL_DETAIL_CHARS: usize = 300;
+
+#[derive(Default)]
 pub struct ClaudeSession {
     cwd: Option<PathBuf>,
     model: Option<String>,
+    env: HashMap<String, String>,
+    inherit_settings: bool,
+    events: Option<UnboundedSender<SessionEvent>>,
+    detail: TranscriptDetail,
+    cancel: Option<CancellationToken>,
+    max_cost_usd: Option<f64>,
+    summary_prompt: Option<String>,
+    api_log: Option<PathBuf>,
+    max_transcript_bytes: Option<usize>,
+    spend: Option<Spend>,
 }
 
 impl ClaudeSession {
@@ -34,6 +240,16 @@ impl ClaudeSession {
         Self {
             cwd: None,
             model: model.map(|s| s.to_string()),
+            env: HashMap::new(),
+            inherit_settings: false,
+            events: None,
+            detail: TranscriptDetail::default(),
+            cancel: None,
+            max_cost_usd: None,
+            summary_prompt: None,
+            api_log: None,
+            max_transcript_bytes: None,
+            spend: None,
         }
     }
 
@@ -41,6 +257,104 @@ impl ClaudeSession {
         Self {
             cwd: Some(cwd.to_path_buf()),
             model: model.map(|s| s.to_string()),
+            env: HashMap::new(),
+            inherit_settings: false,
+            events: None,
+            detail: TranscriptDetail::default(),
+            cancel: None,
+            max_cost_usd: None,
+            summary_prompt: None,
+            api_log: None,
+            max_transcript_bytes: None,
+            spend: None,
+        }
+    }
+
+    /// Extra environment variables for the Claude Code subprocess
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env.extend(env);
+        self
+    }
+
+    /// Load the user, project and local Claude Code settings (including
+    /// CLAUDE.md) like an interactive `claude` session. The SDK loads none
+    /// of them by default.
+    pub fn inherit_settings(mut self, inherit: bool) -> Self {
+        self.inherit_settings = inherit;
+        self
+    }
+
+    /// Send progress events for implementation runs to `events`
+    pub fn with_events(mut self, events: UnboundedSender<SessionEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// How much tool detail to record in transcripts
+    pub fn with_transcript_detail(mut self, detail: TranscriptDetail) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    /// Stop the session early, disconnecting from Claude Code, once `cancel`
+    /// is cancelled
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Have Claude Code end the session once it has cost more than
+    /// `max_cost_usd`
+    pub fn with_max_cost(mut self, max_cost_usd: Option<f64>) -> Self {
+        self.max_cost_usd = max_cost_usd;
+        self
+    }
+
+    /// Once an implementation finishes successfully, send `prompt` in the
+    /// same session and keep the reply as `SessionResult::summary`
+    pub fn with_summary_prompt(mut self, prompt: &str) -> Self {
+        self.summary_prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Cut the middle out of implementation transcripts longer than
+    /// `max_bytes`, keeping their start and latest output
+    pub fn with_max_transcript_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.max_transcript_bytes = max_bytes;
+        self
+    }
+
+    /// Log the implementation session's API traffic (model, token usage and
+    /// latency per response) to `path`
+    pub fn with_api_log(mut self, path: &Path) -> Self {
+        self.api_log = Some(path.to_path_buf());
+        self
+    }
+
+    /// Add what the session's queries cost to `spend` as they report it
+    pub fn with_spend(mut self, spend: Spend) -> Self {
+        self.spend = Some(spend);
+        self
+    }
+
+    fn record_cost(&self, cost_usd: Option<f64>) {
+        if let (Some(spend), Some(cost_usd)) = (&self.spend, cost_usd) {
+            spend.add(cost_usd);
+        }
+    }
+
+    /// Resolve when the session's token is cancelled (never, without one)
+    async fn cancelled(&self) {
+        match &self.cancel {
+            Some(cancel) => cancel.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    fn emit(&self, event: SessionEvent) {
+        if let Some(events) = &self.events {
+            // The receiver going away just means nobody is listening anymore
+            let _ = events.send(event);
         }
     }
 
@@ -49,6 +363,15 @@ impl ClaudeSession {
             permission_mode: Some(permission_mode),
             cwd: self.cwd.clone(),
             model: self.model.clone(),
+            env: self.env.clone(),
+            setting_sources: self.inherit_settings.then(|| {
+                vec![
+                    SettingSource::User,
+                    SettingSource::Project,
+                    SettingSource::Local,
+                ]
+            }),
+            max_budget_usd: self.max_cost_usd,
             ..Default::default()
         }
     }
@@ -57,13 +380,100 @@ impl ClaudeSession {
     /// Returns the full response text
     pub async fn query_strategy(&self, prompt: &str) -> Result<String, SessionError> {
         tracing::debug!(prompt = %prompt, "Querying for strategy");
+        self.query_read_only(prompt).await
+    }
+
+    /// Like `query_strategy`, but calls `on_text` with each chunk of response
+    /// text as it is generated. Returns the same full response text.
+    pub async fn query_strategy_streaming(
+        &self,
+        prompt: &str,
+        mut on_text: impl FnMut(&str),
+    ) -> Result<String, SessionError> {
+        tracing::debug!(prompt = %prompt, "Querying for strategy (streaming)");
+        let mut attempt = 1;
+        loop {
+            // Once text has been shown, a retry would show it all again
+            let mut streamed = false;
+            let result = self
+                .stream_read_only(prompt, &mut |text| {
+                    streamed = true;
+                    on_text(text);
+                })
+                .await;
+            match result {
+                Err(e) if !streamed && self.should_retry(&e, attempt).await => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn stream_read_only(
+        &self,
+        prompt: &str,
+        on_text: &mut impl FnMut(&str),
+    ) -> Result<String, SessionError> {
+        let mut options = self.build_options(PermissionMode::Plan);
+        options.include_partial_messages = true;
+        let mut stream = query_stream(prompt, Some(options)).await?;
 
+        let mut response_text = String::new();
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => message,
+                _ = self.cancelled() => return Err(SessionError::Cancelled),
+            };
+            let Some(message) = message else {
+                break;
+            };
+            let message = message?;
+            if let Some(e) = api_error(&message) {
+                return Err(e);
+            }
+            if let Message::Result(result) = &message {
+                self.record_cost(result.total_cost_usd);
+            }
+            if let Message::StreamEvent(event) = &message {
+                if let Some(text) = text_delta(&event.event) {
+                    on_text(text);
+                }
+            } else if let Some(text) = extract_text_from_message(&message, self.detail) {
+                response_text.push_str(&text);
+                response_text.push('\n');
+            }
+        }
+
+        Ok(response_text)
+    }
+
+    /// Run a one-shot query in plan mode (read-only, no writes or commands)
+    /// Returns the full response text
+    pub async fn query_read_only(&self, prompt: &str) -> Result<String, SessionError> {
+        let mut attempt = 1;
+        loop {
+            match self.query_read_only_once(prompt).await {
+                Err(e) if self.should_retry(&e, attempt).await => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn query_read_only_once(&self, prompt: &str) -> Result<String, SessionError> {
         let options = self.build_options(PermissionMode::Plan);
-        let messages = query(prompt, Some(options)).await?;
+        let messages = tokio::select! {
+            messages = query(prompt, Some(options)) => messages?,
+            _ = self.cancelled() => return Err(SessionError::Cancelled),
+        };
 
         let mut response_text = String::new();
         for message in messages {
-            if let Some(text) = extract_text_from_message(&message) {
+            if let Some(e) = api_error(&message) {
+                return Err(e);
+            }
+            if let Message::Result(result) = &message {
+                self.record_cost(result.total_cost_usd);
+            }
+            if let Some(text) = extract_text_from_message(&message, self.detail) {
                 response_text.push_str(&text);
                 response_text.push('\n');
             }
@@ -72,6 +482,26 @@ impl ClaudeSession {
         Ok(response_text)
     }
 
+    /// After failed attempt number `attempt`, wait before the next one if
+    /// `error` is worth retrying. Returns whether to retry.
+    async fn should_retry(&self, error: &SessionError, attempt: u32) -> bool {
+        if !error.is_transient() || attempt >= QUERY_ATTEMPTS {
+            return false;
+        }
+        let delay = RETRY_DELAY * 2u32.pow(attempt - 1);
+        tracing::warn!(
+            kind = error.kind(),
+            error = %error,
+            attempt,
+            "Query failed, retrying in {}s",
+            delay.as_secs()
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => true,
+            _ = self.cancelled() => false,
+        }
+    }
+
     /// Run full implementation in the given workspace with streaming
     /// Returns the complete session transcript
     pub async fn run_implementation(&self, prompt: &str) -> Result<SessionResult, SessionError> {
@@ -80,77 +510,288 @@ impl ClaudeSession {
         let options = self.build_options(PermissionMode::BypassPermissions);
         let mut client = ClaudeClient::new(options);
 
+        let mut api_log = self.api_log.as_deref().and_then(|path| {
+            ApiLog::open(path)
+                .inspect_err(
+                    |e| tracing::warn!(path = ?path, error = %e, "Failed to create API log"),
+                )
+                .ok()
+        });
+        if let Some(log) = &mut api_log {
+            log.line(&format!(
+                "session: model={} cwd={}",
+                self.model.as_deref().unwrap_or("default"),
+                self.cwd
+                    .as_deref()
+                    .map_or("-".to_string(), |cwd| cwd.display().to_string())
+            ));
+        }
+
         client.connect().await?;
         client.query(prompt).await?;
+        if let Some(log) = &mut api_log {
+            log.request(&format!("prompt ({} chars)", prompt.len()));
+        }
 
-        let mut transcript = String::new();
+        let mut transcript = Transcript::new(self.max_transcript_bytes);
         transcript.push_str(&format!("=== PROMPT ===\n{}\n\n", prompt));
         transcript.push_str("=== SESSION ===\n");
 
+        let mut cost_usd = None;
+        let mut num_turns = None;
+        let mut over_budget = false;
+        let mut error = None;
+
         let mut stream = client.receive_response();
-        while let Some(result) = stream.next().await {
+        loop {
+            let result = tokio::select! {
+                result = stream.next() => result,
+                _ = self.cancelled() => {
+                    tracing::debug!("Session cancelled, disconnecting");
+                    transcript.push_str("\n=== CANCELLED ===\n");
+                    if let Some(log) = &mut api_log {
+                        log.line("cancelled");
+                    }
+                    drop(stream);
+                    client.disconnect().await.ok();
+                    return Ok(SessionResult {
+                        transcript: transcript.into_string(),
+                        success: false,
+                        cost_usd,
+                        num_turns,
+                        cancelled: true,
+                        over_budget: false,
+                        error,
+                        summary: None,
+                    });
+                }
+            };
+            let Some(result) = result else {
+                break;
+            };
             match result {
                 Ok(message) => {
-                    if let Some(text) = extract_text_from_message(&message) {
+                    if let Some(log) = &mut api_log {
+                        log.record(&message);
+                    }
+                    if let Some(text) = extract_text_from_message(&message, self.detail) {
                         transcript.push_str(&text);
                         transcript.push('\n');
+                        self.emit(SessionEvent::Output(text));
+                    }
+                    if let Some(e) = api_error(&message) {
+                        tracing::warn!(kind = e.kind(), error = %e, "API error in session");
+                        transcript.push_str(&format!("\n=== API ERROR ===\n{}\n", e));
+                        error = Some(e);
                     }
                     // Log message type for debugging
                     match &message {
-                        Message::Result(_) => {
+                        Message::Result(result_msg) => {
+                            cost_usd = result_msg.total_cost_usd;
+                            self.record_cost(cost_usd);
+                            num_turns = Some(result_msg.num_turns);
+                            over_budget = exceeded_budget(result_msg, self.max_cost_usd);
+                            if over_budget {
+                                tracing::debug!(cost_usd, "Session stopped over budget");
+                                transcript.push_str("\n=== OVER BUDGET ===\n");
+                            }
                             tracing::debug!("Received result message, session complete");
                             break;
                         }
                         Message::Assistant(_) => {
                             tracing::trace!("Received assistant message");
                         }
+                        Message::User(_) => {
+                            // Tool results are delivered back to the agent as user messages
+                            self.emit(SessionEvent::ToolBatchComplete);
+                        }
                         _ => {}
                     }
                 }
                 Err(e) => {
+                    let e = SessionError::from(e);
                     let error_msg = format!("Stream error: {}", e);
-                    tracing::error!("{}", error_msg);
+                    tracing::error!(kind = e.kind(), "{}", error_msg);
                     transcript.push_str(&format!("\n=== ERROR ===\n{}\n", error_msg));
+                    if let Some(log) = &mut api_log {
+                        log.line(&error_msg);
+                    }
                     drop(stream);
                     client.disconnect().await.ok();
                     return Ok(SessionResult {
-                        transcript,
+                        transcript: transcript.into_string(),
                         success: false,
+                        cost_usd,
+                        num_turns,
+                        cancelled: false,
+                        over_budget: false,
+                        error: Some(e),
+                        summary: None,
                     });
                 }
             }
         }
 
         drop(stream);
+
+        let success = !over_budget && error.is_none();
+        let mut summary = None;
+        if let Some(summary_prompt) = self.summary_prompt.as_deref().filter(|_| success) {
+            if let Some((text, total_cost)) = self
+                .ask_summary(&mut client, summary_prompt, api_log.as_mut())
+                .await
+            {
+                transcript.push_str(&format!("\n=== SUMMARY ===\n{}\n", text));
+                // Claude Code reports the session's running total
+                self.record_cost(total_cost.map(|total| total - cost_usd.unwrap_or_default()));
+                cost_usd = total_cost.or(cost_usd);
+                summary = Some(text);
+            }
+        }
         client.disconnect().await.ok();
 
         Ok(SessionResult {
-            transcript,
-            success: true,
+            transcript: transcript.into_string(),
+            success,
+            cost_usd,
+            num_turns,
+            cancelled: false,
+            over_budget,
+            error,
+            summary,
         })
     }
-}
 
-impl Default for ClaudeSession {
-    fn default() -> Self {
-        Self {
-            cwd: None,
-            model: None,
+    /// Send `prompt` as a follow-up in `client`'s session and collect the
+    /// reply's text and the reported total cost. A failed or cancelled
+    /// follow-up gives `None` without failing the session.
+    async fn ask_summary(
+        &self,
+        client: &mut ClaudeClient,
+        prompt: &str,
+        mut api_log: Option<&mut ApiLog>,
+    ) -> Option<(String, Option<f64>)> {
+        if let Err(e) = client.query(prompt).await {
+            tracing::warn!(error = %e, "Failed to ask for a summary");
+            return None;
         }
+        if let Some(log) = api_log.as_deref_mut() {
+            log.request("summary prompt");
+        }
+        let mut text = String::new();
+        let mut total_cost = None;
+        let mut stream = client.receive_response();
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => message,
+                _ = self.cancelled() => return None,
+            };
+            if let (Some(log), Some(Ok(message))) = (api_log.as_deref_mut(), &message) {
+                log.record(message);
+            }
+            match message {
+                Some(Ok(Message::Assistant(assistant))) => {
+                    for block in &assistant.message.content {
+                        if let ContentBlock::Text(t) = block {
+                            text.push_str(&t.text);
+                        }
+                    }
+                }
+                Some(Ok(Message::Result(result))) => {
+                    total_cost = result.total_cost_usd;
+                    break;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    tracing::warn!(error = %e, "Summary stream failed");
+                    return None;
+                }
+                None => break,
+            }
+        }
+        let text = text.trim().to_string();
+        (!text.is_empty()).then_some((text, total_cost))
     }
 }
 
+/// Whether a session ended because it spent more than `max_cost_usd`:
+/// Claude Code stopped it for the budget, or its reported cost is past it
+fn exceeded_budget(result: &ResultMessage, max_cost_usd: Option<f64>) -> bool {
+    let Some(max) = max_cost_usd else {
+        return false;
+    };
+    result.subtype == "error_max_budget_usd" || result.total_cost_usd.is_some_and(|c| c > max)
+}
+
+/// The API error an assistant message stands in for, if any
+fn api_error(message: &Message) -> Option<SessionError> {
+    let Message::Assistant(assistant) = message else {
+        return None;
+    };
+    let kind = assistant.message.error?;
+    let text: String = assistant
+        .message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text(t) => Some(t.text.as_str()),
+            _ => None,
+        })
+        .collect();
+    Some(SessionError::from_api_error(kind, text))
+}
+
+/// The file a tool call reads or writes, for tools that take one
+fn tool_file_path(input: &serde_json::Value) -> Option<&str> {
+    ["file_path", "notebook_path", "path"]
+        .iter()
+        .find_map(|key| input.get(key).and_then(|v| v.as_str()))
+}
+
+/// Cut tool detail down to one line of at most `MAX_TOOL_DETAIL_CHARS`
+fn truncate_detail(s: &str) -> String {
+    let one_line = s.trim().replace('\n', " ");
+    if one_line.chars().count() > MAX_TOOL_DETAIL_CHARS {
+        let cut: String = one_line.chars().take(MAX_TOOL_DETAIL_CHARS).collect();
+        format!("{}...", cut)
+    } else {
+        one_line
+    }
+}
+
+/// Text from a partial-message `content_block_delta` stream event
+fn text_delta(event: &serde_json::Value) -> Option<&str> {
+    if event.get("type")?.as_str()? != "content_block_delta" {
+        return None;
+    }
+    let delta = event.get("delta")?;
+    if delta.get("type")?.as_str()? != "text_delta" {
+        return None;
+    }
+    delta.get("text")?.as_str()
+}
+
 /// Extract text content from a Message
-fn extract_text_from_message(message: &Message) -> Option<String> {
+fn extract_text_from_message(message: &Message, detail: TranscriptDetail) -> Option<String> {
     match message {
         Message::Assistant(assistant_msg) => {
             let mut text = String::new();
             for block in &assistant_msg.message.content {
                 match block {
-                    claude_code_agent_sdk::ContentBlock::Text(t) => {
+                    ContentBlock::Text(t) => {
                         text.push_str(&t.text);
                     }
-                    claude_code_agent_sdk::ContentBlock::ToolUse(tool) => {
+                    ContentBlock::ToolUse(tool) if detail == TranscriptDetail::Full => {
+                        text.push_str(&format!("[Tool: {}]", tool.name));
+                        if let Some(path) = tool_file_path(&tool.input) {
+                            text.push_str(&format!(" {}", path));
+                        }
+                        text.push_str(&format!(
+                            "\n  input: {}\n",
+                            truncate_detail(&tool.input.to_string())
+                        ));
+                    }
+                    ContentBlock::ToolUse(tool) => {
                         text.push_str(&format!("[Tool: {}]\n", tool.name));
                     }
                     _ => {}
@@ -162,6 +803,34 @@ fn extract_text_from_message(message: &Message) -> Option<String> {
                 Some(text)
             }
         }
+        // Tool results come back to the agent as user messages
+        Message::User(user_msg) if detail == TranscriptDetail::Full => {
+            let mut text = String::new();
+            for block in user_msg.content.iter().flatten() {
+                if let ContentBlock::ToolResult(result) = block {
+                    let content = match &result.content {
+                        Some(ToolResultContent::Text(t)) => t.clone(),
+                        Some(ToolResultContent::Blocks(blocks)) => blocks
+                            .iter()
+                            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        None => String::new(),
+                    };
+                    let label = if result.is_error == Some(true) {
+                        "Tool error"
+                    } else {
+                        "Result"
+                    };
+                    text.push_str(&format!("[{}] {}\n", label, truncate_detail(&content)));
+                }
+            }
+            if text.is_empty() {
+                None
+            } else {
+                Some(text)
+            }
+        }
         Message::System(sys_msg) => Some(format!("[System: {}]", sys_msg.subtype)),
         Message::Result(result_msg) => Some(format!(
             "[Session complete - cost: ${:.4}]",
@@ -170,3 +839,79 @@ fn extract_text_from_message(message: &Message) -> Option<String> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_sorts_errors_by_what_to_do_about_them() {
+        let kind = |message: &str| SessionError::classify(message.to_string()).kind();
+        assert_eq!(
+            kind("API Error: 401 {\"type\":\"authentication_error\"}"),
+            "authentication"
+        );
+        assert_eq!(
+            kind("Invalid API key · Please run /login"),
+            "authentication"
+        );
+        assert_eq!(kind("API Error: 429 rate_limit_error"), "rate_limited");
+        assert_eq!(kind("API Error: 529 Overloaded"), "rate_limited");
+        assert_eq!(
+            kind("API Error: 404 model: claude-opus-9 not_found_error"),
+            "model_not_found"
+        );
+        assert_eq!(kind("Connection error: getaddrinfo ENOTFOUND"), "network");
+        assert_eq!(kind("Process exited with code 1"), "sdk");
+    }
+
+    #[test]
+    fn test_transient_and_fatal_errors() {
+        assert!(SessionError::RateLimited(String::new()).is_transient());
+        assert!(SessionError::Network(String::new()).is_transient());
+        assert!(!SessionError::Authentication(String::new()).is_transient());
+        assert!(SessionError::Authentication(String::new()).is_fatal());
+        assert!(SessionError::CliNotFound(String::new()).is_fatal());
+        assert!(!SessionError::SdkError(String::new()).is_fatal());
+    }
+
+    #[test]
+    fn test_model_retry() {
+        let model_not_found = "API Error: 404 model: claude-opus-9 not_found_error";
+        let cases = [
+            (model_not_found, 1, true, ModelRetry::Retry),
+            (model_not_found, 2, true, ModelRetry::FallBack),
+            (model_not_found, 2, false, ModelRetry::GiveUp),
+            (
+                "API Error: 429 rate_limit_error",
+                1,
+                true,
+                ModelRetry::Retry,
+            ),
+            ("API Error: 529 Overloaded", 2, true, ModelRetry::FallBack),
+            (
+                "Invalid API key · Please run /login",
+                1,
+                true,
+                ModelRetry::GiveUp,
+            ),
+            (
+                "Connection error: getaddrinfo ENOTFOUND",
+                1,
+                true,
+                ModelRetry::GiveUp,
+            ),
+            ("Process exited with code 1", 2, true, ModelRetry::GiveUp),
+        ];
+        for (message, attempt, can_fall_back, expected) in cases {
+            let error = SessionError::classify(message.to_string());
+            assert_eq!(
+                error.model_retry(attempt, can_fall_back),
+                expected,
+                "{} on attempt {}",
+                message,
+                attempt
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+use tempfile::TempPath;
+
+/// Editor when `$EDITOR` isn't set
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// The user's editor: `$EDITOR`, else `vi` (`notepad` on Windows)
+fn editor() -> String {
+    std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Open `path` in the user's editor and wait for it to exit. `$EDITOR` may
+/// carry arguments (`code --wait`) and on Windows name a `.cmd` or `.bat`
+/// script, so like git, it is run through the shell.
+pub fn edit(path: &Path) -> io::Result<ExitStatus> {
+    editor_command(&editor(), path).status()
+}
+
+#[cfg(not(windows))]
+fn editor_command(editor: &str, path: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .arg(path);
+    command
+}
+
+#[cfg(windows)]
+fn editor_command(editor: &str, path: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut command = Command::new("cmd");
+    // With /S, cmd strips the outer quotes and runs the rest as written,
+    // so a quoted editor path such as "C:\Program Files\..." survives
+    command.raw_arg(format!("/S /C \"{} \"{}\"\"", editor, path.display()));
+    command
+}
+
+/// A temporary file holding `content` for an editor, with `suffix` (e.g.
+/// `.md`) so it gets the right syntax highlighting. The file is closed,
+/// since Windows editors can't save over a file another process holds
+/// open; it is deleted when the returned path is dropped.
+pub fn temp_file(content: &str, suffix: &str) -> io::Result<TempPath> {
+    let mut file = tempfile::Builder::new().suffix(suffix).tempfile()?;
+    file.write_all(content.as_bytes())?;
+    file.flush()?;
+    Ok(file.into_temp_path())
+}
+
+/// What an editor saved in `path`, without the byte order mark and CRLF
+/// line endings Windows editors add
+pub fn read_edited(path: &Path) -> io::Result<String> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(&content)
+        .replace("\r\n", "\n"))
+}
+
+/// Where `name` is on `PATH`. On Windows that includes scripts such as
+/// npm's `claude.cmd`, which `Command::new` only finds as `.exe`; elsewhere
+/// `name` is returned as it is.
+pub fn program(name: &str) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let extensions =
+            std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+        let dirs = std::env::var_os("PATH").unwrap_or_default();
+        for dir in std::env::split_paths(&dirs) {
+            for extension in extensions.split(';').filter(|e| !e.is_empty()) {
+                let candidate = dir.join(format!("{}{}", name, extension));
+                if candidate.is_file() {
+                    return candidate;
+                }
+            }
+        }
+    }
+    PathBuf::from(name)
+}
+
+/// Whether `program` runs through cmd.exe, which can't pass line breaks
+/// in its arguments
+pub fn is_batch_script(program: &Path) -> bool {
+    program
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cmd") || ext.eq_ignore_ascii_case("bat"))
+}
+
+/// Drop key presses still queued from an editor or chat session, so they
+/// don't act on the TUI taking the terminal back (on Windows the Enter
+/// that closed the program often is). Call with raw mode enabled.
+pub fn discard_pending_input() -> io::Result<()> {
+    while crossterm::event::poll(Duration::ZERO)? {
+        crossterm::event::read()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit() {
+        let path = temp_file("plan: a\n", ".md").unwrap();
+        #[cfg(unix)]
+        {
+            // An editor given as a shell command line, like `code --wait`
+            let editor = "printf 'plan: b\\n' | tee >/dev/null";
+            let status = editor_command(editor, &path).status().unwrap();
+            assert!(status.success());
+            assert_eq!(read_edited(&path).unwrap(), "plan: b\n");
+        }
+
+        std::fs::write(&path, "\u{feff}line one\r\nline two\r\n").unwrap();
+        assert_eq!(read_edited(&path).unwrap(), "line one\nline two\n");
+        assert!(is_batch_script(Path::new("C:\\npm\\claude.CMD")));
+        assert!(!is_batch_script(Path::new("/usr/bin/claude")));
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// What a run's sessions have cost so far, added to as each one reports its
+/// cost and shared by everything that shows a running total
+#[derive(Debug, Clone, Default)]
+pub struct Spend {
+    /// Millionths of a dollar, so sessions can add to it without a lock
+    micro_usd: Arc<AtomicU64>,
+}
+
+impl Spend {
+    pub fn add(&self, usd: f64) {
+        if usd > 0.0 {
+            self.micro_usd
+                .fetch_add((usd * 1e6).round() as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub fn total_usd(&self) -> f64 {
+        self.micro_usd.load(Ordering::Relaxed) as f64 / 1e6
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spend() {
+        let spend = Spend::default();
+        let shared = spend.clone();
+        shared.add(0.125);
+        spend.add(1.5);
+        shared.add(-2.0);
+        assert_eq!(spend.total_usd(), 1.625);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::history::{self, InstanceRecord, RunRecord};
+use std::collections::HashMap;
+
+/// Rating every model and archetype starts from
+const INITIAL_RATING: f64 = 1500.0;
+/// Maximum rating change per head-to-head outcome
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub name: String,
+    pub rating: f64,
+    pub wins: usize,
+    pub losses: usize,
+}
+
+/// Replay head-to-head outcomes, oldest first, into Elo ratings keyed by
+/// `key`. Outcomes between two instances with the same key are skipped,
+/// since they say nothing about that key. Sorted best first.
+fn elo(runs: &[RunRecord], key: impl Fn(&InstanceRecord) -> &str) -> Vec<Rating> {
+    let mut ratings: HashMap<String, Rating> = HashMap::new();
+    for run in runs {
+        for (winner, loser) in run.outcomes() {
+            let (w, l) = (key(winner), key(loser));
+            if w == l {
+                continue;
+            }
+            for name in [w, l] {
+                ratings.entry(name.to_string()).or_insert_with(|| Rating {
+                    name: name.to_string(),
+                    rating: INITIAL_RATING,
+                    wins: 0,
+                    losses: 0,
+                });
+            }
+            let expected =
+                1.0 / (1.0 + 10f64.powf((ratings[l].rating - ratings[w].rating) / 400.0));
+            let delta = K_FACTOR * (1.0 - expected);
+            if let Some(r) = ratings.get_mut(w) {
+                r.rating += delta;
+                r.wins += 1;
+            }
+            if let Some(r) = ratings.get_mut(l) {
+                r.rating -= delta;
+                r.losses += 1;
+            }
+        }
+    }
+    let mut sorted: Vec<Rating> = ratings.into_values().collect();
+    sorted.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+    sorted
+}
+
+/// `actually stats`: print Elo ratings for models and strategy archetypes
+pub fn print_stats() -> anyhow::Result<()> {
+    let runs = history::load()?;
+    println!(
+        "{} runs recorded in {}",
+        runs.len(),
+        history::history_path()?.display()
+    );
+
+    for (title, ratings) in [
+        ("Models", elo(&runs, |i| &i.model)),
+        ("Strategy archetypes", elo(&runs, |i| &i.archetype)),
+    ] {
+        println!();
+        println!("{}", title);
+        if ratings.is_empty() {
+            println!("  (no head-to-head results yet)");
+            continue;
+        }
+        println!("  {:>6}  {:>7}  Name", "Rating", "W-L");
+        for r in ratings {
+            println!(
+                "  {:>6.0}  {:>7}  {}",
+                r.rating,
+                format!("{}-{}", r.wins, r.losses),
+                r.name
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elo_rewards_winner() {
+        let instance = |id, model: &str, rank| InstanceRecord {
+            instance_id: id,
+            model: model.to_string(),
+            archetype: "other".to_string(),
+            success: true,
+            cost_usd: None,
+            judge_rank: Some(rank),
+            defeated: vec![],
+        };
+        let run = RunRecord {
+            timestamp: 0,
+            prompt: String::new(),
+            run_dir: String::new(),
+            instances: vec![instance(0, "opus", 1), instance(1, "haiku", 2)],
+        };
+        let ratings = elo(&[run], |i| &i.model);
+        assert_eq!(ratings[0].name, "opus");
+        assert_eq!(ratings[0].rating, INITIAL_RATING + K_FACTOR / 2.0);
+        assert_eq!((ratings[1].wins, ratings[1].losses), (0, 1));
+        assert!(elo(&[], |i| &i.archetype).is_empty());
+    }
+}
@@ -1,25 +1,45 @@
+use crate::config::Criterion;
+use serde::Deserialize;
 use std::fmt;
 
 /// Structured representation of a strategy
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Strategy {
     /// Full markdown-formatted strategy text
     pub markdown: String,
     /// Plain text with markdown syntax stripped
     pub raw: String,
-    /// Key qualities/features extracted from **bold** markers
+    /// Key qualities/features extracted from **bold** markers, or guessed
+    /// from the text when there are none
     pub highlights: Vec<String>,
+    /// Short label for lists: the agent's own title, or the start of the
+    /// first sentence
+    pub title: String,
 }
 
+/// Most words in a title derived from the strategy text
+const MAX_TITLE_WORDS: usize = 8;
+
+/// Most highlights guessed from a strategy without bold markers
+const MAX_GUESSED_HIGHLIGHTS: usize = 4;
+
+/// Longest first-sentence highlight, for strategies with nothing better
+const MAX_SENTENCE_HIGHLIGHT_CHARS: usize = 60;
+
 impl Strategy {
     /// Parse a strategy string into structured form
     pub fn parse(text: &str) -> Self {
         let markdown = text.to_string();
-        let (raw, highlights) = Self::extract_formatting(&markdown);
+        let (raw, mut highlights) = Self::extract_formatting(&markdown);
+        if highlights.is_empty() {
+            highlights = Self::guess_highlights(&markdown, &raw);
+        }
+        let title = derive_title(&raw);
         Self {
             markdown,
             raw,
             highlights,
+            title,
         }
     }
 
@@ -55,37 +75,286 @@ impl Strategy {
         (raw, highlights)
     }
 
+    /// Highlights for a strategy nobody bolded anything in: its Markdown
+    /// headers, else the technology names it mentions, else its first
+    /// sentence
+    fn guess_highlights(markdown: &str, raw: &str) -> Vec<String> {
+        let headers: Vec<String> = markdown
+            .lines()
+            .filter_map(|l| l.trim_start().strip_prefix('#'))
+            .map(|h| h.trim_start_matches('#').trim().replace('*', ""))
+            .filter(|h| !h.is_empty())
+            .take(MAX_GUESSED_HIGHLIGHTS)
+            .collect();
+        if !headers.is_empty() {
+            return headers;
+        }
+
+        let names = tech_names(markdown, raw);
+        if !names.is_empty() {
+            return names;
+        }
+
+        let sentence = first_sentence(raw);
+        if sentence.is_empty() {
+            Vec::new()
+        } else {
+            vec![truncate_at_word(sentence, MAX_SENTENCE_HIGHLIGHT_CHARS)]
+        }
+    }
+
     /// Create a failed/placeholder strategy
     pub fn failed(error_msg: &str) -> Self {
         Self {
             markdown: format!("[FAILED] {}", error_msg),
             raw: format!("[FAILED] {}", error_msg),
             highlights: vec![],
+            title: "Failed".to_string(),
+        }
+    }
+}
+
+/// Words that look like names of tools, libraries or protocols: `code`
+/// spans, CamelCase or acronym-like words (`SQLx`, `gRPC`, `REST`), dotted
+/// names (`Node.js`), and capitalized words that don't start a sentence
+fn tech_names(markdown: &str, raw: &str) -> Vec<String> {
+    let mut names: Vec<String> = markdown
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty() && s.len() <= 30)
+        .collect();
+
+    let mut sentence_start = true;
+    for token in raw.split_whitespace() {
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric() && c != '+' && c != '#');
+        let starts_sentence = sentence_start;
+        sentence_start = token.ends_with(['.', '!', '?', ':']);
+        if word.len() < 2 || word.starts_with('#') {
+            continue;
+        }
+        let mut chars = word.chars();
+        let first_upper = chars.next().is_some_and(char::is_uppercase);
+        let inner_upper = chars.any(char::is_uppercase);
+        let dotted = word.len() > 3 && word.contains('.') && word.chars().any(char::is_alphabetic);
+        if inner_upper || dotted || (first_upper && !starts_sentence) {
+            names.push(word.to_string());
+        }
+    }
+
+    let mut seen = Vec::new();
+    names.retain(|n| {
+        let key = n.to_lowercase();
+        let new = !seen.contains(&key);
+        seen.push(key);
+        new
+    });
+    names.truncate(MAX_GUESSED_HIGHLIGHTS);
+    names
+}
+
+/// First sentence of the first non-empty line, without its full stop
+fn first_sentence(raw: &str) -> &str {
+    raw.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(|l| l.split(". ").next().unwrap_or(l).trim_end_matches('.'))
+        .unwrap_or_default()
+}
+
+/// Title from the first `MAX_TITLE_WORDS` words of the first sentence
+fn derive_title(raw: &str) -> String {
+    let sentence = first_sentence(raw).trim_start_matches('#').trim();
+    let words: Vec<&str> = sentence.split_whitespace().collect();
+    if words.len() <= MAX_TITLE_WORDS {
+        words.join(" ")
+    } else {
+        let title = words[..MAX_TITLE_WORDS].join(" ");
+        format!("{}…", title.trim_end_matches([',', ';', ':']))
+    }
+}
+
+/// Cut `s` to at most `max` characters at a word boundary, marking the cut
+fn truncate_at_word(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let cut: String = s.chars().take(max.saturating_sub(1)).collect();
+    let cut = match cut.rfind(' ') {
+        Some(idx) if idx > 0 => &cut[..idx],
+        _ => &cut,
+    };
+    format!("{}…", cut.trim_end_matches([',', ';', ' ']))
+}
+
+/// How firmly implementation prompts steer each instance away from the
+/// other instances' strategies (`--exclusion-strength`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExclusionStrength {
+    /// No exclusions: instances are independent samples of their strategies
+    None,
+    /// The other strategies are listed as approaches to avoid where possible
+    Soft,
+    /// The other strategies are forbidden
+    #[default]
+    Normal,
+    /// Forbidden, along with borrowing any of their key qualities
+    Strict,
+}
+
+impl ExclusionStrength {
+    /// Heading above the list of other strategies, if there is one
+    fn header(self) -> Option<&'static str> {
+        match self {
+            ExclusionStrength::None => None,
+            ExclusionStrength::Soft => Some("OTHER APPROACHES (taken by other engineers; avoid reusing them where your strategy leaves you a choice):"),
+            ExclusionStrength::Normal => Some("FORBIDDEN APPROACHES (do not use these):"),
+            ExclusionStrength::Strict => Some("FORBIDDEN APPROACHES (do not use these, and do not borrow any of their bolded key qualities, even partially; if your strategy seems to need one, find another way):"),
         }
     }
 }
 
+/// How much a strategy agent is asked to write (`--strategy-depth`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrategyDepth {
+    /// A one-sentence approach, without steps
+    Brief,
+    /// A short summary and a few steps
+    #[default]
+    Standard,
+    /// A structured plan: summary, ordered steps and the risks to watch
+    Detailed,
+}
+
+impl StrategyDepth {
+    /// How long the summary (or answer) should be
+    fn length(self) -> &'static str {
+        match self {
+            StrategyDepth::Brief => "one sentence",
+            StrategyDepth::Standard => "2-4 sentences",
+            StrategyDepth::Detailed => "a paragraph of 3-6 sentences",
+        }
+    }
+
+    /// The reply's fields after `summary` and `highlights`, as JSON
+    fn extra_fields(self) -> &'static str {
+        match self {
+            StrategyDepth::Brief => "",
+            StrategyDepth::Standard => r#", "steps": ["<implementation step>", ...]"#,
+            StrategyDepth::Detailed => {
+                r#", "steps": ["<implementation step>", ...], "risks": ["<risk, and how you will handle it>", ...]"#
+            }
+        }
+    }
+
+    /// How many highlights, steps and risks to give
+    fn counts(self) -> &'static str {
+        match self {
+            StrategyDepth::Brief => r#"Give 1-3 "highlights"."#,
+            StrategyDepth::Standard => r#"Give 2-4 "highlights" and at most 5 short "steps"."#,
+            StrategyDepth::Detailed => {
+                r#"Give 2-4 "highlights", 4-10 concrete "steps" in the order you will take them, and 1-4 "risks" that could sink the approach, each with how you will handle it."#
+            }
+        }
+    }
+}
+
+/// A temperament an instance is asked to have, from strategy to
+/// implementation (`--variation`), so instances differ by more than the
+/// strategies they must avoid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variation {
+    /// Proven techniques and the smallest change that works
+    Conservative,
+    /// No particular lean: the prompts as they are
+    #[default]
+    Balanced,
+    /// Ambitious or unconventional approaches
+    Adventurous,
+}
+
+impl Variation {
+    /// What the instance is told, if anything
+    pub fn instruction(self) -> Option<&'static str> {
+        match self {
+            Variation::Conservative => Some("Be conservative: prefer proven, widely used techniques and the smallest change that fully solves the task, and avoid anything experimental."),
+            Variation::Balanced => None,
+            Variation::Adventurous => Some("Be adventurous: favor an ambitious or unconventional approach that other engineers would be unlikely to try, as long as it still fully solves the task."),
+        }
+    }
+
+    /// Prompt paragraph for the strategy prompt, or an empty string
+    fn prompt_section(self) -> String {
+        self.instruction()
+            .map(|instruction| format!("STYLE: {}\n\n", instruction))
+            .unwrap_or_default()
+    }
+}
+
+/// What becomes of strategies that failed to generate before implementation
+/// (`--failed-strategies`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailedStrategies {
+    /// Implement them anyway, as instances that fail straight away
+    #[default]
+    Implement,
+    /// Skip them
+    Skip,
+    /// Ask for each once more, skipping the ones that fail again
+    Retry,
+}
+
+/// Language/framework every instance must work within (`--language`, `--framework`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StackConstraints {
+    pub language: Option<String>,
+    pub framework: Option<String>,
+}
+
+impl StackConstraints {
+    /// Prompt paragraph stating the constraints, or an empty string if there are none
+    fn prompt_section(&self) -> String {
+        let stack = match (&self.language, &self.framework) {
+            (None, None) => return String::new(),
+            (Some(language), None) => language.clone(),
+            (None, Some(framework)) => framework.clone(),
+            (Some(language), Some(framework)) => format!("{} with {}", language, framework),
+        };
+        format!(
+            "STACK CONSTRAINT: You MUST use {}. This is not negotiable: vary only your approach within this stack, never the stack itself.\n\n",
+            stack
+        )
+    }
+}
+
 impl fmt::Display for Strategy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.markdown)
     }
 }
 
-const STRATEGY_PROMPT_TEMPLATE: &str = r#"If the user prompt is a question, answer it in 2-4 sentences.  If it is a task to perform, describe ONLY your implementation plan in 2-4 sentences. Do not implement anything yet.
+const STRATEGY_PROMPT_TEMPLATE: &str = r#"If the user prompt is a question, answer it in {length}.  If it is a task to perform, describe ONLY your implementation plan in {length}. Do not implement anything yet.
 
 User prompt: {task}
 
 IMPORTANT: Commit to ONE specific approach. Do NOT say "alternatively", "or", "optionally", or suggest multiple options. Pick one concrete solution and describe only that.
 
-Formatting: Using Markdown, put bold markers on the main features of your approach, and wrap any code snippets in backticks.
+Reply with ONLY a JSON object in exactly this shape, and nothing before or after it:
+{"title": "<5-8 word title>", "summary": "<your approach in {length}>", "highlights": ["<2-4 word main feature>", ...]{fields}}
 
-Reply with exactly this format:
-STRATEGY: <your approach in 2-4 sentences>
+The "title" names your approach in 5-8 plain words, without Markdown. In "summary", use Markdown: put bold markers on the main features of your approach, and wrap any code snippets in backticks. {counts}
 
-{exclusions}"#;
+{variation}{constraints}{exclusions}"#;
 
 const EXCLUSION_HEADER: &str = "You MUST suggest a novel approach UTTERLY DIFFERENT from your competitors while still satisfying the task. The **bolded** text in each approach represents the key qualities you must avoid. Your competitors are using these approaches:";
 
+const DIVERSITY_EXCLUSION_HEADER: &str = "You MUST suggest a novel approach that differs from every competitor on each of these axes: {axes}. Make a genuinely different choice on every axis, not a cosmetic variation, while still satisfying the task. The **bolded** text in each approach represents the key qualities you must avoid. Your competitors are using these approaches:";
+
 const IMPLEMENTATION_PROMPT_TEMPLATE: &str = r#"Implement the following task using the specified strategy.
 
 Task: {task}
@@ -93,48 +362,341 @@ Task: {task}
 YOUR STRATEGY (you must follow this):
 {strategy}
 
-{exclusions}
+{instructions}{constraints}{exclusions}
+
+When you are done, write a NOTES.md in the current directory covering the decisions you made, their trade-offs, and how to run the result.
 
 Proceed with implementation."#;
 
-pub fn build_strategy_prompt(task: &str, existing_strategies: &[String]) -> String {
+/// Follow-up sent in the implementation session once it finishes
+pub const SELF_SUMMARY_PROMPT: &str = "In a few short Markdown bullet points, summarize what you built and what is untested or unfinished. Do not change any files.";
+
+const VOTE_PROMPT_TEMPLATE: &str = r#"You proposed a strategy for a task, and other engineers proposed competing strategies. Judge theirs fairly; you are not voting on your own.
+
+Task: {task}
+
+YOUR STRATEGY:
+{own}
+
+OTHER STRATEGIES:
+{others}
+
+Briefly note what makes each of the other strategies strong or weak. Then end your response with a single line ranking every other strategy from most to least promising, in exactly this form:
+
+RANKING: C2, C0, C1"#;
+
+const CROSS_REVIEW_PROMPT_TEMPLATE: &str = r#"You are reviewing another engineer's solution to a task. The solution is in the current directory. Do not modify anything.
+
+Task: {task}
+
+Their strategy:
+{strategy}
+
+Their changes:
+{changes}
+
+Write a short critique (at most 10 bullet points) in Markdown with three sections: **Bugs**, **Risks**, and **Strengths**. Be specific and cite files where you can."#;
+
+const JUDGE_PROMPT_TEMPLATE: &str = r#"You are judging competing solutions to the same task. Each solution lives in its own directory under the current directory. Do not modify anything.
+
+Task: {task}
+
+{candidates}
+
+Score every solution on each of these criteria, from 0 (worst) to 10 (best):
+{rubric}
+
+Write concise notes in Markdown: for each solution, its main strengths and weaknesses, and which parts of it are worth keeping. Then give your scores as a JSON object in a ```json code block, keyed by solution and then criterion, for example:
+
+```json
+{"C0": {"{example_criterion}": 7}, "C1": {"{example_criterion}": 9}}
+```
+
+Finally, end your response with a single line ranking every solution from best to worst, in exactly this form:
+
+RANKING: C2, C0, C1"#;
+
+const SYNTHESIS_PROMPT_TEMPLATE: &str = r#"Several engineers independently implemented the same task, and a judge ranked their solutions. Produce a single combined solution in the current directory that keeps the best parts of each.
+
+Task: {task}
+
+Top solutions (read them, but do not modify them):
+{sources}
+
+Judge's notes:
+{notes}
+
+Start from the strongest solution, bring over the improvements the judge identified in the others, and resolve any conflicts in favor of correctness. Proceed with implementation."#;
+
+const DEBATE_TURN_PROMPT_TEMPLATE: &str = r#"You are {speaker} in a moderated debate about how best to approach a task. You argue FOR your strategy; your opponent argues for theirs.
+
+Task: {task}
+
+YOUR STRATEGY:
+{own}
+
+YOUR OPPONENT'S STRATEGY:
+{opponent}
+
+Debate so far:
+{history}
+
+This is round {round} of {rounds}. In at most 150 words, make your strongest case for your strategy and rebut your opponent's latest points. Be concrete about trade-offs (correctness, complexity, risk, effort). Do not propose a third approach. Do not implement anything."#;
+
+const DEBATE_VERDICT_PROMPT_TEMPLATE: &str = r#"You moderated a debate between two strategies for a task.
+
+Task: {task}
+
+{a_label}'s strategy:
+{a_strategy}
+
+{b_label}'s strategy:
+{b_strategy}
+
+Debate transcript:
+{history}
+
+In Markdown, give the strongest argument for each side in one bullet each, then say which strategy looks more promising for this task and why, in 2-3 sentences. Do not propose a third approach."#;
+
+/// `diversify_on` names the axes (e.g. "architecture", "libraries") the new
+/// strategy must differ on; when empty, the generic framing is used
+pub fn build_strategy_prompt(
+    task: &str,
+    existing_strategies: &[String],
+    diversify_on: &[String],
+    stack: &StackConstraints,
+    depth: StrategyDepth,
+    variation: Variation,
+) -> String {
     let exclusions = if existing_strategies.is_empty() {
         String::new()
     } else {
-        let mut lines = vec![EXCLUSION_HEADER.to_string()];
+        let header = if diversify_on.is_empty() {
+            EXCLUSION_HEADER.to_string()
+        } else {
+            DIVERSITY_EXCLUSION_HEADER.replace("{axes}", &diversify_on.join(", "))
+        };
+        let mut lines = vec![header];
         for (i, strategy) in existing_strategies.iter().enumerate() {
             lines.push(format!("{}. {}", i + 1, strategy));
         }
         lines.join("\n")
     };
 
+    strategy_template(task, stack, depth)
+        .replace("{variation}", &variation.prompt_section())
+        .replace("{exclusions}", &exclusions)
+}
+
+/// The strategy prompt with a run's own settings filled in, leaving the
+/// `{variation}` and `{exclusions}` that differ from instance to instance
+pub fn strategy_template(task: &str, stack: &StackConstraints, depth: StrategyDepth) -> String {
     STRATEGY_PROMPT_TEMPLATE
+        .replace("{length}", depth.length())
+        .replace("{fields}", depth.extra_fields())
+        .replace("{counts}", depth.counts())
         .replace("{task}", task)
-        .replace("{exclusions}", &exclusions)
+        .replace("{constraints}", &stack.prompt_section())
+}
+
+/// Prompt paragraph of extra instructions given for an implementation, or
+/// an empty string if there are none
+fn instructions_section(instructions: &[&str]) -> String {
+    let instructions: Vec<&str> = instructions
+        .iter()
+        .map(|i| i.trim())
+        .filter(|i| !i.is_empty())
+        .collect();
+    if instructions.is_empty() {
+        return String::new();
+    }
+    format!(
+        "ADDITIONAL INSTRUCTIONS (follow these as well as your strategy):\n{}\n\n",
+        instructions.join("\n")
+    )
 }
 
+/// The prompt an implementation session starts with. `instructions` come
+/// from the user rather than the strategy agent, and go right after the
+/// strategy.
 pub fn build_implementation_prompt(
     task: &str,
     strategy: &str,
+    instructions: &[&str],
     excluded_strategies: &[String],
+    stack: &StackConstraints,
+    strength: ExclusionStrength,
 ) -> String {
-    let exclusions = if excluded_strategies.is_empty() {
-        String::new()
-    } else {
-        let mut lines = vec!["FORBIDDEN APPROACHES (do not use these):".to_string()];
-        for (i, s) in excluded_strategies.iter().enumerate() {
-            lines.push(format!("{}. {}", i + 1, s));
+    let exclusions = match strength.header() {
+        Some(header) if !excluded_strategies.is_empty() => {
+            let mut lines = vec![header.to_string()];
+            for (i, s) in excluded_strategies.iter().enumerate() {
+                lines.push(format!("{}. {}", i + 1, s));
+            }
+            lines.join("\n")
         }
-        lines.join("\n")
+        _ => String::new(),
     };
 
+    implementation_template(task, stack)
+        .replace("{strategy}", strategy)
+        .replace("{instructions}", &instructions_section(instructions))
+        .replace("{exclusions}", &exclusions)
+}
+
+/// The implementation prompt with a run's own settings filled in, leaving
+/// each instance's `{strategy}`, `{instructions}` and `{exclusions}`
+pub fn implementation_template(task: &str, stack: &StackConstraints) -> String {
     IMPLEMENTATION_PROMPT_TEMPLATE
+        .replace("{task}", task)
+        .replace("{constraints}", &stack.prompt_section())
+}
+
+pub fn build_judge_prompt(task: &str, candidates: &str, rubric: &[Criterion]) -> String {
+    let criteria = rubric
+        .iter()
+        .map(|c| match &c.description {
+            Some(desc) => format!("- {} (weight {}): {}", c.name, c.weight, desc),
+            None => format!("- {} (weight {})", c.name, c.weight),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    JUDGE_PROMPT_TEMPLATE
+        .replace("{task}", task)
+        .replace("{candidates}", candidates)
+        .replace("{rubric}", &criteria)
+        .replace(
+            "{example_criterion}",
+            rubric.first().map(|c| c.name.as_str()).unwrap_or("score"),
+        )
+}
+
+pub fn build_synthesis_prompt(task: &str, sources: &str, notes: &str) -> String {
+    SYNTHESIS_PROMPT_TEMPLATE
+        .replace("{task}", task)
+        .replace("{sources}", sources)
+        .replace("{notes}", notes)
+}
+
+pub fn build_debate_turn_prompt(
+    task: &str,
+    speaker: &str,
+    own: &str,
+    opponent: &str,
+    history: &str,
+    round: usize,
+    rounds: usize,
+) -> String {
+    DEBATE_TURN_PROMPT_TEMPLATE
+        .replace("{speaker}", speaker)
+        .replace("{task}", task)
+        .replace("{own}", own)
+        .replace("{opponent}", opponent)
+        .replace(
+            "{history}",
+            if history.is_empty() {
+                "(nothing yet - you open the debate)"
+            } else {
+                history
+            },
+        )
+        .replace("{round}", &round.to_string())
+        .replace("{rounds}", &rounds.to_string())
+}
+
+pub fn build_debate_verdict_prompt(
+    task: &str,
+    (a_label, a_strategy): (&str, &str),
+    (b_label, b_strategy): (&str, &str),
+    history: &str,
+) -> String {
+    DEBATE_VERDICT_PROMPT_TEMPLATE
+        .replace("{task}", task)
+        .replace("{a_label}", a_label)
+        .replace("{a_strategy}", a_strategy)
+        .replace("{b_label}", b_label)
+        .replace("{b_strategy}", b_strategy)
+        .replace("{history}", history)
+}
+
+pub fn build_vote_prompt(task: &str, own: &str, others: &str) -> String {
+    VOTE_PROMPT_TEMPLATE
+        .replace("{task}", task)
+        .replace("{own}", own)
+        .replace("{others}", others)
+}
+
+pub fn build_cross_review_prompt(task: &str, strategy: &str, changes: &str) -> String {
+    CROSS_REVIEW_PROMPT_TEMPLATE
         .replace("{task}", task)
         .replace("{strategy}", strategy)
-        .replace("{exclusions}", &exclusions)
+        .replace("{changes}", changes)
+}
+
+/// Fill in a user-supplied CLAUDE.md template for one instance.
+/// Supports `{task}`, `{strategy}` and `{instance}` placeholders; a template
+/// without placeholders is used verbatim. The template is filled in one
+/// pass, so a task or strategy that mentions a placeholder keeps it as is.
+pub fn render_claude_md(template: &str, task: &str, strategy: &str, instance_id: usize) -> String {
+    let instance = format!("C{}", instance_id);
+    let values = [
+        ("{task}", task),
+        ("{strategy}", strategy),
+        ("{instance}", instance.as_str()),
+    ];
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match values.iter().find(|(key, _)| rest.starts_with(key)) {
+            Some((key, value)) => {
+                rendered.push_str(value);
+                rest = &rest[key.len()..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// STRATEGY.md for one instance's workspace: the task and the strategy it
+/// was assigned, for the agent and for whoever opens the workspace later
+pub fn render_strategy_md(task: &str, strategy: &str, instance_id: usize) -> String {
+    format!(
+        "# Strategy for C{}\n\n## Task\n\n{}\n\n## Strategy\n\n{}\n",
+        instance_id,
+        task.trim(),
+        strategy.trim()
+    )
 }
 
+/// The JSON reply the strategy prompt asks for
+#[derive(Debug, Deserialize)]
+struct StrategyReply {
+    #[serde(default)]
+    title: String,
+    summary: String,
+    #[serde(default)]
+    highlights: Vec<String>,
+    #[serde(default)]
+    steps: Vec<String>,
+    /// Only asked for with `--strategy-depth detailed`
+    #[serde(default)]
+    risks: Vec<String>,
+}
+
+/// Parse a strategy reply: the JSON object the strategy prompt asks for, or
+/// failing that the older `STRATEGY:` text format
 pub fn parse_strategy(response: &str) -> Strategy {
+    if let Some(strategy) = parse_strategy_json(response) {
+        return strategy;
+    }
+
     // Look for "STRATEGY:" prefix and extract the rest
     let text = if let Some(idx) = response.find("STRATEGY:") {
         let after_prefix = &response[idx + "STRATEGY:".len()..];
@@ -168,13 +730,76 @@ pub fn parse_strategy(response: &str) -> Strategy {
     Strategy::parse(&text)
 }
 
+/// The strategy in a JSON reply, tolerating a code fence or stray text
+/// around the object. Steps become a numbered list after the summary, and
+/// risks a bulleted one after that.
+fn parse_strategy_json(response: &str) -> Option<Strategy> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    let reply: StrategyReply = serde_json::from_str(response.get(start..=end)?).ok()?;
+    let summary = reply.summary.trim();
+    if summary.is_empty() {
+        return None;
+    }
+
+    let mut markdown = summary.to_string();
+    let steps: Vec<&str> = reply
+        .steps
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if !steps.is_empty() {
+        markdown.push_str("\n\n");
+        for (i, step) in steps.iter().enumerate() {
+            markdown.push_str(&format!("{}. {}\n", i + 1, step));
+        }
+    }
+    let risks: Vec<&str> = reply
+        .risks
+        .iter()
+        .map(|r| r.trim())
+        .filter(|r| !r.is_empty())
+        .collect();
+    if !risks.is_empty() {
+        markdown.push_str(if steps.is_empty() { "\n\n" } else { "\n" });
+        markdown.push_str("Risks:\n");
+        for risk in risks {
+            markdown.push_str(&format!("- {}\n", risk));
+        }
+    }
+
+    let mut strategy = Strategy::parse(markdown.trim_end());
+    let highlights: Vec<String> = reply
+        .highlights
+        .into_iter()
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .collect();
+    if !highlights.is_empty() {
+        strategy.highlights = highlights;
+    }
+    let title = reply.title.trim();
+    if !title.is_empty() {
+        strategy.title = title.to_string();
+    }
+    Some(strategy)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_build_strategy_prompt_no_exclusions() {
-        let prompt = build_strategy_prompt("Build a REST API", &[]);
+        let prompt = build_strategy_prompt(
+            "Build a REST API",
+            &[],
+            &[],
+            &StackConstraints::default(),
+            StrategyDepth::Standard,
+            Variation::Balanced,
+        );
         assert!(prompt.contains("Build a REST API"));
         assert!(!prompt.contains("MUST NOT"));
     }
@@ -185,13 +810,113 @@ mod tests {
             "Use Express with SQLite".to_string(),
             "Use Fastify with PostgreSQL".to_string(),
         ];
-        let prompt = build_strategy_prompt("Build a REST API", &existing);
+        let prompt = build_strategy_prompt(
+            "Build a REST API",
+            &existing,
+            &[],
+            &StackConstraints::default(),
+            StrategyDepth::Standard,
+            Variation::Balanced,
+        );
         assert!(prompt.contains("UTTERLY DIFFERENT"));
         assert!(prompt.contains("bolded"));
         assert!(prompt.contains("Express with SQLite"));
         assert!(prompt.contains("Fastify with PostgreSQL"));
     }
 
+    #[test]
+    fn test_build_strategy_prompt_with_diversity_axes() {
+        let existing = vec!["Use Express with SQLite".to_string()];
+        let axes = vec!["architecture".to_string(), "libraries".to_string()];
+        let prompt = build_strategy_prompt(
+            "Build a REST API",
+            &existing,
+            &axes,
+            &StackConstraints::default(),
+            StrategyDepth::Standard,
+            Variation::Balanced,
+        );
+        assert!(prompt.contains("on each of these axes: architecture, libraries"));
+        assert!(!prompt.contains("UTTERLY DIFFERENT"));
+        assert!(prompt.contains("Express with SQLite"));
+    }
+
+    #[test]
+    fn test_stack_constraints_in_prompts() {
+        let stack = StackConstraints {
+            language: Some("Rust".to_string()),
+            framework: Some("axum".to_string()),
+        };
+        let strategy_prompt = build_strategy_prompt(
+            "Build a REST API",
+            &[],
+            &[],
+            &stack,
+            StrategyDepth::Standard,
+            Variation::Balanced,
+        );
+        assert!(strategy_prompt.contains("You MUST use Rust with axum"));
+        // What a run records of its prompts keeps the per-instance parts open
+        let template = strategy_template("Build a REST API", &stack, StrategyDepth::Standard);
+        assert!(template.contains("User prompt: Build a REST API"));
+        assert!(template.contains("You MUST use Rust with axum"));
+        assert!(template.contains("{variation}") && template.contains("{exclusions}"));
+        let template = implementation_template("Build a REST API", &stack);
+        assert!(template.contains("You MUST use Rust with axum"));
+        assert!(template.contains("{strategy}\n\n{instructions}"));
+        let impl_prompt = build_implementation_prompt(
+            "Build a REST API",
+            "Use SQLx",
+            &["Also write integration tests", " "],
+            &[],
+            &stack,
+            ExclusionStrength::Normal,
+        );
+        assert!(impl_prompt.contains("You MUST use Rust with axum"));
+        assert!(impl_prompt.contains(
+            "Use SQLx\n\nADDITIONAL INSTRUCTIONS (follow these as well as your strategy):\nAlso write integration tests\n\nSTACK"
+        ));
+        let plain = build_implementation_prompt(
+            "t",
+            "s",
+            &[],
+            &[],
+            &StackConstraints::default(),
+            ExclusionStrength::Normal,
+        );
+        assert!(!plain.contains("ADDITIONAL INSTRUCTIONS"));
+        assert!(!build_implementation_prompt(
+            "t",
+            "s",
+            &[],
+            &[],
+            &StackConstraints::default(),
+            ExclusionStrength::Normal
+        )
+        .contains("STACK CONSTRAINT"));
+    }
+
+    #[test]
+    fn test_implementation_prompt_exclusion_strength() {
+        let excluded = vec!["Use **Diesel**".to_string()];
+        let prompt = |strength| {
+            build_implementation_prompt(
+                "t",
+                "s",
+                &[],
+                &excluded,
+                &StackConstraints::default(),
+                strength,
+            )
+        };
+        assert!(prompt(ExclusionStrength::Normal).contains("FORBIDDEN APPROACHES"));
+        assert!(prompt(ExclusionStrength::Strict).contains("do not borrow"));
+        assert!(prompt(ExclusionStrength::Soft).contains("OTHER APPROACHES"));
+        let independent = prompt(ExclusionStrength::None);
+        assert!(!independent.contains("Diesel"));
+        assert!(!independent.contains("APPROACHES"));
+    }
+
     #[test]
     fn test_parse_strategy() {
         let response =
@@ -208,6 +933,99 @@ mod tests {
         assert_eq!(strategy.highlights, vec!["Actix-web", "SQLx"]);
     }
 
+    #[test]
+    fn test_parse_strategy_json() {
+        let response = "```json\n{\"summary\": \"Use **Actix-web** with `sqlx`.\", \"highlights\": [\"Actix-web server\", \"compile-time SQL\"], \"steps\": [\"Scaffold the app\", \"Add routes\"]}\n```";
+        let strategy = parse_strategy(response);
+        assert_eq!(
+            strategy.markdown,
+            "Use **Actix-web** with `sqlx`.\n\n1. Scaffold the app\n2. Add routes"
+        );
+        assert_eq!(
+            strategy.highlights,
+            vec!["Actix-web server", "compile-time SQL"]
+        );
+
+        // Without highlights, the bold markers in the summary are used
+        assert_eq!(strategy.title, "Use Actix-web with sqlx");
+
+        let strategy = parse_strategy(
+            r#"{"title": "Axum with an in-memory store", "summary": "Use **Axum**."}"#,
+        );
+        assert_eq!(strategy.markdown, "Use **Axum**.");
+        assert_eq!(strategy.highlights, vec!["Axum"]);
+        assert_eq!(strategy.title, "Axum with an in-memory store");
+    }
+
+    #[test]
+    fn test_variation() {
+        let stack = StackConstraints::default();
+        let depth = StrategyDepth::Standard;
+        let prompt =
+            build_strategy_prompt("Cache it", &[], &[], &stack, depth, Variation::Adventurous);
+        assert!(prompt.contains("STYLE: Be adventurous"));
+        let prompt =
+            build_strategy_prompt("Cache it", &[], &[], &stack, depth, Variation::Balanced);
+        assert!(!prompt.contains("STYLE") && !prompt.contains("{variation}"));
+    }
+
+    #[test]
+    fn test_strategy_depth() {
+        let prompt = |depth| {
+            build_strategy_prompt(
+                "Cache it",
+                &[],
+                &[],
+                &StackConstraints::default(),
+                depth,
+                Variation::Balanced,
+            )
+        };
+        let brief = prompt(StrategyDepth::Brief);
+        assert!(brief.contains(r#""summary": "<your approach in one sentence>""#));
+        assert!(!brief.contains("steps"));
+        assert!(prompt(StrategyDepth::Standard).contains("at most 5 short \"steps\""));
+        let detailed = prompt(StrategyDepth::Detailed);
+        assert!(detailed.contains(r#""risks": ["#));
+        assert!(!detailed.contains("{length}"));
+
+        let strategy = parse_strategy(
+            r#"{"summary": "Add **Redis**.", "steps": ["Add a client"], "risks": ["Stale reads: expire keys"]}"#,
+        );
+        assert_eq!(
+            strategy.markdown,
+            "Add **Redis**.\n\n1. Add a client\n\nRisks:\n- Stale reads: expire keys"
+        );
+        let strategy = parse_strategy(r#"{"summary": "Add Redis.", "risks": [" "]}"#);
+        assert_eq!(strategy.markdown, "Add Redis.");
+    }
+
+    #[test]
+    fn test_guessed_highlights() {
+        let strategy =
+            Strategy::parse("## Event sourcing\nStore every change.\n## CQRS read models");
+        assert_eq!(
+            strategy.highlights,
+            vec!["Event sourcing", "CQRS read models"]
+        );
+
+        let strategy = Strategy::parse(
+            "Write the server with Axum and store data in PostgreSQL via `sqlx`. Deploy to Fly.io.",
+        );
+        assert_eq!(
+            strategy.highlights,
+            vec!["sqlx", "Axum", "PostgreSQL", "Fly.io"]
+        );
+
+        let strategy = Strategy::parse(
+            "write a small shell script that polls the directory every few seconds and rebuilds when anything changes. then stop.",
+        );
+        assert_eq!(
+            strategy.highlights,
+            vec!["write a small shell script that polls the directory every…"]
+        );
+    }
+
     #[test]
     fn test_parse_strategy_fallback() {
         let response = "Some response without the prefix";
@@ -215,6 +1033,19 @@ mod tests {
         assert_eq!(strategy.markdown, "Some response without the prefix");
     }
 
+    #[test]
+    fn test_render_claude_md() {
+        assert_eq!(
+            render_claude_md(
+                "{instance}: {task} via {strategy} {x}",
+                "Use {strategy}",
+                "{task}",
+                2
+            ),
+            "C2: Use {strategy} via {task} {x}"
+        );
+    }
+
     #[test]
     fn test_strategy_display() {
         let strategy = Strategy::parse("Use **bold** text");
@@ -0,0 +1,133 @@
+use crate::strategy::StackConstraints;
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Longest a `--strategy-source` command may take over one answer
+const SOURCE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// What a `--strategy-source` command answers with
+#[derive(Deserialize)]
+struct SourceReply {
+    /// Strategy markdown, one per candidate
+    strategies: Vec<String>,
+}
+
+/// `--strategy-source`: a shell command that proposes phase 1 strategies.
+/// It is asked again whenever the strategies it gave so far are used up,
+/// and once it has none left the strategy agent takes over.
+pub struct StrategySource {
+    command: String,
+    pending: VecDeque<String>,
+    exhausted: bool,
+}
+
+impl StrategySource {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            pending: VecDeque::new(),
+            exhausted: false,
+        }
+    }
+
+    /// The next strategy from the command, or `None` once it has no more.
+    /// The command runs in the current directory and reads the task, the
+    /// strategies so far and how many more are wanted as JSON on stdin; it
+    /// writes `{"strategies": ["...", ...]}` on stdout.
+    pub async fn next(
+        &mut self,
+        task: &str,
+        existing: &[String],
+        wanted: usize,
+        diversify_on: &[String],
+        stack: &StackConstraints,
+    ) -> anyhow::Result<Option<String>> {
+        if self.pending.is_empty() && !self.exhausted {
+            let request = json!({
+                "task": task,
+                "existing": existing,
+                "count": wanted,
+                "diversify_on": diversify_on,
+                "language": stack.language,
+                "framework": stack.framework,
+            });
+            let strategies = self.ask(&request.to_string()).await?;
+            self.exhausted = strategies.is_empty();
+            self.pending.extend(strategies);
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    async fn ask(&self, request: &str) -> anyhow::Result<Vec<String>> {
+        tracing::info!(command = %self.command, "Asking the strategy source");
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start the strategy source")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A source that doesn't read its input can still answer
+            let _ = stdin.write_all(request.as_bytes()).await;
+        }
+        let output = tokio::time::timeout(SOURCE_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow::anyhow!("The strategy source timed out"))??;
+        if !output.status.success() {
+            anyhow::bail!(
+                "The strategy source failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let reply: SourceReply = serde_json::from_slice(&output.stdout)
+            .context("The strategy source's answer is not valid JSON")?;
+        Ok(reply
+            .strategies
+            .into_iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_next() {
+        // Answers once, then has nothing more
+        let dir = tempfile::tempdir().unwrap();
+        let flag = dir.path().join("asked");
+        let command = format!(
+            r#"test -e {flag} && echo '{{"strategies": []}}' && exit; touch {flag}; grep -q '"count":3' && echo '{{"strategies": ["Cache it", " "]}}'"#,
+            flag = flag.display()
+        );
+        let mut source = StrategySource::new(&command);
+        let stack = StackConstraints::default();
+
+        let first = source.next("Speed it up", &[], 3, &[], &stack).await;
+        assert_eq!(first.unwrap().as_deref(), Some("Cache it"));
+        let existing = ["Cache it".to_string()];
+        let second = source.next("Speed it up", &existing, 2, &[], &stack).await;
+        assert_eq!(second.unwrap(), None);
+        let third = source.next("Speed it up", &existing, 2, &[], &stack).await;
+        assert_eq!(third.unwrap(), None);
+
+        let mut broken = StrategySource::new("echo nope");
+        assert!(broken
+            .next("Speed it up", &[], 1, &[], &stack)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::layout::RunLayout;
+use anyhow::Context;
+use crossterm::style::{Color, Stylize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Suffix of the transcript each instance streams into the run directory
+/// as its session runs, e.g. `C0-transcript.log`
+pub const TRANSCRIPT_LOG_SUFFIX: &str = "transcript.log";
+
+/// How often the transcripts are checked for new output
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Prefix colors, by instance id
+const COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+/// A transcript being followed
+struct FollowedLog {
+    path: PathBuf,
+    id: usize,
+    label: String,
+    /// Bytes of the file already read
+    offset: u64,
+    /// Read output not yet ended by a newline
+    partial: Vec<u8>,
+}
+
+impl FollowedLog {
+    /// Print the lines added since the last call
+    fn print_new(&mut self, width: usize) -> std::io::Result<()> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let read = file.read_to_end(&mut self.partial)?;
+        self.offset += read as u64;
+        let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        let complete: Vec<u8> = self.partial.drain(..=end).collect();
+        for line in String::from_utf8_lossy(&complete).lines() {
+            self.print_line(line, width);
+        }
+        Ok(())
+    }
+
+    /// Print output the instance never ended with a newline
+    fn print_rest(&mut self, width: usize) {
+        if !self.partial.is_empty() {
+            let rest = String::from_utf8_lossy(&self.partial).into_owned();
+            self.print_line(&rest, width);
+            self.partial.clear();
+        }
+    }
+
+    fn print_line(&self, line: &str, width: usize) {
+        let prefix = format!("{:<width$} |", self.label, width = width);
+        println!("{} {}", prefix.with(color(self.id)).bold(), line);
+    }
+}
+
+/// The color instance `id`'s lines are prefixed in
+pub fn color(id: usize) -> Color {
+    COLORS[id % COLORS.len()]
+}
+
+/// Instance id and label from a transcript log's file name, e.g.
+/// `C0-alpha-transcript.log` is instance 0, `alpha`
+pub fn parse_log_name(file_name: &str) -> Option<(usize, String)> {
+    let prefix = file_name
+        .strip_suffix(TRANSCRIPT_LOG_SUFFIX)?
+        .strip_suffix('-')?
+        .strip_prefix('C')?;
+    let (id, name) = match prefix.split_once('-') {
+        Some((id, name)) => (id, Some(name)),
+        None => (prefix, None),
+    };
+    let id: usize = id.parse().ok()?;
+    Some((id, crate::names::label(id, name)))
+}
+
+/// `actually tail`: print the transcripts streamed into `run_dir` as they
+/// grow, interleaved line by line with each line prefixed by its instance
+/// in that instance's color. Only `instance` is followed if given. A
+/// running run is followed until it writes its results; a finished one is
+/// printed and left.
+pub async fn run(run_dir: &Path, instance: Option<usize>) -> anyhow::Result<()> {
+    if !run_dir.is_dir() {
+        anyhow::bail!("{} is not a run directory", run_dir.display());
+    }
+    let mut logs: Vec<FollowedLog> = Vec::new();
+    loop {
+        // Checked before reading, so nothing written before the run ended is missed
+        let finished = run_dir.join("results.json").exists();
+
+        let mut paths = Vec::new();
+        for dir in RunLayout::of(run_dir).artifact_dirs(run_dir) {
+            let entries = std::fs::read_dir(&dir)
+                .with_context(|| format!("Failed to read {}", dir.display()))?;
+            paths.extend(entries.flatten().map(|entry| entry.path()));
+        }
+        for path in paths {
+            let Some((id, label)) = path
+                .file_name()
+                .and_then(|name| parse_log_name(&name.to_string_lossy()))
+            else {
+                continue;
+            };
+            if instance.is_some_and(|wanted| wanted != id) || logs.iter().any(|l| l.path == path) {
+                continue;
+            }
+            logs.push(FollowedLog {
+                path,
+                id,
+                label,
+                offset: 0,
+                partial: Vec::new(),
+            });
+            logs.sort_by_key(|log| log.id);
+        }
+
+        let width = logs.iter().map(|log| log.label.len()).max().unwrap_or(0);
+        for log in &mut logs {
+            if let Err(e) = log.print_new(width) {
+                eprintln!("Failed to read {}: {}", log.path.display(), e);
+            }
+        }
+
+        if finished {
+            for log in &mut logs {
+                log.print_rest(width);
+            }
+            if logs.is_empty() {
+                println!("No transcripts in {}", run_dir.display());
+            }
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_name() {
+        assert_eq!(
+            parse_log_name("C0-transcript.log"),
+            Some((0, "C0".to_string()))
+        );
+        assert_eq!(
+            parse_log_name("C12-event-sourced-transcript.log"),
+            Some((12, "event-sourced".to_string()))
+        );
+        assert_eq!(parse_log_name("C0-verify.log"), None);
+        assert_eq!(parse_log_name("Cx-transcript.log"), None);
+    }
+}
@@ -0,0 +1,217 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+/// What a key press did to a `TextEditor`
+#[derive(Debug, PartialEq, Eq)]
+pub enum EditOutcome {
+    /// Still editing
+    Editing,
+    /// Ctrl+S: keep the text
+    Save(String),
+    /// Esc: throw the changes away
+    Cancel,
+}
+
+/// A small multi-line text editor drawn over a TUI, for tweaks not worth
+/// suspending the TUI for $EDITOR: typing, deleting, arrows and word wrap
+pub struct TextEditor {
+    text: Vec<char>,
+    /// Index into `text` the next character goes in at
+    cursor: usize,
+    /// First row shown
+    scroll: usize,
+    /// Width the text was last wrapped to, which Up/Down move within
+    width: usize,
+}
+
+impl TextEditor {
+    /// Edit `text`, starting at its end
+    pub fn new(text: &str) -> Self {
+        let text: Vec<char> = text.chars().collect();
+        Self {
+            cursor: text.len(),
+            text,
+            scroll: 0,
+            width: 80,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> EditOutcome {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return EditOutcome::Cancel,
+            KeyCode::Char('s') if control => return EditOutcome::Save(self.text()),
+            KeyCode::Char(_) if control => {}
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Enter => self.insert('\n'),
+            KeyCode::Tab => {
+                for _ in 0..4 {
+                    self.insert(' ');
+                }
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.text.len() => {
+                self.text.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.text.len()),
+            KeyCode::Up => self.move_rows(-1),
+            KeyCode::Down => self.move_rows(1),
+            KeyCode::Home => {
+                let rows = wrap(&self.text, self.width);
+                self.cursor = rows[cursor_row(&rows, self.cursor)].0;
+            }
+            KeyCode::End => {
+                let rows = wrap(&self.text, self.width);
+                let row = cursor_row(&rows, self.cursor);
+                self.cursor = row_end(&rows, row);
+            }
+            _ => {}
+        }
+        EditOutcome::Editing
+    }
+
+    fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    /// Move the cursor `delta` rows up or down, keeping its column where the
+    /// row is long enough
+    fn move_rows(&mut self, delta: isize) {
+        let rows = wrap(&self.text, self.width);
+        let row = cursor_row(&rows, self.cursor);
+        let Some(target) = row
+            .checked_add_signed(delta)
+            .filter(|&target| target < rows.len())
+        else {
+            return;
+        };
+        let column = self.cursor - rows[row].0;
+        self.cursor = (rows[target].0 + column).min(row_end(&rows, target));
+    }
+
+    /// Draw the editor as a box over `area`, titled `title`
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, title: &str) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", title))
+            .title_bottom(" Ctrl+S: save  Esc: cancel ");
+        let inner = block.inner(area);
+        self.width = (inner.width as usize).max(1);
+        let height = (inner.height as usize).max(1);
+
+        let rows = wrap(&self.text, self.width);
+        let row = cursor_row(&rows, self.cursor);
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + height {
+            self.scroll = row + 1 - height;
+        }
+        let lines: Vec<Line> = rows
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|&(start, end)| Line::from(self.text[start..end].iter().collect::<String>()))
+            .collect();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+        let column = (self.cursor - rows[row].0).min(self.width - 1);
+        frame.set_cursor_position((
+            inner.x + column as u16,
+            inner.y + (row - self.scroll) as u16,
+        ));
+    }
+}
+
+/// Split `text` into rows of at most `width` characters, breaking lines at
+/// their newlines and otherwise after the last space that fits (or mid-word
+/// when none does). Each row is a `(start, end)` range of `text`, newlines
+/// left out.
+fn wrap(text: &[char], width: usize) -> Vec<(usize, usize)> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+    loop {
+        let line_end = text[line_start..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map_or(text.len(), |offset| line_start + offset);
+        let mut start = line_start;
+        while line_end - start > width {
+            let limit = start + width;
+            let split = (start + 1..=limit)
+                .rev()
+                .find(|&i| text[i - 1] == ' ')
+                .unwrap_or(limit);
+            rows.push((start, split));
+            start = split;
+        }
+        rows.push((start, line_end));
+        if line_end == text.len() {
+            return rows;
+        }
+        line_start = line_end + 1;
+    }
+}
+
+/// The row the cursor at `cursor` is on
+fn cursor_row(rows: &[(usize, usize)], cursor: usize) -> usize {
+    rows.iter()
+        .rposition(|&(start, _)| start <= cursor)
+        .unwrap_or(0)
+}
+
+/// The last place the cursor can be on `row`: its end, or for a row that
+/// wraps onto the next, just before the next row starts
+fn row_end(rows: &[(usize, usize)], row: usize) -> usize {
+    let (start, end) = rows[row];
+    match rows.get(row + 1) {
+        Some(&(next, _)) if next == end && end > start => end - 1,
+        _ => end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_editor() {
+        let text: Vec<char> = "Use a cache layer\n\nthen measure".chars().collect();
+        assert_eq!(
+            wrap(&text, 10),
+            vec![(0, 6), (6, 12), (12, 17), (18, 18), (19, 24), (24, 31)]
+        );
+        assert_eq!(wrap(&"abcdefgh".chars().collect::<Vec<_>>(), 3).len(), 3);
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut editor = TextEditor::new("Use a cache\nthen measure");
+        editor.width = 20;
+        editor.handle_key(key(KeyCode::Up));
+        editor.handle_key(key(KeyCode::Home));
+        for c in "Do: ".chars() {
+            editor.handle_key(key(KeyCode::Char(c)));
+        }
+        editor.handle_key(key(KeyCode::End));
+        editor.handle_key(key(KeyCode::Backspace));
+        editor.handle_key(key(KeyCode::Down));
+        editor.handle_key(key(KeyCode::Left));
+        editor.handle_key(key(KeyCode::Delete));
+        assert_eq!(editor.text(), "Do: Use a cach\nthen measur");
+        assert_eq!(
+            editor.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+            EditOutcome::Save("Do: Use a cach\nthen measur".to_string())
+        );
+        assert_eq!(editor.handle_key(key(KeyCode::Esc)), EditOutcome::Cancel);
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// A session transcript as it is built, held to at most `max_bytes`
+/// (`--max-transcript-bytes`) by cutting out its middle. The first half of
+/// the cap keeps the start of the session as written; the rest keeps its
+/// latest output, so memory stays bounded however much a session prints.
+#[derive(Debug, Default)]
+pub struct Transcript {
+    max_bytes: Option<usize>,
+    /// Everything, until the transcript first outgrows the cap; then its start
+    head: String,
+    /// The latest output, once the middle has been cut out
+    tail: String,
+    /// Bytes cut out between `head` and `tail`
+    omitted: usize,
+}
+
+impl Transcript {
+    pub fn new(max_bytes: Option<usize>) -> Self {
+        Self {
+            max_bytes,
+            ..Default::default()
+        }
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        let Some(max) = self.max_bytes else {
+            self.head.push_str(text);
+            return;
+        };
+        if self.omitted == 0 && self.tail.is_empty() {
+            self.head.push_str(text);
+            if self.head.len() <= max {
+                return;
+            }
+            let cut = line_boundary_before(&self.head, max / 2);
+            self.tail = self.head.split_off(cut);
+        } else {
+            self.tail.push_str(text);
+        }
+
+        // Cut the tail back to half its share whenever it outgrows it, so
+        // the front of the string isn't moved on every push
+        let share = max.saturating_sub(self.head.len());
+        if self.tail.len() > share {
+            let start = line_boundary_after(&self.tail, self.tail.len() - share / 2);
+            self.tail.drain(..start);
+            self.omitted += start;
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        let mut buf = [0; 4];
+        self.push_str(c.encode_utf8(&mut buf));
+    }
+
+    pub fn into_string(self) -> String {
+        if self.omitted == 0 {
+            return self.head + &self.tail;
+        }
+        format!(
+            "{}\n=== TRUNCATED: {} bytes omitted (--max-transcript-bytes) ===\n{}",
+            self.head, self.omitted, self.tail
+        )
+    }
+}
+
+/// The `C<n>-transcript.log` a session streams its output to, held to
+/// `max_bytes` like `Transcript`. Output is appended as it comes until it
+/// fills the head's share of the cap; after that only the latest output is
+/// kept, in memory, and `finish` appends it. The file only ever grows, so
+/// `actually tail` can follow it to the end.
+pub struct LiveLog {
+    file: File,
+    max_bytes: Option<usize>,
+    written: usize,
+    /// Output since the log stopped being appended to, once it has
+    latest: Option<String>,
+    /// Bytes dropped from the front of `latest`
+    omitted: usize,
+}
+
+impl LiveLog {
+    /// Open `path` to append to, carrying on after what an earlier attempt
+    /// of the session wrote there
+    pub fn open(path: &Path, max_bytes: Option<usize>) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let written = file.metadata()?.len() as usize;
+        Ok(Self {
+            file,
+            max_bytes,
+            written,
+            latest: None,
+            omitted: 0,
+        })
+    }
+
+    /// Append `text` and a newline
+    pub fn write_line(&mut self, text: &str) -> io::Result<()> {
+        let line = format!("{}\n", text);
+        let Some(max) = self.max_bytes else {
+            return self.file.write_all(line.as_bytes());
+        };
+        if let Some(latest) = &mut self.latest {
+            latest.push_str(&line);
+            // Cut back to half its share whenever it outgrows it, as
+            // `Transcript` does
+            let share = max - max / 2;
+            if latest.len() > share {
+                let start = line_boundary_after(latest, latest.len() - share / 2);
+                latest.drain(..start);
+                self.omitted += start;
+            }
+            return Ok(());
+        }
+        if self.written + line.len() > max / 2 {
+            self.latest = Some(line);
+            return writeln!(
+                self.file,
+                "=== TRUNCATED: the latest output follows when the session ends (--max-transcript-bytes) ==="
+            );
+        }
+        self.written += line.len();
+        self.file.write_all(line.as_bytes())
+    }
+
+    /// Once the session is over, append the latest output of a log that
+    /// stopped being appended to
+    pub fn finish(mut self) -> io::Result<()> {
+        let Some(latest) = self.latest.take() else {
+            return Ok(());
+        };
+        if self.omitted > 0 {
+            writeln!(
+                self.file,
+                "=== TRUNCATED: {} bytes omitted (--max-transcript-bytes) ===",
+                self.omitted
+            )?;
+        }
+        self.file.write_all(latest.as_bytes())
+    }
+}
+
+/// `text` with its middle cut out to fit in `max_bytes`, if given
+pub fn truncate_middle(text: &str, max_bytes: Option<usize>) -> String {
+    let mut transcript = Transcript::new(max_bytes);
+    transcript.push_str(text);
+    transcript.into_string()
+}
+
+/// The end of the last full line within the first `limit` bytes of `text`,
+/// or the nearest character boundary if that line is longer than the limit
+fn line_boundary_before(text: &str, limit: usize) -> usize {
+    let mut cut = limit.min(text.len());
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    match text[..cut].rfind('\n') {
+        Some(newline) => newline + 1,
+        None => cut,
+    }
+}
+
+/// The start of the first full line from byte `from` of `text`, or the
+/// nearest character boundary if that would drop more than half of the rest
+fn line_boundary_after(text: &str, from: usize) -> usize {
+    let mut cut = from.min(text.len());
+    while !text.is_char_boundary(cut) {
+        cut += 1;
+    }
+    match text[cut..].find('\n') {
+        Some(newline) if newline < (text.len() - cut) / 2 => cut + newline + 1,
+        _ => cut,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_cap() {
+        let mut transcript = Transcript::new(Some(100));
+        transcript.push_str("=== PROMPT ===\nbuild it\n");
+        for i in 0..1000 {
+            transcript.push_str(&format!("line {}\n", i));
+            assert!(transcript.head.len() + transcript.tail.len() <= 100);
+        }
+        transcript.push_str("=== DONE ===\n");
+        let text = transcript.into_string();
+        assert!(text.starts_with("=== PROMPT ===\nbuild it\nline 0\n"));
+        assert!(text.contains(" bytes omitted (--max-transcript-bytes) ===\n"));
+        assert!(text.ends_with("line 999\n=== DONE ===\n"));
+
+        assert_eq!(truncate_middle("short\n", Some(100)), "short\n");
+        assert_eq!(truncate_middle(&"x".repeat(500), None).len(), 500);
+        // Multi-byte characters are never split
+        let cut = truncate_middle(&"é".repeat(100), Some(51));
+        assert!(cut.starts_with(&"é".repeat(12)));
+        assert!(cut.ends_with('é'));
+    }
+
+    #[test]
+    fn test_live_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("C0-transcript.log");
+        let mut log = LiveLog::open(&path, Some(200)).unwrap();
+        for i in 0..1000 {
+            log.write_line(&format!("line {}", i)).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() < 200);
+        }
+        let live = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            live.starts_with("line 0\n")
+                && live.ends_with("session ends (--max-transcript-bytes) ===\n")
+        );
+        log.finish().unwrap();
+        let finished = std::fs::read_to_string(&path).unwrap();
+        // Appended to, never rewritten, so whoever follows it misses nothing
+        assert!(finished.starts_with(&live));
+        assert!(finished.contains(" bytes omitted (--max-transcript-bytes) ===\n"));
+        assert!(finished.ends_with("line 999\n"));
+        assert!(finished.len() < 400);
+    }
+}
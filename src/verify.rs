@@ -0,0 +1,356 @@
+use crate::cancel::CancellationToken;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+/// Longest a verification command may run before it is stopped and failed
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Test commands by the project manifest that implies them, in order of
+/// preference when a workspace has several
+const TEST_COMMANDS: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo test"),
+    ("package.json", "npm test"),
+    ("pyproject.toml", "pytest"),
+];
+
+/// Build commands by project manifest, like `TEST_COMMANDS`
+const BUILD_COMMANDS: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo build --all-targets"),
+    ("package.json", "npm run build --if-present"),
+    ("pyproject.toml", "python -m compileall -q ."),
+];
+
+/// Lint commands by project manifest, like `TEST_COMMANDS`
+const LINT_COMMANDS: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo clippy --all-targets"),
+    ("package.json", "npx eslint ."),
+    ("pyproject.toml", "ruff check ."),
+];
+
+/// Coverage commands by project manifest, like `TEST_COMMANDS`; their
+/// summaries are what `parse_coverage` reads
+const COVERAGE_COMMANDS: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo llvm-cov --summary-only"),
+    (
+        "package.json",
+        "npx jest --coverage --coverageReporters=text-summary",
+    ),
+    ("pyproject.toml", "pytest --cov --cov-report=term"),
+];
+
+/// What a check run in a workspace is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckKind {
+    Build,
+    Test,
+    Lint,
+    Coverage,
+}
+
+impl CheckKind {
+    fn commands(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Build => BUILD_COMMANDS,
+            Self::Test => TEST_COMMANDS,
+            Self::Lint => LINT_COMMANDS,
+            Self::Coverage => COVERAGE_COMMANDS,
+        }
+    }
+
+    /// Suffix of the check's log file, `C<n>-<suffix>.log`
+    pub fn log_name(self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Test => "verify",
+            Self::Lint => "lint",
+            Self::Coverage => "coverage",
+        }
+    }
+}
+
+/// Whether and how a kind of check is run in each workspace after
+/// implementation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum VerifyMode {
+    /// Pick the command from the workspace's project files
+    Detect,
+    /// Run this shell command (`--verify`, `--build <command>`, ...)
+    Command(String),
+    /// Don't run the check (`--no-verify`, or no `--build`/`--lint`)
+    #[default]
+    Off,
+}
+
+impl VerifyMode {
+    /// The `kind` command to check the workspace in `dir` with, and whether
+    /// it was inferred from the project files rather than given
+    pub fn command_for(&self, kind: CheckKind, dir: &Path) -> Option<(String, bool)> {
+        match self {
+            Self::Detect => detect_command(kind, dir).map(|c| (c.to_string(), true)),
+            Self::Command(command) => Some((command.clone(), false)),
+            Self::Off => None,
+        }
+    }
+}
+
+/// Outcome of a check command run in an instance workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Check {
+    /// Shell command that was run
+    pub command: String,
+    /// Detected from the workspace's project files rather than given
+    pub inferred: bool,
+    pub passed: bool,
+    /// `None` if the command couldn't start or was stopped for taking too long
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Warnings and errors the linter reported (lint checks only)
+    #[serde(default)]
+    pub warnings: Option<usize>,
+    /// Line coverage percentage the tests reached (coverage checks only)
+    #[serde(default)]
+    pub coverage_pct: Option<f64>,
+}
+
+impl Check {
+    /// One-line description, e.g. "passed (`cargo test`, detected)" or
+    /// "failed, 3 warnings (`ruff check .`)"
+    pub fn describe(&self) -> String {
+        format!(
+            "{}{}{} (`{}`{})",
+            if self.passed { "passed" } else { "failed" },
+            match self.warnings {
+                Some(1) => ", 1 warning".to_string(),
+                Some(n) => format!(", {} warnings", n),
+                None => String::new(),
+            },
+            match self.coverage_pct {
+                Some(pct) => format!(", {:.1}% covered", pct),
+                None => String::new(),
+            },
+            self.command,
+            if self.inferred { ", detected" } else { "" }
+        )
+    }
+}
+
+/// `kind` command for the project in `dir`, guessed from its manifest
+pub fn detect_command(kind: CheckKind, dir: &Path) -> Option<&'static str> {
+    kind.commands()
+        .iter()
+        .find(|(manifest, _)| dir.join(manifest).is_file())
+        .map(|(_, command)| *command)
+}
+
+/// Count the problems in a linter's output: the summary count of ruff
+/// ("Found 3 errors.") or eslint ("✖ 3 problems"), otherwise the number of
+/// rustc-style `warning:`/`error:` diagnostics
+pub fn count_warnings(output: &str) -> usize {
+    let summary = output.lines().find_map(|line| {
+        let line = line.trim();
+        let count = line
+            .strip_prefix("Found ")
+            .filter(|rest| rest.contains(" error"))
+            .or_else(|| {
+                line.strip_prefix("✖ ")
+                    .filter(|rest| rest.contains(" problem"))
+            })?;
+        count.split_whitespace().next()?.parse().ok()
+    });
+    summary.unwrap_or_else(|| {
+        output
+            .lines()
+            .filter(|line| line.starts_with("warning:") || line.starts_with("error:"))
+            .filter(|line| {
+                !line.contains(" generated ")
+                    && !line.contains("could not compile")
+                    && !line.contains("aborting due to")
+            })
+            .count()
+    })
+}
+
+/// Line coverage percentage from a coverage summary: jest's
+/// "Lines : 81.25% ( 13/16 )", or the `TOTAL` row of pytest-cov (whose only
+/// percentage is lines) and cargo-llvm-cov (whose third is)
+pub fn parse_coverage(output: &str) -> Option<f64> {
+    let percentages = |line: &str| -> Vec<f64> {
+        line.split_whitespace()
+            .filter_map(|word| word.strip_suffix('%')?.parse().ok())
+            .collect()
+    };
+    output.lines().find_map(|line| {
+        let line = line.trim();
+        if line.starts_with("Lines") && line.contains(':') {
+            percentages(line).first().copied()
+        } else if line.starts_with("TOTAL") {
+            let found = percentages(line);
+            match found.len() {
+                0 => None,
+                1 | 2 => found.last().copied(),
+                _ => Some(found[2]),
+            }
+        } else {
+            None
+        }
+    })
+}
+
+/// Run the `kind` check `command` with `sh -c` in `dir`, writing its output
+/// to `log`. Returns `None` if `cancel` fires first.
+pub async fn run_check(
+    kind: CheckKind,
+    command: &str,
+    inferred: bool,
+    dir: &Path,
+    env: &HashMap<String, String>,
+    log: &Path,
+    cancel: &CancellationToken,
+) -> Option<Check> {
+    tracing::debug!(?kind, command, dir = ?dir, "Running check");
+    let output = run_logged(command, dir, env, log, cancel).await?;
+    let warnings = (kind == CheckKind::Lint && output.exit_code.is_some())
+        .then(|| count_warnings(&output.text));
+    let coverage_pct = (kind == CheckKind::Coverage)
+        .then(|| parse_coverage(&output.text))
+        .flatten();
+
+    Some(Check {
+        command: command.to_string(),
+        inferred,
+        passed: output.exit_code == Some(0),
+        exit_code: output.exit_code,
+        duration_ms: output.duration_ms,
+        warnings,
+        coverage_pct,
+    })
+}
+
+/// What a command run by `run_logged` printed and how it ended
+pub struct CommandOutput {
+    /// `None` if it couldn't start or was stopped for taking too long
+    pub exit_code: Option<i32>,
+    /// The command line followed by its stdout and stderr
+    pub text: String,
+    pub duration_ms: u64,
+}
+
+/// Run `command` with `sh -c` in `dir`, writing its output to `log`, for at
+/// most `VERIFY_TIMEOUT`. Returns `None` if `cancel` fires first.
+pub async fn run_logged(
+    command: &str,
+    dir: &Path,
+    env: &HashMap<String, String>,
+    log: &Path,
+    cancel: &CancellationToken,
+) -> Option<CommandOutput> {
+    let started = Instant::now();
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .envs(env)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    let (exit_code, text) = tokio::select! {
+        output = tokio::time::timeout(VERIFY_TIMEOUT, output) => match output {
+            Ok(Ok(output)) => (
+                output.status.code(),
+                format!(
+                    "$ {}\n{}{}",
+                    command,
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            ),
+            Ok(Err(e)) => (None, format!("$ {}\nFailed to start: {}\n", command, e)),
+            Err(_) => (
+                None,
+                format!("$ {}\nStopped after {}s\n", command, VERIFY_TIMEOUT.as_secs()),
+            ),
+        },
+        _ = cancel.cancelled() => return None,
+    };
+    if let Err(e) = std::fs::write(log, &text) {
+        tracing::warn!(log = ?log, error = %e, "Failed to write check log");
+    }
+    Some(CommandOutput {
+        exit_code,
+        text,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_command() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_command(CheckKind::Test, dir.path()), None);
+        assert_eq!(
+            VerifyMode::Detect.command_for(CheckKind::Test, dir.path()),
+            None
+        );
+
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(
+            detect_command(CheckKind::Test, dir.path()),
+            Some("npm test")
+        );
+        assert_eq!(
+            detect_command(CheckKind::Lint, dir.path()),
+            Some("npx eslint .")
+        );
+        assert_eq!(
+            detect_command(CheckKind::Build, dir.path()),
+            Some("npm run build --if-present")
+        );
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        assert_eq!(
+            VerifyMode::Detect.command_for(CheckKind::Test, dir.path()),
+            Some(("cargo test".to_string(), true))
+        );
+        assert_eq!(
+            VerifyMode::Command("make check".to_string()).command_for(CheckKind::Test, dir.path()),
+            Some(("make check".to_string(), false))
+        );
+        assert_eq!(
+            VerifyMode::Off.command_for(CheckKind::Lint, dir.path()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_count_warnings() {
+        let clippy = "warning: unused variable: `x`\n --> src/main.rs:2:9\n\
+                      error: this looks like a bug\n\
+                      warning: `demo` (bin \"demo\") generated 1 warning\n\
+                      error: could not compile `demo` due to 1 previous error\n";
+        assert_eq!(count_warnings(clippy), 2);
+        assert_eq!(
+            count_warnings("src/a.py:1:1: F401 unused\nFound 4 errors.\n"),
+            4
+        );
+        assert_eq!(count_warnings("\n✖ 7 problems (2 errors, 5 warnings)\n"), 7);
+        assert_eq!(count_warnings("All checks passed!\n"), 0);
+    }
+
+    #[test]
+    fn test_parse_coverage() {
+        let llvm_cov = "Filename   Regions  Missed Regions  Cover   Functions  Missed Functions  Executed  Lines  Missed Lines  Cover\n\
+                        TOTAL          40               8  80.00%          10                 1    90.00%    120            30  75.00%\n";
+        assert_eq!(parse_coverage(llvm_cov), Some(75.0));
+        let jest = "Statements   : 83.33% ( 15/18 )\nLines        : 81.25% ( 13/16 )\n";
+        assert_eq!(parse_coverage(jest), Some(81.25));
+        assert_eq!(parse_coverage("TOTAL      52      7    87%\n"), Some(87.0));
+        assert_eq!(parse_coverage("no coverage here\n"), None);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::judge::parse_verdict;
+use crate::session::ClaudeSession;
+use crate::strategy::build_vote_prompt;
+use futures::future::join_all;
+
+/// Have every strategy's author rank all the other strategies, and tally the
+/// ballots as Borda points: with `k` other strategies a voter's first choice
+/// earns `k` points, its second `k - 1`, and so on. `strategies` pairs each
+/// instance id with its strategy text; the returned points are in the same
+/// order. Voters whose query fails abstain.
+pub async fn vote(session: &ClaudeSession, task: &str, strategies: &[(usize, &str)]) -> Vec<usize> {
+    let ballots = strategies.iter().map(|&(voter, own)| {
+        let others: Vec<(usize, &str)> = strategies
+            .iter()
+            .copied()
+            .filter(|(id, _)| *id != voter)
+            .collect();
+        async move {
+            let listing = others
+                .iter()
+                .map(|(id, strategy)| format!("### C{}\n{}", id, strategy))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let prompt = build_vote_prompt(task, own, &listing);
+            let ids: Vec<usize> = others.iter().map(|(id, _)| *id).collect();
+            match session.query_read_only(&prompt).await {
+                Ok(response) => Some(parse_verdict(&response, &ids).ranking),
+                Err(e) => {
+                    tracing::warn!(voter, error = %e, "Strategy vote failed");
+                    None
+                }
+            }
+        }
+    });
+
+    let ballots: Vec<Vec<usize>> = join_all(ballots).await.into_iter().flatten().collect();
+    let ids: Vec<usize> = strategies.iter().map(|(id, _)| *id).collect();
+    borda_points(&ids, &ballots)
+}
+
+/// Borda count over `ballots` (each a ranking of ids, best first)
+fn borda_points(ids: &[usize], ballots: &[Vec<usize>]) -> Vec<usize> {
+    let mut points = vec![0; ids.len()];
+    for ballot in ballots {
+        for (rank, id) in ballot.iter().enumerate() {
+            if let Some(pos) = ids.iter().position(|i| i == id) {
+                points[pos] += ballot.len() - rank;
+            }
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borda_points() {
+        // Three voters, each ranking the other two
+        let ballots = vec![vec![2, 1], vec![2, 0], vec![0, 1]];
+        assert_eq!(borda_points(&[0, 1, 2], &ballots), vec![3, 2, 4]);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::checkpoint::Checkpoint;
+use crate::conductor::InstanceResult;
+use crate::git;
+use crate::http::{self, Connection};
+use crate::layout::RunLayout;
+use crate::names;
+use crate::output;
+use crate::tail::parse_log_name;
+use anyhow::Context;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Where `actually web` listens by default
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7879";
+
+/// The dashboard page; it polls the JSON endpoints below
+const PAGE: &str = include_str!("web.html");
+
+/// Transcripts are sent from this many bytes before their end
+const MAX_TRANSCRIPT_VIEW_BYTES: usize = 256 * 1024;
+
+/// What the run directory says about one instance
+struct InstanceView {
+    label: String,
+    title: String,
+    /// Where it got to: `pending`, `running`, `succeeded` or `failed`
+    state: &'static str,
+    result: Option<InstanceResult>,
+    /// Streamed transcript in the run directory, once the session started
+    transcript_log: Option<PathBuf>,
+    workspace: PathBuf,
+}
+
+/// The run as it stands, read afresh from `run_dir` for every request
+struct RunView {
+    prompt: String,
+    phase: String,
+    finished: bool,
+    instances: BTreeMap<usize, InstanceView>,
+}
+
+impl RunView {
+    fn load(run_dir: &Path) -> Self {
+        let checkpoint = Checkpoint::load(run_dir).ok();
+        let results: Option<Vec<InstanceResult>> =
+            std::fs::read_to_string(run_dir.join("results.json"))
+                .ok()
+                .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+                .and_then(|json| serde_json::from_value(json["instances"].clone()).ok());
+
+        let layout = RunLayout::of(run_dir);
+        let mut logs: BTreeMap<usize, (String, PathBuf)> = BTreeMap::new();
+        let entries = layout
+            .artifact_dirs(run_dir)
+            .into_iter()
+            .flat_map(std::fs::read_dir)
+            .flatten()
+            .flatten();
+        for entry in entries {
+            let path = entry.path();
+            if let Some((id, label)) = path
+                .file_name()
+                .and_then(|name| parse_log_name(&name.to_string_lossy()))
+            {
+                logs.insert(id, (label, path));
+            }
+        }
+
+        let workspace_dir = checkpoint
+            .as_ref()
+            .and_then(|c| c.workspace_dir.clone())
+            .unwrap_or_else(|| run_dir.to_path_buf());
+        let mut instances = BTreeMap::new();
+        let strategies = checkpoint
+            .iter()
+            .flat_map(|c| c.strategies.iter().enumerate());
+        for (id, strategy) in strategies.filter(|(_, s)| !s.skipped) {
+            let log = logs.get(&id);
+            let label = log.map_or_else(|| names::label(id, None), |(label, _)| label.clone());
+            let name = log.and_then(|(label, _)| label_name(id, label));
+            let workspace = layout.workspace(&workspace_dir, id, name);
+            instances.insert(
+                id,
+                InstanceView {
+                    label,
+                    title: strategy.title.clone(),
+                    state: if log.is_some() { "running" } else { "pending" },
+                    result: checkpoint.as_ref().and_then(|c| c.finished_instance(id)),
+                    transcript_log: log.map(|(_, path)| path.clone()),
+                    workspace,
+                },
+            );
+        }
+        // Results cover the synthesized instance too, which the checkpoint doesn't
+        for result in results.iter().flatten() {
+            let id = result.instance_id;
+            let view = instances.entry(id).or_insert_with(|| InstanceView {
+                label: String::new(),
+                title: result
+                    .strategy
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                state: "pending",
+                result: None,
+                transcript_log: logs.get(&id).map(|(_, path)| path.clone()),
+                workspace: PathBuf::new(),
+            });
+            view.result = Some(result.clone());
+        }
+        for view in instances.values_mut() {
+            if let Some(result) = &view.result {
+                view.label = result.label();
+                view.state = if result.success {
+                    "succeeded"
+                } else {
+                    "failed"
+                };
+                if !result.workspace_path.is_empty() {
+                    view.workspace = output::recorded_workspace(&result.workspace_path, run_dir);
+                }
+            }
+        }
+
+        Self {
+            prompt: checkpoint
+                .as_ref()
+                .map(|c| c.prompt.clone())
+                .unwrap_or_default(),
+            phase: checkpoint
+                .as_ref()
+                .and_then(|c| serde_json::to_value(c.phase).ok())
+                .and_then(|phase| phase.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            finished: results.is_some(),
+            instances,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let instances: Vec<Value> = self
+            .instances
+            .iter()
+            .map(|(id, view)| {
+                let result = view.result.as_ref();
+                json!({
+                    "id": id,
+                    "label": view.label,
+                    "title": view.title,
+                    "state": view.state,
+                    "cost_usd": result.and_then(|r| r.cost_usd),
+                    "duration_ms": result.and_then(|r| r.duration_ms),
+                    "num_turns": result.and_then(|r| r.num_turns),
+                    "error": result.and_then(|r| r.error.clone()),
+                    "diffstat": result.and_then(|r| r.diffstat),
+                })
+            })
+            .collect();
+        let costs: Vec<f64> = self
+            .instances
+            .values()
+            .filter_map(|view| view.result.as_ref()?.cost_usd)
+            .collect();
+        json!({
+            "prompt": self.prompt,
+            "phase": self.phase,
+            "finished": self.finished,
+            "cost_usd": (!costs.is_empty()).then(|| costs.iter().sum::<f64>()),
+            "instances": instances,
+        })
+    }
+}
+
+/// Name of the instance whose files carry `label`, the inverse of the
+/// labels `parse_log_name` gives
+fn label_name(id: usize, label: &str) -> Option<&str> {
+    (label != names::label(id, None)).then_some(label)
+}
+
+/// What a request asks for
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    Page,
+    State,
+    Transcript(usize),
+    Diff(usize),
+}
+
+impl Route {
+    fn parse(method: &str, path: &str) -> Option<Self> {
+        let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, parts.as_slice()) {
+            ("GET", [""]) => Some(Self::Page),
+            ("GET", ["api", "state"]) => Some(Self::State),
+            ("GET", ["api", "instances", id, "transcript"]) => {
+                id.parse().ok().map(Self::Transcript)
+            }
+            ("GET", ["api", "instances", id, "diff"]) => id.parse().ok().map(Self::Diff),
+            _ => None,
+        }
+    }
+}
+
+/// `actually web`: serve a read-only browser dashboard of the run in
+/// `run_dir` (instance status, transcripts, diffs and costs), for watching
+/// a run on a remote machine
+pub async fn run(run_dir: &Path, listen: &str) -> anyhow::Result<()> {
+    if !run_dir.is_dir() {
+        anyhow::bail!("{} is not a run directory", run_dir.display());
+    }
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", listen))?;
+    println!(
+        "Dashboard for {} at http://{}",
+        run_dir.display(),
+        listener.local_addr()?
+    );
+    let local = listener.local_addr()?;
+    if http::is_exposed(local) {
+        eprintln!(
+            "Warning: {} can be reached from other machines, and the dashboard has no \
+             authentication: anyone who can reach it can read the run",
+            local
+        );
+    }
+    let run_dir = Arc::new(run_dir.to_path_buf());
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let run_dir = Arc::clone(&run_dir);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, local, &run_dir).await {
+                eprintln!("Request failed: {:#}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    local: SocketAddr,
+    run_dir: &Path,
+) -> anyhow::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let Some(request) = http::read_request(&mut stream).await? else {
+        return Ok(());
+    };
+    // The run's prompt, transcripts and code are for the user alone
+    if !request.is_local(local) {
+        return http::respond_forbidden(&mut stream).await;
+    }
+    let Some(route) = Route::parse(&request.method, &request.path) else {
+        return http::respond_error(&mut stream, "404 Not Found", "no such endpoint").await;
+    };
+    let view = RunView::load(run_dir);
+    let instance = match route {
+        Route::Transcript(id) | Route::Diff(id) => match view.instances.get(&id) {
+            Some(instance) => Some(instance),
+            None => {
+                return http::respond_error(&mut stream, "404 Not Found", "no such instance").await
+            }
+        },
+        Route::Page | Route::State => None,
+    };
+
+    match (route, instance) {
+        (Route::Page, _) => {
+            let page = PAGE.as_bytes();
+            http::respond(&mut stream, "200 OK", "text/html; charset=utf-8", page).await
+        }
+        (Route::State, _) => http::respond_json(&mut stream, "200 OK", &view.to_json()).await,
+        (Route::Transcript(_), Some(instance)) => {
+            let text = transcript(instance);
+            respond_text(&mut stream, &text).await
+        }
+        (Route::Diff(_), Some(instance)) => {
+            let text = diff(run_dir, instance).await;
+            respond_text(&mut stream, &text).await
+        }
+        _ => http::respond_error(&mut stream, "404 Not Found", "no such instance").await,
+    }
+}
+
+async fn respond_text(stream: &mut Connection, text: &str) -> anyhow::Result<()> {
+    let content_type = "text/plain; charset=utf-8";
+    http::respond(stream, "200 OK", content_type, text.as_bytes()).await
+}
+
+/// The end of the instance's streamed transcript, or of its session.log
+/// for runs from before transcripts were streamed
+fn transcript(instance: &InstanceView) -> String {
+    let path = instance
+        .transcript_log
+        .clone()
+        .unwrap_or_else(|| instance.workspace.join("session.log"));
+    let Ok(bytes) = std::fs::read(&path) else {
+        return String::new();
+    };
+    let start = bytes.len().saturating_sub(MAX_TRANSCRIPT_VIEW_BYTES);
+    let text = String::from_utf8_lossy(&bytes[start..]);
+    if start == 0 {
+        text.into_owned()
+    } else {
+        format!("[... {} earlier bytes not shown ...]\n{}", start, text)
+    }
+}
+
+/// The instance's exported patch, or while it is still working, the
+/// changes in its workspace so far
+async fn diff(run_dir: &Path, instance: &InstanceView) -> String {
+    if let Some(patch) = instance.result.as_ref().and_then(|r| r.patch.as_ref()) {
+        if let Ok(patch) = std::fs::read_to_string(run_dir.join(patch)) {
+            return patch;
+        }
+    }
+    git::working_diff(&instance.workspace)
+        .await
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_parse() {
+        assert_eq!(Route::parse("GET", "/"), Some(Route::Page));
+        assert_eq!(Route::parse("GET", "/api/state"), Some(Route::State));
+        assert_eq!(
+            Route::parse("GET", "/api/instances/2/diff"),
+            Some(Route::Diff(2))
+        );
+        assert_eq!(Route::parse("POST", "/api/state"), None);
+        assert_eq!(label_name(1, "C1"), None);
+        assert_eq!(label_name(1, "bravo"), Some("bravo"));
+    }
+
+    #[test]
+    fn test_remote_host() {
+        let request = |host: &str| http::Request {
+            method: "GET".to_string(),
+            path: "/api/state".to_string(),
+            host: Some(host.to_string()),
+            origin: None,
+            content_type: None,
+            body: Vec::new(),
+        };
+        let everywhere: SocketAddr = "0.0.0.0:7879".parse().unwrap();
+        assert!(request("192.168.1.5:7879").is_local(everywhere));
+        assert!(!request("rebound.example:7879").is_local(everywhere));
+        let loopback: SocketAddr = "127.0.0.1:7879".parse().unwrap();
+        assert!(!request("192.168.1.5:7879").is_local(loopback));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::conductor::InstanceResult;
+use crate::config::WinnerWeights;
+use crate::verify::Check;
+
+/// Score every successful instance on the factors there is data for and
+/// mark the best one as the suggested winner. Each factor is scaled to 0-1
+/// (1 being best among the instances) and combined as a weighted average;
+/// factors no instance has data for are left out. Needs at least two
+/// successful instances. Returns the suggested instance's id.
+pub fn suggest_winner(results: &mut [InstanceResult], weights: &WinnerWeights) -> Option<usize> {
+    for result in results.iter_mut() {
+        result.composite_score = None;
+        result.suggested_winner = false;
+    }
+    let candidates: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.success)
+        .map(|(i, _)| i)
+        .collect();
+    if candidates.len() < 2 {
+        return None;
+    }
+
+    let factors = factor_scores(results, &candidates);
+    let total_weight: f64 = factors
+        .iter()
+        .map(|(factor, _)| factor.weight(weights))
+        .sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+    for (j, &i) in candidates.iter().enumerate() {
+        let score: f64 = factors
+            .iter()
+            .map(|(factor, scores)| factor.weight(weights) * scores[j])
+            .sum();
+        results[i].composite_score = Some(score / total_weight);
+    }
+
+    // Ties go to the earlier instance
+    let best = candidates.iter().copied().reduce(|best, i| {
+        if results[i].composite_score > results[best].composite_score {
+            i
+        } else {
+            best
+        }
+    })?;
+    results[best].suggested_winner = true;
+    Some(results[best].instance_id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Factor {
+    Build,
+    Tests,
+    Lint,
+    Judge,
+    DiffSize,
+    Cost,
+}
+
+impl Factor {
+    fn weight(self, weights: &WinnerWeights) -> f64 {
+        match self {
+            Self::Build => weights.build,
+            Self::Tests => weights.tests,
+            Self::Lint => weights.lint,
+            Self::Judge => weights.judge,
+            Self::DiffSize => weights.diff_size,
+            Self::Cost => weights.cost,
+        }
+    }
+}
+
+/// Each factor with data, and the candidates' 0-1 scores on it (in
+/// `candidates` order; a candidate without data scores 0)
+fn factor_scores(results: &[InstanceResult], candidates: &[usize]) -> Vec<(Factor, Vec<f64>)> {
+    let passed = |check: &Option<Check>| check.as_ref().map(|c| c.passed as u8 as f64);
+    let n = candidates.len();
+    let raw: Vec<(Factor, Vec<Option<f64>>)> = vec![
+        (
+            Factor::Build,
+            candidates
+                .iter()
+                .map(|&i| passed(&results[i].build))
+                .collect(),
+        ),
+        (
+            Factor::Tests,
+            candidates
+                .iter()
+                .map(|&i| passed(&results[i].verification))
+                .collect(),
+        ),
+        (
+            Factor::Lint,
+            candidates
+                .iter()
+                .map(|&i| {
+                    let lint = results[i].lint.as_ref()?;
+                    Some(match lint.warnings {
+                        Some(warnings) => 1.0 / (1.0 + warnings as f64),
+                        None => lint.passed as u8 as f64,
+                    })
+                })
+                .collect(),
+        ),
+        (
+            Factor::Judge,
+            candidates
+                .iter()
+                .map(|&i| {
+                    let result = &results[i];
+                    match (result.judge_score, result.judge_rank) {
+                        (Some(score), _) => Some(score / 10.0),
+                        (None, Some(rank)) => Some(1.0 - (rank - 1) as f64 / (n - 1) as f64),
+                        (None, None) => None,
+                    }
+                })
+                .collect(),
+        ),
+        (
+            Factor::DiffSize,
+            smaller_is_better(
+                candidates
+                    .iter()
+                    .map(|&i| {
+                        // An empty diff did nothing, rather than the least
+                        let lines = results[i].diffstat.map(|d| d.insertions + d.deletions)?;
+                        (lines > 0).then_some(lines as f64)
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            Factor::Cost,
+            smaller_is_better(candidates.iter().map(|&i| results[i].cost_usd).collect()),
+        ),
+    ];
+    raw.into_iter()
+        .filter(|(_, scores)| scores.iter().any(Option::is_some))
+        .map(|(factor, scores)| {
+            let scores = scores
+                .into_iter()
+                .map(|s| s.unwrap_or(0.0).clamp(0.0, 1.0))
+                .collect();
+            (factor, scores)
+        })
+        .collect()
+}
+
+/// Scale amounts where less is better so the smallest scores 1 and the
+/// others the fraction of it they are (half as much cost: 0.5)
+fn smaller_is_better(amounts: Vec<Option<f64>>) -> Vec<Option<f64>> {
+    let min = amounts
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    amounts
+        .into_iter()
+        .map(|amount| amount.map(|a| if a <= 0.0 { 1.0 } else { min.max(0.0) / a }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::DiffStat;
+
+    fn result(id: usize, success: bool, cost: f64, lines: usize) -> InstanceResult {
+        InstanceResult {
+            instance_id: id,
+            success,
+            cost_usd: Some(cost),
+            diffstat: Some(DiffStat {
+                files_changed: 1,
+                insertions: lines,
+                deletions: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_suggest_winner() {
+        let weights = WinnerWeights::default();
+        let mut results = vec![
+            result(0, true, 2.0, 400),
+            result(1, true, 1.0, 100),
+            result(2, false, 0.1, 10),
+        ];
+        assert_eq!(suggest_winner(&mut results, &weights), Some(1));
+        assert!(results[1].suggested_winner && !results[0].suggested_winner);
+        assert_eq!(results[1].composite_score, Some(1.0));
+        assert_eq!(results[2].composite_score, None);
+
+        // A judge ranking outweighs being cheaper and smaller
+        let mut results = vec![result(0, true, 2.0, 400), result(1, true, 1.0, 100)];
+        results[0].judge_rank = Some(1);
+        results[1].judge_rank = Some(2);
+        assert_eq!(suggest_winner(&mut results, &weights), Some(0));
+
+        // Changing nothing doesn't make for the smallest diff
+        let mut results = vec![result(0, true, 1.0, 0), result(1, true, 1.0, 100)];
+        assert_eq!(suggest_winner(&mut results, &weights), Some(1));
+
+        let mut results = vec![result(0, true, 1.0, 100)];
+        assert_eq!(suggest_winner(&mut results, &weights), None);
+    }
+}
@@ -1,3 +1,12 @@
+use crate::api_log::API_LOG_FILE;
+use crate::config::BuildCacheConfig;
+use crate::layout::{self, RunLayout};
+use crate::names;
+use crate::protect::Protection;
+use ignore::WalkBuilder;
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -6,6 +15,52 @@ use thiserror::Error;
 pub enum WorkspaceError {
     #[error("Failed to create workspace directory: {0}")]
     CreateFailed(#[from] std::io::Error),
+    #[error("Failed to walk seed directory: {0}")]
+    SeedWalk(#[from] ignore::Error),
+    #[error("Failed to copy {0} into workspace: {1}")]
+    SeedCopy(PathBuf, std::io::Error),
+    #[error("Failed to copy {0} back into the run directory: {1}")]
+    CopyBack(PathBuf, std::io::Error),
+}
+
+/// Files of a workspace that record its session rather than its code, kept
+/// when a workspace is stripped (`actually export --strip-workspaces`,
+/// `--keep-workspaces`)
+pub const RECORD_FILES: &[&str] = &[STRATEGY_FILE, NOTES_FILE, "session.log", API_LOG_FILE];
+
+/// Project-level ignore file (gitignore syntax) for workspace seeding
+pub const ACTUALLY_IGNORE_FILE: &str = ".actuallyignore";
+
+/// Project-level Claude Code files that make a session behave like the
+/// user's normal sessions in that project
+const CLAUDE_PROJECT_FILES: &[&str] = &[
+    "CLAUDE.md",
+    ".claude/settings.json",
+    ".claude/settings.local.json",
+];
+
+/// The instance's task and strategy, written into every workspace
+pub const STRATEGY_FILE: &str = "STRATEGY.md";
+
+/// Notes the implementation agent is asked to leave in its workspace
+pub const NOTES_FILE: &str = "NOTES.md";
+
+/// Directories whose churn says nothing about an agent's progress
+const UNWATCHED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// What happened to a file in a watched workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub kind: ChangeKind,
 }
 
 pub struct Workspace {
@@ -14,16 +69,496 @@ pub struct Workspace {
 
 impl Workspace {
     /// Create a new workspace directory for the given instance
-    /// Creates: {run_dir}/c{instance_id}/
-    pub fn create(run_dir: &Path, instance_id: usize) -> Result<Self, WorkspaceError> {
-        let path = run_dir.join(format!("c{}", instance_id));
+    /// Creates: {run_dir}/c{instance_id}/workspace/, or c{instance_id}-{name}/workspace/
+    /// for a named instance (`{run_dir}/instances/{instance_id}/workspace/` when nested)
+    pub fn create(
+        run_dir: &Path,
+        layout: RunLayout,
+        instance_id: usize,
+        name: Option<&str>,
+    ) -> Result<Self, WorkspaceError> {
+        Self::create_at(layout.workspace(run_dir, instance_id, name))
+    }
+
+    /// Create a workspace directory in one with an explicit name, e.g.
+    /// `c-synth/workspace/`
+    pub fn create_named(run_dir: &Path, name: &str) -> Result<Self, WorkspaceError> {
+        Self::create_at(run_dir.join(name).join(layout::WORKSPACE_DIR))
+    }
+
+    fn create_at(path: PathBuf) -> Result<Self, WorkspaceError> {
         fs::create_dir_all(&path)?;
 
         Ok(Self { path })
     }
 
+    /// Delete instance `instance_id`'s workspace, if a previous (interrupted)
+    /// attempt left one behind, so the instance starts from scratch
+    pub fn discard(
+        run_dir: &Path,
+        layout: RunLayout,
+        instance_id: usize,
+        name: Option<&str>,
+    ) -> Result<(), WorkspaceError> {
+        match fs::remove_dir_all(layout.workspace(run_dir, instance_id, name)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
     /// Get the workspace path
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Write CLAUDE.md into the workspace. If one already exists (e.g. from
+    /// seeding), the new content is appended after it rather than replacing it.
+    pub fn write_claude_md(&self, content: &str) -> Result<(), WorkspaceError> {
+        let path = self.path.join("CLAUDE.md");
+        let combined = match fs::read_to_string(&path) {
+            Ok(existing) if !existing.trim().is_empty() => {
+                format!("{}\n\n{}", existing.trim_end(), content)
+            }
+            _ => content.to_string(),
+        };
+        fs::write(&path, combined)?;
+        Ok(())
+    }
+
+    /// Write STRATEGY.md into the workspace, unless seeding brought one along.
+    /// Returns whether it was written.
+    pub fn write_strategy_md(&self, content: &str) -> Result<bool, WorkspaceError> {
+        let path = self.path.join(STRATEGY_FILE);
+        if path.exists() {
+            return Ok(false);
+        }
+        fs::write(&path, content)?;
+        Ok(true)
+    }
+
+    /// Contents of the agent's NOTES.md, if it wrote a non-empty one
+    pub fn read_notes(&self) -> Option<String> {
+        fs::read_to_string(self.path.join(NOTES_FILE))
+            .ok()
+            .filter(|notes| !notes.trim().is_empty())
+    }
+
+    /// Copy the project's CLAUDE.md and .claude/ settings files from `source`
+    /// into the workspace, leaving any already present (e.g. from seeding) alone.
+    pub fn copy_claude_files(&self, source: &Path) -> Result<(), WorkspaceError> {
+        for relative in CLAUDE_PROJECT_FILES {
+            let src = source.join(relative);
+            let dest = self.path.join(relative);
+            if !src.is_file() || dest.exists() {
+                continue;
+            }
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&src, &dest)
+                .map_err(|e| WorkspaceError::SeedCopy(PathBuf::from(relative), e))?;
+        }
+        Ok(())
+    }
+
+    /// Report file changes in the workspace (outside `.git`, `target` and
+    /// `node_modules`) to `on_change` until the returned watcher is dropped
+    pub fn watch(
+        &self,
+        on_change: impl Fn(FileChange) + Send + 'static,
+    ) -> notify::Result<RecommendedWatcher> {
+        let roots = [self.path.clone(), self.path.canonicalize()?];
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let kind = match event.kind {
+                    EventKind::Create(_) => ChangeKind::Created,
+                    EventKind::Modify(ModifyKind::Metadata(_)) => return,
+                    EventKind::Modify(_) => ChangeKind::Modified,
+                    EventKind::Remove(_) => ChangeKind::Removed,
+                    _ => return,
+                };
+                for path in &event.paths {
+                    if kind != ChangeKind::Removed && path.is_dir() {
+                        continue;
+                    }
+                    if let Some(path) = roots.iter().find_map(|root| watched_path(root, path)) {
+                        on_change(FileChange { path, kind });
+                    }
+                }
+            })?;
+        watcher.watch(&self.path, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    }
+
+    /// Copy the contents of `source` into the workspace, skipping anything
+    /// matched by .gitignore or .actuallyignore files, the `.git` directory
+    /// itself, and `actually-*` run directories that live next to `run_dir`.
+    /// .actuallyignore rules take precedence, so `!path` can re-include
+    /// something .gitignore excludes. Paths `protection` excludes are left
+    /// out too.
+    pub fn seed_from(
+        &self,
+        source: &Path,
+        run_dir: &Path,
+        protection: &Protection,
+    ) -> Result<usize, WorkspaceError> {
+        let source = source.canonicalize()?;
+        let walk_root = source.clone();
+        let protection = protection.clone();
+        let runs_base = run_dir
+            .canonicalize()?
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+
+        let walker = WalkBuilder::new(&source)
+            .hidden(false)
+            .require_git(false)
+            .add_custom_ignore_filename(ACTUALLY_IGNORE_FILE)
+            .filter_entry(move |entry| {
+                let name = entry.file_name().to_string_lossy();
+                if name == ".git" {
+                    return false;
+                }
+                if let Ok(relative) = entry.path().strip_prefix(&walk_root) {
+                    let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                    if !relative.as_os_str().is_empty() && protection.excludes(relative, is_dir) {
+                        return false;
+                    }
+                }
+                let is_run_dir = entry.path().parent() == Some(runs_base.as_path())
+                    && name.starts_with("actually-");
+                !is_run_dir
+            })
+            .build();
+
+        let mut copied = 0;
+        for entry in walker {
+            let entry = entry?;
+            let relative = match entry.path().strip_prefix(&source) {
+                Ok(r) if !r.as_os_str().is_empty() => r,
+                _ => continue,
+            };
+            let dest = self.path.join(relative);
+            let Some(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            let result = if file_type.is_dir() {
+                fs::create_dir_all(&dest)
+            } else if file_type.is_symlink() {
+                copy_symlink(entry.path(), &dest)
+            } else {
+                copied += 1;
+                fs::copy(entry.path(), &dest).map(|_| ())
+            };
+            result.map_err(|e| WorkspaceError::SeedCopy(relative.to_path_buf(), e))?;
+        }
+
+        Ok(copied)
+    }
+}
+
+/// `path` relative to `root`, or `None` if it is outside `root`, is `root`
+/// itself, or is inside one of the `UNWATCHED_DIRS`
+fn watched_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let unwatched = relative
+        .components()
+        .any(|c| UNWATCHED_DIRS.iter().any(|dir| c.as_os_str() == *dir))
+        || relative == Path::new(API_LOG_FILE);
+    if relative.as_os_str().is_empty() || unwatched {
+        return None;
+    }
+    Some(relative.display().to_string())
+}
+
+/// Move the workspace at `src` to `dest`, across filesystems, leaving out
+/// what its `.gitignore` ignores (build output, dependencies) but keeping
+/// its git history (`--copy-back-workspaces`)
+pub fn copy_back(src: &Path, dest: &Path) -> Result<(), WorkspaceError> {
+    let walker = WalkBuilder::new(src)
+        .hidden(false)
+        .parents(false)
+        .require_git(false)
+        .build();
+    for entry in walker {
+        let entry = entry?;
+        let relative = match entry.path().strip_prefix(src) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let target = dest.join(relative);
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        let result = if file_type.is_dir() {
+            fs::create_dir_all(&target)
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), &target)
+        } else {
+            fs::copy(entry.path(), &target).map(|_| ())
+        };
+        result.map_err(|e| WorkspaceError::CopyBack(entry.path().to_path_buf(), e))?;
+    }
+    fs::remove_dir_all(src)?;
+    Ok(())
+}
+
+/// Delete everything in the workspace at `dir` but its `RECORD_FILES`
+pub fn strip(dir: &Path) -> Result<(), WorkspaceError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if RECORD_FILES.contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let target = fs::read_link(src)?;
+    std::os::unix::fs::symlink(target, dest)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    fs::copy(src, dest).map(|_| ())
+}
+
+/// Environment variables that point every instance at the same build caches,
+/// so N implementations don't each compile the world from scratch.
+/// Cargo serializes concurrent builds on a shared target dir; that is the
+/// price of sharing compiled dependencies. The paths are absolute, since
+/// each instance reads them from its own workspace.
+pub fn build_cache_env(config: &BuildCacheConfig, run_dir: &Path) -> HashMap<String, String> {
+    let cache_dir = config
+        .dir
+        .clone()
+        .unwrap_or_else(|| run_dir.join(".build-cache"));
+    let cache_dir = std::path::absolute(&cache_dir).unwrap_or(cache_dir);
+    let mut env = HashMap::new();
+
+    if config.rust {
+        env.insert(
+            "CARGO_TARGET_DIR".to_string(),
+            cache_dir.join("cargo-target").to_string_lossy().to_string(),
+        );
+    }
+    if config.node {
+        env.insert(
+            "npm_config_cache".to_string(),
+            cache_dir.join("npm").to_string_lossy().to_string(),
+        );
+        // pnpm hardlinks packages out of its store into each node_modules
+        env.insert(
+            "npm_config_store_dir".to_string(),
+            cache_dir.join("pnpm-store").to_string_lossy().to_string(),
+        );
+    }
+
+    env
+}
+
+/// `[env]` for one instance: `{instance}`, `{name}`, `{workspace}` and
+/// `{run_dir}` in the values are replaced with the instance's own, so each
+/// instance can get e.g. its own test database. The paths are absolute,
+/// since what reads them runs in the workspace.
+pub fn instance_env(
+    vars: &HashMap<String, String>,
+    instance_id: usize,
+    name: Option<&str>,
+    workspace: &Path,
+    run_dir: &Path,
+) -> HashMap<String, String> {
+    let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let (workspace, run_dir) = (absolute(workspace), absolute(run_dir));
+    vars.iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace("{instance}", &instance_id.to_string())
+                .replace("{name}", &names::label(instance_id, name))
+                .replace("{workspace}", &workspace.to_string_lossy())
+                .replace("{run_dir}", &run_dir.to_string_lossy());
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_from_skips_ignored_and_run_dirs() {
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(source.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(source.path().join("target/debug")).unwrap();
+        fs::write(source.path().join("target/debug/app"), "binary").unwrap();
+        fs::create_dir_all(source.path().join(".git")).unwrap();
+        fs::write(source.path().join(".git/HEAD"), "ref").unwrap();
+
+        let run_dir = source.path().join("actually-123");
+        let workspace = Workspace::create(&run_dir, RunLayout::Flat, 0, None).unwrap();
+        workspace
+            .seed_from(source.path(), &run_dir, &Protection::default())
+            .unwrap();
+
+        assert!(workspace.path().join("main.rs").exists());
+        assert!(workspace.path().join(".gitignore").exists());
+        assert!(!workspace.path().join("target").exists());
+        assert!(!workspace.path().join(".git").exists());
+        assert!(!workspace.path().join("actually-123").exists());
+    }
+
+    #[test]
+    fn test_seed_from_honors_actuallyignore() {
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(
+            source.path().join(ACTUALLY_IGNORE_FILE),
+            "fixtures/\n!keep.log\n",
+        )
+        .unwrap();
+        fs::create_dir_all(source.path().join("fixtures")).unwrap();
+        fs::write(source.path().join("fixtures/big.bin"), "data").unwrap();
+        fs::write(source.path().join("keep.log"), "kept").unwrap();
+        fs::write(source.path().join("drop.log"), "dropped").unwrap();
+
+        let run_dir = source.path().join("actually-123");
+        let workspace = Workspace::create(&run_dir, RunLayout::Flat, 0, None).unwrap();
+        workspace
+            .seed_from(source.path(), &run_dir, &Protection::default())
+            .unwrap();
+
+        assert!(!workspace.path().join("fixtures").exists());
+        assert!(workspace.path().join("keep.log").exists());
+        assert!(!workspace.path().join("drop.log").exists());
+    }
+
+    #[test]
+    fn test_watched_path() {
+        let root = Path::new("/runs/c0");
+        assert_eq!(
+            watched_path(root, Path::new("/runs/c0/src/main.rs")),
+            Some(format!("src{}main.rs", std::path::MAIN_SEPARATOR))
+        );
+        assert_eq!(watched_path(root, Path::new("/runs/c0")), None);
+        assert_eq!(watched_path(root, Path::new("/runs/c1/main.rs")), None);
+        assert_eq!(watched_path(root, Path::new("/runs/c0/.git/index")), None);
+        assert_eq!(
+            watched_path(root, Path::new("/runs/c0/target/debug/app")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_copy_back() {
+        let scratch = tempfile::tempdir().unwrap();
+        let src = scratch.path().join("c0");
+        fs::create_dir_all(src.join(".git")).unwrap();
+        fs::create_dir_all(src.join("target/debug")).unwrap();
+        fs::write(src.join(".gitignore"), "target/\n").unwrap();
+        fs::write(src.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(src.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(src.join("target/debug/app"), "binary").unwrap();
+
+        let run_dir = tempfile::tempdir().unwrap();
+        let dest = run_dir.path().join("c0");
+        copy_back(&src, &dest).unwrap();
+        assert!(dest.join(".git/HEAD").is_file());
+        assert!(dest.join("main.rs").is_file());
+        assert!(!dest.join("target").exists());
+        assert!(!src.exists());
+    }
+
+    #[test]
+    fn test_build_cache_env() {
+        let config = BuildCacheConfig {
+            rust: true,
+            node: true,
+            dir: None,
+        };
+        let env = build_cache_env(&config, Path::new("./actually-123"));
+        for var in [
+            "CARGO_TARGET_DIR",
+            "npm_config_cache",
+            "npm_config_store_dir",
+        ] {
+            assert!(
+                Path::new(&env[var]).is_absolute(),
+                "{} is {}",
+                var,
+                env[var]
+            );
+        }
+        assert!(env["CARGO_TARGET_DIR"].ends_with(&format!(
+            "actually-123{0}.build-cache{0}cargo-target",
+            std::path::MAIN_SEPARATOR
+        )));
+
+        let config = BuildCacheConfig {
+            dir: Some(PathBuf::from("cache")),
+            ..config
+        };
+        let env = build_cache_env(&config, Path::new("./actually-123"));
+        assert!(Path::new(&env["npm_config_cache"]).is_absolute());
+    }
+
+    #[test]
+    fn test_instance_env() {
+        let vars = HashMap::from([
+            (
+                "DATABASE_URL".to_string(),
+                "postgres://localhost/test_{instance}".to_string(),
+            ),
+            ("CACHE".to_string(), "{workspace}/.cache-{name}".to_string()),
+            ("LOG".to_string(), "debug".to_string()),
+        ]);
+        let env = instance_env(
+            &vars,
+            2,
+            Some("bravo"),
+            Path::new("/runs/c2-bravo"),
+            Path::new("/runs"),
+        );
+        assert_eq!(env["DATABASE_URL"], "postgres://localhost/test_2");
+        assert_eq!(env["CACHE"], "/runs/c2-bravo/.cache-bravo");
+        assert_eq!(env["LOG"], "debug");
+
+        // Sessions and commands read them from within the workspace
+        let vars = HashMap::from([
+            (
+                "DATABASE_URL".to_string(),
+                "sqlite://{workspace}/test.db".to_string(),
+            ),
+            ("RUNS".to_string(), "{run_dir}".to_string()),
+        ]);
+        let env = instance_env(
+            &vars,
+            0,
+            None,
+            Path::new("./actually-123/c0"),
+            Path::new("./actually-123"),
+        );
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            env["DATABASE_URL"],
+            format!(
+                "sqlite://{}/test.db",
+                cwd.join("actually-123").join("c0").display()
+            )
+        );
+        assert_eq!(env["RUNS"], cwd.join("actually-123").to_string_lossy());
+    }
 }